@@ -0,0 +1,41 @@
+//! Compares the batch parser with the incremental one, on a simulated capture.
+//!
+//! `cargo bench --bench parse`
+
+use std::borrow::Borrow;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use enocean::frame::{parse_all, ESP3Frame, FrameDecoder};
+
+const FRAMES: usize = 100_000;
+const RUNS: u32 = 10;
+
+/// Util : the average duration of `RUNS` runs of `f`
+fn time(f: impl Fn()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    start.elapsed() / RUNS
+}
+
+fn main() {
+    let telegram = ESP3Frame::assemble(0x01, &[0xa5, 0x00, 0x00, 0x55, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00], &[0x00, 0xff, 0xff, 0xff, 0xff, 0x2d, 0x00]);
+    let capture: Vec<u8> = (0..FRAMES).flat_map(|_| Borrow::<[u8]>::borrow(&telegram).to_vec()).collect();
+
+    let incremental = time(|| {
+        let mut decoder = FrameDecoder::new();
+        let frames = capture.iter().filter_map(|&byte| decoder.push(byte)).count();
+        assert_eq!(black_box(frames), FRAMES);
+    });
+    let batch = time(|| {
+        let (frames, consumed) = parse_all(black_box(&capture));
+        assert_eq!((frames.len(), consumed), (FRAMES, capture.len()));
+    });
+
+    let mb = capture.len() as f64 / 1e6;
+    println!("{} frames, {:.1} MB", FRAMES, mb);
+    println!("incremental  {:>10.2?}  {:>8.1} MB/s", incremental, mb / incremental.as_secs_f64());
+    println!("parse_all    {:>10.2?}  {:>8.1} MB/s", batch, mb / batch.as_secs_f64());
+}
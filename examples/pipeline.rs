@@ -0,0 +1,48 @@
+//! Decode telegrams on a worker thread: the reading thread detaches each packet from its
+//! frame with `into_owned`, and sends it over a channel.
+//!
+//! `cargo run --example pipeline -- /dev/ttyUsb300`
+
+use std::sync::mpsc;
+use std::thread;
+
+use enocean::packet::{Packet, RadioErp1};
+use enocean::port::Port;
+use enocean::registry::{DeviceEntry, DeviceRegistry};
+use enocean::FrameReadError;
+
+fn main() {
+    let port_name = std::env::args().nth(1).unwrap_or_else(|| "/dev/ttyUsb300".into());
+    let mut port = Port::open(&port_name).expect("cannot open the port");
+    let (sender, receiver) = mpsc::channel::<RadioErp1<'static>>();
+
+    let worker = thread::spawn(move || {
+        let mut registry = DeviceRegistry::new();
+        registry.insert("0180998e".parse().unwrap(), DeviceEntry::new("A5-02-05".parse().unwrap()));
+        for erp in receiver {
+            match registry.decode(&erp) {
+                Ok(report) => println!("{} {:?}", erp.sender_id, report.values),
+                Err(e) => println!("{} {} ({})", erp.sender_id, hex::encode(&erp.user_data), e),
+            }
+        }
+    });
+
+    loop {
+        let frame = match port.read_frame() {
+            Ok(frame) => frame,
+            Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(e) => {
+                eprintln!("read error: {}", e);
+                break;
+            }
+        };
+        // The packet borrows from `frame`, dropped at the end of the iteration
+        if let Ok(Packet::RadioErp1(erp)) = Packet::decode(frame.as_ref()) {
+            if sender.send(erp.into_owned()).is_err() {
+                break;
+            }
+        }
+    }
+    drop(sender);
+    worker.join().unwrap();
+}
@@ -0,0 +1,166 @@
+//! Acknowledged commands to D2-01 actuators
+//!
+//! D2-01 actuators answer a set output command with a status response, but routinely miss
+//! commands. A [`CommandTracker`] follows a command until the actuator confirms it: it
+//! tells when to send the command, recognizes the status response, and resends the command
+//! after [`ACK_TIMEOUT`], up to [`RETRIES`] times. It does no I/O; see
+//! `device::Device::set_output` for the loop over a port.
+//!
+//! ```
+//! use enocean::actuator::{Action, CommandTracker};
+//! use std::time::Instant;
+//!
+//! let mut tracker = CommandTracker::new("05123456".parse()?, 0, 100);
+//! let Action::Send(command) = tracker.poll(Instant::now()) else { unreachable!() };
+//! assert_eq!(command.encode(), vec![0x01, 0x00, 0x64]);
+//! assert_eq!(tracker.poll(Instant::now()), Action::Wait);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::eep::d2_01::{Command, DimMode, Message, Status, ALL_CHANNELS};
+use crate::packet::{Address, RadioErp1};
+
+/// How long to wait for the status response before resending the command
+pub const ACK_TIMEOUT: Duration = Duration::from_millis(1500);
+/// How many times a command is resent
+pub const RETRIES: u8 = 2;
+
+/// What to do next for a tracked command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Send the command to the actuator
+    Send(Command),
+    /// Wait for the status response
+    Wait,
+    /// The actuator did not answer any attempt
+    GiveUp,
+}
+
+/// How a tracked command ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandOutcome {
+    /// The actuator reports the requested value
+    Applied(Status),
+    /// The actuator answered with another value, e.g. set by local control meanwhile
+    NotApplied(Status),
+    /// No status response to any attempt
+    NoResponse { attempts: u8 },
+}
+
+/// A set output command, until the actuator confirms it
+#[derive(Debug, Clone)]
+pub struct CommandTracker {
+    actuator: Address,
+    channel: u8,
+    value: u8,
+    dim: DimMode,
+    timeout: Duration,
+    retries: u8,
+    attempts: u8,
+    deadline: Option<Instant>,
+}
+
+impl CommandTracker {
+    /// Set output `channel` of `actuator` to `value` (0 = off, 1..100 %), immediately
+    pub fn new(actuator: Address, channel: u8, value: u8) -> Self {
+        Self {
+            actuator, channel, value: value.min(100),
+            dim: DimMode::Immediate,
+            timeout: ACK_TIMEOUT,
+            retries: RETRIES,
+            attempts: 0,
+            deadline: None,
+        }
+    }
+
+    pub fn with_dim(mut self, dim: DimMode) -> Self {
+        self.dim = dim;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn actuator(&self) -> Address {
+        self.actuator
+    }
+
+    /// Times the command was sent
+    pub fn attempts(&self) -> u8 {
+        self.attempts
+    }
+
+    /// What to do at `now`. Once [`Action::Send`] is returned, the command is considered
+    /// sent, and its response awaited until the timeout.
+    pub fn poll(&mut self, now: Instant) -> Action {
+        match self.deadline {
+            Some(deadline) if now < deadline => Action::Wait,
+            Some(_) if self.attempts > self.retries => Action::GiveUp,
+            _ => {
+                self.attempts += 1;
+                self.deadline = Some(now + self.timeout);
+                Action::Send(Command::SetOutput { channel: self.channel, value: self.value, dim: self.dim })
+            }
+        }
+    }
+
+    /// Process a received telegram, returning the outcome if it is the status response of
+    /// the actuator for the channel
+    pub fn handle(&mut self, erp: &RadioErp1) -> Option<CommandOutcome> {
+        if erp.sender_id != self.actuator || self.attempts == 0 {
+            return None;
+        }
+        let Ok(Message::Status(status)) = Message::decode(erp) else { return None };
+        if self.channel != ALL_CHANNELS && status.channel != self.channel {
+            return None;
+        }
+        self.deadline = None;
+        Some(if status.value == Some(self.value) {
+            CommandOutcome::Applied(status)
+        } else {
+            CommandOutcome::NotApplied(status)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_no_response_then_resend_and_give_up() {
+        let start = Instant::now();
+        let mut tracker = CommandTracker::new("05123456".parse().unwrap(), 1, 50).with_retries(1);
+        assert!(matches!(tracker.poll(start), Action::Send(Command::SetOutput { channel: 1, value: 50, .. })));
+        assert_eq!(tracker.poll(start + ACK_TIMEOUT / 2), Action::Wait);
+        assert!(matches!(tracker.poll(start + ACK_TIMEOUT), Action::Send(_)));
+        assert_eq!(tracker.poll(start + ACK_TIMEOUT * 2), Action::GiveUp);
+        assert_eq!(tracker.attempts(), 2);
+    }
+
+    #[test]
+    fn given_status_response_then_report_outcome() {
+        let mut tracker = CommandTracker::new("05123456".parse().unwrap(), 1, 50);
+        tracker.poll(Instant::now());
+
+        assert_eq!(tracker.handle(&erp1(&[0xd2, 0x04, 0x01, 0x32, 0x01, 0x02, 0x03, 0x04, 0x00])), None);
+        assert_eq!(tracker.handle(&erp1(&[0xd2, 0x04, 0x00, 0x32, 0x05, 0x12, 0x34, 0x56, 0x00])), None);
+        let Some(CommandOutcome::Applied(applied)) = tracker.handle(&erp1(&[0xd2, 0x04, 0x01, 0x32, 0x05, 0x12, 0x34, 0x56, 0x00]))
+            else { panic!("not applied") };
+        assert_eq!(applied.value, Some(50));
+        assert!(matches!(
+            tracker.handle(&erp1(&[0xd2, 0x04, 0x01, 0x00, 0x05, 0x12, 0x34, 0x56, 0x00])),
+            Some(CommandOutcome::NotApplied(Status { value: Some(0), .. }))
+        ));
+    }
+}
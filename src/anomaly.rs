@@ -0,0 +1,194 @@
+//! Detection of replayed and spoofed telegrams of non-secure devices
+//!
+//! Telegrams of non-secure devices carry no rolling code, so a recorded telegram can be
+//! sent again, e.g. to open a window contact on the alarm panel. An [`AnomalyDetector`]
+//! watches for the traces such attacks leave:
+//!
+//! - bursts of identical telegrams: the copies of a telegram relayed by repeaters arrive
+//!   within [`REPEATER_WINDOW`], and are not counted; [`BURST_COUNT`] identical telegrams
+//!   further apart, within [`BURST_WINDOW`], are reported;
+//! - jumps of the signal strength: the telegrams of a device that was not moved are heard at
+//!   about the same strength, while a transmitter elsewhere is not. A telegram heard directly
+//!   more than [`RSSI_JUMP`] dB away from the average of the last ones of its sender is
+//!   reported.
+//!
+//! Telegrams with secure RORGs are ignored. Devices legitimately sending the same telegram
+//! quickly, e.g. rocker switches pressed several times, are excluded with
+//! [`AnomalyDetector::ignore`]. A [`Gateway`](crate::device::Gateway) with a detector reports
+//! the anomalies among its events.
+//!
+//! ```
+//! use enocean::anomaly::{Anomaly, AnomalyDetector};
+//! use enocean::packet::RadioErp1;
+//! use enocean::enocean::Rorg;
+//! use std::time::{Duration, Instant};
+//!
+//! let sender = "0180998e".parse()?;
+//! let open = RadioErp1::builder().choice(Rorg::Bs1).user_data(&[0x08]).sender(sender).build()?;
+//! let mut detector = AnomalyDetector::new();
+//! let start = Instant::now();
+//! for n in 0..3 {
+//!     let anomalies = detector.record(&open, start + n * Duration::from_millis(500));
+//!     assert_eq!(anomalies, if n < 2 { vec![] } else { vec![Anomaly::Burst { sender, count: 3 }] });
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::enocean::Rorg;
+use crate::packet::{Address, RadioErp1};
+
+/// Identical telegrams closer than this are copies relayed by repeaters
+pub const REPEATER_WINDOW: Duration = Duration::from_millis(100);
+
+/// Window identical telegrams are counted in
+pub const BURST_WINDOW: Duration = Duration::from_secs(5);
+
+/// Identical telegrams within [`BURST_WINDOW`] reported as a burst
+pub const BURST_COUNT: usize = 3;
+
+/// Difference to the average signal strength of a sender reported as a jump, in dB
+pub const RSSI_JUMP: i16 = 20;
+
+/// Telegrams heard directly from a sender before its signal strength is checked
+pub const RSSI_BASELINE: usize = 5;
+
+/// Telegrams heard directly averaged for the signal strength of a sender
+const RSSI_WINDOW: usize = 16;
+
+/// A suspicious telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Anomaly {
+    /// `count` identical telegrams of `sender` within the burst window, further apart than
+    /// repeater copies
+    Burst { sender: Address, count: usize },
+    /// A telegram of `sender` heard at `rssi` dBm, while its last ones averaged `usual` dBm
+    RssiJump { sender: Address, usual: i16, rssi: i16 },
+}
+
+#[derive(Debug, Clone, Default)]
+struct Sender {
+    /// Last telegrams within the burst window, as RORG and user data
+    telegrams: VecDeque<(Instant, Vec<u8>)>,
+    /// Signal strength of the last telegrams heard directly
+    rssi: VecDeque<i16>,
+}
+
+/// Watches telegrams for replays and spoofing, by sender
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    burst_window: Duration,
+    burst_count: usize,
+    rssi_jump: i16,
+    ignored: BTreeSet<Address>,
+    senders: BTreeMap<Address, Sender>,
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnomalyDetector {
+    /// A detector with the default thresholds
+    pub fn new() -> Self {
+        Self { burst_window: BURST_WINDOW, burst_count: BURST_COUNT, rssi_jump: RSSI_JUMP, ignored: BTreeSet::new(), senders: BTreeMap::new() }
+    }
+
+    /// Report `count` identical telegrams within `window` as a burst
+    pub fn with_burst(mut self, count: usize, window: Duration) -> Self {
+        self.burst_count = count.max(2);
+        self.burst_window = window;
+        self
+    }
+
+    /// Report signal strengths more than `db` away from the average of the sender
+    pub fn with_rssi_jump(mut self, db: i16) -> Self {
+        self.rssi_jump = db;
+        self
+    }
+
+    /// Do not watch the telegrams of `address`
+    pub fn ignore(&mut self, address: Address) {
+        self.ignored.insert(address);
+        self.senders.remove(&address);
+    }
+
+    /// Forget what was learned of a sender, e.g. after moving it
+    pub fn reset(&mut self, address: &Address) {
+        self.senders.remove(address);
+    }
+
+    /// Record a telegram received at `now`, returning what is suspicious about it
+    pub fn record(&mut self, erp: &RadioErp1, now: Instant) -> Vec<Anomaly> {
+        let secure = matches!(erp.choice, Rorg::Sec | Rorg::SecEncaps | Rorg::SecCdm | Rorg::SecTi);
+        if secure || self.ignored.contains(&erp.sender_id) {
+            return vec![];
+        }
+        let sender = self.senders.entry(erp.sender_id).or_default();
+        let mut anomalies = vec![];
+
+        let mut telegram = vec![u8::from(erp.choice)];
+        telegram.extend_from_slice(&erp.user_data);
+        while sender.telegrams.front().is_some_and(|(at, _)| now.duration_since(*at) > self.burst_window) {
+            sender.telegrams.pop_front();
+        }
+        let identical: Vec<Instant> = sender.telegrams.iter().filter(|(_, data)| *data == telegram).map(|(at, _)| *at).collect();
+        if !identical.iter().any(|at| now.duration_since(*at) < REPEATER_WINDOW) {
+            let count = identical.len() + 1;
+            if count >= self.burst_count {
+                anomalies.push(Anomaly::Burst { sender: erp.sender_id, count });
+            }
+            sender.telegrams.push_back((now, telegram));
+        }
+
+        if let (Some(rssi), 0) = (erp.rssi_dbm(), erp.status_byte().repeater_count()) {
+            if sender.rssi.len() >= RSSI_BASELINE {
+                let usual = (sender.rssi.iter().map(|rssi| *rssi as i32).sum::<i32>() / sender.rssi.len() as i32) as i16;
+                if (rssi - usual).abs() > self.rssi_jump {
+                    anomalies.push(Anomaly::RssiJump { sender: erp.sender_id, usual, rssi });
+                }
+            }
+            if sender.rssi.len() == RSSI_WINDOW {
+                sender.rssi.pop_front();
+            }
+            sender.rssi.push_back(rssi);
+        }
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ESP3Frame;
+
+    #[test]
+    fn given_replayed_telegrams_then_report_bursts_and_signal_jumps() {
+        let received = |data: u8, status: u8, rssi: u8| {
+            let frame = ESP3Frame::assemble(0x01, &[0xd5, data, 0x01, 0x80, 0x99, 0x8e, status], &[0x01, 0xff, 0xff, 0xff, 0xff, rssi, 0x00]);
+            RadioErp1::decode(frame.as_ref()).unwrap().into_owned()
+        };
+        let sender = "0180998e".parse().unwrap();
+        let mut detector = AnomalyDetector::new().with_burst(2, Duration::from_secs(5));
+        let start = Instant::now();
+
+        // A telegram and its repeated copy, then the opposite state, five times
+        for n in 0..5 {
+            let at = start + Duration::from_secs(60 * n);
+            let data = if n % 2 == 0 { 0x08 } else { 0x09 };
+            assert_eq!(detector.record(&received(data, 0x00, 0x46), at), vec![]);
+            assert_eq!(detector.record(&received(data, 0x01, 0x30), at + Duration::from_millis(40)), vec![]);
+        }
+        let later = start + Duration::from_secs(600);
+        assert_eq!(detector.record(&received(0x09, 0x00, 0x1e), later), vec![Anomaly::RssiJump { sender, usual: -0x46, rssi: -0x1e }]);
+        assert_eq!(detector.record(&received(0x09, 0x00, 0x46), later + Duration::from_secs(1)), vec![Anomaly::Burst { sender, count: 2 }]);
+
+        detector.ignore(sender);
+        assert_eq!(detector.record(&received(0x09, 0x00, 0x10), later + Duration::from_secs(2)), vec![]);
+    }
+}
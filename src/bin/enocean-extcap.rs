@@ -0,0 +1,120 @@
+//! Wireshark extcap interface: live capture of the frames of an EnOcean gateway
+//!
+//! Installed (or linked) in the extcap directory of Wireshark, it lists the serial ports as
+//! capture interfaces. Wireshark then runs it as
+//!
+//! ```text
+//! enocean-extcap --capture --extcap-interface /dev/ttyUSB0 --fifo /tmp/wireshark_extcap_...
+//! ```
+//!
+//! and reads the frames as pcapng, with the link type `LINKTYPE_USER0` (147) of
+//! [`enocean::capture::pcapng`]; map it to an EnOcean dissector in the DLT_USER preferences.
+//! Without `--fifo`, or with `--fifo -`, the capture is written to stdout, e.g. for
+//! `enocean-extcap --capture --extcap-interface /dev/ttyUSB0 | wireshark -k -i -`.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+use enocean::capture::pcapng::{PcapngWriter, LINKTYPE_USER0};
+use enocean::port::Port;
+use enocean::FrameReadError;
+
+const USAGE: &str = "usage: enocean-extcap --extcap-interfaces | --extcap-interface PORT (--extcap-dlts | --extcap-config | --capture [--fifo PATH])";
+
+#[derive(Default)]
+struct Options {
+    interfaces: bool,
+    interface: Option<String>,
+    dlts: bool,
+    config: bool,
+    capture: bool,
+    fifo: Option<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--extcap-interfaces" => options.interfaces = true,
+                "--extcap-interface" => options.interface = Some(value()?),
+                "--extcap-dlts" => options.dlts = true,
+                "--extcap-config" => options.config = true,
+                "--capture" => options.capture = true,
+                "--fifo" => options.fifo = Some(value()?),
+                // Passed by Wireshark, without use here
+                "--extcap-version" | "--extcap-capture-filter" => { value()?; }
+                _ if arg.starts_with("--extcap-version=") => {}
+                _ => return Err(format!("unexpected argument {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn interfaces() {
+    println!("extcap {{version={}}}{{help={}}}", env!("CARGO_PKG_VERSION"), env!("CARGO_PKG_REPOSITORY"));
+    for port in serialport::available_ports().unwrap_or_default() {
+        println!("interface {{value={}}}{{display=EnOcean ESP3 ({})}}", port.port_name, port.port_name);
+    }
+}
+
+fn capture(interface: &str, fifo: Option<&str>) -> Result<(), String> {
+    let mut port = Port::open(interface).map_err(|e| format!("cannot open {}: {}", interface, e))?;
+    let output: Box<dyn Write> = match fifo {
+        None | Some("-") => Box::new(io::stdout()),
+        Some(path) => Box::new(OpenOptions::new().write(true).open(path).map_err(|e| format!("cannot open {}: {}", path, e))?),
+    };
+    let mut writer = PcapngWriter::new(output, LINKTYPE_USER0).map_err(|e| format!("cannot write capture: {}", e))?;
+    writer.flush().map_err(|e| format!("cannot write capture: {}", e))?;
+
+    loop {
+        let frame = match port.read_frame() {
+            Ok(frame) => frame,
+            Err(FrameReadError::IOError(e)) if e.kind() == io::ErrorKind::TimedOut => continue,
+            Err(e) => return Err(format!("read error: {}", e)),
+        };
+        // Wireshark closes the fifo to stop the capture
+        match writer.record(SystemTime::now(), &frame).and_then(|()| writer.flush()) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(format!("cannot write capture: {}", e)),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if options.interfaces {
+        interfaces();
+        return ExitCode::SUCCESS;
+    }
+    let Some(interface) = options.interface else {
+        eprintln!("missing --extcap-interface\n{}", USAGE);
+        return ExitCode::from(2);
+    };
+    if options.dlts {
+        println!("dlt {{number={}}}{{name=USER0}}{{display=EnOcean ESP3}}", LINKTYPE_USER0);
+    } else if options.config {
+        // No options: the port settings of ESP3 are fixed
+    } else if options.capture {
+        if let Err(e) = capture(&interface, options.fifo.as_deref()) {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    } else {
+        eprintln!("nothing to do\n{}", USAGE);
+        return ExitCode::from(2);
+    }
+    ExitCode::SUCCESS
+}
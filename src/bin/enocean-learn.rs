@@ -0,0 +1,138 @@
+//! Learn devices through an EnOcean gateway, and record them in a registry file
+//!
+//! ```text
+//! enocean-learn /dev/ttyUSB0 --registry devices.json --seconds 120
+//! 0180998e A5-02-05 (Eltako), accept? [y/N] y
+//! fefa1234 F6-02-01 (guessed), accept? [y/N] n
+//! ```
+//!
+//! Each device whose teach-in is received during the learn window is printed with its
+//! profile and manufacturer, and added to the registry once accepted; `--yes` accepts them
+//! all. The registry file is the JSON form of [`enocean::registry::DeviceRegistry`]; it is
+//! created if missing, and written after each accepted device.
+
+use std::io::{BufRead, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use enocean::enocean::Rorg;
+use enocean::learn::{DeviceLearned, LearnManager};
+use enocean::manufacturer::Manufacturer;
+use enocean::port::Port;
+use enocean::registry::DeviceRegistry;
+
+const USAGE: &str = "usage: enocean-learn <port> --registry FILE [--seconds N] [--yes]";
+
+struct Options {
+    port: String,
+    registry: String,
+    seconds: u64,
+    yes: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self { port: String::new(), registry: String::new(), seconds: 60, yes: false };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--registry" => options.registry = value()?,
+                "--seconds" => {
+                    let value = value()?;
+                    options.seconds = value.parse().map_err(|e| format!("bad duration {}: {}", value, e))?;
+                }
+                "--yes" => options.yes = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ if options.port.is_empty() => options.port = arg,
+                _ => return Err(format!("unexpected argument {}", arg)),
+            }
+        }
+        if options.port.is_empty() {
+            return Err("missing port".into());
+        }
+        if options.registry.is_empty() {
+            return Err("missing --registry".into());
+        }
+        Ok(options)
+    }
+}
+
+fn load(path: &str) -> Result<DeviceRegistry, String> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("bad registry {}: {}", path, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DeviceRegistry::new()),
+        Err(e) => Err(format!("cannot read {}: {}", path, e)),
+    }
+}
+
+fn save(path: &str, registry: &DeviceRegistry) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(registry).map_err(|e| format!("cannot encode registry: {}", e))?;
+    std::fs::write(path, json + "\n").map_err(|e| format!("cannot write {}: {}", path, e))
+}
+
+fn describe(device: &DeviceLearned) -> String {
+    let eep = match device.eep {
+        Some(eep) => eep.to_string(),
+        None => "unknown profile".into(),
+    };
+    let manufacturer = match device.manufacturer {
+        Some(id) => Manufacturer::describe(id),
+        None if device.eep.is_some_and(|eep| eep.rorg() == u8::from(Rorg::Rps)) => "guessed".into(),
+        None => "unknown manufacturer".into(),
+    };
+    format!("{} {} ({})", device.address, eep, manufacturer)
+}
+
+/// Ask whether to accept a device, `false` unless answered yes
+fn confirm(question: &str) -> bool {
+    print!("{}, accept? [y/N] ", question);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).is_ok() && matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut registry = load(&options.registry)?;
+    let mut port = Port::open(&options.port).map_err(|e| format!("cannot open {}: {}", options.port, e))?;
+    let base = port.read_id_base().map_err(|e| format!("cannot read base ID: {}", e))?;
+
+    let mut learn = LearnManager::new(base.0);
+    learn.start(Duration::from_secs(options.seconds));
+    eprintln!("learning for {} s, press the teach-in button of the devices", options.seconds);
+    while let Some(device) = learn.run(&mut port).map_err(|e| format!("read error: {}", e))? {
+        let description = describe(&device);
+        if device.eep.is_none() {
+            println!("{}, skipped", description);
+            continue;
+        }
+        let accepted = if options.yes {
+            println!("{}, accepted", description);
+            true
+        } else {
+            confirm(&description)
+        };
+        if accepted {
+            registry.learn(&device);
+            save(&options.registry, &registry)?;
+        }
+    }
+    eprintln!("learn window closed, {} devices in {}", registry.len(), options.registry);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Interactive shell on an EnOcean gateway, for commissioning
+//!
+//! ```text
+//! enocean-repl /dev/ttyUSB0
+//! learn on 60
+//! learned 0180998e A5-02-05
+//! 1697371200.123 0180998e A5 -45dBm 00008008 A5-02-05 Temperature(Temperature(19.92))
+//! send a500000008 0180998e
+//! sent a500000008 from ff9b1280
+//! ```
+//!
+//! Commands are read from the standard input, one per line; received telegrams are printed
+//! meanwhile, decoded for the devices learned during the session. `help` lists the commands.
+
+use std::io::BufRead;
+use std::process::ExitCode;
+use std::sync::mpsc::{self, TryRecvError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use enocean::enocean::Rorg;
+use enocean::filter::ReceiveFilter;
+use enocean::learn::LearnManager;
+use enocean::packet::{Address, IdBase, Packet, RadioErp1};
+use enocean::port::Port;
+use enocean::registry::DeviceRegistry;
+use enocean::FrameReadError;
+
+const USAGE: &str = "usage: enocean-repl <port>";
+
+const HELP: &str = "\
+version                      print the version of the gateway
+idbase                       print the base ID of the gateway
+learn on [SECONDS]           open a learn window, 60 s by default
+learn off                    close the learn window
+send HEX [ADDRESS]           send the RORG and user data HEX, broadcast or to ADDRESS
+filter list                  print the receive filter
+filter allow|block ADDRESS   only print the telegrams of ADDRESS, or never
+filter forget ADDRESS        remove ADDRESS from the filter
+filter rorg RORG             only print the telegrams with RORG, e.g. a5
+filter rssi DBM|off          only print the telegrams stronger than DBM, e.g. -85
+filter clear                 print all telegrams
+devices                      list the devices learned
+help                         print this help
+quit                         exit";
+
+/// Default learn window
+const LEARN_WINDOW: Duration = Duration::from_secs(60);
+
+enum Command {
+    Version,
+    IdBase,
+    LearnOn(Duration),
+    LearnOff,
+    Send { rorg: Rorg, data: Vec<u8>, destination: Option<Address> },
+    FilterList,
+    FilterAllow(Address),
+    FilterBlock(Address),
+    FilterForget(Address),
+    FilterRorg(Rorg),
+    FilterRssi(Option<i16>),
+    FilterClear,
+    Devices,
+    Help,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Option<Self>, String> {
+        let address = |value: &str| value.parse::<Address>().map_err(|e| format!("bad address {}: {}", value, e));
+        let words: Vec<_> = line.split_whitespace().collect();
+        Ok(Some(match words[..] {
+            [] => return Ok(None),
+            ["version"] => Self::Version,
+            ["idbase"] => Self::IdBase,
+            ["learn", "on"] => Self::LearnOn(LEARN_WINDOW),
+            ["learn", "on", seconds] =>
+                Self::LearnOn(Duration::from_secs(seconds.parse().map_err(|e| format!("bad duration {}: {}", seconds, e))?)),
+            ["learn", "off"] => Self::LearnOff,
+            ["send", data, ref destination @ ..] if destination.len() <= 1 => {
+                let data = hex::decode(data).map_err(|e| format!("bad hex data: {}", e))?;
+                let (rorg, data) = data.split_first().ok_or("missing RORG")?;
+                let destination = destination.first().map(|value| address(value)).transpose()?;
+                Self::Send { rorg: Rorg::from(*rorg), data: data.to_vec(), destination }
+            }
+            ["filter", "list"] => Self::FilterList,
+            ["filter", "allow", value] => Self::FilterAllow(address(value)?),
+            ["filter", "block", value] => Self::FilterBlock(address(value)?),
+            ["filter", "forget", value] => Self::FilterForget(address(value)?),
+            ["filter", "rorg", value] =>
+                Self::FilterRorg(Rorg::from(u8::from_str_radix(value, 16).map_err(|e| format!("bad RORG {}: {}", value, e))?)),
+            ["filter", "rssi", "off"] => Self::FilterRssi(None),
+            ["filter", "rssi", value] =>
+                Self::FilterRssi(Some(value.parse().map_err(|e| format!("bad signal strength {}: {}", value, e))?)),
+            ["filter", "clear"] => Self::FilterClear,
+            ["devices"] => Self::Devices,
+            ["help"] => Self::Help,
+            ["quit"] | ["exit"] => Self::Quit,
+            _ => return Err(format!("unknown command: {} (try help)", line.trim())),
+        }))
+    }
+}
+
+struct Session {
+    port: Port,
+    base: IdBase,
+    learn: LearnManager,
+    registry: DeviceRegistry,
+    filter: ReceiveFilter,
+}
+
+impl Session {
+    fn open(port_name: &str) -> Result<Self, String> {
+        let mut port = Port::open(port_name).map_err(|e| format!("cannot open {}: {}", port_name, e))?;
+        let base = port.read_id_base().map_err(|e| format!("cannot read base ID: {}", e))?;
+        Ok(Self { port, base, learn: LearnManager::new(base.0), registry: DeviceRegistry::new(), filter: ReceiveFilter::new() })
+    }
+
+    /// Run a command, returning `false` to exit
+    fn execute(&mut self, command: Command) -> Result<bool, String> {
+        match command {
+            Command::Version => {
+                let version = self.port.read_version_information().map_err(|e| format!("cannot read version: {}", e))?;
+                println!("{}", version);
+            }
+            Command::IdBase => println!("{}", self.base.0),
+            Command::LearnOn(window) => {
+                self.learn.start(window);
+                println!("learning for {} s", window.as_secs());
+            }
+            Command::LearnOff => {
+                self.learn.stop();
+                println!("learning stopped");
+            }
+            Command::Send { rorg, data, destination } => {
+                let erp = match destination {
+                    Some(destination) => RadioErp1::addressed(rorg, &data, self.base.0, destination),
+                    None => RadioErp1 { choice: rorg, user_data: data.as_slice().into(), sender_id: self.base.0, status: 0, subtel_num: None, destination: None, rssi: None, security: None },
+                };
+                let response = self.port.write_packet(Packet::RadioErp1(erp)).map_err(|e| format!("cannot send: {}", e))?;
+                response.code.result().map_err(|e| format!("gateway refused the telegram: {}", e))?;
+                println!("sent {:02x}{} from {}", u8::from(rorg), hex::encode(&data), self.base.0);
+            }
+            Command::FilterList => {
+                let list = |addresses: Vec<String>| if addresses.is_empty() { "-".to_owned() } else { addresses.join(" ") };
+                println!("allowed senders: {}", list(self.filter.allowed_senders().map(Address::to_string).collect()));
+                println!("blocked senders: {}", list(self.filter.blocked_senders().map(Address::to_string).collect()));
+                println!("allowed RORGs: {}", list(self.filter.allowed_rorgs().map(|rorg| format!("{:02x}", u8::from(rorg))).collect()));
+                println!("minimum signal: {}", self.filter.min_rssi().map(|dbm| format!("{}dBm", dbm)).unwrap_or_else(|| "-".into()));
+            }
+            Command::FilterAllow(address) => self.filter.allow_sender(address),
+            Command::FilterBlock(address) => self.filter.block_sender(address),
+            Command::FilterForget(address) => self.filter.forget_sender(&address),
+            Command::FilterRorg(rorg) => self.filter.allow_rorg(rorg),
+            Command::FilterRssi(dbm) => self.filter.set_min_rssi(dbm),
+            Command::FilterClear => self.filter.clear(),
+            Command::Devices => {
+                for (address, entry) in self.registry.iter() {
+                    println!("{} {}", address, entry.eep);
+                }
+            }
+            Command::Help => println!("{}", HELP),
+            Command::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Print a received telegram, and enroll its sender if it is a teach-in
+    fn receive(&mut self, erp: &RadioErp1, rssi: Option<i16>) -> Result<(), String> {
+        let outcome = self.learn.handle(erp);
+        if let Some(response) = outcome.response {
+            self.port.write_frame(&response).map_err(|e| format!("cannot send teach-in response: {}", e))?;
+        }
+        if let Some(device) = outcome.learned {
+            self.registry.learn(&device);
+            match device.eep {
+                Some(eep) => println!("learned {} {}", device.address, eep),
+                None => println!("learned {} (unknown profile)", device.address),
+            }
+        }
+        if self.filter.accepts(erp) {
+            self.print(erp, rssi);
+        }
+        Ok(())
+    }
+
+    fn print(&self, erp: &RadioErp1, rssi: Option<i16>) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let rorg: u8 = erp.choice.into();
+        let rssi = rssi.map(|rssi| format!("{}dBm", rssi)).unwrap_or_else(|| "-".into());
+        let mut line = format!("{}.{:03} {} {:02X} {} {}", now.as_secs(), now.subsec_millis(), erp.sender_id, rorg, rssi, hex::encode(&erp.user_data));
+        if let Some(entry) = self.registry.get(&erp.sender_id) {
+            match self.registry.decode(erp) {
+                Ok(report) => line += &format!(" {} {:?}", entry.eep, report.values),
+                Err(e) => line += &format!(" {} ({})", entry.eep, e),
+            }
+        }
+        println!("{}", line);
+    }
+
+    /// Print the telegrams received until a command is typed, and run it
+    fn run(&mut self) -> Result<(), String> {
+        let (lines, commands) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        loop {
+            match commands.try_recv() {
+                Ok(line) => {
+                    match Command::parse(&line).and_then(|command| command.map_or(Ok(true), |command| self.execute(command))) {
+                        Ok(true) => {}
+                        Ok(false) => return Ok(()),
+                        Err(e) => eprintln!("{}", e),
+                    }
+                    continue;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+
+            let frame = match self.port.read_frame() {
+                Ok(frame) => frame,
+                Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(e) => return Err(format!("read error: {}", e)),
+            };
+            match Packet::decode(frame.as_ref()) {
+                Ok(Packet::RadioErp1(erp)) => self.receive(&erp, erp.rssi_dbm())?,
+                Ok(Packet::RadioSubTel(sub)) => self.receive(&sub.erp, sub.best_rssi_dbm())?,
+                _ => {}
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let (Some(port_name), None) = (args.next(), args.next()) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    match Session::open(&port_name).and_then(|mut session| session.run()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
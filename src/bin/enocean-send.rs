@@ -0,0 +1,147 @@
+//! Send telegrams through an EnOcean gateway
+//!
+//! ```text
+//! enocean-send /dev/ttyUSB0 raw a500008008
+//! enocean-send /dev/ttyUSB0 a5-38-08 --dim 80 --ramp 2 --dest 0501abcd
+//! enocean-send /dev/ttyUSB0 a5-38-08 --switch off
+//! enocean-send /dev/ttyUSB0 d2-01 --channel 0 --value 100 --dest 0501abcd
+//! ```
+//!
+//! In raw mode, the hex string is the RORG followed by the user data. Telegrams are sent
+//! from the base ID of the gateway, plus `--offset` (0 to 127), and broadcast unless
+//! `--dest` is given.
+
+use std::process::ExitCode;
+
+use enocean::eep::a5_38::CentralCommand;
+use enocean::eep::d2_01::{Command, DimMode};
+use enocean::enocean::Rorg;
+use enocean::packet::{Address, Packet, RadioErp1};
+use enocean::port::Port;
+
+const USAGE: &str = "usage: enocean-send <port> [--dest ADDRESS] [--offset N] (raw HEX | a5-38-08 (--switch on|off | --dim N) [--ramp S] | d2-01 --channel N --value N)";
+
+#[derive(Default)]
+struct Options {
+    port: String,
+    destination: Option<Address>,
+    offset: u8,
+    mode: Vec<String>,
+    switch: Option<bool>,
+    dim: Option<u8>,
+    ramp: u8,
+    channel: u8,
+    value: Option<u8>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+            let number = |value: String| value.parse::<u8>().map_err(|e| format!("bad number {}: {}", value, e));
+            match arg.as_str() {
+                "--dest" => {
+                    let value = value()?;
+                    options.destination = Some(value.parse().map_err(|e| format!("bad address {}: {}", value, e))?);
+                }
+                "--offset" => options.offset = number(value()?)?,
+                "--switch" => options.switch = Some(match value()?.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    other => return Err(format!("expected on or off, got {}", other)),
+                }),
+                "--dim" => options.dim = Some(number(value()?)?),
+                "--ramp" => options.ramp = number(value()?)?,
+                "--channel" => options.channel = number(value()?)?,
+                "--value" => options.value = Some(number(value()?)?),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ if options.port.is_empty() => options.port = arg,
+                _ => options.mode.push(arg),
+            }
+        }
+        if options.port.is_empty() {
+            return Err("missing port".into());
+        }
+        Ok(options)
+    }
+
+    /// RORG and user data of the telegram to send
+    fn telegram(&self) -> Result<(Rorg, Vec<u8>), String> {
+        match self.mode.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            ["raw", data] => {
+                let data = hex::decode(data).map_err(|e| format!("bad hex data: {}", e))?;
+                let (rorg, data) = data.split_first().ok_or("missing RORG")?;
+                Ok((Rorg::from(*rorg), data.to_vec()))
+            }
+            ["a5-38-08"] => {
+                let command = match (self.switch, self.dim) {
+                    (Some(on), None) => CentralCommand::Switching { on, time: 0, delay: false, lock: false },
+                    (None, Some(value)) => CentralCommand::Dimming { on: value > 0, value, relative: true, ramp: self.ramp, store: false },
+                    _ => return Err("expected one of --switch and --dim".into()),
+                };
+                Ok((Rorg::Bs4, command.encode().to_vec()))
+            }
+            ["d2-01"] => {
+                let value = self.value.ok_or("missing --value")?;
+                Ok((Rorg::Vld, Command::SetOutput { channel: self.channel, value, dim: DimMode::Immediate }.encode()))
+            }
+            _ => Err("expected a mode: raw, a5-38-08 or d2-01".into()),
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let (rorg, data) = options.telegram()?;
+    let mut port = Port::open(&options.port).map_err(|e| format!("cannot open {}: {}", options.port, e))?;
+    let base = port.read_id_base().map_err(|e| format!("cannot read base ID: {}", e))?;
+    let sender = base.sender(options.offset).ok_or_else(|| format!("offset {} out of the base ID range", options.offset))?;
+
+    let erp = match options.destination {
+        Some(destination) => RadioErp1::addressed(rorg, &data, sender, destination),
+        None => RadioErp1 { choice: rorg, user_data: data.as_slice().into(), sender_id: sender, status: 0, subtel_num: None, destination: None, rssi: None, security: None },
+    };
+    let response = port.write_packet(Packet::RadioErp1(erp)).map_err(|e| format!("cannot send: {}", e))?;
+    response.code.result().map_err(|e| format!("gateway refused the telegram: {}", e))?;
+    println!("sent {:02x}{} from {}", u8::from(rorg), hex::encode(&data), sender);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_documented_examples_then_build_their_telegrams() {
+        let examples: Vec<_> = include_str!("enocean-send.rs").lines()
+            .filter_map(|line| line.strip_prefix("//! enocean-send "))
+            .collect();
+        assert_eq!(examples.len(), 4);
+        for example in examples {
+            let options = Options::parse(example.split_whitespace().map(String::from)).unwrap();
+            assert!(options.telegram().is_ok(), "{}", example);
+        }
+
+        let options = Options::parse(["/dev/ttyUSB0", "raw", "a500008008"].into_iter().map(String::from)).unwrap();
+        assert_eq!(options.telegram().unwrap(), (Rorg::Bs4, vec![0x00, 0x00, 0x80, 0x08]));
+        let options = Options::parse(["/dev/ttyUSB0", "raw", "a5020000088"].into_iter().map(String::from)).unwrap();
+        assert!(options.telegram().is_err());
+    }
+}
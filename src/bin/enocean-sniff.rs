@@ -0,0 +1,113 @@
+//! Print the telegrams received by an EnOcean gateway
+//!
+//! ```text
+//! enocean-sniff /dev/ttyUSB0 --device 0180998e=A5-02-05 --rorg a5
+//! 1697371200.123 0180998e A5 -45dBm 00008008 A5-02-05 Temperature(Temperature(19.92))
+//! ```
+//!
+//! Telegrams from the devices given with `--device` are decoded with their profile.
+//! `--address` and `--rorg` only keep the telegrams from the given senders, and with the
+//! given RORGs; both can be repeated.
+
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use enocean::packet::{Address, EEPProfileCode, Packet, RadioErp1};
+use enocean::port::Port;
+use enocean::registry::{DeviceEntry, DeviceRegistry};
+use enocean::FrameReadError;
+
+const USAGE: &str = "usage: enocean-sniff <port> [--device ADDRESS=EEP]... [--address ADDRESS]... [--rorg RORG]...";
+
+#[derive(Default)]
+struct Options {
+    port: String,
+    registry: DeviceRegistry,
+    addresses: Vec<Address>,
+    rorgs: Vec<u8>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--device" => {
+                    let value = value()?;
+                    let (address, eep) = value.split_once('=').ok_or_else(|| format!("expected ADDRESS=EEP, got {}", value))?;
+                    let address: Address = address.parse().map_err(|e| format!("bad address {}: {}", address, e))?;
+                    let eep: EEPProfileCode = eep.parse().map_err(|e| format!("bad profile {}: {}", eep, e))?;
+                    options.registry.insert(address, DeviceEntry::new(eep));
+                }
+                "--address" => {
+                    let value = value()?;
+                    options.addresses.push(value.parse().map_err(|e| format!("bad address {}: {}", value, e))?);
+                }
+                "--rorg" => {
+                    let value = value()?;
+                    options.rorgs.push(u8::from_str_radix(&value, 16).map_err(|e| format!("bad RORG {}: {}", value, e))?);
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ if options.port.is_empty() => options.port = arg,
+                _ => return Err(format!("unexpected argument {}", arg)),
+            }
+        }
+        if options.port.is_empty() {
+            return Err("missing port".into());
+        }
+        Ok(options)
+    }
+
+    fn accepts(&self, erp: &RadioErp1) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(&erp.sender_id))
+            && (self.rorgs.is_empty() || self.rorgs.contains(&erp.choice.into()))
+    }
+}
+
+fn print(options: &Options, erp: &RadioErp1, rssi: Option<i16>) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let rorg: u8 = erp.choice.into();
+    let rssi = rssi.map(|rssi| format!("{}dBm", rssi)).unwrap_or_else(|| "-".into());
+    let mut line = format!("{}.{:03} {} {:02X} {} {}", now.as_secs(), now.subsec_millis(), erp.sender_id, rorg, rssi, hex::encode(&erp.user_data));
+    if let Some(entry) = options.registry.get(&erp.sender_id) {
+        match options.registry.decode(erp) {
+            Ok(report) => line += &format!(" {} {:?}", entry.eep, report.values),
+            Err(e) => line += &format!(" {} ({})", entry.eep, e),
+        }
+    }
+    println!("{}", line);
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let mut port = match Port::open(&options.port) {
+        Ok(port) => port,
+        Err(e) => {
+            eprintln!("cannot open {}: {}", options.port, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    loop {
+        let frame = match port.read_frame() {
+            Ok(frame) => frame,
+            Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(e) => {
+                eprintln!("read error: {}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        match Packet::decode(frame.as_ref()) {
+            Ok(Packet::RadioErp1(erp)) if options.accepts(&erp) => print(&options, &erp, erp.rssi_dbm()),
+            Ok(Packet::RadioSubTel(sub)) if options.accepts(&sub.erp) => print(&options, &sub.erp, sub.best_rssi_dbm()),
+            _ => {}
+        }
+    }
+}
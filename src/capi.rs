@@ -0,0 +1,310 @@
+//! C API (feature `capi`)
+//!
+//! A small C ABI to embed the crate in C and C++ applications: open a gateway, receive
+//! telegrams, decode them to JSON with the profiles of the known devices, and send
+//! telegrams from the base ID of the gateway.
+//!
+//! Build the library with `cargo rustc --release --features capi --crate-type cdylib` (or
+//! `staticlib`), and generate the header with `cbindgen --config cbindgen.toml --output enocean.h`.
+//!
+//! ```c
+//! EnoceanGateway *gateway = enocean_gateway_open("/dev/ttyUSB0");
+//! enocean_gateway_add_device(gateway, "0180998e", "A5-02-05");
+//! EnoceanTelegram telegram;
+//! while (enocean_gateway_receive(gateway, &telegram) >= 0) {
+//!     char *json = enocean_gateway_decode_json(gateway, &telegram);
+//!     if (json) { puts(json); enocean_string_free(json); }
+//! }
+//! enocean_gateway_close(gateway);
+//! ```
+//!
+//! Functions returning `int` return 0 on success and a negative value on failure. Pointers
+//! must be valid, and strings NUL-terminated.
+
+use std::ffi::{c_char, c_int, CStr, CString};
+
+use crate::eep::a5_38::CentralCommand;
+use crate::enocean::{ReturnCode, Rorg};
+use crate::packet::{Address, IdBase, Packet, RadioErp1, BROADCAST};
+use crate::port::Port;
+use crate::registry::{DeviceEntry, DeviceRegistry};
+use crate::FrameReadError;
+
+/// Maximum user data length of a telegram
+pub const ENOCEAN_MAX_DATA: usize = 64;
+
+/// Failure of an operation
+pub const ENOCEAN_ERROR: c_int = -1;
+/// Invalid argument
+pub const ENOCEAN_INVALID: c_int = -2;
+/// No telegram was received before the read timeout
+pub const ENOCEAN_TIMEOUT: c_int = 1;
+
+/// An open gateway, with its known devices
+pub struct EnoceanGateway {
+    port: Port,
+    registry: DeviceRegistry,
+    base: Option<IdBase>,
+}
+
+/// A received telegram
+#[repr(C)]
+pub struct EnoceanTelegram {
+    pub rorg: u8,
+    /// Sender address, as in the telegram (`0x0180998e` for `0180998e`)
+    pub sender: u32,
+    pub status: u8,
+    /// Signal strength in dBm, 0 if unknown
+    pub rssi_dbm: i16,
+    pub data_len: usize,
+    pub data: [u8; ENOCEAN_MAX_DATA],
+}
+
+/// Util : read a C string
+unsafe fn string<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Util : the destination of a telegram, `0xffffffff` to broadcast
+fn destination(address: u32) -> Address {
+    Address::new(address)
+}
+
+/// Open a gateway on a serial port. Returns NULL on failure.
+///
+/// # Safety
+/// `port` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_open(port: *const c_char) -> *mut EnoceanGateway {
+    let Some(port) = string(port) else { return std::ptr::null_mut() };
+    match Port::open(port) {
+        Ok(port) => Box::into_raw(Box::new(EnoceanGateway { port, registry: DeviceRegistry::new(), base: None })),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Close a gateway opened with [`enocean_gateway_open`]
+///
+/// # Safety
+/// `gateway` must come from [`enocean_gateway_open`], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_close(gateway: *mut EnoceanGateway) {
+    if !gateway.is_null() {
+        drop(Box::from_raw(gateway));
+    }
+}
+
+/// Register the profile of a device, e.g. `"0180998e"` and `"A5-02-05"`
+///
+/// # Safety
+/// `gateway` must be an open gateway, `address` and `eep` valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_add_device(gateway: *mut EnoceanGateway, address: *const c_char, eep: *const c_char) -> c_int {
+    let (Some(gateway), Some(address), Some(eep)) = (gateway.as_mut(), string(address), string(eep)) else { return ENOCEAN_INVALID };
+    let (Ok(address), Ok(eep)) = (address.parse(), eep.parse()) else { return ENOCEAN_INVALID };
+    gateway.registry.insert(address, DeviceEntry::new(eep));
+    0
+}
+
+/// Wait for the next telegram. Returns [`ENOCEAN_TIMEOUT`] if none was received within the
+/// read timeout of the port (100 ms).
+///
+/// # Safety
+/// `gateway` must be an open gateway, `telegram` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_receive(gateway: *mut EnoceanGateway, telegram: *mut EnoceanTelegram) -> c_int {
+    let (Some(gateway), Some(telegram)) = (gateway.as_mut(), telegram.as_mut()) else { return ENOCEAN_INVALID };
+    loop {
+        let frame = match gateway.port.read_frame() {
+            Ok(frame) => frame,
+            Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => return ENOCEAN_TIMEOUT,
+            Err(_) => return ENOCEAN_ERROR,
+        };
+        let Ok(Packet::RadioErp1(erp)) = Packet::decode(frame.as_ref()) else { continue };
+        if erp.user_data.len() > ENOCEAN_MAX_DATA {
+            continue;
+        }
+        telegram.rorg = erp.choice.into();
+        telegram.sender = erp.sender_id.into();
+        telegram.status = erp.status;
+        telegram.rssi_dbm = erp.rssi_dbm().unwrap_or(0);
+        telegram.data_len = erp.user_data.len();
+        telegram.data[..erp.user_data.len()].copy_from_slice(&erp.user_data);
+        return 0;
+    }
+}
+
+/// Decode a telegram from a registered device, as the JSON of [`crate::eep::Report`].
+/// Returns NULL if the device is unknown or the telegram cannot be decoded. The string must
+/// be freed with [`enocean_string_free`].
+///
+/// # Safety
+/// `gateway` must be an open gateway, `telegram` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_decode_json(gateway: *const EnoceanGateway, telegram: *const EnoceanTelegram) -> *mut c_char {
+    let (Some(gateway), Some(telegram)) = (gateway.as_ref(), telegram.as_ref()) else { return std::ptr::null_mut() };
+    let choice = Rorg::from(telegram.rorg);
+    let erp = RadioErp1 {
+        choice,
+        user_data: telegram.data[..telegram.data_len.min(ENOCEAN_MAX_DATA)].into(),
+        sender_id: telegram.sender.into(),
+        status: telegram.status,
+        subtel_num: None,
+        destination: None,
+        rssi: None,
+        security: None,
+    };
+    gateway.registry.decode(&erp).ok()
+        .and_then(|report| serde_json::to_string(&report).ok())
+        .and_then(|json| CString::new(json).ok())
+        .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Free a string returned by this library
+///
+/// # Safety
+/// `s` must come from this library, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Util : send a telegram from the base ID of the gateway, plus `offset`
+fn send(gateway: &mut EnoceanGateway, rorg: Rorg, data: &[u8], offset: u8, to: u32) -> c_int {
+    let base = match gateway.base {
+        Some(base) => base,
+        None => match gateway.port.read_id_base() {
+            Ok(base) => *gateway.base.insert(base),
+            Err(_) => return ENOCEAN_ERROR,
+        },
+    };
+    let Some(sender) = base.sender(offset) else { return ENOCEAN_INVALID };
+    let to = destination(to);
+    let erp = if to == BROADCAST {
+        RadioErp1 { choice: rorg, user_data: data.into(), sender_id: sender, status: 0, subtel_num: None, destination: None, rssi: None, security: None }
+    } else {
+        RadioErp1::addressed(rorg, data, sender, to)
+    };
+    match gateway.port.write_packet(Packet::RadioErp1(erp)) {
+        Ok(response) if response.code == ReturnCode::Ok => 0,
+        _ => ENOCEAN_ERROR,
+    }
+}
+
+/// Send a telegram with the given RORG and user data, from the base ID of the gateway plus
+/// `offset` (0 to 127), to `destination` (`0xffffffff` to broadcast)
+///
+/// # Safety
+/// `gateway` must be an open gateway, `data` point to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_send_raw(gateway: *mut EnoceanGateway, rorg: u8, data: *const u8, len: usize, offset: u8, destination: u32) -> c_int {
+    let Some(gateway) = gateway.as_mut() else { return ENOCEAN_INVALID };
+    let rorg = Rorg::from(rorg);
+    if data.is_null() && len > 0 {
+        return ENOCEAN_INVALID;
+    }
+    let data = if len == 0 { &[][..] } else { std::slice::from_raw_parts(data, len) };
+    send(gateway, rorg, data, offset, destination)
+}
+
+/// Send an A5-38-08 dimming command: `value` in %, 0 to switch off, `ramp` in s
+///
+/// # Safety
+/// `gateway` must be an open gateway.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_send_dim(gateway: *mut EnoceanGateway, value: u8, ramp: u8, offset: u8, destination: u32) -> c_int {
+    let Some(gateway) = gateway.as_mut() else { return ENOCEAN_INVALID };
+    let command = CentralCommand::Dimming { on: value > 0, value, relative: true, ramp, store: false };
+    send(gateway, Rorg::Bs4, &command.encode(), offset, destination)
+}
+
+/// Send an A5-38-08 switching command
+///
+/// # Safety
+/// `gateway` must be an open gateway.
+#[no_mangle]
+pub unsafe extern "C" fn enocean_gateway_send_switch(gateway: *mut EnoceanGateway, on: bool, offset: u8, destination: u32) -> c_int {
+    let Some(gateway) = gateway.as_mut() else { return ENOCEAN_INVALID };
+    let command = CentralCommand::Switching { on, time: 0, delay: false, lock: false };
+    send(gateway, Rorg::Bs4, &command.encode(), offset, destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ESP3Frame;
+    use crate::sim::Transceiver;
+
+    /// Util : a gateway on a simulated transceiver, as if opened
+    fn open(transceiver: &Transceiver) -> *mut EnoceanGateway {
+        Box::into_raw(Box::new(EnoceanGateway { port: Port::from_serial(transceiver.clone()), registry: DeviceRegistry::new(), base: None }))
+    }
+
+    fn telegram() -> EnoceanTelegram {
+        EnoceanTelegram { rorg: 0, sender: 0, status: 0, rssi_dbm: 0, data_len: 0, data: [0; ENOCEAN_MAX_DATA] }
+    }
+
+    #[test]
+    fn given_telegram_then_receive_decode_and_free_it() {
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        transceiver.inject(&ESP3Frame::assemble(0x01, &[0xa5, 0x00, 0x00, 0x80, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00], &[]));
+        let gateway = open(&transceiver);
+        let mut received = telegram();
+        unsafe {
+            assert_eq!(enocean_gateway_add_device(gateway, c"0180998e".as_ptr(), c"A5-02-05".as_ptr()), 0);
+            assert_eq!(enocean_gateway_receive(gateway, &mut received), 0);
+            assert_eq!((received.rorg, received.sender, received.data_len), (0xa5, 0x0180998e, 4));
+            assert_eq!(received.data[..4], [0x00, 0x00, 0x80, 0x08]);
+
+            let json = enocean_gateway_decode_json(gateway, &received);
+            assert!(!json.is_null());
+            assert!(CStr::from_ptr(json).to_str().unwrap().contains(r#""eep":"A5-02-05""#));
+            enocean_string_free(json);
+
+            received.sender = 0x05060708;
+            assert!(enocean_gateway_decode_json(gateway, &received).is_null());
+            assert_eq!(enocean_gateway_receive(gateway, &mut received), ENOCEAN_TIMEOUT);
+
+            assert_eq!(enocean_gateway_send_switch(gateway, true, 1, 0x0501abcd), 0);
+            assert_eq!(enocean_gateway_send_raw(gateway, 0xf6, [0x30].as_ptr(), 1, 0, 0xffffffff), 0);
+            assert_eq!(enocean_gateway_send_raw(gateway, 0xf6, [0x30].as_ptr(), 1, 128, 0xffffffff), ENOCEAN_INVALID);
+            enocean_gateway_close(gateway);
+        }
+        let sent = transceiver.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!((sent[0].sender_id, sent[0].destination), ("ff9b1281".parse().unwrap(), Some(Address::new(0x0501abcd))));
+        assert_eq!((sent[1].choice, &sent[1].user_data[..]), (Rorg::Rps, &[0x30][..]));
+    }
+
+    #[test]
+    fn given_null_pointers_then_reject_them() {
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        let gateway = open(&transceiver);
+        let mut received = telegram();
+        unsafe {
+            assert!(enocean_gateway_open(std::ptr::null()).is_null());
+            assert!(enocean_gateway_open(c"/nonexistent/tty".as_ptr()).is_null());
+            enocean_gateway_close(std::ptr::null_mut());
+            enocean_string_free(std::ptr::null_mut());
+
+            assert_eq!(enocean_gateway_add_device(std::ptr::null_mut(), c"0180998e".as_ptr(), c"A5-02-05".as_ptr()), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_add_device(gateway, std::ptr::null(), c"A5-02-05".as_ptr()), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_add_device(gateway, c"0180998e".as_ptr(), c"A5-02".as_ptr()), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_receive(std::ptr::null_mut(), &mut received), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_receive(gateway, std::ptr::null_mut()), ENOCEAN_INVALID);
+            assert!(enocean_gateway_decode_json(std::ptr::null(), &received).is_null());
+            assert!(enocean_gateway_decode_json(gateway, std::ptr::null()).is_null());
+            assert_eq!(enocean_gateway_send_raw(std::ptr::null_mut(), 0xf6, [0x30].as_ptr(), 1, 0, 0xffffffff), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_send_raw(gateway, 0xf6, std::ptr::null(), 1, 0, 0xffffffff), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_send_dim(std::ptr::null_mut(), 50, 0, 0, 0xffffffff), ENOCEAN_INVALID);
+            assert_eq!(enocean_gateway_send_switch(std::ptr::null_mut(), true, 0, 0xffffffff), ENOCEAN_INVALID);
+            enocean_gateway_close(gateway);
+        }
+        assert!(transceiver.sent().is_empty());
+    }
+}
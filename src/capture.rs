@@ -0,0 +1,209 @@
+//! Capture files: record received frames, and replay them later
+//!
+//! A capture starts with the magic `ESP3CAP1`, followed by one record per frame:
+//!
+//! | Bytes  | Field                                           |
+//! |--------|-------------------------------------------------|
+//! | 0..8   | Timestamp, in µs since the Unix epoch (BE)      |
+//! | 8..12  | Frame length (BE)                               |
+//! | 12..   | Complete ESP3 frame, from sync byte to data CRC |
+//!
+//! Captures can also be exchanged with other tools as pcapng files, see [`pcapng`].
+//!
+//! Record the frames of a port, then decode them again as if they came from the port:
+//! ```no_run
+//! use std::fs::File;
+//! use enocean::capture::{CaptureWriter, Replay};
+//! use enocean::packet::Packet;
+//! # let mut port = enocean::port::Port::open("/dev/ttyUSB0")?;
+//!
+//! let mut capture = CaptureWriter::new(File::create("field.cap")?)?;
+//! capture.record_now(&port.read_frame()?)?;
+//!
+//! for frame in Replay::new(File::open("field.cap")?)?.realtime(true) {
+//!     println!("{:?}", Packet::decode(frame?.as_ref()));
+//! }
+//! # Ok::<(),Box<dyn std::error::Error>>(())
+//! ```
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use thiserror::Error;
+
+use crate::frame::ESP3Frame;
+use crate::FrameReadError;
+
+pub mod pcapng;
+
+const MAGIC: &[u8; 8] = b"ESP3CAP1";
+
+/// Largest ESP3 frame: header, 65535 data bytes, 255 optional bytes and CRC
+pub const MAX_FRAME_LEN: usize = 6 + 0xffff + 0xff + 1;
+
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("IO Error")]                 Io(#[from] io::Error),
+    #[error("Not a capture file")]       BadMagic,
+    #[error("Invalid pcapng block")]     BadBlock,
+    #[error("Record of {0} bytes")]      TooLarge(usize),
+    #[error("Invalid frame in capture")] Frame(#[from] FrameReadError),
+}
+
+/// A frame, with the time it was received
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub timestamp: SystemTime,
+    pub frame: ESP3Frame,
+}
+
+/// Writes frames to a capture
+pub struct CaptureWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Start a capture, writing its header
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        Ok(Self { writer })
+    }
+
+    pub fn record(&mut self, timestamp: SystemTime, frame: &ESP3Frame) -> io::Result<()> {
+        let micros = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+        let mut bytes = Vec::new();
+        frame.write_to(&mut bytes)?;
+        self.writer.write_all(&micros.to_be_bytes())?;
+        self.writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+        self.writer.write_all(&bytes)
+    }
+
+    /// Record a frame received just now
+    pub fn record_now(&mut self, frame: &ESP3Frame) -> io::Result<()> {
+        self.record(SystemTime::now(), frame)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads the records of a capture
+pub struct CaptureReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Open a capture, checking its header
+    pub fn new(mut reader: R) -> Result<Self, CaptureError> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(CaptureError::BadMagic);
+        }
+        Ok(Self { reader })
+    }
+
+    fn read_record(&mut self) -> Result<Option<Record>, CaptureError> {
+        let mut header = [0; 12];
+        // A capture may end anywhere between records
+        let read = self.reader.read(&mut header)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.reader.read_exact(&mut header[read..])?;
+
+        let micros = u64::from_be_bytes(header[..8].try_into().unwrap());
+        let length = u32::from_be_bytes(header[8..].try_into().unwrap()) as usize;
+        if length > MAX_FRAME_LEN {
+            return Err(CaptureError::TooLarge(length));
+        }
+        let mut bytes = vec![0; length];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(Some(Record {
+            timestamp: UNIX_EPOCH + Duration::from_micros(micros),
+            frame: ESP3Frame::read_from(&mut &bytes[..])?,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<Record, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Yields the frames of a capture, optionally with their original timing
+pub struct Replay<R: Read> {
+    records: CaptureReader<R>,
+    realtime: bool,
+    start: Option<(SystemTime, Instant)>,
+}
+
+impl<R: Read> Replay<R> {
+    pub fn new(reader: R) -> Result<Self, CaptureError> {
+        Ok(Self { records: CaptureReader::new(reader)?, realtime: false, start: None })
+    }
+
+    /// Wait between frames as long as between their original reception
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+}
+
+impl<R: Read> Iterator for Replay<R> {
+    type Item = Result<ESP3Frame, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+        if self.realtime {
+            let (captured, replayed) = *self.start.get_or_insert((record.timestamp, Instant::now()));
+            let offset = record.timestamp.duration_since(captured).unwrap_or_default();
+            if let Some(wait) = (replayed + offset).checked_duration_since(Instant::now()) {
+                std::thread::sleep(wait);
+            }
+        }
+        Some(Ok(record.frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_recorded_frames_then_replay_them() {
+        let first = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let second = ESP3Frame::assemble(0x02, &[0x00], &[]);
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let mut capture = CaptureWriter::new(Vec::new()).unwrap();
+        capture.record(start, &first).unwrap();
+        capture.record(start + Duration::from_millis(20), &second).unwrap();
+        let bytes = capture.into_inner();
+
+        let records: Vec<Record> = CaptureReader::new(&bytes[..]).unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].timestamp, start + Duration::from_millis(20));
+        assert_eq!(records[0].frame.data(), first.data());
+
+        let began = Instant::now();
+        let frames: Vec<ESP3Frame> = Replay::new(&bytes[..]).unwrap().realtime(true).collect::<Result<_, _>>().unwrap();
+        assert!(began.elapsed() >= Duration::from_millis(20));
+        assert_eq!(frames[1].packet_type(), 0x02);
+
+        assert!(matches!(CaptureReader::new(&b"not a capture"[..]), Err(CaptureError::BadMagic)));
+        let corrupt = [&MAGIC[..], &[0; 8], &u32::MAX.to_be_bytes()].concat();
+        assert!(matches!(CaptureReader::new(&corrupt[..]).unwrap().next(), Some(Err(CaptureError::TooLarge(0xffff_ffff)))));
+    }
+}
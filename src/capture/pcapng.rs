@@ -0,0 +1,199 @@
+//! pcapng export and import
+//!
+//! Frames are stored as Enhanced Packet Blocks holding the complete ESP3 frame, on a single
+//! interface with a user link type, so that the capture can be opened in Wireshark with an
+//! EnOcean dissector mapped to that link type. Timestamps have the default resolution (µs).
+//!
+//! Files written by other tools are read as long as they use one section and the default
+//! timestamp resolution; blocks other than packets are skipped.
+
+use std::borrow::Borrow;
+use std::io::{self, Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{CaptureError, Record};
+use crate::frame::ESP3Frame;
+
+/// `LINKTYPE_USER0`, the default link type of exported captures
+pub const LINKTYPE_USER0: u16 = 147;
+
+const SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const ENHANCED_PACKET: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+/// Largest block read, well above a packet of the largest frame
+const MAX_BLOCK_LEN: usize = 1 << 20;
+
+/// Writes frames to a pcapng capture
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Start a capture, writing its section header and interface description
+    pub fn new(mut writer: W, linktype: u16) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(48);
+        block(&mut header, SECTION_HEADER, &[
+            &BYTE_ORDER_MAGIC.to_le_bytes()[..],
+            &1u16.to_le_bytes(), &0u16.to_le_bytes(),
+            // Section length: unknown
+            &(-1i64).to_le_bytes(),
+        ].concat());
+        block(&mut header, INTERFACE_DESCRIPTION, &[&linktype.to_le_bytes()[..], &[0, 0], &0u32.to_le_bytes()].concat());
+        writer.write_all(&header)?;
+        Ok(Self { writer })
+    }
+
+    pub fn record(&mut self, timestamp: SystemTime, frame: &ESP3Frame) -> io::Result<()> {
+        self.record_raw(timestamp, frame.borrow())
+    }
+
+    /// Record the bytes of a frame as is, e.g. a frame failing its CRC seen by a tap
+    /// ([`crate::link::RawFrame`])
+    pub fn record_raw(&mut self, timestamp: SystemTime, bytes: &[u8]) -> io::Result<()> {
+        let micros = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+        let mut body = Vec::with_capacity(20 + bytes.len() + 3);
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        body.extend_from_slice(bytes);
+        let mut packet = Vec::new();
+        block(&mut packet, ENHANCED_PACKET, &body);
+        self.writer.write_all(&packet)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Util : append a block, padding its body to 32 bits
+fn block(out: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+    let padding = (4 - body.len() % 4) % 4;
+    let length = (12 + body.len() + padding) as u32;
+    out.extend_from_slice(&block_type.to_le_bytes());
+    out.extend_from_slice(&length.to_le_bytes());
+    out.extend_from_slice(body);
+    out.extend_from_slice(&[0; 3][..padding]);
+    out.extend_from_slice(&length.to_le_bytes());
+}
+
+/// Reads the frames of a pcapng capture
+pub struct PcapngReader<R: Read> {
+    reader: R,
+    big_endian: bool,
+}
+
+impl<R: Read> PcapngReader<R> {
+    /// Open a capture, checking its section header
+    pub fn new(reader: R) -> Result<Self, CaptureError> {
+        let mut this = Self { reader, big_endian: false };
+        match this.read_block()? {
+            Some((SECTION_HEADER, _)) => Ok(this),
+            _ => Err(CaptureError::BadMagic),
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = bytes[..4].try_into().unwrap();
+        if self.big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) }
+    }
+
+    /// Read a block, returning its type and body
+    fn read_block(&mut self) -> Result<Option<(u32, Vec<u8>)>, CaptureError> {
+        let mut header = [0; 8];
+        let read = self.reader.read(&mut header)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.reader.read_exact(&mut header[read..])?;
+
+        let block_type = self.u32(&header);
+        let mut body = vec![];
+        if block_type == SECTION_HEADER {
+            // The byte order of the section is only known from the first field of the body
+            let mut magic = [0; 4];
+            self.reader.read_exact(&mut magic)?;
+            self.big_endian = match u32::from_le_bytes(magic) {
+                BYTE_ORDER_MAGIC => false,
+                m if m.swap_bytes() == BYTE_ORDER_MAGIC => true,
+                _ => return Err(CaptureError::BadMagic),
+            };
+            body.extend_from_slice(&magic);
+        }
+        let length = self.u32(&header[4..]) as usize;
+        if length < 12 + body.len() || !length.is_multiple_of(4) {
+            return Err(CaptureError::BadBlock);
+        }
+        if length > MAX_BLOCK_LEN {
+            return Err(CaptureError::TooLarge(length));
+        }
+        let start = body.len();
+        body.resize(length - 8, 0);
+        self.reader.read_exact(&mut body[start..])?;
+        body.truncate(length - 12);
+        Ok(Some((block_type, body)))
+    }
+}
+
+impl<R: Read> Iterator for PcapngReader<R> {
+    type Item = Result<Record, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let body = match self.read_block() {
+                Ok(Some((ENHANCED_PACKET, body))) => body,
+                Ok(Some(_)) => continue,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+            if body.len() < 20 {
+                return Some(Err(CaptureError::BadBlock));
+            }
+            let micros = (self.u32(&body[4..]) as u64) << 32 | self.u32(&body[8..]) as u64;
+            let captured = self.u32(&body[12..]) as usize;
+            let Some(bytes) = body.get(20..20 + captured) else { return Some(Err(CaptureError::BadBlock)) };
+            return Some(ESP3Frame::read_from(&mut &bytes[..])
+                .map(|frame| Record { timestamp: UNIX_EPOCH + Duration::from_micros(micros), frame })
+                .map_err(CaptureError::from));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_exported_frames_then_import_them() {
+        let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let timestamp = UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456);
+
+        let mut writer = PcapngWriter::new(Vec::new(), LINKTYPE_USER0).unwrap();
+        writer.record(timestamp, &frame).unwrap();
+        let bytes = writer.into_inner();
+        assert_eq!(bytes[..4], [0x0a, 0x0d, 0x0d, 0x0a]);
+        assert_eq!(bytes.len() % 4, 0);
+
+        let records: Vec<Record> = PcapngReader::new(&bytes[..]).unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, timestamp);
+        assert_eq!(records[0].frame.data(), frame.data());
+
+        let mut corrupt = bytes.clone();
+        corrupt.extend_from_slice(&[&ENHANCED_PACKET.to_le_bytes()[..], &0xffff_fffcu32.to_le_bytes()].concat());
+        let mut reader = PcapngReader::new(&corrupt[..]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(reader.next(), Some(Err(CaptureError::TooLarge(0xffff_fffc)))));
+        corrupt.truncate(bytes.len() + 4);
+        corrupt.extend_from_slice(&8u32.to_le_bytes());
+        assert!(matches!(PcapngReader::new(&corrupt[..]).unwrap().nth(1), Some(Err(CaptureError::BadBlock))));
+    }
+}
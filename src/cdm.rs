@@ -0,0 +1,219 @@
+//! Chained data messages (CDM, RORG 0x40)
+//!
+//! Messages too long for a single telegram, mostly VLD, are split into a chain of CDM
+//! telegrams. Each telegram starts with a sequence number, shared by the whole chain, and
+//! its index in the chain:
+//!
+//! | Byte | First telegram (IDX = 0)              | Following telegrams   |
+//! |------|---------------------------------------|-----------------------|
+//! | 0    | .7..6 SEQ, .5..0 IDX                  | .7..6 SEQ, .5..0 IDX  |
+//! | 1..3 | Length of the message data            | Data, continued       |
+//! | 3    | RORG of the message                   |                       |
+//! | 4..  | Data                                  |                       |
+//!
+//! The [`ChainAssembler`] rebuilds the messages on the receiving side, and [`fragment`]
+//! splits outgoing messages.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::enocean::Rorg;
+use crate::packet::{Address, RadioErp1};
+
+/// Maximum user data of a radio telegram
+const MAX_USER_DATA: usize = 14;
+
+/// Maximum number of telegrams in a chain, numbered by a 6 bits index
+pub const MAX_CHAIN_LENGTH: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum ChainError {
+    #[error("Not a chained data telegram")]       WrongRorg(Rorg),
+    #[error("Bad chained data telegram length")]  BadLength(usize),
+    #[error("Telegram out of chain sequence")]    Sequence,
+    #[error("Chained message length mismatch")]   LengthMismatch { expected: usize, received: usize },
+    #[error("Message too long for a chain")]      TooLong(usize),
+}
+
+/// A message rebuilt from a chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainedMessage {
+    pub rorg: Rorg,
+    pub data: Vec<u8>,
+    pub sender_id: Address,
+    pub status: u8,
+    pub destination: Option<Address>,
+}
+
+impl ChainedMessage {
+    /// View the message as a single telegram
+    pub fn erp1(&self) -> RadioErp1<'_> {
+        RadioErp1 {
+            choice: self.rorg,
+            user_data: Cow::Borrowed(&self.data),
+            sender_id: self.sender_id,
+            status: self.status,
+            subtel_num: None,
+            destination: self.destination,
+            rssi: None,
+            security: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Chain {
+    rorg: Rorg,
+    length: usize,
+    data: Vec<u8>,
+    next_index: u8,
+    started: Instant,
+}
+
+/// Reassembles chained messages, per sender and sequence number
+#[derive(Debug, Clone)]
+pub struct ChainAssembler {
+    pending: HashMap<(Address, u8), Chain>,
+    timeout: Duration,
+}
+
+impl Default for ChainAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainAssembler {
+    /// An assembler dropping incomplete chains after 1 second
+    pub fn new() -> Self {
+        Self { pending: HashMap::new(), timeout: Duration::from_secs(1) }
+    }
+
+    /// How long to wait for the rest of a chain
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of incomplete chains
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Process a CDM telegram. Returns the message once its chain is complete.
+    pub fn push(&mut self, erp: &RadioErp1) -> Result<Option<ChainedMessage>, ChainError> {
+        if erp.choice != Rorg::Cdm {
+            return Err(ChainError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.len() < 2 {
+            return Err(ChainError::BadLength(d.len()));
+        }
+        let now = Instant::now();
+        let timeout = self.timeout;
+        self.pending.retain(|_, chain| now.duration_since(chain.started) < timeout);
+
+        let (sequence, index) = (d[0] >> 6, d[0] & 0x3f);
+        let key = (erp.sender_id, sequence);
+        if index == 0 {
+            if d.len() < 4 {
+                return Err(ChainError::BadLength(d.len()));
+            }
+            let rorg = Rorg::from(d[3]);
+            let length = u16::from_be_bytes([d[1], d[2]]) as usize;
+            self.pending.insert(key, Chain { rorg, length, data: d[4..].to_vec(), next_index: 1, started: now });
+        } else {
+            let chain = self.pending.get_mut(&key)
+                .filter(|chain| chain.next_index == index)
+                .ok_or(ChainError::Sequence)?;
+            chain.data.extend_from_slice(&d[1..]);
+            chain.next_index += 1;
+        }
+
+        if self.pending[&key].data.len() < self.pending[&key].length {
+            return Ok(None);
+        }
+        let chain = self.pending.remove(&key).unwrap();
+        if chain.data.len() != chain.length {
+            return Err(ChainError::LengthMismatch { expected: chain.length, received: chain.data.len() });
+        }
+        Ok(Some(ChainedMessage {
+            rorg: chain.rorg,
+            data: chain.data,
+            sender_id: erp.sender_id,
+            status: erp.status,
+            destination: erp.destination,
+        }))
+    }
+}
+
+/// Split a message into the user data of CDM telegrams, with sequence number `sequence` (0..3).
+/// Messages needing more than [`MAX_CHAIN_LENGTH`] telegrams are refused.
+pub fn fragment(rorg: Rorg, data: &[u8], sequence: u8) -> Result<Vec<Vec<u8>>, ChainError> {
+    let header = |index: usize| (sequence & 0x03) << 6 | index as u8;
+    let first_size = data.len().min(MAX_USER_DATA - 4);
+    let (first, rest) = data.split_at(first_size);
+    if 1 + rest.len().div_ceil(MAX_USER_DATA - 1) > MAX_CHAIN_LENGTH {
+        return Err(ChainError::TooLong(data.len()));
+    }
+
+    let mut telegram = vec![header(0)];
+    telegram.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    telegram.push(rorg.into());
+    telegram.extend_from_slice(first);
+
+    let mut telegrams = vec![telegram];
+    for (index, chunk) in rest.chunks(MAX_USER_DATA - 1).enumerate() {
+        telegrams.push([&[header(index + 1)], chunk].concat());
+    }
+    Ok(telegrams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    fn telegram(user_data: &[u8]) -> Vec<u8> {
+        [&[0x40], user_data, &[0x01, 0x02, 0x03, 0x04, 0x00]].concat()
+    }
+
+    #[test]
+    fn given_long_message_then_fragment_and_reassemble_it() {
+        let data: Vec<u8> = (0..30).collect();
+        let fragments = fragment(Rorg::Vld, &data, 2).unwrap();
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(fragments[0][..4], [0x80, 0x00, 30, 0xd2]);
+        assert_eq!(fragments[2][0], 0x82);
+
+        let mut assembler = ChainAssembler::new();
+        assert!(matches!(assembler.push(&erp1(&telegram(&fragments[1]))), Err(ChainError::Sequence)));
+        assert_eq!(assembler.push(&erp1(&telegram(&fragments[0]))).unwrap(), None);
+        assert_eq!(assembler.push(&erp1(&telegram(&fragments[1]))).unwrap(), None);
+        let message = assembler.push(&erp1(&telegram(&fragments[2]))).unwrap().unwrap();
+        assert_eq!(message.rorg, Rorg::Vld);
+        assert_eq!(message.data, data);
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn given_incomplete_chain_then_drop_it_after_timeout() {
+        let fragments = fragment(Rorg::Vld, &[0; 20], 0).unwrap();
+        let mut assembler = ChainAssembler::new().with_timeout(Duration::ZERO);
+        assembler.push(&erp1(&telegram(&fragments[0]))).unwrap();
+        assert!(matches!(assembler.push(&erp1(&telegram(&fragments[1]))), Err(ChainError::Sequence)));
+    }
+
+    #[test]
+    fn given_message_longer_than_a_chain_then_refuse_it() {
+        let longest = MAX_USER_DATA - 4 + (MAX_CHAIN_LENGTH - 1) * (MAX_USER_DATA - 1);
+        let fragments = fragment(Rorg::Vld, &vec![0; longest], 1).unwrap();
+        assert_eq!(fragments.len(), MAX_CHAIN_LENGTH);
+        assert_eq!(fragments[MAX_CHAIN_LENGTH - 1][0], 0x7f);
+        assert!(matches!(fragment(Rorg::Vld, &vec![0; longest + 1], 1), Err(ChainError::TooLong(n)) if n == longest + 1));
+        assert!(matches!(fragment(Rorg::Vld, &vec![0; 70000], 1), Err(ChainError::TooLong(70000))));
+    }
+}
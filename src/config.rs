@@ -0,0 +1,307 @@
+//! Transceiver settings, saved and restored
+//!
+//! A transceiver keeps a few settings across restarts, read and written with common
+//! commands: its base ID, repeater, filters, noise threshold and transparent mode, and
+//! whether learn mode is on. A [`GatewayConfig`] snapshots them from one transceiver, and
+//! writes them to others, e.g. to provision a fleet of USB gateways with identical settings
+//! (`device::Gateway::export_config` and `apply_config`). With the `serde` feature, it is
+//! saved as e.g. JSON:
+//!
+//! ```json
+//! {
+//!   "base_id": "ff9b1280",
+//!   "repeater": { "mode": "On", "level": 1 },
+//!   "filters": { "operator": "Or", "entries": [{ "criterion": "Sender", "value": 25204110, "kind": "Apply" }] },
+//!   "learn_mode": false,
+//!   "transparent_mode": false,
+//!   "noise_threshold": -96
+//! }
+//! ```
+//!
+//! Settings the transceiver does not support are `None`, and applying `None` leaves a
+//! setting as it is. The transceiver does not report the kind of its filters, nor how they
+//! combine: exported filters have no kind and operator, to be filled in before applying.
+//! The base ID can only be written a few times, so it is only written when it differs.
+
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+use crate::enocean::CommandError;
+use crate::packet::{Address, CommonCommand, Packet, Response};
+use crate::port::Port;
+use crate::PacketError;
+
+const WR_IDBASE: u8 = 0x07;
+const WR_REPEATER: u8 = 0x09;
+const RD_REPEATER: u8 = 0x0a;
+const WR_FILTER_ADD: u8 = 0x0b;
+const WR_FILTER_DEL_ALL: u8 = 0x0d;
+const WR_FILTER_ENABLE: u8 = 0x0e;
+const RD_FILTER: u8 = 0x0f;
+const WR_LEARNMODE: u8 = 0x17;
+const RD_LEARNMODE: u8 = 0x18;
+const SET_NOISETHRESHOLD: u8 = 0x32;
+const GET_NOISETHRESHOLD: u8 = 0x33;
+const WR_TRANSPARENT_MODE: u8 = 0x3e;
+const RD_TRANSPARENT_MODE: u8 = 0x3f;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Could not exchange with the gateway")]  Packet(#[from] PacketError),
+    #[error("Transceiver refused command {0:#04x}")]  Refused(u8, #[source] CommandError),
+    #[error("Invalid response to command {0:#04x}")] Response(u8),
+    #[error("Setting {0} missing")]                   Incomplete(&'static str),
+}
+
+/// Which telegrams the transceiver repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepeaterMode {
+    Off = 0,
+    On = 1,
+    /// Only the telegrams passing the repeating filters
+    Selective = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepeaterConfig {
+    pub mode: RepeaterMode,
+    /// Repeater level, 1 or 2
+    pub level: u8,
+}
+
+/// What a transceiver filter compares its value to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterCriterion {
+    Sender = 0,
+    Rorg = 1,
+    /// Signal strength, in -dBm
+    Rssi = 2,
+    Destination = 3,
+}
+
+/// What a transceiver filter does with the telegrams it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterKind {
+    /// Do not pass them to the host
+    Block = 0x00,
+    /// Only pass them to the host
+    Apply = 0x80,
+    /// Do not repeat them
+    BlockRepeated = 0x40,
+    /// Only repeat them
+    ApplyRepeated = 0xc0,
+}
+
+/// How the transceiver filters combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterOperator {
+    Or = 0,
+    And = 1,
+    /// OR for the telegrams passed to the host, AND for those repeated
+    RadioOrRepeatedAnd = 8,
+    /// AND for the telegrams passed to the host, OR for those repeated
+    RadioAndRepeatedOr = 9,
+}
+
+/// A filter of the transceiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransceiverFilter {
+    pub criterion: FilterCriterion,
+    pub value: u32,
+    /// Not reported by the transceiver
+    pub kind: Option<FilterKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterConfig {
+    /// Not reported by the transceiver
+    pub operator: Option<FilterOperator>,
+    /// No entry disables the filters
+    pub entries: Vec<TransceiverFilter>,
+}
+
+/// The settings of a transceiver. `None` for the settings it does not support, left as
+/// they are when applied.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GatewayConfig {
+    pub base_id: Option<Address>,
+    pub repeater: Option<RepeaterConfig>,
+    pub filters: Option<FilterConfig>,
+    pub learn_mode: Option<bool>,
+    pub transparent_mode: Option<bool>,
+    /// Telegrams weaker than this are not received, in dBm
+    pub noise_threshold: Option<i16>,
+}
+
+impl GatewayConfig {
+    /// Read the settings of the transceiver
+    pub fn read(port: &mut Port) -> Result<Self, ConfigError> {
+        let repeater = match query(port, RD_REPEATER)? {
+            Some(data) => {
+                let mode = match data.first() {
+                    Some(0) => RepeaterMode::Off,
+                    Some(1) => RepeaterMode::On,
+                    Some(2) => RepeaterMode::Selective,
+                    _ => return Err(ConfigError::Response(RD_REPEATER)),
+                };
+                Some(RepeaterConfig { mode, level: *data.get(1).ok_or(ConfigError::Response(RD_REPEATER))? })
+            }
+            None => None,
+        };
+        let filters = match query(port, RD_FILTER)? {
+            Some(data) => {
+                let entries = data.chunks_exact(5).map(|filter| {
+                    let criterion = match filter[0] {
+                        0 => FilterCriterion::Sender,
+                        1 => FilterCriterion::Rorg,
+                        2 => FilterCriterion::Rssi,
+                        3 => FilterCriterion::Destination,
+                        _ => return Err(ConfigError::Response(RD_FILTER)),
+                    };
+                    Ok(TransceiverFilter { criterion, value: u32::from_be_bytes(filter[1..].try_into().unwrap()), kind: None })
+                });
+                Some(FilterConfig { operator: None, entries: entries.collect::<Result<_, _>>()? })
+            }
+            None => None,
+        };
+        let flag = |data: Option<Vec<u8>>, code| data.map(|data| data.first().map(|&b| b != 0).ok_or(ConfigError::Response(code))).transpose();
+        let learn_mode = flag(query(port, RD_LEARNMODE)?, RD_LEARNMODE)?;
+        let transparent_mode = flag(query(port, RD_TRANSPARENT_MODE)?, RD_TRANSPARENT_MODE)?;
+        let noise_threshold = query(port, GET_NOISETHRESHOLD)?
+            .map(|data| data.first().map(|&rssi| -(rssi as i16)).ok_or(ConfigError::Response(GET_NOISETHRESHOLD)))
+            .transpose()?;
+        Ok(Self { base_id: Some(port.read_id_base()?.0), repeater, filters, learn_mode, transparent_mode, noise_threshold })
+    }
+
+    /// Take the settings set in `other`, keeping the others
+    pub fn update(&mut self, other: &GatewayConfig) {
+        self.base_id = other.base_id.or(self.base_id);
+        self.repeater = other.repeater.or(self.repeater);
+        self.filters = other.filters.clone().or(self.filters.take());
+        self.learn_mode = other.learn_mode.or(self.learn_mode);
+        self.transparent_mode = other.transparent_mode.or(self.transparent_mode);
+        self.noise_threshold = other.noise_threshold.or(self.noise_threshold);
+    }
+
+    /// The settings a transceiver loses when it restarts: all but the base ID, kept in its
+    /// flash, and learn mode, which times out anyway
+    pub fn volatile(&self) -> GatewayConfig {
+        GatewayConfig { base_id: None, learn_mode: None, ..self.clone() }
+    }
+
+    /// Write the settings to the transceiver. Settings left to `None` are not written. Filters
+    /// without kind or operator are rejected before anything is written.
+    pub fn apply(&self, port: &mut Port) -> Result<(), ConfigError> {
+        let filters = match &self.filters {
+            Some(filters) => {
+                let kinds = filters.entries.iter()
+                    .map(|filter| filter.kind.ok_or(ConfigError::Incomplete("filter kind")))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let enable = match filters.operator {
+                    _ if filters.entries.is_empty() => [0, 0],
+                    Some(operator) => [1, operator as u8],
+                    None => return Err(ConfigError::Incomplete("filter operator")),
+                };
+                Some((&filters.entries, kinds, enable))
+            }
+            None => None,
+        };
+
+        if let Some(base_id) = self.base_id {
+            if port.read_id_base()?.0 != base_id {
+                command(port, WR_IDBASE, &base_id.0)?;
+            }
+        }
+        if let Some(repeater) = self.repeater {
+            command(port, WR_REPEATER, &[repeater.mode as u8, repeater.level])?;
+        }
+        if let Some((entries, kinds, enable)) = filters {
+            command(port, WR_FILTER_DEL_ALL, &[])?;
+            for (filter, kind) in entries.iter().zip(kinds) {
+                command(port, WR_FILTER_ADD, &[&[filter.criterion as u8][..], &filter.value.to_be_bytes(), &[kind as u8]].concat())?;
+            }
+            command(port, WR_FILTER_ENABLE, &enable)?;
+        }
+        if let Some(learn_mode) = self.learn_mode {
+            // Default timeout
+            command(port, WR_LEARNMODE, &[learn_mode as u8, 0, 0, 0, 0])?;
+        }
+        if let Some(transparent_mode) = self.transparent_mode {
+            command(port, WR_TRANSPARENT_MODE, &[transparent_mode as u8])?;
+        }
+        if let Some(noise_threshold) = self.noise_threshold {
+            command(port, SET_NOISETHRESHOLD, &[noise_threshold.unsigned_abs().min(0xff) as u8])?;
+        }
+        Ok(())
+    }
+}
+
+/// Util : send a common command without a typed variant
+fn send(port: &mut Port, code: u8, data: &[u8]) -> Result<Response, PacketError> {
+    port.write_packet(Packet::CommonCommand(CommonCommand::Unknown { code, data: Cow::Borrowed(data), optional: Cow::Borrowed(&[]) }))
+}
+
+/// Util : send a command, failing if refused
+fn command(port: &mut Port, code: u8, data: &[u8]) -> Result<(), ConfigError> {
+    send(port, code, data)?.code.result().map_err(|e| ConfigError::Refused(code, e))
+}
+
+/// Util : send a read command, returning the response payload, `None` if not supported
+fn query(port: &mut Port, code: u8) -> Result<Option<Vec<u8>>, ConfigError> {
+    let response = send(port, code, &[])?;
+    match response.code.result() {
+        Ok(()) => Ok(Some(response.data.to_vec())),
+        Err(CommandError::NotSupported) => Ok(None),
+        Err(e) => Err(ConfigError::Refused(code, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::Transceiver;
+
+    #[test]
+    fn given_exported_config_then_apply_it_to_another_transceiver() {
+        let base = "ff9b1280".parse().unwrap();
+        let mut source = Port::from_serial(Transceiver::new(base));
+        let mut config = GatewayConfig::read(&mut source).unwrap();
+        assert_eq!(config.base_id, Some(base));
+        assert_eq!(config.filters, Some(FilterConfig::default()));
+
+        config.repeater = Some(RepeaterConfig { mode: RepeaterMode::Selective, level: 1 });
+        config.filters = Some(FilterConfig {
+            operator: None,
+            entries: vec![TransceiverFilter { criterion: FilterCriterion::Sender, value: 0x0180998e, kind: Some(FilterKind::Apply) }],
+        });
+        config.noise_threshold = Some(-96);
+        let mut target = Port::from_serial(Transceiver::new(base));
+        let blank = GatewayConfig::read(&mut target).unwrap();
+        assert!(matches!(config.apply(&mut target), Err(ConfigError::Incomplete("filter operator"))));
+        assert_eq!(GatewayConfig::read(&mut target).unwrap(), blank);
+        config.filters.as_mut().unwrap().entries[0].kind = None;
+        config.filters.as_mut().unwrap().operator = Some(FilterOperator::Or);
+        assert!(matches!(config.apply(&mut target), Err(ConfigError::Incomplete("filter kind"))));
+        assert_eq!(GatewayConfig::read(&mut target).unwrap(), blank);
+        config.filters.as_mut().unwrap().entries[0].kind = Some(FilterKind::Apply);
+        config.apply(&mut target).unwrap();
+
+        let applied = GatewayConfig::read(&mut target).unwrap();
+        assert_eq!(applied.repeater, config.repeater);
+        assert_eq!(applied.filters.unwrap().entries[0], TransceiverFilter { kind: None, ..config.filters.as_ref().unwrap().entries[0] });
+        assert_eq!((applied.noise_threshold, applied.transparent_mode), (Some(-96), Some(false)));
+
+        // The base ID of the simulator cannot change
+        config.base_id = Some("ff9b1300".parse().unwrap());
+        assert!(matches!(config.apply(&mut target), Err(ConfigError::Refused(WR_IDBASE, CommandError::BaseIdMaxReached))));
+    }
+}
@@ -0,0 +1,191 @@
+//! Suppression of unchanged sensor values
+//!
+//! Many sensors send their values periodically whether they changed or not, e.g. every
+//! 100 s, and the systems downstream get mostly duplicates. A [`ChangeFilter`] keeps the
+//! last values passed on for each watched device, and only passes new values on when they
+//! changed: measured quantities by more than the [`Deadband`] of their unit, and everything
+//! else at all. A [`Gateway`](crate::device::Gateway) with a change filter drops the values
+//! it rejects from [`Device::events`](crate::device::Device::events).
+//!
+//! ```
+//! use enocean::deadband::{ChangeFilter, Deadband};
+//! use enocean::eep::Decoded;
+//! use enocean::eep::quantity::Temperature;
+//!
+//! let sensor = "0180998e".parse()?;
+//! let mut changes = ChangeFilter::new();
+//! changes.watch(sensor, Deadband::new().with(Temperature::UNIT, 0.2));
+//!
+//! assert!(changes.accepts(sensor, &Decoded::Temperature(Temperature(21.0))));
+//! assert!(!changes.accepts(sensor, &Decoded::Temperature(Temperature(21.16))));
+//! assert!(changes.accepts(sensor, &Decoded::Temperature(Temperature(21.32))));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Values are compared to the last ones passed on rather than the last ones received, so
+//! that a slow drift passes once it exceeds the deadband. Rocker and push button telegrams
+//! are events rather than states, and always pass.
+
+use std::collections::BTreeMap;
+
+use crate::eep::quantity::{Illuminance, RelativeHumidity, Temperature, Voltage};
+use crate::eep::Decoded;
+use crate::packet::Address;
+
+/// How much measured quantities may change without being passed on, by unit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Deadband {
+    bands: Vec<(&'static str, f32)>,
+}
+
+impl Deadband {
+    /// A deadband passing any change
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pass the quantities in `unit`, e.g. [`Temperature::UNIT`], only when they changed by
+    /// more than `band`
+    pub fn with(mut self, unit: &'static str, band: f32) -> Self {
+        self.bands.retain(|(u, _)| *u != unit);
+        self.bands.push((unit, band.abs()));
+        self
+    }
+
+    /// The change allowed for quantities in `unit`, 0 if not set
+    pub fn band(&self, unit: &str) -> f32 {
+        self.bands.iter().find(|(u, _)| *u == unit).map_or(0.0, |(_, band)| *band)
+    }
+
+    /// Whether `current` is the same as `previous`, within the deadband
+    pub fn unchanged(&self, previous: &Decoded, current: &Decoded) -> bool {
+        let near = |unit: &str, a: f32, b: f32| (a - b).abs() <= self.band(unit);
+        let near_opt = |unit: &str, a: Option<f32>, b: Option<f32>| match (a, b) {
+            (Some(a), Some(b)) => near(unit, a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        let (t, h, l, v) = (Temperature::UNIT, RelativeHumidity::UNIT, Illuminance::UNIT, Voltage::UNIT);
+        match (previous, current) {
+            (Decoded::Temperature(a), Decoded::Temperature(b)) => near(t, a.0, b.0),
+            (Decoded::TemperatureHumidity(a), Decoded::TemperatureHumidity(b)) => {
+                near(h, a.humidity.0, b.humidity.0)
+                    && near_opt(t, a.temperature.map(|t| t.0), b.temperature.map(|t| t.0))
+                    && a.event_triggered == b.event_triggered
+            }
+            (Decoded::Light(a), Decoded::Light(b)) => {
+                near(l, a.illuminance.0, b.illuminance.0) && near(v, a.supply_voltage.0, b.supply_voltage.0)
+            }
+            (Decoded::LightTemperatureOccupancy(a), Decoded::LightTemperatureOccupancy(b)) => {
+                near(v, a.supply_voltage.0, b.supply_voltage.0)
+                    && near(l, a.illuminance.0, b.illuminance.0)
+                    && near(t, a.temperature.0, b.temperature.0)
+                    && (a.motion, a.occupancy_button) == (b.motion, b.occupancy_button)
+            }
+            (Decoded::RoomPanel(a), Decoded::RoomPanel(b)) => {
+                near(t, a.temperature.0, b.temperature.0)
+                    && near_opt(h, a.humidity.map(|h| h.0), b.humidity.map(|h| h.0))
+                    && near_opt(l, a.illuminance.map(|l| l.0), b.illuminance.map(|l| l.0))
+                    && near_opt(v, a.supply_voltage.map(|v| v.0), b.supply_voltage.map(|v| v.0))
+                    && (a.set_point, a.fan_speed, a.occupancy, a.slide_switch, a.contact)
+                        == (b.set_point, b.fan_speed, b.occupancy, b.slide_switch, b.contact)
+            }
+            (Decoded::MultiFunction(a), Decoded::MultiFunction(b)) => {
+                near(v, a.supply_voltage.0, b.supply_voltage.0)
+                    && near_opt(l, a.illuminance.map(|l| l.0), b.illuminance.map(|l| l.0))
+                    && (a.contact, a.locked, a.window, a.vibration) == (b.contact, b.locked, b.window, b.vibration)
+            }
+            (Decoded::Multisensor(a), Decoded::Multisensor(b)) => {
+                near_opt(t, a.temperature.map(|t| t.0), b.temperature.map(|t| t.0))
+                    && near_opt(h, a.humidity.map(|h| h.0), b.humidity.map(|h| h.0))
+                    && near_opt(l, a.illuminance.map(|l| l.0), b.illuminance.map(|l| l.0))
+                    && (a.acceleration, a.contact, a.smoke_alarm, a.sensor_fault)
+                        == (b.acceleration, b.contact, b.smoke_alarm, b.sensor_fault)
+            }
+            _ => previous == current,
+        }
+    }
+}
+
+/// The deadbands of the watched devices, and the last values passed on
+#[derive(Debug, Clone, Default)]
+pub struct ChangeFilter {
+    devices: BTreeMap<Address, Deadband>,
+    /// Last values passed on, by device and channel
+    last: BTreeMap<(Address, Option<u8>), Decoded>,
+}
+
+impl ChangeFilter {
+    /// A filter passing everything until devices are watched
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pass the values of `device` only when they changed beyond `deadband`
+    pub fn watch(&mut self, device: Address, deadband: Deadband) {
+        self.devices.insert(device, deadband);
+    }
+
+    /// Pass all the values of `device` again
+    pub fn unwatch(&mut self, device: &Address) {
+        self.devices.remove(device);
+        self.last.retain(|(address, _), _| address != device);
+    }
+
+    pub fn deadband(&self, device: &Address) -> Option<&Deadband> {
+        self.devices.get(device)
+    }
+
+    /// Whether to pass on `decoded`, sent by `sender`. Values passed on become the reference
+    /// for the next ones.
+    pub fn accepts(&mut self, sender: Address, decoded: &Decoded) -> bool {
+        let Some(deadband) = self.devices.get(&sender) else { return true };
+        if matches!(decoded, Decoded::Rocker(_) | Decoded::PushButton(_)) {
+            return true;
+        }
+        let key = (sender, decoded.channel());
+        if self.last.get(&key).is_some_and(|last| deadband.unchanged(last, decoded)) {
+            return false;
+        }
+        self.last.insert(key, *decoded);
+        true
+    }
+
+    /// Forget the last values of `device`, so that the next ones pass
+    pub fn reset(&mut self, device: &Address) {
+        self.last.retain(|(address, _), _| address != device);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::a5_04;
+    use crate::eep::f6_02::{Button, RockerEvent};
+
+    #[test]
+    fn given_deadband_then_pass_only_meaningful_changes() {
+        let (sensor, other) = ("0180998e".parse().unwrap(), "fefa1234".parse().unwrap());
+        let reading = |humidity: f32, temperature: Option<f32>| Decoded::TemperatureHumidity(a5_04::Reading {
+            humidity: RelativeHumidity(humidity),
+            temperature: temperature.map(Temperature),
+            event_triggered: None,
+        });
+        let mut changes = ChangeFilter::new();
+        changes.watch(sensor, Deadband::new().with(Temperature::UNIT, 0.2).with(RelativeHumidity::UNIT, 1.0));
+
+        assert!(changes.accepts(sensor, &reading(50.0, Some(21.0))));
+        assert!(!changes.accepts(sensor, &reading(50.8, Some(21.0))));
+        assert!(!changes.accepts(sensor, &reading(50.0, Some(21.15))));
+        // The drift adds up against the last values passed on
+        assert!(changes.accepts(sensor, &reading(51.2, Some(21.0))));
+        assert!(changes.accepts(sensor, &reading(51.2, None)));
+        assert!(changes.accepts(other, &reading(51.2, None)));
+        assert!(changes.accepts(other, &reading(51.2, None)));
+
+        let press = Decoded::Rocker(RockerEvent::Pressed { first: Button::A1, second: None });
+        assert!(changes.accepts(sensor, &press) && changes.accepts(sensor, &press));
+        changes.reset(&sensor);
+        assert!(changes.accepts(sensor, &reading(51.2, None)));
+        assert!(!changes.accepts(sensor, &reading(51.2, None)));
+    }
+}
@@ -0,0 +1,1178 @@
+//! High-level devices
+//!
+//! A [`Gateway`] ties a [`Port`] to the address it sends from and to a [`DeviceRegistry`].
+//! It hands out [`Device`]s, which receive the decoded values of a single device, and send
+//! it commands according to its profile.
+//!
+//! ```no_run
+//! use enocean::device::Gateway;
+//! use enocean::port::Port;
+//! use enocean::registry::DeviceEntry;
+//!
+//! let mut gateway = Gateway::new(Port::open("/dev/ttyUSB0")?)?;
+//! gateway.registry_mut().insert("0180998e".parse()?, DeviceEntry::new("A5-02-05".parse()?));
+//! gateway.registry_mut().insert("05123456".parse()?, DeviceEntry::new("D2-01-12".parse()?));
+//!
+//! gateway.device("05123456".parse()?).unwrap().set_level(80)?;
+//! for values in gateway.device("0180998e".parse()?).unwrap().events() {
+//!     println!("{:?}", values?);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Commands are supported for the actuators of the profiles below.
+//!
+//! | Profile  | `switch`             | `set_level`                |
+//! |----------|----------------------|----------------------------|
+//! | D2-01-xx | Set output, 0 or 100 | Set output, 0..100 %       |
+//! | A5-38-08 | Switching command    | Dimming command, 0..100 %  |
+//!
+//! D2-01 actuators confirm commands with a status response: [`Device::set_output`] waits
+//! for it, and resends the command when it is missed.
+//!
+//! A device commands all the channels of a multi-channel actuator at once. [`Gateway::endpoint`]
+//! hands out a [`DeviceChannel`] instead, which commands a single channel and only receives
+//! the values about it, so that each channel is handled as a device of its own.
+//!
+//! With a [`PresenceTracker`], the gateway records the telegrams of every sender it reads,
+//! and [`Gateway::check_presence`] reports the devices that went silent. With a
+//! [`SignalQuality`], it keeps the signal strength and hop counts of their last telegrams.
+//! With [`TrafficStats`], it measures the telegram rates and airtime, by sender and RORG.
+//! Telegrams rejected by its [`ReceiveFilter`] are dropped before all of this.
+//!
+//! On connect, [`Gateway::new`] reads the version, base ID and frequency of the
+//! transceiver, kept in its [`GatewayInfo`]. When the transceiver restarts on its own, e.g.
+//! after a watchdog reset, it announces it with a CO_READY event: the gateway writes the
+//! repeater, filter, transparent mode and noise threshold settings of
+//! [`Gateway::apply_config`] again, reads its information again, and reports a
+//! [`GatewayEvent::Reset`]. The filter, registry and trackers are kept by the gateway, and
+//! survive the reset. ESP3 has no return code for a transceiver not ready yet, so resets
+//! are only detected from CO_READY.
+//!
+//! With a [`KeepAlive`], the gateway sends the last command of the scheduled devices again,
+//! for the actuators that fall back to a default without regular commands: while reading
+//! [`Device::events`], or on [`Gateway::send_keepalives`].
+//!
+//! With a [`Poller`], the gateway queries the scheduled devices that only report when asked,
+//! and reports how each poll ended as [`GatewayEvent::Poll`]: while reading
+//! [`Device::events`], or on [`Gateway::send_poll`].
+//!
+//! With a [`ChangeFilter`], [`Device::events`] only hands out the values of the watched
+//! devices that changed beyond their deadband, rather than every periodic telegram.
+//!
+//! Chained data messages (CDM), split over several telegrams, are reassembled by a
+//! [`ChainAssembler`] before they are decoded: the fragments only count in the trackers.
+//!
+//! With an [`AnomalyDetector`], the gateway watches the telegrams of non-secure devices for
+//! replays and spoofing, and reports what it finds as [`GatewayEvent::Anomaly`].
+//!
+//! Frames the gateway cannot make sense of are dropped, unless [`Gateway::on_undecodable`]
+//! hands them to the application: malformed packets, telegrams of senders missing from the
+//! registry, broken chains, and telegrams the profile of their sender does not decode.
+//! Collected, they are the evidence needed to support new devices, with a field mapping in
+//! the registry, or with the application decoders of [`Gateway::with_profiles`].
+//! Transceivers reporting out-of-spec values in otherwise sound telegrams are read with
+//! [`Gateway::with_decode_options`]. To see everything on the link instead, raw frames sent or
+//! received included, [`Gateway::on_raw_frame`] taps the port, e.g. for a capture running
+//! beside the application.
+//!
+//! The settings of the transceiver are saved with [`Gateway::export_config`], and restored,
+//! possibly on another transceiver, with [`Gateway::apply_config`].
+//!
+//! Whatever the gateway notices while reading is published as a [`GatewayEvent`]: decoded
+//! telegrams of registered devices, teach-in telegrams, transmissions, duty cycle limits,
+//! resets, presence changes, secure device events, anomalies and polls. An application can
+//! handle all of them in a single loop over [`Gateway::events`], take those noticed while
+//! reading devices with [`Gateway::take_events`], or [`Gateway::subscribe`] from another
+//! thread.
+//!
+//! ```no_run
+//! use enocean::device::{Gateway, GatewayEvent};
+//! use enocean::port::Port;
+//!
+//! let mut gateway = Gateway::new(Port::open("/dev/ttyUSB0")?)?;
+//! for event in gateway.events() {
+//!     match event? {
+//!         GatewayEvent::Telegram { sender, values } => println!("{sender}: {values:?}"),
+//!         GatewayEvent::TeachIn { sender, teach_in } => println!("{sender} teaches in {:?}", teach_in.eep()),
+//!         event => println!("{event:?}"),
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Gateway::press`] emulates a rocker switch (F6-02), for the actuators that only learn
+//! RPS switches: teach the gateway address in like a wall switch, then press its buttons.
+
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+
+use thiserror::Error;
+
+use crate::actuator::{Action, CommandOutcome, CommandTracker};
+use crate::anomaly::{Anomaly, AnomalyDetector};
+use crate::cdm::{ChainAssembler, ChainError};
+use crate::config::{ConfigError, GatewayConfig};
+use crate::deadband::ChangeFilter;
+use crate::eep::a5_38::CentralCommand;
+use crate::eep::d2_01::{self, DimMode};
+use crate::eep::f6_02::{Button, RockerEvent};
+use crate::eep::profiles::ProfileDecoders;
+use crate::eep::teach_in::TeachIn;
+use crate::eep::{DecodeError, Decoded};
+use crate::endpoint::Endpoint;
+use crate::enocean::{CommandError, Rorg};
+use crate::filter::ReceiveFilter;
+use crate::keepalive::{KeepAlive, Resend};
+use crate::link::{RawFrame, TxStatus};
+use crate::frame::ESP3Frame;
+use crate::packet::{Address, BuildError, DecodeOptions, EEPProfileCode, Event, FrequencyInfo, IdBase, Packet, ParseError, RadioErp1, Version, VersionResponse, WakeupCause};
+use crate::poll::{Poll, PollOutcome, Poller};
+use crate::port::Port;
+use crate::presence::{PresenceEvent, PresenceTracker};
+use crate::quality::{LinkQuality, SignalQuality};
+use crate::registry::DeviceRegistry;
+use crate::traffic::{TrafficStats, TrafficSummary};
+use crate::{FrameReadError, PacketError};
+
+#[derive(Debug, Error)]
+pub enum DeviceError {
+    #[error("Could not exchange with the gateway")]  Packet(#[from] PacketError),
+    #[error("Could not read frame")]                 Frame(#[from] FrameReadError),
+    #[error("Could not decode telegram")]            Decode(#[from] DecodeError),
+    #[error("Invalid telegram")]                     Build(#[from] BuildError),
+    #[error("Command not supported by profile {0}")] Unsupported(EEPProfileCode),
+    #[error("Gateway refused the telegram")]         Refused(#[from] CommandError),
+    #[error("Telegram not sent, cause {0}")]         TxFailed(u8),
+    #[error("Could not restore the settings")]       Config(#[from] ConfigError),
+}
+
+/// How long [`Device::send_confirmed`] waits for the transceiver to report
+pub const TX_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long [`Gateway::press`] holds the buttons for a short press. Dimmers take longer
+/// presses for dimming.
+pub const SHORT_PRESS: Duration = Duration::from_millis(150);
+
+/// What the transceiver reports about itself
+#[derive(Debug, Clone)]
+pub struct GatewayInfo {
+    pub version: VersionResponse,
+    pub base_id: IdBase,
+    /// `None` on firmware without CO_GET_FREQUENCY_INFO, or not answering it properly
+    pub frequency: Option<FrequencyInfo>,
+}
+
+impl GatewayInfo {
+    /// Read the information from the transceiver. The frequency is optional, and does not
+    /// fail the read.
+    pub fn read(port: &mut Port) -> Result<Self, PacketError> {
+        Ok(Self {
+            version: port.read_version_information()?,
+            base_id: port.read_id_base()?,
+            frequency: port.read_frequency_info().ok().flatten(),
+        })
+    }
+
+    pub fn chip_id(&self) -> Address {
+        self.version.chip_id
+    }
+
+    /// Version of the application firmware
+    pub fn firmware(&self) -> Version {
+        self.version.app
+    }
+}
+
+/// What the gateway noticed while reading, see [`Gateway::events`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GatewayEvent {
+    /// A telegram of a registered device, decoded with its profile and passed by the change
+    /// filter
+    Telegram { sender: Address, values: Decoded },
+    /// A teach-in telegram, of a registered device or not
+    TeachIn { sender: Address, teach_in: TeachIn },
+    /// The transceiver sent a telegram on air (CO_TX_DONE)
+    TxDone,
+    /// The transceiver could not send a telegram (CO_TX_FAILED)
+    TxFailed { cause: u8 },
+    /// The transceiver reached its duty cycle limit, and holds telegrams back
+    /// (CO_DUTYCYCLE_LIMIT)
+    DutyCycleLimit { cause: u8 },
+    /// The transceiver restarted, for `cause` if known. Its [`GatewayInfo`], if read on
+    /// connect, was read again.
+    Reset { cause: Option<WakeupCause> },
+    /// A device went silent, or was heard from again, reported by the presence tracker
+    Presence(PresenceEvent),
+    /// The transceiver reports a secure device, e.g. for a telegram failing its RLC or CMAC
+    /// check (CO_EVENT_SECURE_DEVICES)
+    SecureDevice { cause: u8, device: Address },
+    /// A suspicious telegram, reported by the anomaly detector
+    Anomaly(Anomaly),
+    /// A poll of the poller was answered, or missed
+    Poll(PollOutcome),
+}
+
+/// Events other than telegrams kept for [`Gateway::take_events`] and [`Gateway::events`].
+/// Beyond, the oldest are dropped.
+pub const MAX_PENDING_EVENTS: usize = 1024;
+
+/// [`GatewayEvent::Telegram`] events kept for [`Gateway::take_events`] and
+/// [`Gateway::events`], apart from the other events so that a busy network does not push
+/// them out. Beyond, the oldest are dropped.
+pub const MAX_PENDING_TELEGRAMS: usize = 1024;
+
+/// The events not taken yet, and the subscribers
+#[derive(Debug, Default)]
+struct EventBus {
+    pending: VecDeque<GatewayEvent>,
+    /// Telegram events in `pending`
+    telegrams: usize,
+    subscribers: Vec<mpsc::Sender<GatewayEvent>>,
+}
+
+impl EventBus {
+    fn publish(&mut self, event: GatewayEvent) {
+        self.notify(event);
+        let telegram = is_telegram(&event);
+        let (count, max) = if telegram {
+            (self.telegrams, MAX_PENDING_TELEGRAMS)
+        } else {
+            (self.pending.len() - self.telegrams, MAX_PENDING_EVENTS)
+        };
+        if count == max {
+            if let Some(oldest) = self.pending.iter().position(|pending| is_telegram(pending) == telegram) {
+                self.pending.remove(oldest);
+            }
+        } else if telegram {
+            self.telegrams += 1;
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Hand an event to the subscribers only, e.g. a telegram already returned to its reader
+    fn notify(&mut self, event: GatewayEvent) {
+        self.subscribers.retain(|subscriber| subscriber.send(event).is_ok());
+    }
+
+    fn pop(&mut self) -> Option<GatewayEvent> {
+        let event = self.pending.pop_front()?;
+        if is_telegram(&event) {
+            self.telegrams -= 1;
+        }
+        Some(event)
+    }
+
+    fn take(&mut self) -> Vec<GatewayEvent> {
+        self.telegrams = 0;
+        self.pending.drain(..).collect()
+    }
+}
+
+fn is_telegram(event: &GatewayEvent) -> bool {
+    matches!(event, GatewayEvent::Telegram { .. })
+}
+
+/// Util : a telegram read by the gateway, see `Gateway::receive`
+struct Received {
+    erp: RadioErp1<'static>,
+    /// The values of a registered sender, `None` for unknown senders and for values the
+    /// change filter finds unchanged
+    values: Option<Result<Decoded, DecodeError>>,
+}
+
+/// Why a received frame could not be decoded
+#[derive(Debug, Error)]
+pub enum UndecodableError {
+    #[error("Invalid packet")]      Packet(#[source] ParseError),
+    #[error("Undecodable telegram")] Telegram(#[source] DecodeError),
+    #[error("Broken chained message")] Chain(#[source] ChainError),
+}
+
+/// A received frame that could not be decoded, see [`Gateway::on_undecodable`]
+#[derive(Debug)]
+pub struct Undecodable<'a> {
+    pub frame: &'a ESP3Frame,
+    pub error: UndecodableError,
+    pub received: SystemTime,
+}
+
+type UndecodableHook = Box<dyn FnMut(&Undecodable) + Send>;
+
+/// A port, with the address telegrams are sent from and the known devices
+pub struct Gateway {
+    port: Port,
+    sender: Address,
+    info: Option<GatewayInfo>,
+    /// Settings written with `apply_config`, written again after a reset
+    settings: GatewayConfig,
+    registry: DeviceRegistry,
+    filter: ReceiveFilter,
+    presence: Option<PresenceTracker>,
+    quality: Option<SignalQuality>,
+    traffic: Option<TrafficStats>,
+    keepalive: Option<KeepAlive>,
+    poller: Option<Poller>,
+    changes: Option<ChangeFilter>,
+    anomalies: Option<AnomalyDetector>,
+    chains: ChainAssembler,
+    undecodable: Option<UndecodableHook>,
+    decode: DecodeOptions,
+    profiles: Option<ProfileDecoders<Decoded>>,
+    bus: EventBus,
+}
+
+impl Gateway {
+    /// A gateway sending from the base ID of the transceiver, after reading its [`GatewayInfo`]
+    pub fn new(mut port: Port) -> Result<Self, PacketError> {
+        let info = GatewayInfo::read(&mut port)?;
+        let mut gateway = Self::with_sender(port, info.base_id.0);
+        gateway.info = Some(info);
+        Ok(gateway)
+    }
+
+    /// A gateway sending from `sender`, e.g. another address of the base ID range. The
+    /// transceiver is not queried.
+    pub fn with_sender(port: Port, sender: Address) -> Self {
+        Self { port, sender, info: None, settings: GatewayConfig::default(), registry: DeviceRegistry::new(), filter: ReceiveFilter::new(), presence: None, quality: None, traffic: None, keepalive: None, poller: None, changes: None, anomalies: None, chains: ChainAssembler::new(), undecodable: None, decode: DecodeOptions::default(), profiles: None, bus: EventBus::default() }
+    }
+
+    /// Track the presence of the devices heard from
+    pub fn with_presence(mut self, presence: PresenceTracker) -> Self {
+        self.presence = Some(presence);
+        self
+    }
+
+    /// Keep the signal quality of the devices heard from
+    pub fn with_signal_quality(mut self, quality: SignalQuality) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Measure the traffic of the telegrams heard
+    pub fn with_traffic(mut self, traffic: TrafficStats) -> Self {
+        self.traffic = Some(traffic);
+        self
+    }
+
+    /// Watch the telegrams for replays and spoofing, reporting anomalies among the events
+    pub fn with_anomaly_detector(mut self, detector: AnomalyDetector) -> Self {
+        self.anomalies = Some(detector);
+        self
+    }
+
+    /// Send the last command of the devices it schedules again
+    pub fn with_keepalive(mut self, keepalive: KeepAlive) -> Self {
+        self.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Query the devices it schedules, reporting the outcome of the polls among the events
+    pub fn with_poller(mut self, poller: Poller) -> Self {
+        self.poller = Some(poller);
+        self
+    }
+
+    /// Only hand out the values of the devices it watches when they changed
+    pub fn with_change_filter(mut self, changes: ChangeFilter) -> Self {
+        self.changes = Some(changes);
+        self
+    }
+
+    /// Hand the frames read but not decoded to `hook`: packets that do not decode, telegrams
+    /// of senders missing from the registry, and telegrams of registered devices that their
+    /// profile does not decode. Telegrams rejected by the filter are not reported.
+    pub fn on_undecodable(mut self, hook: impl FnMut(&Undecodable) + Send + 'static) -> Self {
+        self.undecodable = Some(Box::new(hook));
+        self
+    }
+
+    /// Decode the frames read as strictly as `options` say, e.g. [`DecodeOptions::LENIENT`]
+    /// for transceivers reporting out-of-spec values
+    pub fn with_decode_options(mut self, options: DecodeOptions) -> Self {
+        self.decode = options;
+        self
+    }
+
+    /// Decode the telegrams of the devices whose profile is registered in `profiles` with the
+    /// application decoder, rather than the built-in one. Field mappings take precedence.
+    pub fn with_profiles(mut self, profiles: ProfileDecoders<Decoded>) -> Self {
+        self.profiles = Some(profiles);
+        self
+    }
+
+    /// Hand every frame read or written by the port to `tap`, before it is decoded: raw
+    /// bytes, including frames failing their CRC. See [`Port::set_tap`].
+    pub fn on_raw_frame(mut self, tap: impl FnMut(&RawFrame) + Send + 'static) -> Self {
+        self.port.set_tap(tap);
+        self
+    }
+
+    pub fn sender(&self) -> Address {
+        self.sender
+    }
+
+    /// The information read on connect, `None` if created [`with_sender`](Self::with_sender)
+    /// and not refreshed
+    pub fn info(&self) -> Option<&GatewayInfo> {
+        self.info.as_ref()
+    }
+
+    /// Read the information from the transceiver again, e.g. after changing its base ID
+    pub fn refresh_info(&mut self) -> Result<&GatewayInfo, PacketError> {
+        Ok(self.info.insert(GatewayInfo::read(&mut self.port)?))
+    }
+
+    /// Read the settings of the transceiver, see [`GatewayConfig`]
+    pub fn export_config(&mut self) -> Result<GatewayConfig, ConfigError> {
+        GatewayConfig::read(&mut self.port)
+    }
+
+    /// Write settings to the transceiver. After a change of base ID, the information of the
+    /// transceiver is read again, and a gateway sending from the former base ID sends from
+    /// the new one. The settings are kept, and written again when the transceiver resets, see
+    /// [`GatewayConfig::volatile`].
+    pub fn apply_config(&mut self, config: &GatewayConfig) -> Result<(), ConfigError> {
+        config.apply(&mut self.port)?;
+        self.settings.update(config);
+        if let (Some(base_id), Some(info)) = (config.base_id, &self.info) {
+            if info.base_id.0 != base_id {
+                if self.sender == info.base_id.0 {
+                    self.sender = base_id;
+                }
+                self.refresh_info()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The events of the gateway since the last call, noticed while reading telegrams
+    pub fn take_events(&mut self) -> Vec<GatewayEvent> {
+        self.bus.take()
+    }
+
+    /// A channel receiving every event of the gateway from now on, as it is noticed. Events
+    /// are still kept for [`take_events`](Self::take_events) and [`events`](Self::events).
+    pub fn subscribe(&mut self) -> mpsc::Receiver<GatewayEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.bus.subscribers.push(sender);
+        receiver
+    }
+
+    /// The events of the gateway, reading telegrams to notice them: the single loop of an
+    /// application handling everything as events. Read timeouts of the port are waited out,
+    /// and the keepalive commands and polls are sent meanwhile.
+    pub fn events(&mut self) -> impl Iterator<Item = Result<GatewayEvent, DeviceError>> + '_ {
+        std::iter::from_fn(move || loop {
+            if let Some(event) = self.bus.pop() {
+                return Some(Ok(event));
+            }
+            if let Err(e) = self.receive(None) {
+                return Some(Err(e));
+            }
+        })
+    }
+
+    pub fn registry(&self) -> &DeviceRegistry {
+        &self.registry
+    }
+
+    pub fn registry_mut(&mut self) -> &mut DeviceRegistry {
+        &mut self.registry
+    }
+
+    pub fn filter(&self) -> &ReceiveFilter {
+        &self.filter
+    }
+
+    /// The filter of the received telegrams, accepting all of them until configured
+    pub fn filter_mut(&mut self) -> &mut ReceiveFilter {
+        &mut self.filter
+    }
+
+    pub fn presence(&self) -> Option<&PresenceTracker> {
+        self.presence.as_ref()
+    }
+
+    pub fn presence_mut(&mut self) -> Option<&mut PresenceTracker> {
+        self.presence.as_mut()
+    }
+
+    /// The devices that went silent, or were heard from again, since the last check. They
+    /// are also published as [`GatewayEvent::Presence`]; reading telegrams checks too.
+    pub fn check_presence(&mut self) -> Vec<PresenceEvent> {
+        let events = self.presence.as_mut().map_or_else(Vec::new, |presence| presence.check(Instant::now()));
+        for event in &events {
+            self.bus.publish(GatewayEvent::Presence(*event));
+        }
+        events
+    }
+
+    pub fn signal_quality(&self) -> Option<&SignalQuality> {
+        self.quality.as_ref()
+    }
+
+    /// Signal strength and hop counts of the last telegrams of `address`
+    pub fn link_quality(&self, address: &Address) -> Option<LinkQuality> {
+        self.quality.as_ref()?.get(address)
+    }
+
+    pub fn traffic(&self) -> Option<&TrafficStats> {
+        self.traffic.as_ref()
+    }
+
+    /// Telegram rates and airtime over the window of the traffic statistics, until now
+    pub fn traffic_summary(&self) -> Option<TrafficSummary> {
+        Some(self.traffic.as_ref()?.summary(Instant::now()))
+    }
+
+    pub fn anomaly_detector_mut(&mut self) -> Option<&mut AnomalyDetector> {
+        self.anomalies.as_mut()
+    }
+
+    pub fn keepalive(&self) -> Option<&KeepAlive> {
+        self.keepalive.as_ref()
+    }
+
+    pub fn keepalive_mut(&mut self) -> Option<&mut KeepAlive> {
+        self.keepalive.as_mut()
+    }
+
+    /// Send the commands whose keepalive interval elapsed, returning how many were sent.
+    /// [`Device::events`] does so while reading.
+    pub fn send_keepalives(&mut self) -> Result<usize, DeviceError> {
+        let Some(keepalive) = self.keepalive.as_mut() else { return Ok(0) };
+        let now = Instant::now();
+        let due = keepalive.due(now);
+        for resend in &due {
+            send_command(&mut self.port, self.sender, resend)?;
+            keepalive.sent(&resend.destination, now);
+        }
+        Ok(due.len())
+    }
+
+    pub fn change_filter_mut(&mut self) -> Option<&mut ChangeFilter> {
+        self.changes.as_mut()
+    }
+
+    pub fn poller(&self) -> Option<&Poller> {
+        self.poller.as_ref()
+    }
+
+    pub fn poller_mut(&mut self) -> Option<&mut Poller> {
+        self.poller.as_mut()
+    }
+
+    /// Send the next poll if it is due, returning it. The polls whose response timed out are
+    /// reported among the events. [`Device::events`] does so while reading, and reports the
+    /// responses.
+    pub fn send_poll(&mut self) -> Result<Option<Poll>, DeviceError> {
+        let Some(poller) = self.poller.as_mut() else { return Ok(None) };
+        next_poll(&mut self.port, self.sender, poller, &mut self.bus)
+    }
+
+    /// A handle on a registered device
+    pub fn device(&mut self, address: Address) -> Option<Device<'_>> {
+        let eep = self.registry.get(&address)?.eep;
+        Some(Device { gateway: self, address, eep })
+    }
+
+    /// A handle on a channel of a registered device
+    pub fn endpoint(&mut self, endpoint: Endpoint) -> Option<DeviceChannel<'_>> {
+        Some(self.device(endpoint.address)?.channel(endpoint.channel))
+    }
+
+    /// Emulate a rocker switch sending from the gateway address: press `first` (and
+    /// `second`) for `hold`, e.g. [`SHORT_PRESS`], then release
+    pub fn press(&mut self, first: Button, second: Option<Button>, hold: Duration) -> Result<(), DeviceError> {
+        self.send_rocker(RockerEvent::Pressed { first, second })?;
+        std::thread::sleep(hold);
+        self.send_rocker(RockerEvent::Released)
+    }
+
+    fn send_rocker(&mut self, event: RockerEvent) -> Result<(), DeviceError> {
+        let (data, status) = event.encode();
+        let data = [data];
+        let erp = RadioErp1::builder()
+            .choice(Rorg::Rps)
+            .user_data(&data)
+            .sender(self.sender)
+            .status(status)
+            .build()?;
+        let response = self.port.write_packet(Packet::RadioErp1(erp))?;
+        Ok(response.code.result()?)
+    }
+
+    /// Util : send the keepalive commands and polls due, then read a frame and run it through
+    /// the filter, trackers and registry, publishing the events it causes. Returns the
+    /// telegram read, `None` for other packets and read timeouts. The values of `reader`, returned
+    /// to it, are published to the subscribers only.
+    fn receive(&mut self, reader: Option<Address>) -> Result<Option<Received>, DeviceError> {
+        let now = Instant::now();
+        if let Some(keepalive) = self.keepalive.as_mut() {
+            for resend in keepalive.due(now) {
+                send_command(&mut self.port, self.sender, &resend)?;
+                keepalive.sent(&resend.destination, now);
+            }
+        }
+        if let Some(poller) = self.poller.as_mut() {
+            next_poll(&mut self.port, self.sender, poller, &mut self.bus)?;
+        }
+        for event in self.presence.as_mut().map_or_else(Vec::new, |presence| presence.check(now)) {
+            self.bus.publish(GatewayEvent::Presence(event));
+        }
+
+        let frame = match self.port.read_frame() {
+            Ok(frame) => frame,
+            Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let erp = match Packet::decode_with(frame.as_ref(), self.decode) {
+            Ok(Packet::RadioErp1(erp)) => erp,
+            Ok(Packet::Event(event)) => {
+                match event {
+                    Event::COReady { wakeup, .. } => transceiver_ready(&mut self.port, &mut self.info, &self.settings, &mut self.bus, wakeup)?,
+                    Event::COTXDone => self.bus.publish(GatewayEvent::TxDone),
+                    Event::COTXFailed { cause } => self.bus.publish(GatewayEvent::TxFailed { cause }),
+                    Event::CODutyCycleLimit { cause } => self.bus.publish(GatewayEvent::DutyCycleLimit { cause }),
+                    Event::COEventSecureDevices { cause, device } => self.bus.publish(GatewayEvent::SecureDevice { cause, device }),
+                    _ => {}
+                }
+                return Ok(None);
+            }
+            Ok(_) => return Ok(None),
+            Err(e) => {
+                self.report(&frame, UndecodableError::Packet(e));
+                return Ok(None);
+            }
+        };
+        if !self.filter.accepts(&erp) {
+            return Ok(None);
+        }
+
+        let sender = erp.sender_id;
+        if let Some(presence) = self.presence.as_mut() {
+            presence.record(sender, now);
+        }
+        if let Some(quality) = self.quality.as_mut() {
+            quality.record(&erp);
+        }
+        if let Some(traffic) = self.traffic.as_mut() {
+            traffic.record(&erp, now);
+        }
+        for anomaly in self.anomalies.as_mut().map_or_else(Vec::new, |anomalies| anomalies.record(&erp, now)) {
+            self.bus.publish(GatewayEvent::Anomaly(anomaly));
+        }
+        let erp = match erp.choice {
+            Rorg::Cdm => match self.chains.push(&erp) {
+                Ok(Some(message)) => message.erp1().into_owned(),
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    self.report(&frame, UndecodableError::Chain(e));
+                    return Ok(None);
+                }
+            },
+            _ => erp,
+        };
+        if let Some(outcome) = self.poller.as_mut().and_then(|poller| poller.handle(&erp, now)) {
+            self.bus.publish(GatewayEvent::Poll(outcome));
+        }
+        // Answer within the receive window of the device
+        if let Some(keepalive) = self.keepalive.as_mut() {
+            if let Some(resend) = keepalive.on_telegram(&erp) {
+                send_command(&mut self.port, self.sender, &resend)?;
+                keepalive.sent(&resend.destination, now);
+            }
+        }
+        if let Some(teach_in) = TeachIn::detect(&erp) {
+            self.bus.publish(GatewayEvent::TeachIn { sender, teach_in });
+        }
+
+        let values = match self.registry.get(&sender).map(|entry| match &self.profiles {
+            Some(profiles) => entry.decode_with(profiles, &erp, self.decode),
+            None => entry.decode(&erp, self.decode),
+        }) {
+            None => {
+                self.report(&frame, UndecodableError::Telegram(DecodeError::UnknownDevice(sender)));
+                None
+            }
+            Some(decoded) => match decoded {
+                Err(e) => {
+                    self.report(&frame, UndecodableError::Telegram(e.clone()));
+                    Some(Err(e))
+                }
+                Ok(values) if self.changes.as_mut().is_some_and(|changes| !changes.accepts(sender, &values)) => None,
+                Ok(values) => {
+                    if reader == Some(sender) {
+                        self.bus.notify(GatewayEvent::Telegram { sender, values });
+                    } else {
+                        self.bus.publish(GatewayEvent::Telegram { sender, values });
+                    }
+                    Some(Ok(values))
+                }
+            },
+        };
+        Ok(Some(Received { erp: erp.into_owned(), values }))
+    }
+
+    /// Util : hand a frame that could not be decoded to the hook
+    fn report(&mut self, frame: &ESP3Frame, error: UndecodableError) {
+        if let Some(hook) = self.undecodable.as_mut() {
+            hook(&Undecodable { frame, error, received: SystemTime::now() });
+        }
+    }
+
+    pub fn into_inner(self) -> Port {
+        self.port
+    }
+}
+
+/// A registered device, reached through its gateway
+pub struct Device<'g> {
+    gateway: &'g mut Gateway,
+    address: Address,
+    eep: EEPProfileCode,
+}
+
+impl<'g> Device<'g> {
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn eep(&self) -> EEPProfileCode {
+        self.eep
+    }
+
+    /// A handle on a channel of the device
+    pub fn channel(self, channel: u8) -> DeviceChannel<'g> {
+        DeviceChannel { device: self, channel }
+    }
+
+    /// The values sent by the device, decoded with its profile, unless the change filter finds
+    /// them unchanged. Telegrams of other devices are dropped, once recorded by the trackers
+    /// and published as events, and read timeouts of the port are waited out. Meanwhile, the
+    /// keepalive commands and polls are sent. The values returned here are not kept for
+    /// [`Gateway::take_events`], only handed to the subscribers.
+    pub fn events(&mut self) -> impl Iterator<Item = Result<Decoded, DeviceError>> + '_ {
+        let (address, gateway) = (self.address, &mut *self.gateway);
+        std::iter::from_fn(move || loop {
+            match gateway.receive(Some(address)) {
+                Ok(Some(Received { erp, values: Some(values) })) if erp.sender_id == address => return Some(values.map_err(DeviceError::from)),
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        })
+    }
+
+    /// Switch the output of an actuator on or off
+    pub fn switch(&mut self, on: bool) -> Result<(), DeviceError> {
+        let (rorg, data) = switch_command(self.eep, None, on)?;
+        self.send_command(rorg, &data)
+    }
+
+    /// Set the output of a dimming actuator, in %
+    pub fn set_level(&mut self, level: u8) -> Result<(), DeviceError> {
+        let (rorg, data) = level_command(self.eep, None, level)?;
+        self.send_command(rorg, &data)
+    }
+
+    /// Set output `channel` of a D2-01 actuator to `value` (0 = off, 1..100 %), and wait for
+    /// its status response, resending the command when it is missed. Telegrams received
+    /// meanwhile are only published as events.
+    pub fn set_output(&mut self, channel: u8, value: u8) -> Result<CommandOutcome, DeviceError> {
+        if self.eep.rorg() != 0xD2 || self.eep.func() != 0x01 {
+            return Err(DeviceError::Unsupported(self.eep));
+        }
+        let mut tracker = CommandTracker::new(self.address, channel, value);
+        loop {
+            match tracker.poll(Instant::now()) {
+                Action::Send(command) => self.send_command(Rorg::Vld, &command.encode())?,
+                Action::Wait => {}
+                Action::GiveUp => return Ok(CommandOutcome::NoResponse { attempts: tracker.attempts() }),
+            }
+            if let Some(received) = self.gateway.receive(None)? {
+                if let Some(outcome) = tracker.handle(&received.erp) {
+                    return Ok(outcome);
+                }
+            }
+        }
+    }
+
+    /// Send a telegram to the device, e.g. a query. Commands are sent with
+    /// [`Self::send_command`].
+    pub fn send(&mut self, rorg: Rorg, user_data: &[u8]) -> Result<(), DeviceError> {
+        let command = Resend { destination: self.address, rorg, user_data: user_data.to_vec() };
+        send_command(&mut self.gateway.port, self.gateway.sender, &command)
+    }
+
+    /// Send a command to the device. If the gateway keepalive schedules the device, the
+    /// command is remembered as the one to send again.
+    pub fn send_command(&mut self, rorg: Rorg, user_data: &[u8]) -> Result<(), DeviceError> {
+        self.send(rorg, user_data)?;
+        if let Some(keepalive) = self.gateway.keepalive.as_mut() {
+            keepalive.remember(self.address, rorg, user_data, Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Send a telegram to the device, and wait until it left the air interface
+    pub fn send_confirmed(&mut self, rorg: Rorg, user_data: &[u8]) -> Result<(), DeviceError> {
+        let erp = RadioErp1::builder()
+            .choice(rorg)
+            .user_data(user_data)
+            .sender(self.gateway.sender)
+            .destination(self.address)
+            .build()?;
+        match self.gateway.port.send_confirmed(erp, TX_TIMEOUT)? {
+            TxStatus::Sent => {
+                self.gateway.bus.publish(GatewayEvent::TxDone);
+                Ok(())
+            }
+            TxStatus::Failed { cause } => {
+                self.gateway.bus.publish(GatewayEvent::TxFailed { cause });
+                Err(DeviceError::TxFailed(cause))
+            }
+            TxStatus::Refused(e) => Err(e.into()),
+        }
+    }
+}
+
+/// A channel of a registered device, reached through its gateway
+pub struct DeviceChannel<'g> {
+    device: Device<'g>,
+    channel: u8,
+}
+
+impl DeviceChannel<'_> {
+    pub fn endpoint(&self) -> Endpoint {
+        Endpoint::new(self.device.address, self.channel)
+    }
+
+    pub fn eep(&self) -> EEPProfileCode {
+        self.device.eep
+    }
+
+    /// The values sent by the device about the channel, or about the whole device. See
+    /// [`Device::events`].
+    pub fn events(&mut self) -> impl Iterator<Item = Result<Decoded, DeviceError>> + '_ {
+        let channel = self.channel;
+        self.device.events().filter(move |decoded| match decoded {
+            Ok(decoded) => decoded.channel().is_none_or(|c| c == channel),
+            Err(_) => true,
+        })
+    }
+
+    /// Switch the output of the channel on or off
+    pub fn switch(&mut self, on: bool) -> Result<(), DeviceError> {
+        let (rorg, data) = switch_command(self.device.eep, Some(self.channel), on)?;
+        self.device.send_command(rorg, &data)
+    }
+
+    /// Set the output of a dimming channel, in %
+    pub fn set_level(&mut self, level: u8) -> Result<(), DeviceError> {
+        let (rorg, data) = level_command(self.device.eep, Some(self.channel), level)?;
+        self.device.send_command(rorg, &data)
+    }
+
+    /// Set the output of the channel of a D2-01 actuator, and wait for its status response.
+    /// See [`Device::set_output`].
+    pub fn set_output(&mut self, value: u8) -> Result<CommandOutcome, DeviceError> {
+        self.device.set_output(self.channel, value)
+    }
+}
+
+/// Util : record a start of the transceiver, write the settings it lost again, and read its
+/// information again if it was read on connect. Wake-ups from sleep lose no state, and are
+/// ignored.
+fn transceiver_ready(port: &mut Port, info: &mut Option<GatewayInfo>, settings: &GatewayConfig, bus: &mut EventBus, wakeup: u8) -> Result<(), DeviceError> {
+    let cause = WakeupCause::try_from(wakeup).ok();
+    if cause.is_some_and(|cause| !cause.is_reset()) {
+        return Ok(());
+    }
+    bus.publish(GatewayEvent::Reset { cause });
+    settings.volatile().apply(port)?;
+    if info.is_some() {
+        *info = Some(GatewayInfo::read(port)?);
+    }
+    Ok(())
+}
+
+/// Util : report the polls that timed out, and send the next poll from `sender` if due
+fn next_poll(port: &mut Port, sender: Address, poller: &mut Poller, bus: &mut EventBus) -> Result<Option<Poll>, DeviceError> {
+    let now = Instant::now();
+    for poll in poller.expired(now) {
+        bus.publish(GatewayEvent::Poll(PollOutcome::Missed(poll)));
+    }
+    let Some(poll) = poller.next(now) else { return Ok(None) };
+    let (rorg, user_data) = poll.query.telegram();
+    send_command(port, sender, &Resend { destination: poll.device, rorg, user_data })?;
+    Ok(Some(poll))
+}
+
+/// Util : send a command from `sender`
+fn send_command(port: &mut Port, sender: Address, command: &Resend) -> Result<(), DeviceError> {
+    let erp = RadioErp1::builder()
+        .choice(command.rorg)
+        .user_data(&command.user_data)
+        .sender(sender)
+        .destination(command.destination)
+        .build()?;
+    let response = port.write_packet(Packet::RadioErp1(erp))?;
+    Ok(response.code.result()?)
+}
+
+/// Util : user data switching `channel` of an actuator of profile `eep`, all channels if
+/// `None`. Single channel profiles only have channel 0.
+fn switch_command(eep: EEPProfileCode, channel: Option<u8>, on: bool) -> Result<(Rorg, Vec<u8>), DeviceError> {
+    match (eep.rorg(), eep.func(), eep.eep_type(), channel) {
+        (0xD2, 0x01, _, _) => {
+            let channel = channel.unwrap_or(d2_01::ALL_CHANNELS);
+            let command = d2_01::Command::SetOutput { channel, value: if on { 100 } else { 0 }, dim: DimMode::Immediate };
+            Ok((Rorg::Vld, command.encode()))
+        }
+        (0xA5, 0x38, 0x08, None | Some(0)) => {
+            let command = CentralCommand::Switching { on, time: 0, delay: false, lock: false };
+            Ok((Rorg::Bs4, command.encode().to_vec()))
+        }
+        _ => Err(DeviceError::Unsupported(eep)),
+    }
+}
+
+/// Util : user data setting the level of `channel` of a dimming actuator of profile `eep`,
+/// all channels if `None`
+fn level_command(eep: EEPProfileCode, channel: Option<u8>, level: u8) -> Result<(Rorg, Vec<u8>), DeviceError> {
+    let level = level.min(100);
+    match (eep.rorg(), eep.func(), eep.eep_type(), channel) {
+        (0xD2, 0x01, _, _) => {
+            let channel = channel.unwrap_or(d2_01::ALL_CHANNELS);
+            let command = d2_01::Command::SetOutput { channel, value: level, dim: DimMode::Immediate };
+            Ok((Rorg::Vld, command.encode()))
+        }
+        (0xA5, 0x38, 0x08, None | Some(0)) => {
+            let command = CentralCommand::Dimming { on: level > 0, value: level, relative: true, ramp: 0, store: false };
+            Ok((Rorg::Bs4, command.encode().to_vec()))
+        }
+        _ => Err(DeviceError::Unsupported(eep)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::a5_3f;
+    use crate::keepalive::Trigger;
+    use crate::poll::Query;
+    use crate::registry::DeviceEntry;
+    use crate::config::{RepeaterConfig, RepeaterMode};
+    use crate::sim::{Actuator, TemperatureSensor, Transceiver};
+    use std::error::Error as _;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn given_actuator_profile_then_encode_commands() {
+        let d2 = EEPProfileCode::new(0xD2, 0x01, 0x12);
+        assert_eq!(level_command(d2, None, 80).unwrap(), (Rorg::Vld, vec![0x01, 0x1e, 80]));
+        assert_eq!(switch_command(d2, None, false).unwrap(), (Rorg::Vld, vec![0x01, 0x1e, 0]));
+        assert_eq!(switch_command(d2, Some(1), true).unwrap(), (Rorg::Vld, vec![0x01, 0x01, 100]));
+
+        let a5 = EEPProfileCode::new(0xA5, 0x38, 0x08);
+        assert_eq!(level_command(a5, None, 120).unwrap(), (Rorg::Bs4, vec![0x02, 100, 0, 0x0d]));
+        assert_eq!(switch_command(a5, Some(0), true).unwrap(), (Rorg::Bs4, vec![0x01, 0, 0, 0x09]));
+        assert!(matches!(switch_command(a5, Some(1), true), Err(DeviceError::Unsupported(_))));
+
+        assert!(matches!(level_command(EEPProfileCode::new(0xA5, 0x02, 0x05), None, 50), Err(DeviceError::Unsupported(_))));
+    }
+
+    #[test]
+    fn given_two_channel_relay_then_handle_each_endpoint() {
+        let (base, address) = ("ff9b1280".parse().unwrap(), "05123456".parse().unwrap());
+        let transceiver = Transceiver::new(base);
+        let relay = transceiver.add(Actuator::new(address, 0x12, 2).with_controller(base));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap();
+        gateway.registry_mut().insert(address, DeviceEntry::new("D2-01-12".parse().unwrap()).with_channel(0, "Ceiling").with_channel(1, "Wall lamp"));
+        let [ceiling, lamp] = gateway.registry().endpoints().collect::<Vec<_>>()[..] else { panic!("not 2 endpoints") };
+
+        gateway.endpoint(lamp).unwrap().switch(true).unwrap();
+        assert!(matches!(gateway.endpoint(ceiling).unwrap().set_output(30).unwrap(), CommandOutcome::Applied(status) if status.channel == 0));
+        let outputs = relay.lock().unwrap().clone();
+        assert_eq!((outputs.output(0), outputs.output(1)), (Some(30), Some(100)));
+
+        // The status of the lamp comes first, and is left out
+        gateway.endpoint(lamp).unwrap().switch(false).unwrap();
+        gateway.endpoint(ceiling).unwrap().switch(false).unwrap();
+        let decoded = gateway.endpoint(ceiling).unwrap().events().next().unwrap().unwrap();
+        assert_eq!(decoded.channel(), Some(0));
+        assert_eq!(relay.lock().unwrap().output(1), Some(0));
+    }
+
+    #[test]
+    fn given_poller_then_query_relay_and_report_the_response() {
+        let (base, address) = ("ff9b1280".parse().unwrap(), "05123456".parse().unwrap());
+        let transceiver = Transceiver::new(base);
+        transceiver.add(Actuator::new(address, 0x12, 2).with_controller(base));
+        let mut poller = Poller::new();
+        poller.schedule(address, Query::Status { channel: 1 }, Duration::from_secs(60));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap().with_poller(poller);
+        gateway.registry_mut().insert(address, DeviceEntry::new("D2-01-12".parse().unwrap()));
+
+        let decoded = gateway.device(address).unwrap().events().next().unwrap().unwrap();
+        assert_eq!(decoded.channel(), Some(1));
+        assert!(matches!(gateway.take_events()[..], [GatewayEvent::Poll(PollOutcome::Answered { poll, .. })] if poll.device == address));
+        assert_eq!(gateway.send_poll().unwrap(), None);
+    }
+
+    #[test]
+    fn given_keepalive_then_send_the_last_command_again_but_not_the_queries() {
+        let (base, address) = ("ff9b1280".parse().unwrap(), "05123456".parse().unwrap());
+        let transceiver = Transceiver::new(base);
+        transceiver.add(Actuator::new(address, 0x12, 2).with_controller(base));
+        let mut keepalive = KeepAlive::new();
+        keepalive.schedule(address, Trigger::Interval(Duration::ZERO));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver.clone())).unwrap().with_keepalive(keepalive);
+        gateway.registry_mut().insert(address, DeviceEntry::new("D2-01-12".parse().unwrap()));
+
+        gateway.device(address).unwrap().send(Rorg::Vld, &[0x03, 0x1e]).unwrap();
+        assert_eq!(gateway.send_keepalives().unwrap(), 0);
+        gateway.device(address).unwrap().switch(true).unwrap();
+        assert_eq!(gateway.send_keepalives().unwrap(), 1);
+        let sent = transceiver.sent();
+        assert_eq!(sent[sent.len() - 1].user_data, sent[sent.len() - 2].user_data);
+        assert_eq!(&sent[sent.len() - 1].user_data[..], &[0x01, 0x1e, 100]);
+    }
+
+    #[test]
+    fn given_measurement_poll_then_send_the_d201_query() {
+        let (base, address) = ("ff9b1280".parse().unwrap(), "05123456".parse().unwrap());
+        let transceiver = Transceiver::new(base);
+        transceiver.add(Actuator::new(address, 0x0e, 2).with_controller(base));
+        let mut poller = Poller::new();
+        poller.schedule(address, Query::Measurement { channel: 1, power: true }, Duration::from_secs(60));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver.clone())).unwrap().with_poller(poller);
+        gateway.registry_mut().insert(address, DeviceEntry::new("D2-01-0E".parse().unwrap()));
+
+        let decoded = gateway.device(address).unwrap().events().next().unwrap().unwrap();
+        assert_eq!(decoded.channel(), Some(1));
+        assert_eq!(&transceiver.sent()[0].user_data[..], &[0x06, 0x21]);
+        assert!(matches!(gateway.take_events()[..], [GatewayEvent::Poll(PollOutcome::Answered { .. })]));
+    }
+
+    #[test]
+    fn given_subscriber_then_publish_every_event_on_the_bus() {
+        let sensor = "0180998e".parse().unwrap();
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        transceiver.add(TemperatureSensor::new(sensor, 0x05, Duration::from_secs(1)));
+        transceiver.inject(&ESP3Frame::assemble(0x04, &[0x06, 0x01], &[]));
+        transceiver.inject(&ESP3Frame::assemble(0x04, &[0x05, 0x02, 0x01, 0x80, 0x99, 0x8e], &[]));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap();
+        gateway.registry_mut().insert(sensor, DeviceEntry::new("A5-02-05".parse().unwrap()));
+        let subscriber = gateway.subscribe();
+
+        let events = gateway.events().take(3).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(events[..2], [GatewayEvent::DutyCycleLimit { cause: 1 }, GatewayEvent::SecureDevice { cause: 2, device: sensor }]);
+        assert!(matches!(events[2], GatewayEvent::Telegram { sender, values: Decoded::Temperature(_) } if sender == sensor));
+        assert_eq!(subscriber.try_iter().collect::<Vec<_>>(), events);
+        assert!(gateway.take_events().is_empty());
+
+        // Reading a device publishes to the subscribers, without keeping what it returned
+        gateway.device(sensor).unwrap().events().next().unwrap().unwrap();
+        assert!(matches!(subscriber.try_recv(), Ok(GatewayEvent::Telegram { .. })));
+        assert!(gateway.take_events().is_empty());
+    }
+
+    #[test]
+    fn given_transceiver_reset_then_write_its_settings_again() {
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        let mut gateway = Gateway::new(Port::from_serial(transceiver.clone())).unwrap();
+        let repeater = Some(RepeaterConfig { mode: RepeaterMode::On, level: 2 });
+        gateway.apply_config(&GatewayConfig { repeater, ..GatewayConfig::default() }).unwrap();
+        gateway.apply_config(&GatewayConfig { noise_threshold: Some(-90), ..GatewayConfig::default() }).unwrap();
+
+        transceiver.reset();
+        assert!(matches!(gateway.events().next(), Some(Ok(GatewayEvent::Reset { cause: Some(WakeupCause::Watchdog) }))));
+        let config = gateway.export_config().unwrap();
+        assert_eq!((config.repeater, config.noise_threshold), (repeater, Some(-90)));
+    }
+
+    #[test]
+    fn given_busy_network_then_keep_the_other_events_apart() {
+        let mut bus = EventBus::default();
+        bus.publish(GatewayEvent::Reset { cause: None });
+        for _ in 0..MAX_PENDING_TELEGRAMS + 10 {
+            bus.publish(GatewayEvent::Telegram { sender: Address::new(1), values: Decoded::Temperature(crate::eep::quantity::Temperature(20.0)) });
+        }
+        assert_eq!(bus.pop(), Some(GatewayEvent::Reset { cause: None }));
+        assert_eq!(bus.telegrams, MAX_PENDING_TELEGRAMS);
+        assert_eq!(bus.take().len(), MAX_PENDING_TELEGRAMS);
+        assert_eq!(bus.telegrams, 0);
+    }
+
+    #[test]
+    fn given_undecodable_frames_then_hand_them_to_the_hook() {
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        transceiver.add(TemperatureSensor::new("fefa1234".parse().unwrap(), 0x05, Duration::from_secs(1)));
+        transceiver.add(TemperatureSensor::new("0180998e".parse().unwrap(), 0x05, Duration::from_secs(1)));
+        transceiver.inject(&ESP3Frame::assemble(0x04, &[0x42], &[]));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook = seen.clone();
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap()
+            .on_undecodable(move |undecodable| hook.lock().unwrap().push((undecodable.frame.packet_type(), undecodable.error.source().unwrap().to_string())));
+        gateway.registry_mut().insert("0180998e".parse().unwrap(), DeviceEntry::new("A5-99-01".parse().unwrap()));
+
+        let mut device = gateway.device("0180998e".parse().unwrap()).unwrap();
+        assert!(matches!(device.events().next(), Some(Err(DeviceError::Decode(DecodeError::UnsupportedProfile(_))))));
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        assert!(seen[0].0 == 0x04 && seen[0].1.starts_with("Invalid value 0x42 at offset 0"));
+        assert_eq!(seen[1..], [(0x01, "Unknown device fefa1234".to_owned()), (0x01, "Unsupported profile".to_owned())]);
+    }
+
+    #[test]
+    fn given_chained_message_then_decode_it_once_complete() {
+        let meter = "05123456".parse().unwrap();
+        let chained = |user_data: &[u8]| ESP3Frame::assemble(0x01, &[&[0x40], user_data, &[0x05, 0x12, 0x34, 0x56, 0x00]].concat(), &[]);
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        // 19 W on channel 0 (D2-01 measurement response), in two telegrams
+        transceiver.inject(&chained(&[0x40, 0x00, 0x06, 0xd2, 0x07, 0x60, 0x00]));
+        transceiver.inject(&chained(&[0x41, 0x00, 0x00, 0x13]));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap().with_traffic(TrafficStats::new(Duration::from_secs(60)));
+        gateway.registry_mut().insert(meter, DeviceEntry::new("D2-01-0E".parse().unwrap()));
+
+        let values = gateway.device(meter).unwrap().events().next().unwrap().unwrap();
+        assert!(matches!(values, Decoded::Actuator(d2_01::Message::Measurement(measurement)) if measurement.value == 19));
+        assert_eq!(gateway.traffic().unwrap().summary(Instant::now()).rorgs[&0x40], 2);
+    }
+
+    #[test]
+    fn given_field_mapping_then_decode_the_device_with_it() {
+        let sensor = "ff9b1282".parse().unwrap();
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        transceiver.inject(&ESP3Frame::assemble(0x01, &[0xa5, 0x7d, 0x00, 0x00, 0x08, 0xff, 0x9b, 0x12, 0x82, 0x00], &[]));
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap();
+        gateway.registry_mut().insert(sensor, DeviceEntry::new(a5_3f::EEP).with_field("level", "0:8 0..250 = 0..100 %".parse().unwrap()).unwrap());
+
+        let Decoded::Mapped(reading) = gateway.device(sensor).unwrap().events().next().unwrap().unwrap() else { panic!("not mapped") };
+        assert_eq!(reading.values(), &[50.0]);
+    }
+
+    #[test]
+    fn given_registered_profile_then_decode_the_device_with_it() {
+        let (sensor, other) = ("0180998e".parse().unwrap(), "0180998f".parse().unwrap());
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap());
+        transceiver.inject(&ESP3Frame::assemble(0x01, &[0xa5, 0x2a, 0x00, 0x00, 0x08, 0x01, 0x80, 0x99, 0x8f, 0x00], &[]));
+        transceiver.inject(&ESP3Frame::assemble(0x01, &[0xa5, 0x2a, 0x00, 0x00, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00], &[]));
+        let mut profiles = ProfileDecoders::new();
+        profiles.register("A5-FF-01".parse().unwrap(), |_, erp: &RadioErp1| {
+            Ok(Decoded::Temperature(crate::eep::quantity::Temperature(erp.user_data[0] as f32 / 2.0)))
+        });
+        let mut gateway = Gateway::new(Port::from_serial(transceiver)).unwrap().with_profiles(profiles);
+        gateway.registry_mut().insert(sensor, DeviceEntry::new("A5-FF-01".parse().unwrap()));
+        gateway.registry_mut().insert(other, DeviceEntry::new("A5-02-05".parse().unwrap()));
+
+        let values = gateway.device(sensor).unwrap().events().next().unwrap().unwrap();
+        assert_eq!(values, Decoded::Temperature(crate::eep::quantity::Temperature(21.0)));
+        // Profiles without an application decoder keep the built-in one
+        assert!(matches!(gateway.take_events()[..], [GatewayEvent::Telegram { sender, values: Decoded::Temperature(_) }] if sender == other));
+    }
+}
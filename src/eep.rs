@@ -1,8 +1,352 @@
 //! EnOcean Equipment Profiles - interpretation of radio packet payloads
 
 use crate::enocean::*;
+use crate::packet::{Address, DecodeOptions, EEPProfileCode, RadioErp1, Security};
+use self::field::{bits, linear};
 use crate::*;
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
+// Without `std`, the legacy payload maps are ordered maps
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+
+pub mod a5_02;
+pub mod a5_04;
+pub mod a5_06;
+pub mod a5_07;
+pub mod a5_08;
+pub mod a5_09;
+pub mod a5_10;
+pub mod a5_11;
+pub mod a5_13;
+pub mod a5_14;
+pub mod a5_20;
+pub mod a5_38;
+pub mod a5_3f;
+#[cfg(feature = "loader")]
+pub mod compat;
+pub mod d2_01;
+pub mod d2_03;
+pub mod d2_05;
+pub mod d2_06;
+pub mod d2_14;
+pub mod d2_15;
+pub mod d2_50;
+pub mod d5_00;
+pub mod eltako;
+pub mod f6_02;
+pub mod f6_03;
+pub mod f6_04;
+pub mod f6_05;
+pub mod f6_10;
+pub mod field;
+pub mod gp;
+#[cfg(feature = "loader")]
+pub mod loader;
+pub mod profiles;
+pub mod quantity;
+pub mod tables;
+pub mod teach_in;
+
+/// Errors returned by the typed profile decoders
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError {
+    WrongRorg(Rorg),
+    BadLength(usize),
+    InvalidValue(u8),
+    UnsupportedType(u8),
+    UnsupportedProfile(EEPProfileCode),
+    TeachIn,
+    NotTeachIn,
+    UnknownDevice(Address),
+    UnknownManufacturer(u16),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongRorg(_)           => write!(f, "Unexpected RORG for this profile"),
+            Self::BadLength(_)           => write!(f, "Bad user data length"),
+            Self::InvalidValue(_)        => write!(f, "Invalid field value"),
+            Self::UnsupportedType(_)     => write!(f, "Unsupported profile type"),
+            Self::UnsupportedProfile(_)  => write!(f, "Unsupported profile"),
+            Self::TeachIn                => write!(f, "Telegram is a teach-in telegram"),
+            Self::NotTeachIn             => write!(f, "Telegram is not a teach-in telegram"),
+            Self::UnknownDevice(address) => write!(f, "Unknown device {}", address),
+            Self::UnknownManufacturer(_) => write!(f, "No decoder for manufacturer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl StdError for DecodeError {}
+
+/// A telegram decoded through one of the typed profile decoders
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Decoded {
+    /// F6-10-xx
+    HandlePosition(f6_10::HandlePosition),
+    /// F6-02-01, F6-02-02, F6-03-01, F6-03-02
+    Rocker(f6_02::RockerEvent),
+    /// F6-04-xx
+    KeyCard(f6_04::KeyCardState),
+    /// F6-05-00, F6-05-01, F6-05-02
+    Detector(f6_05::DetectorState),
+    /// D5-00-01
+    Contact(d5_00::ContactState),
+    /// A5-02-xx, in °C
+    Temperature(quantity::Temperature),
+    /// A5-04-xx
+    TemperatureHumidity(a5_04::Reading),
+    /// A5-06-xx
+    Light(a5_06::Reading),
+    /// A5-07-xx
+    Occupancy(a5_07::Occupancy),
+    /// A5-08-xx
+    LightTemperatureOccupancy(a5_08::Reading),
+    /// A5-09-xx
+    Gas(a5_09::Reading),
+    /// A5-10-xx
+    RoomPanel(a5_10::RoomPanel),
+    /// A5-11-01 to A5-11-05
+    ControllerStatus(a5_11::Status),
+    /// A5-13-xx
+    Environment(a5_13::Message),
+    /// A5-14-01 to A5-14-0A
+    MultiFunction(a5_14::Reading),
+    /// A5-20-01
+    ValveStatus(a5_20::Status),
+    /// A5-20-04
+    RadiatorValveStatus(a5_20::RadiatorStatus),
+    /// A5-38-08
+    CentralCommand(a5_38::CentralCommand),
+    /// D2-01-xx
+    Actuator(d2_01::Message),
+    /// D2-03-0A
+    PushButton(d2_03::PushButton),
+    /// D2-05-00
+    Blind(d2_05::Reply),
+    /// D2-06-01
+    HandleMultisensor(d2_06::Message),
+    /// D2-14-30, D2-14-31, D2-14-40, D2-14-41
+    Multisensor(d2_14::Reading),
+    /// D2-15-00
+    PeopleActivity(d2_15::Activity),
+    /// D2-50-xx
+    Ventilation(d2_50::Message),
+    /// A5-3F-7F, or any 4BS device with a field mapping, see [`a5_3f`]
+    Mapped(a5_3f::MappedReading),
+}
+
+impl Decoded {
+    /// The channel of a multi-channel device the values are about, see [`crate::endpoint`].
+    /// `None` for the values of the whole device.
+    pub fn channel(&self) -> Option<u8> {
+        match self {
+            Self::Actuator(message) => Some(message.channel()),
+            Self::Blind(reply) => Some(reply.channel),
+            _ => None,
+        }
+    }
+}
+
+/// Decode a radio telegram according to the profile of its sender.
+///
+/// The profile is not transmitted with data telegrams, so it has to be known in advance
+/// (typically learned from the teach-in telegram of the device). Profiles without a decoder
+/// here can be registered by applications, see [`profiles`].
+pub fn decode(eep: EEPProfileCode, erp: &RadioErp1) -> Result<Decoded, DecodeError> {
+    match (eep.rorg(), eep.func(), eep.eep_type()) {
+        (0xF6, 0x10, 0x00..=0x01) => Ok(Decoded::HandlePosition(f6_10::HandlePosition::decode(erp)?)),
+        (0xF6, 0x02, 0x01 | 0x02) => Ok(Decoded::Rocker(f6_02::RockerEvent::decode(erp)?)),
+        (0xF6, 0x03, 0x01 | 0x02) => Ok(Decoded::Rocker(f6_03::decode(erp)?)),
+        (0xF6, 0x04, t)           => Ok(Decoded::KeyCard(f6_04::KeyCardState::decode(erp, t)?)),
+        (0xF6, 0x05, t)           => Ok(Decoded::Detector(f6_05::DetectorState::decode(erp, t)?)),
+        (0xD5, 0x00, 0x01)        => Ok(Decoded::Contact(d5_00::ContactState::decode(erp)?)),
+        (0xA5, 0x02, t)           => Ok(Decoded::Temperature(a5_02::decode(erp, t)?)),
+        (0xA5, 0x04, t)           => Ok(Decoded::TemperatureHumidity(a5_04::Reading::decode(erp, t)?)),
+        (0xA5, 0x06, t)           => Ok(Decoded::Light(a5_06::Reading::decode(erp, t)?)),
+        (0xA5, 0x07, t)           => Ok(Decoded::Occupancy(a5_07::Occupancy::decode(erp, t)?)),
+        (0xA5, 0x08, t)           => Ok(Decoded::LightTemperatureOccupancy(a5_08::Reading::decode(erp, t)?)),
+        (0xA5, 0x09, t)           => Ok(Decoded::Gas(a5_09::Reading::decode(erp, t)?)),
+        (0xA5, 0x10, t)           => Ok(Decoded::RoomPanel(a5_10::RoomPanel::decode(erp, t)?)),
+        (0xA5, 0x11, t)           => Ok(Decoded::ControllerStatus(a5_11::Status::decode(erp, t)?)),
+        (0xA5, 0x13, t)           => Ok(Decoded::Environment(a5_13::Message::decode(erp, t)?)),
+        (0xA5, 0x14, t)           => Ok(Decoded::MultiFunction(a5_14::Reading::decode(erp, t)?)),
+        (0xA5, 0x20, 0x01)        => Ok(Decoded::ValveStatus(a5_20::Status::decode(erp)?)),
+        (0xA5, 0x20, 0x04)        => Ok(Decoded::RadiatorValveStatus(a5_20::RadiatorStatus::decode(erp)?)),
+        (0xA5, 0x38, 0x08)        => Ok(Decoded::CentralCommand(a5_38::CentralCommand::decode(erp)?)),
+        (0xD2, 0x01, 0x00..=0x14) => Ok(Decoded::Actuator(d2_01::Message::decode(erp)?)),
+        (0xD2, 0x03, 0x0A)        => Ok(Decoded::PushButton(d2_03::PushButton::decode(erp)?)),
+        (0xD2, 0x05, 0x00)        => Ok(Decoded::Blind(d2_05::Reply::decode(erp)?)),
+        (0xD2, 0x06, 0x01)        => Ok(Decoded::HandleMultisensor(d2_06::Message::decode(erp)?)),
+        (0xD2, 0x14, t)           => Ok(Decoded::Multisensor(d2_14::Reading::decode(erp, t)?)),
+        (0xD2, 0x15, 0x00)        => Ok(Decoded::PeopleActivity(d2_15::Activity::decode(erp)?)),
+        (0xD2, 0x50, 0x00 | 0x01 | 0x10 | 0x11) => Ok(Decoded::Ventilation(d2_50::Message::decode(erp)?)),
+        _ => Err(DecodeError::UnsupportedProfile(eep)),
+    }
+}
+
+/// Decode a radio telegram like [`decode`], as strictly as `options` say: in strict mode,
+/// telegrams with a subtelegram number or security level out of the specification, kept by
+/// a lenient packet decoding, are not decoded.
+pub fn decode_with(eep: EEPProfileCode, erp: &RadioErp1, options: DecodeOptions) -> Result<Decoded, DecodeError> {
+    check_options(erp, options)?;
+    decode(eep, erp)
+}
+
+/// Util : reject the telegrams `options` do not tolerate, before decoding them
+pub(crate) fn check_options(erp: &RadioErp1, options: DecodeOptions) -> Result<(), DecodeError> {
+    if options.strict {
+        if let Some(subtel_num) = erp.subtel_num.filter(|subtel_num| !subtel_num.is_defined()) {
+            return Err(DecodeError::InvalidValue(subtel_num.into()));
+        }
+        if let Some(Security::Other(value)) = erp.security {
+            return Err(DecodeError::InvalidValue(value));
+        }
+    }
+    Ok(())
+}
+
+/// A decoded telegram along with its origin and raw user data, as published by gateways.
+///
+/// With the `serde` feature, it serializes as
+/// `{"eep": "A5-02-05", "sender": "0180998e", "raw": "00008008", "values": {"Temperature": {"value": 19.92, "unit": "°C"}}}`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Report {
+    pub eep: EEPProfileCode,
+    pub sender: Address,
+    /// User data, as hex
+    pub raw: String,
+    pub values: Decoded,
+    /// Fields of [`Decoded::Mapped`] values, by name; empty for the typed decoders
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub mapping: BTreeMap<String, a5_3f::FieldMapping>,
+}
+
+impl Report {
+    /// Decode a telegram with the typed decoder of its profile
+    pub fn decode(eep: EEPProfileCode, erp: &RadioErp1) -> Result<Self, DecodeError> {
+        Ok(Self {
+            eep,
+            sender: erp.sender_id,
+            raw: hex::encode(&erp.user_data),
+            values: decode(eep, erp)?,
+            mapping: BTreeMap::new(),
+        })
+    }
+
+    /// The numeric values of the report, for metrics. Fields are named after their path in
+    /// the JSON form of [`Self::values`], in snake case and without the variant name, e.g.
+    /// `temperature` and `humidity` for A5-04; booleans count as 0 and 1, and textual
+    /// values are left out. Mapped values are named after the fields of [`Self::mapping`].
+    #[cfg(any(feature = "prometheus", feature = "influxdb"))]
+    pub fn data_points(&self) -> Vec<DataPoint> {
+        fn walk(value: &serde_json::Value, path: &mut Vec<String>, points: &mut Vec<DataPoint>) {
+            use serde_json::Value;
+            let field = || match &path[..] {
+                [variant] => variant.clone(),
+                [_, fields @ ..] => fields.join("_"),
+                [] => String::new(),
+            };
+            match value {
+                Value::Object(map) => match (map.get("value"), map.get("unit")) {
+                    (Some(Value::Number(value)), Some(Value::String(unit))) if map.len() == 2 => points.extend(
+                        value.as_f64().map(|value| DataPoint { field: field(), value, unit: Some(unit.clone()) })),
+                    _ => for (key, value) in map {
+                        path.push(snake_case(key));
+                        walk(value, path, points);
+                        path.pop();
+                    },
+                },
+                Value::Array(values) => for (index, value) in values.iter().enumerate() {
+                    path.push(index.to_string());
+                    walk(value, path, points);
+                    path.pop();
+                },
+                Value::Number(value) => points.extend(value.as_f64().map(|value| DataPoint { field: field(), value, unit: None })),
+                Value::Bool(value) => points.push(DataPoint { field: field(), value: *value as u8 as f64, unit: None }),
+                Value::String(_) | Value::Null => (),
+            }
+        }
+
+        fn snake_case(name: &str) -> String {
+            let mut snake = String::new();
+            for (index, c) in name.chars().enumerate() {
+                if c.is_uppercase() && index > 0 {
+                    snake.push('_');
+                }
+                snake.extend(c.to_lowercase());
+            }
+            snake
+        }
+
+        if let Decoded::Mapped(reading) = self.values {
+            if !self.mapping.is_empty() {
+                return reading.named(&self.mapping).into_iter()
+                    .map(|field| DataPoint {
+                        field: field.name.to_string(),
+                        value: field.value as f64,
+                        unit: (!field.unit.is_empty()).then(|| field.unit.to_string()),
+                    })
+                    .collect();
+            }
+        }
+
+        let mut points = Vec::new();
+        if let Ok(values) = serde_json::to_value(self.values) {
+            walk(&values, &mut Vec::new(), &mut points);
+        }
+        points
+    }
+}
+
+/// A numeric value of a [`Report`]
+#[cfg(any(feature = "prometheus", feature = "influxdb"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataPoint {
+    pub field: String,
+    pub value: f64,
+    pub unit: Option<String>,
+}
+
+/// Util : check that a telegram has the RORG and user data length a profile expects
+fn check_telegram(erp: &RadioErp1, rorg: Rorg, len: usize) -> Result<(), DecodeError> {
+    if erp.choice != rorg {
+        return Err(DecodeError::WrongRorg(erp.choice));
+    }
+    if erp.user_data.len() != len {
+        return Err(DecodeError::BadLength(erp.user_data.len()));
+    }
+    Ok(())
+}
+
+/// Util : check a VLD telegram and return its command ID (low nibble of the first byte)
+fn vld_command(erp: &RadioErp1, min_len: usize) -> Result<u8, DecodeError> {
+    if erp.choice != Rorg::Vld {
+        return Err(DecodeError::WrongRorg(erp.choice));
+    }
+    if erp.user_data.len() < min_len {
+        return Err(DecodeError::BadLength(erp.user_data.len()));
+    }
+    Ok(erp.user_data[0] & 0x0f)
+}
+
+/// Util : get the 4 data bytes (DB3..DB0) of a 4BS data telegram, rejecting teach-in telegrams
+fn data_4bs(erp: &RadioErp1) -> Result<[u8; 4], DecodeError> {
+    check_telegram(erp, Rorg::Bs4, 4)?;
+    if erp.user_data[3] & 0x08 == 0 {
+        return Err(DecodeError::TeachIn);
+    }
+    Ok(erp.user_data[..].try_into().unwrap())
+}
 
 pub fn parse_erp1_payload(esp: &ESP3) -> ParseEspResult<HashMap<String, String>> {
     //
@@ -274,10 +618,8 @@ pub fn create_f60201_telegram(command: F602EmulateCommand)->ParseEspResult<ESP3>
 
     // CRCs
     let crc_header = compute_crc8(&header);
-    println!("{}",crc_header);
     data.append(&mut opt_data);
     let crc_data = compute_crc8(&data);
-    println!("{}",crc_data);
 
     packet.extend_from_slice(&header);
     packet.push(crc_header);
@@ -465,8 +807,16 @@ pub fn create_smart_plug_default_config_packet(socket_id: [u8; 4]) -> ParseEspRe
 
 /// Unit Tests
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::frame::ESP3FrameRef;
+    use alloc::string::ToString;
+
+    /// Util : build a RadioErp1 view over raw ERP1 data (RORG, user data, sender ID, status)
+    pub(crate) fn erp1(data: &[u8]) -> RadioErp1<'_> {
+        RadioErp1::decode(ESP3FrameRef { packet_type: 1, data, optional_data: &[] }).unwrap()
+    }
+
     // ESP3 - ERP1 - EEP specified fields PARSING
     // --------------------------------------------------------------------
     #[test]
@@ -594,6 +944,61 @@ mod tests {
             [false, false, true, true, true, false, true, false]
         );
     }
+
+    // Typed decoding reports
+    // --------------------------------------------------------------------
+    #[test]
+    fn given_a50205_telegram_then_build_report() {
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0x00, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00]);
+        let report = Report::decode("A5-02-05".parse().unwrap(), &erp).unwrap();
+        assert_eq!(report.sender.to_string(), "0180998e");
+        assert_eq!(report.raw, "00000008");
+        assert_eq!(report.values, Decoded::Temperature(quantity::Temperature(40.0)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_report_then_serialize_values_with_units() {
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0x00, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00]);
+        let report = Report::decode("A5-02-05".parse().unwrap(), &erp).unwrap();
+        assert_eq!(serde_json::to_string(&report).unwrap(),
+                   r#"{"eep":"A5-02-05","sender":"0180998e","raw":"00000008","values":{"Temperature":{"value":40.0,"unit":"°C"}}}"#);
+    }
+
+    #[cfg(any(feature = "prometheus", feature = "influxdb"))]
+    #[test]
+    fn given_multi_quantity_report_then_list_a_data_point_per_quantity() {
+        let point = |field: &str, value: f64, unit: Option<&str>| DataPoint { field: field.into(), value, unit: unit.map(Into::into) };
+        let report = Report::decode("A5-04-01".parse().unwrap(), &erp1(&[0xa5, 0, 125, 125, 0x0a, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(report.data_points(), vec![point("humidity", 50.0, Some("%")), point("temperature", 20.0, Some("°C"))]);
+
+        let report = Report::decode("A5-04-03".parse().unwrap(), &erp1(&[0xa5, 255, 0x03, 0xff, 0x09, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(report.data_points(), vec![
+            point("event_triggered", 1.0, None), point("humidity", 100.0, Some("%")), point("temperature", 60.0, Some("°C")),
+        ]);
+    }
+
+    #[cfg(any(feature = "prometheus", feature = "influxdb"))]
+    #[test]
+    fn given_report_without_optional_values_then_skip_them() {
+        let report = Report::decode("A5-04-01".parse().unwrap(), &erp1(&[0xa5, 0, 125, 125, 0x08, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(report.data_points(), vec![DataPoint { field: "humidity".into(), value: 50.0, unit: Some("%".into()) }]);
+    }
+
+    #[cfg(any(feature = "prometheus", feature = "influxdb"))]
+    #[test]
+    fn given_mapped_report_then_name_the_data_points_after_the_fields() {
+        let mut registry = crate::registry::DeviceRegistry::new();
+        let entry = crate::registry::DeviceEntry::new(a5_3f::EEP)
+            .with_field("level", "0:8 0..250 = 0..100 %".parse().unwrap()).unwrap()
+            .with_field("mode", "24:4".parse().unwrap()).unwrap();
+        registry.insert("01020304".parse().unwrap(), entry);
+        let report = registry.decode(&erp1(&[0xa5, 0x7d, 0x00, 0x00, 0x38, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(report.data_points(), vec![
+            DataPoint { field: "level".into(), value: 50.0, unit: Some("%".into()) },
+            DataPoint { field: "mode".into(), value: 3.0, unit: None },
+        ]);
+    }
     // TELEGRAMS examples :
     //
     // A50401 when button is pushed
@@ -0,0 +1,141 @@
+//! A5-02 : Temperature sensors
+//!
+//! All the types of the family share the same layout and only differ by their range.
+//! The temperature is sent inverted: the maximum raw value maps to the lowest temperature.
+//!
+//!  - Types 0x01 to 0x1B use 8 bits in DB1.
+//!  - Types 0x20 and 0x30 use 10 bits in DB2.1..DB1.0.
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::eep::a5_02;
+//!
+//! let data = [0xa5, 0x00, 0x00, 0x80, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! let temperature = a5_02::decode(&erp, 0x05)?;  // A5-02-05, 0..40 °C
+//! assert!((temperature.0 - 19.92).abs() < 0.01);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`encode`] builds the data bytes of a sensor telegram in return, e.g. to emulate a sensor.
+
+use super::field::{encode_4bs, DataField};
+use super::quantity::Temperature;
+use super::{data_4bs, DecodeError};
+use crate::packet::RadioErp1;
+
+/// Per-type range: (type, min °C, max °C, resolution in bits)
+pub const RANGES: [(u8, f32, f32, u8); 25] = [
+    (0x01, -40.0,   0.0, 8),
+    (0x02, -30.0,  10.0, 8),
+    (0x03, -20.0,  20.0, 8),
+    (0x04, -10.0,  30.0, 8),
+    (0x05,   0.0,  40.0, 8),
+    (0x06,  10.0,  50.0, 8),
+    (0x07,  20.0,  60.0, 8),
+    (0x08,  30.0,  70.0, 8),
+    (0x09,  40.0,  80.0, 8),
+    (0x0A,  50.0,  90.0, 8),
+    (0x0B,  60.0, 100.0, 8),
+    (0x10, -60.0,  20.0, 8),
+    (0x11, -50.0,  30.0, 8),
+    (0x12, -40.0,  40.0, 8),
+    (0x13, -30.0,  50.0, 8),
+    (0x14, -20.0,  60.0, 8),
+    (0x15, -10.0,  70.0, 8),
+    (0x16,   0.0,  80.0, 8),
+    (0x17,  10.0,  90.0, 8),
+    (0x18,  20.0, 100.0, 8),
+    (0x19,  30.0, 110.0, 8),
+    (0x1A,  40.0, 120.0, 8),
+    (0x1B,  50.0, 130.0, 8),
+    (0x20, -10.0,  41.2, 10),
+    (0x30, -40.0,  62.3, 10),
+];
+
+/// The supported types, in the order of [`RANGES`]
+pub const TYPES: [u8; RANGES.len()] = {
+    let mut types = [0; RANGES.len()];
+    let mut i = 0;
+    while i < RANGES.len() {
+        types[i] = RANGES[i].0;
+        i += 1;
+    }
+    types
+};
+
+/// The temperature field of each type, built from [`RANGES`]
+pub const FIELDS: [(u8, DataField); RANGES.len()] = {
+    let mut fields = [(0, DataField::new(0, 0)); RANGES.len()];
+    let mut i = 0;
+    while i < RANGES.len() {
+        let (eep_type, min, max, bits) = RANGES[i];
+        fields[i] = (eep_type, match bits {
+            10 => DataField::new(14, 10).scaled((1023, 0), (min, max), Temperature::UNIT),
+            _  => DataField::new(16, 8).scaled((255, 0), (min, max), Temperature::UNIT),
+        });
+        i += 1;
+    }
+    fields
+};
+
+/// The temperature field of a type
+pub fn field(eep_type: u8) -> Result<DataField, DecodeError> {
+    FIELDS.iter()
+        .find(|(t, _)| *t == eep_type)
+        .map(|(_, field)| *field)
+        .ok_or(DecodeError::UnsupportedType(eep_type))
+}
+
+/// Decode an A5-02 telegram
+pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Temperature, DecodeError> {
+    let field = field(eep_type)?;
+    Ok(Temperature(field.value(&data_4bs(erp)?)?))
+}
+
+/// The data bytes of an A5-02 data telegram. Temperatures out of the range of the type
+/// are clamped.
+pub fn encode(temperature: Temperature, eep_type: u8) -> Result<[u8; 4], DecodeError> {
+    encode_4bs(&[(field(eep_type)?, temperature.0)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50205_telegram_then_decode_temperature() {
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0xff, 0x08, 1, 2, 3, 4, 0x00]);
+        assert_eq!(decode(&erp, 0x05).unwrap(), Temperature(0.0));
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0x00, 0x08, 1, 2, 3, 4, 0x00]);
+        assert_eq!(decode(&erp, 0x05).unwrap(), Temperature(40.0));
+    }
+
+    #[test]
+    fn given_a50230_telegram_then_decode_10bit_temperature() {
+        let erp = erp1(&[0xa5, 0x00, 0x03, 0xff, 0x08, 1, 2, 3, 4, 0x00]);
+        assert!((decode(&erp, 0x30).unwrap().0 + 40.0).abs() < 0.001);
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0x00, 0x08, 1, 2, 3, 4, 0x00]);
+        assert!((decode(&erp, 0x30).unwrap().0 - 62.3).abs() < 0.001);
+    }
+
+    #[test]
+    fn given_temperature_then_encode_a_data_telegram() {
+        assert_eq!(encode(Temperature(19.92), 0x05).unwrap(), [0x00, 0x00, 0x80, 0x08]);
+        assert_eq!(encode(Temperature(-40.0), 0x30).unwrap(), [0x00, 0x03, 0xff, 0x08]);
+        for eep_type in [0x04, 0x13, 0x20] {
+            let data = [[0xa5].as_slice(), &encode(Temperature(5.0), eep_type).unwrap(), &[1, 2, 3, 4, 0x00]].concat();
+            assert!((decode(&erp1(&data), eep_type).unwrap().0 - 5.0).abs() < 0.2);
+        }
+        assert!(matches!(encode(Temperature(5.0), 0x0c), Err(DecodeError::UnsupportedType(0x0c))));
+    }
+
+    #[test]
+    fn given_teach_in_or_unknown_type_then_return_error() {
+        let erp = erp1(&[0xa5, 0x08, 0x28, 0x46, 0x80, 1, 2, 3, 4, 0x00]);
+        assert!(matches!(decode(&erp, 0x05), Err(DecodeError::TeachIn)));
+        assert!(matches!(decode(&erp, 0x0c), Err(DecodeError::UnsupportedType(0x0c))));
+    }
+}
@@ -0,0 +1,146 @@
+//! A5-04 : Temperature and humidity sensors
+//!
+//! | Type | Humidity        | Temperature                     | Flags                  |
+//! |------|-----------------|---------------------------------|------------------------|
+//! | 0x01 | DB2, 0..250     | DB1, 0..250 → 0..+40 °C         | DB0.1 sensor available |
+//! | 0x02 | DB2, 0..250     | DB1, 0..250 → -20..+60 °C       | DB0.1 sensor available |
+//! | 0x03 | DB3, 0..255     | DB2.1..DB1, 0..1023 → -20..+60 °C | DB0.0 telegram type  |
+
+use super::field::{encode_4bs, DataField};
+use super::quantity::{RelativeHumidity, Temperature};
+use super::{data_4bs, DecodeError};
+use crate::packet::RadioErp1;
+
+/// The fields of a type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fields {
+    pub eep_type: u8,
+    pub humidity: DataField,
+    pub temperature: DataField,
+    /// DB0.1 sensor available for types 1 and 2, DB0.0 telegram type for type 3
+    pub flag: DataField,
+}
+
+/// Fields of every supported type
+pub const FIELDS: [Fields; 3] = [
+    Fields {
+        eep_type: 0x01,
+        humidity: DataField::new(8, 8).scaled((0, 250), (0.0, 100.0), RelativeHumidity::UNIT),
+        temperature: DataField::new(16, 8).scaled((0, 250), (0.0, 40.0), Temperature::UNIT),
+        flag: DataField::new(30, 1),
+    },
+    Fields {
+        eep_type: 0x02,
+        humidity: DataField::new(8, 8).scaled((0, 250), (0.0, 100.0), RelativeHumidity::UNIT),
+        temperature: DataField::new(16, 8).scaled((0, 250), (-20.0, 60.0), Temperature::UNIT),
+        flag: DataField::new(30, 1),
+    },
+    Fields {
+        eep_type: 0x03,
+        humidity: DataField::new(0, 8).scaled((0, 255), (0.0, 100.0), RelativeHumidity::UNIT),
+        temperature: DataField::new(14, 10).scaled((0, 1023), (-20.0, 60.0), Temperature::UNIT),
+        flag: DataField::new(31, 1),
+    },
+];
+
+/// The fields of a type
+pub fn fields(eep_type: u8) -> Result<&'static Fields, DecodeError> {
+    FIELDS.iter().find(|fields| fields.eep_type == eep_type).ok_or(DecodeError::UnsupportedType(eep_type))
+}
+
+/// A temperature and humidity measurement (A5-04-01, A5-04-02, A5-04-03)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reading {
+    pub humidity: RelativeHumidity,
+    /// `None` if the device reports its sensor as not available
+    pub temperature: Option<Temperature>,
+    /// A5-04-03 only: whether the telegram was triggered by an event rather than a heartbeat
+    pub event_triggered: Option<bool>,
+}
+
+impl Reading {
+    /// Decode an A5-04 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        let fields = fields(eep_type)?;
+        let db = data_4bs(erp)?;
+        // Raw values past 250 (A5-04-01/02) are out of the specification, and decoded past the
+        // end of the scale rather than clamped
+        let humidity = RelativeHumidity(fields.humidity.scale_raw(fields.humidity.raw(&db)?));
+        let temperature = Temperature(fields.temperature.scale_raw(fields.temperature.raw(&db)?));
+        let flag = fields.flag.raw(&db)? != 0;
+
+        Ok(match eep_type {
+            0x03 => Self { humidity, temperature: Some(temperature), event_triggered: Some(flag) },
+            _ => Self { humidity, temperature: flag.then_some(temperature), event_triggered: None },
+        })
+    }
+
+    /// The data bytes of an A5-04 data telegram. Without temperature, A5-04-01/02 clear the
+    /// sensor available flag, and A5-04-03 sends the lowest temperature.
+    pub fn encode(&self, eep_type: u8) -> Result<[u8; 4], DecodeError> {
+        let fields = fields(eep_type)?;
+        let flag = match eep_type {
+            0x03 => self.event_triggered.unwrap_or(false),
+            _ => self.temperature.is_some(),
+        };
+        encode_4bs(&[
+            (fields.humidity, self.humidity.0),
+            (fields.temperature, self.temperature.map_or(fields.temperature.scale.0, |t| t.0)),
+            (fields.flag, flag as u8 as f32),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50401_telegram_then_decode_temperature_and_humidity() {
+        // Same telegram as the legacy parser test: HUM 91.6, TMP 32.64
+        let erp = erp1(&[0xa5, 0, 229, 204, 10, 5, 17, 114, 247, 0]);
+        let reading = Reading::decode(&erp, 0x01).unwrap();
+        assert!((reading.humidity.0 - 91.6).abs() < 0.01);
+        assert!((reading.temperature.unwrap().0 - 32.64).abs() < 0.01);
+        assert_eq!(reading.event_triggered, None);
+    }
+
+    #[test]
+    fn given_a50402_telegram_without_temperature_sensor_then_temperature_is_none() {
+        let erp = erp1(&[0xa5, 0, 125, 125, 0x08, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x02).unwrap();
+        assert_eq!(reading.humidity, RelativeHumidity(50.0));
+        assert_eq!(reading.temperature, None);
+    }
+
+    #[test]
+    fn given_raw_values_past_the_range_then_decode_them_past_the_scale() {
+        let erp = erp1(&[0xa5, 0, 251, 255, 0x0a, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x01).unwrap();
+        assert!((reading.humidity.0 - 100.4).abs() < 0.001);
+        assert!((reading.temperature.unwrap().0 - 40.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn given_a50403_telegram_then_decode_10bit_temperature() {
+        let erp = erp1(&[0xa5, 255, 0x03, 0xff, 0x09, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x03).unwrap();
+        assert_eq!(reading.humidity, RelativeHumidity(100.0));
+        assert_eq!(reading.temperature, Some(Temperature(60.0)));
+        assert_eq!(reading.event_triggered, Some(true));
+    }
+
+    #[test]
+    fn given_reading_then_encode_a_data_telegram() {
+        let reading = Reading { humidity: RelativeHumidity(50.0), temperature: Some(Temperature(20.0)), event_triggered: None };
+        assert_eq!(reading.encode(0x01).unwrap(), [0x00, 125, 125, 0x0a]);
+        assert_eq!(Reading { temperature: None, ..reading }.encode(0x02).unwrap(), [0x00, 125, 0, 0x08]);
+
+        let reading = Reading { humidity: RelativeHumidity(100.0), temperature: Some(Temperature(60.0)), event_triggered: Some(true) };
+        assert_eq!(reading.encode(0x03).unwrap(), [255, 0x03, 0xff, 0x09]);
+        assert!(matches!(reading.encode(0x04), Err(DecodeError::UnsupportedType(0x04))));
+    }
+}
@@ -0,0 +1,80 @@
+//! A5-06 : Light sensors
+//!
+//! Types 0x01 and 0x02 carry two illuminance values with different ranges;
+//! the range select bit DB0.0 tells which one is valid:
+//!
+//! | Type | DB2 (RS = 1)       | DB1 (RS = 0)        | Supply voltage (DB3) |
+//! |------|--------------------|---------------------|----------------------|
+//! | 0x01 | 300..30000 lx      | 600..60000 lx       | 0..5.1 V             |
+//! | 0x02 | 0..510 lx          | 0..1020 lx          | 0..5.1 V             |
+//!
+//! Type 0x03 has a single 10-bit illuminance value in DB2..DB1.6 (0..1000 lx),
+//! and a supply voltage of 0..5.0 V for values 0..250 in DB3.
+
+use super::quantity::{Illuminance, Voltage};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// An illuminance measurement (A5-06-01, A5-06-02, A5-06-03)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reading {
+    pub illuminance: Illuminance,
+    pub supply_voltage: Voltage,
+}
+
+impl Reading {
+    /// Decode an A5-06 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        match eep_type {
+            0x01 => Self::decode_dual_range(erp, (300.0, 30000.0), (600.0, 60000.0)),
+            0x02 => Self::decode_dual_range(erp, (0.0, 510.0), (0.0, 1020.0)),
+            0x03 => {
+                let db = data_4bs(erp)?;
+                let raw = (db[1] as u16) << 2 | (db[2] >> 6) as u16;
+                Ok(Self {
+                    illuminance: Illuminance(raw.min(1000) as f32),
+                    supply_voltage: Voltage(linear(db[0] as u16, 250, 0.0, 5.0)),
+                })
+            }
+            _ => Err(DecodeError::UnsupportedType(eep_type)),
+        }
+    }
+
+    fn decode_dual_range(erp: &RadioErp1, ill2: (f32, f32), ill1: (f32, f32)) -> Result<Self, DecodeError> {
+        let db = data_4bs(erp)?;
+        let illuminance = match db[3] & 0x01 {
+            1 => linear(db[1] as u16, 255, ill2.0, ill2.1),
+            _ => linear(db[2] as u16, 255, ill1.0, ill1.1),
+        };
+        Ok(Self {
+            illuminance: Illuminance(illuminance),
+            supply_voltage: Voltage(linear(db[0] as u16, 255, 0.0, 5.1)),
+        })
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50601_telegram_then_use_range_select_bit() {
+        let low_range = erp1(&[0xa5, 255, 255, 0, 0x09, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&low_range, 0x01).unwrap();
+        assert_eq!(reading.illuminance, Illuminance(30000.0));
+        assert!((reading.supply_voltage.0 - 5.1).abs() < 0.001);
+
+        let high_range = erp1(&[0xa5, 0, 0, 255, 0x08, 1, 2, 3, 4, 0]);
+        assert_eq!(Reading::decode(&high_range, 0x01).unwrap().illuminance, Illuminance(60000.0));
+    }
+
+    #[test]
+    fn given_a50603_telegram_then_decode_10bit_illuminance() {
+        let erp = erp1(&[0xa5, 250, 0x7d, 0x00, 0x08, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x03).unwrap();
+        assert_eq!(reading.illuminance, Illuminance(500.0));
+        assert_eq!(reading.supply_voltage, Voltage(5.0));
+    }
+}
@@ -0,0 +1,76 @@
+//! A5-07 : Occupancy sensors
+//!
+//! | Type | Motion                          | Supply voltage (DB3, 0..250 → 0..5 V) | Illuminance               |
+//! |------|---------------------------------|---------------------------------------|---------------------------|
+//! | 0x01 | DB1 ≥ 128                       | only if DB0.0 is set                  | -                         |
+//! | 0x02 | DB0.7                           | always                                | -                         |
+//! | 0x03 | DB0.7                           | always                                | DB2..DB1.6, 0..1000 lx    |
+
+use super::quantity::{Illuminance, Voltage};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// An occupancy report (A5-07-01, A5-07-02, A5-07-03)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Occupancy {
+    /// Motion detected. `false` means occupancy is uncertain, not that the room is empty.
+    pub motion: bool,
+    /// Supply voltage, when reported
+    pub supply_voltage: Option<Voltage>,
+    /// A5-07-03 only
+    pub illuminance: Option<Illuminance>,
+}
+
+impl Occupancy {
+    /// Decode an A5-07 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        if !(0x01..=0x03).contains(&eep_type) {
+            return Err(DecodeError::UnsupportedType(eep_type));
+        }
+        let db = data_4bs(erp)?;
+        let supply_voltage = Voltage(linear(db[0].min(250) as u16, 250, 0.0, 5.0));
+
+        Ok(match eep_type {
+            0x01 => Self {
+                motion: db[2] >= 128,
+                supply_voltage: (db[3] & 0x01 != 0).then_some(supply_voltage),
+                illuminance: None,
+            },
+            0x02 => Self {
+                motion: db[3] & 0x80 != 0,
+                supply_voltage: Some(supply_voltage),
+                illuminance: None,
+            },
+            _ => Self {
+                motion: db[3] & 0x80 != 0,
+                supply_voltage: Some(supply_voltage),
+                illuminance: Some(Illuminance(((db[1] as u16) << 2 | (db[2] >> 6) as u16).min(1000) as f32)),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50701_telegram_then_decode_pir_status() {
+        let erp = erp1(&[0xa5, 250, 0, 200, 0x09, 1, 2, 3, 4, 0]);
+        assert_eq!(Occupancy::decode(&erp, 0x01).unwrap(),
+                   Occupancy { motion: true, supply_voltage: Some(Voltage(5.0)), illuminance: None });
+        let erp = erp1(&[0xa5, 250, 0, 20, 0x08, 1, 2, 3, 4, 0]);
+        assert_eq!(Occupancy::decode(&erp, 0x01).unwrap(),
+                   Occupancy { motion: false, supply_voltage: None, illuminance: None });
+    }
+
+    #[test]
+    fn given_a50703_telegram_then_decode_illuminance() {
+        let erp = erp1(&[0xa5, 125, 0x7d, 0x00, 0x88, 1, 2, 3, 4, 0]);
+        assert_eq!(Occupancy::decode(&erp, 0x03).unwrap(),
+                   Occupancy { motion: true, supply_voltage: Some(Voltage(2.5)), illuminance: Some(Illuminance(500.0)) });
+    }
+}
@@ -0,0 +1,103 @@
+//! A5-08 : Light, temperature and occupancy sensors
+//!
+//! All types share the same layout, only the scaling differs:
+//!
+//! | Type | Illuminance (DB2, 0..255) | Temperature (DB1, 0..255) |
+//! |------|---------------------------|---------------------------|
+//! | 0x01 | 0..510 lx                 | 0..+51 °C                 |
+//! | 0x02 | 0..1020 lx                | 0..+51 °C                 |
+//! | 0x03 | 0..1530 lx                | -30..+50 °C               |
+//!
+//! DB3 is the supply voltage (0..5.1 V). The PIR and occupancy button bits
+//! (DB0.1 and DB0.0) use inverted logic: 0 means detected / pressed.
+
+use super::field::DataField;
+use super::quantity::{Illuminance, Temperature, Voltage};
+use super::{data_4bs, DecodeError};
+use crate::packet::RadioErp1;
+
+/// The scaled fields of a type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fields {
+    pub eep_type: u8,
+    pub supply_voltage: DataField,
+    pub illuminance: DataField,
+    pub temperature: DataField,
+}
+
+const fn row(eep_type: u8, illuminance_max: f32, temperature: (f32, f32)) -> Fields {
+    Fields {
+        eep_type,
+        supply_voltage: DataField::new(0, 8).scaled((0, 255), (0.0, 5.1), Voltage::UNIT),
+        illuminance: DataField::new(8, 8).scaled((0, 255), (0.0, illuminance_max), Illuminance::UNIT),
+        temperature: DataField::new(16, 8).scaled((0, 255), temperature, Temperature::UNIT),
+    }
+}
+
+/// Fields of every supported type
+pub const FIELDS: [Fields; 3] = [
+    row(0x01, 510.0, (0.0, 51.0)),
+    row(0x02, 1020.0, (0.0, 51.0)),
+    row(0x03, 1530.0, (-30.0, 50.0)),
+];
+
+/// The scaled fields of a type
+pub fn fields(eep_type: u8) -> Result<&'static Fields, DecodeError> {
+    FIELDS.iter().find(|fields| fields.eep_type == eep_type).ok_or(DecodeError::UnsupportedType(eep_type))
+}
+
+/// A combined light, temperature and occupancy report (A5-08-01, A5-08-02, A5-08-03)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reading {
+    pub supply_voltage: Voltage,
+    pub illuminance: Illuminance,
+    pub temperature: Temperature,
+    /// Motion detected by the PIR sensor
+    pub motion: bool,
+    /// Occupancy button pressed
+    pub occupancy_button: bool,
+}
+
+impl Reading {
+    /// Decode an A5-08 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        let fields = fields(eep_type)?;
+        let db = data_4bs(erp)?;
+
+        Ok(Self {
+            supply_voltage: Voltage(fields.supply_voltage.value(&db)?),
+            illuminance: Illuminance(fields.illuminance.value(&db)?),
+            temperature: Temperature(fields.temperature.value(&db)?),
+            motion: db[3] & 0x02 == 0,
+            occupancy_button: db[3] & 0x01 == 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50801_telegram_then_decode_all_fields() {
+        let erp = erp1(&[0xa5, 0, 255, 0, 0x09, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x01).unwrap();
+        assert_eq!(reading.illuminance, Illuminance(510.0));
+        assert_eq!(reading.temperature, Temperature(0.0));
+        assert!(reading.motion);
+        assert!(!reading.occupancy_button);
+    }
+
+    #[test]
+    fn given_a50803_telegram_then_use_its_scaling() {
+        let erp = erp1(&[0xa5, 255, 255, 255, 0x0a, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x03).unwrap();
+        assert_eq!(reading.illuminance, Illuminance(1530.0));
+        assert_eq!(reading.temperature, Temperature(50.0));
+        assert!(!reading.motion);
+        assert!(reading.occupancy_button);
+    }
+}
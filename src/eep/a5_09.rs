@@ -0,0 +1,115 @@
+//! A5-09 : Gas sensors
+//!
+//! Supported types:
+//!
+//!  - 0x02 : CO sensor (DB2, 0..255 → 0..1020 ppm), temperature DB1 (0..51 °C) and supply voltage DB3
+//!  - 0x04 : CO2 sensor (DB2, 0..255 → 0..2550 ppm), humidity DB3 (0..200 → 0..100 %), temperature DB1 (0..51 °C)
+//!  - 0x05 : VOC sensor (DB3..DB2, 0..65535 ppb before scaling), VOC identification DB1, scale multiplier DB0.1..0
+//!  - 0x08 : Pure CO2 sensor (DB2, 0..255 → 0..2000 ppm)
+
+use super::quantity::{RelativeHumidity, Temperature, Voltage};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// A gas concentration report (A5-09-02, A5-09-04, A5-09-05, A5-09-08)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Reading {
+    CarbonMonoxide {
+        /// Concentration, in ppm
+        concentration: f32,
+        /// If the sensor is available
+        temperature: Option<Temperature>,
+        supply_voltage: Voltage,
+    },
+    CarbonDioxide {
+        /// Concentration, in ppm
+        concentration: f32,
+        /// If the sensor is available
+        humidity: Option<RelativeHumidity>,
+        /// If the sensor is available
+        temperature: Option<Temperature>,
+    },
+    Voc {
+        /// Concentration, in ppb
+        concentration: f32,
+        /// VOC identification, see [`voc_name`]
+        substance: u8,
+    },
+}
+
+/// Human-readable name of a VOC identification code (A5-09-05, DB1)
+pub fn voc_name(substance: u8) -> Option<&'static str> {
+    const NAMES: [&str; 27] = [
+        "VOCT (total)", "Formaldehyde", "Benzene", "Styrene", "Toluene", "Tetrachloroethylene",
+        "Xylene", "n-Hexane", "n-Octane", "Cyclopentane", "Methanol", "Ethanol", "1-Pentanol",
+        "Acetone", "Ethylene oxide", "Acetaldehyde", "Acetic acid", "Propionic acid", "Valeric acid",
+        "Butyric acid", "Ammoniac", "", "Hydrogen sulfide", "Dimethylsulfide", "2-Butanol",
+        "2-Methylpropanol", "Diethyl ether",
+    ];
+    match substance {
+        0xFF => Some("Ozone"),
+        s => NAMES.get(s as usize).copied().filter(|name| !name.is_empty()),
+    }
+}
+
+impl Reading {
+    /// Decode an A5-09 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        if ![0x02, 0x04, 0x05, 0x08].contains(&eep_type) {
+            return Err(DecodeError::UnsupportedType(eep_type));
+        }
+        let db = data_4bs(erp)?;
+        let temperature = Temperature(linear(db[2] as u16, 255, 0.0, 51.0));
+
+        Ok(match eep_type {
+            0x02 => Self::CarbonMonoxide {
+                concentration: linear(db[1] as u16, 255, 0.0, 1020.0),
+                temperature: (db[3] & 0x02 != 0).then_some(temperature),
+                supply_voltage: Voltage(linear(db[0].min(250) as u16, 250, 0.0, 5.0)),
+            },
+            0x04 => Self::CarbonDioxide {
+                concentration: linear(db[1] as u16, 255, 0.0, 2550.0),
+                humidity: (db[3] & 0x04 != 0).then(|| RelativeHumidity(linear(db[0].min(200) as u16, 200, 0.0, 100.0))),
+                temperature: (db[3] & 0x02 != 0).then_some(temperature),
+            },
+            0x05 => {
+                let multiplier = [0.01, 0.1, 1.0, 10.0][(db[3] & 0x03) as usize];
+                Self::Voc {
+                    concentration: u16::from_be_bytes([db[0], db[1]]) as f32 * multiplier,
+                    substance: db[2],
+                }
+            }
+            _ => Self::CarbonDioxide {
+                concentration: linear(db[1] as u16, 255, 0.0, 2000.0),
+                humidity: None,
+                temperature: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50904_telegram_then_decode_co2_humidity_and_temperature() {
+        let erp = erp1(&[0xa5, 100, 80, 255, 0x0e, 1, 2, 3, 4, 0]);
+        assert_eq!(Reading::decode(&erp, 0x04).unwrap(), Reading::CarbonDioxide {
+            concentration: 800.0,
+            humidity: Some(RelativeHumidity(50.0)),
+            temperature: Some(Temperature(51.0)),
+        });
+    }
+
+    #[test]
+    fn given_a50905_telegram_then_apply_scale_multiplier() {
+        let erp = erp1(&[0xa5, 0x01, 0xf4, 0x01, 0x09, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x05).unwrap();
+        assert_eq!(reading, Reading::Voc { concentration: 50.0, substance: 1 });
+        assert_eq!(voc_name(1), Some("Formaldehyde"));
+    }
+}
@@ -0,0 +1,281 @@
+//! A5-10 : Room operating panels
+//!
+//! The family has many types combining the same few fields in different positions.
+//! Each type is described by a row of [`LAYOUTS`], and a single decoder interprets it.
+//!
+//! Field encodings:
+//!
+//!  - Fan speed in DB3: 210..255 auto, 190..209 stage 0, 165..189 stage 1, 145..164 stage 2, 0..144 stage 3
+//!  - Fan speed in DB0.6..4: 0 auto, 1..6 stage 0..5, 7 off
+//!  - Set point: relative position of the knob, reported here as a fraction in 0..=1
+//!  - Switch in DB0.0: occupancy button (0 = pressed), day/night slide switch (0 = night), or contact (0 = open)
+//!
+//! Types 0x20 to 0x23 also carry special heating states in DB0, which are not decoded.
+
+use super::quantity::{Illuminance, RelativeHumidity, Temperature, Voltage};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// Fan speed selector position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum FanSpeed {
+    Auto,
+    Stage(u8),
+    Off,
+}
+
+/// Position of the day/night slide switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum SlideSwitch {
+    Day,
+    Night,
+}
+
+/// Where a type carries its fan speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum FanField {
+    None,
+    /// Ranges of DB3
+    Db3,
+    /// Enumeration in DB0.6..4
+    Db0,
+}
+
+/// Meaning of DB0.0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum SwitchField {
+    None,
+    Occupancy,
+    DayNight,
+    Contact,
+}
+
+/// Meaning of a full byte in the telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ByteField {
+    None,
+    /// Set point, 0..255
+    SetPoint,
+    /// Set point, 0..63 in bits 7..2
+    SetPoint6,
+    /// Relative humidity, 0..250 → 0..100 %
+    Humidity,
+    /// Illuminance, 0..250 → 0..1000 lx
+    Illuminance,
+    /// Supply voltage, 0..250 → 0..5 V
+    SupplyVoltage,
+}
+
+/// Encoding of the temperature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum TemperatureField {
+    /// DB1, 255..0 → 0..40 °C
+    Inverted,
+    /// DB1, 0..250 → 0..40 °C
+    Linear,
+    /// DB2.1..DB1.0, 1023..0 → -10..+41.2 °C
+    TenBits,
+}
+
+/// Description of one type of the A5-10 family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Layout {
+    pub eep_type: u8,
+    pub db3: ByteField,
+    pub db2: ByteField,
+    pub fan: FanField,
+    pub temperature: TemperatureField,
+    pub switch: SwitchField,
+}
+
+const fn row(eep_type: u8, db3: ByteField, db2: ByteField, fan: FanField, temperature: TemperatureField, switch: SwitchField) -> Layout {
+    Layout { eep_type, db3, db2, fan, temperature, switch }
+}
+
+use ByteField as B;
+use FanField as F;
+use SwitchField as S;
+use TemperatureField as T;
+
+/// Layout of every supported type
+pub const LAYOUTS: [Layout; 33] = [
+    row(0x01, B::None,          B::SetPoint,    F::Db3,  T::Inverted, S::Occupancy),
+    row(0x02, B::None,          B::SetPoint,    F::Db3,  T::Inverted, S::DayNight),
+    row(0x03, B::None,          B::SetPoint,    F::None, T::Inverted, S::None),
+    row(0x04, B::None,          B::SetPoint,    F::Db3,  T::Inverted, S::None),
+    row(0x05, B::None,          B::SetPoint,    F::None, T::Inverted, S::Occupancy),
+    row(0x06, B::None,          B::SetPoint,    F::None, T::Inverted, S::DayNight),
+    row(0x07, B::None,          B::None,        F::Db3,  T::Inverted, S::None),
+    row(0x08, B::None,          B::None,        F::Db3,  T::Inverted, S::Occupancy),
+    row(0x09, B::None,          B::None,        F::Db3,  T::Inverted, S::DayNight),
+    row(0x0A, B::None,          B::SetPoint,    F::None, T::Inverted, S::Contact),
+    row(0x0B, B::None,          B::None,        F::None, T::Inverted, S::Contact),
+    row(0x0C, B::None,          B::None,        F::None, T::Inverted, S::Occupancy),
+    row(0x0D, B::None,          B::None,        F::None, T::Inverted, S::DayNight),
+    row(0x10, B::SetPoint,      B::Humidity,    F::None, T::Linear,   S::Occupancy),
+    row(0x11, B::SetPoint,      B::Humidity,    F::None, T::Linear,   S::DayNight),
+    row(0x12, B::SetPoint,      B::Humidity,    F::None, T::Linear,   S::None),
+    row(0x13, B::None,          B::Humidity,    F::None, T::Linear,   S::Occupancy),
+    row(0x14, B::None,          B::Humidity,    F::None, T::Linear,   S::DayNight),
+    row(0x15, B::None,          B::SetPoint6,   F::None, T::TenBits,  S::None),
+    row(0x16, B::None,          B::SetPoint6,   F::None, T::TenBits,  S::Occupancy),
+    row(0x17, B::None,          B::None,        F::None, T::TenBits,  S::Occupancy),
+    row(0x18, B::Illuminance,   B::SetPoint,    F::Db0,  T::Inverted, S::Occupancy),
+    row(0x19, B::Humidity,      B::SetPoint,    F::Db0,  T::Inverted, S::Occupancy),
+    row(0x1A, B::SupplyVoltage, B::SetPoint,    F::Db0,  T::Inverted, S::Occupancy),
+    row(0x1B, B::SupplyVoltage, B::Illuminance, F::Db0,  T::Inverted, S::Occupancy),
+    row(0x1C, B::Illuminance,   B::SetPoint,    F::Db0,  T::Inverted, S::Occupancy),
+    row(0x1D, B::Humidity,      B::SetPoint,    F::Db0,  T::Inverted, S::Occupancy),
+    row(0x1E, B::SupplyVoltage, B::Illuminance, F::Db0,  T::Inverted, S::Occupancy),
+    row(0x1F, B::None,          B::SetPoint,    F::Db3,  T::Inverted, S::Occupancy),
+    row(0x20, B::None,          B::SetPoint,    F::None, T::Inverted, S::None),
+    row(0x21, B::Humidity,      B::SetPoint,    F::None, T::Inverted, S::None),
+    row(0x22, B::Humidity,      B::SetPoint,    F::Db0,  T::Inverted, S::None),
+    row(0x23, B::Humidity,      B::SetPoint,    F::Db0,  T::Inverted, S::Occupancy),
+];
+
+/// The supported types, in the order of [`LAYOUTS`]
+pub const TYPES: [u8; LAYOUTS.len()] = {
+    let mut types = [0; LAYOUTS.len()];
+    let mut i = 0;
+    while i < LAYOUTS.len() {
+        types[i] = LAYOUTS[i].eep_type;
+        i += 1;
+    }
+    types
+};
+
+/// The state of a room operating panel (A5-10-01 to A5-10-23)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RoomPanel {
+    pub temperature: Temperature,
+    /// Set point knob position, in 0..=1
+    pub set_point: Option<f32>,
+    pub fan_speed: Option<FanSpeed>,
+    pub humidity: Option<RelativeHumidity>,
+    pub illuminance: Option<Illuminance>,
+    pub supply_voltage: Option<Voltage>,
+    /// Occupancy button pressed
+    pub occupancy: Option<bool>,
+    pub slide_switch: Option<SlideSwitch>,
+    /// Contact closed
+    pub contact: Option<bool>,
+}
+
+impl RoomPanel {
+    /// Decode an A5-10 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        let layout = LAYOUTS.iter()
+            .find(|l| l.eep_type == eep_type)
+            .ok_or(DecodeError::UnsupportedType(eep_type))?;
+        let db = data_4bs(erp)?;
+        let mut panel = RoomPanel::default();
+
+        for (field, byte) in [(layout.db3, db[0]), (layout.db2, db[1])] {
+            match field {
+                B::None => {}
+                B::SetPoint => panel.set_point = Some(byte as f32 / 255.0),
+                B::SetPoint6 => panel.set_point = Some((byte >> 2) as f32 / 63.0),
+                B::Humidity => panel.humidity = Some(RelativeHumidity(linear(byte.min(250) as u16, 250, 0.0, 100.0))),
+                B::Illuminance => panel.illuminance = Some(Illuminance(linear(byte.min(250) as u16, 250, 0.0, 1000.0))),
+                B::SupplyVoltage => panel.supply_voltage = Some(Voltage(linear(byte.min(250) as u16, 250, 0.0, 5.0))),
+            }
+        }
+
+        panel.fan_speed = match layout.fan {
+            F::None => None,
+            F::Db3 => Some(match db[0] {
+                210..=255 => FanSpeed::Auto,
+                190..=209 => FanSpeed::Stage(0),
+                165..=189 => FanSpeed::Stage(1),
+                145..=164 => FanSpeed::Stage(2),
+                _ => FanSpeed::Stage(3),
+            }),
+            F::Db0 => Some(match (db[3] >> 4) & 0x07 {
+                0 => FanSpeed::Auto,
+                7 => FanSpeed::Off,
+                n => FanSpeed::Stage(n - 1),
+            }),
+        };
+
+        panel.temperature = Temperature(match layout.temperature {
+            T::Inverted => linear(255 - db[2] as u16, 255, 0.0, 40.0),
+            T::Linear => linear(db[2].min(250) as u16, 250, 0.0, 40.0),
+            T::TenBits => {
+                let raw = ((db[1] & 0x03) as u16) << 8 | db[2] as u16;
+                linear(1023 - raw, 1023, -10.0, 41.2)
+            }
+        });
+
+        let bit = db[3] & 0x01 != 0;
+        match layout.switch {
+            S::None => {}
+            S::Occupancy => panel.occupancy = Some(!bit),
+            S::DayNight => panel.slide_switch = Some(if bit { SlideSwitch::Day } else { SlideSwitch::Night }),
+            S::Contact => panel.contact = Some(bit),
+        }
+
+        Ok(panel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a51001_telegram_then_decode_fan_set_point_temperature_and_occupancy() {
+        let erp = erp1(&[0xa5, 180, 255, 0, 0x08, 1, 2, 3, 4, 0]);
+        let panel = RoomPanel::decode(&erp, 0x01).unwrap();
+        assert_eq!(panel.fan_speed, Some(FanSpeed::Stage(1)));
+        assert_eq!(panel.set_point, Some(1.0));
+        assert_eq!(panel.temperature, Temperature(40.0));
+        assert_eq!(panel.occupancy, Some(true));
+        assert_eq!(panel.humidity, None);
+    }
+
+    #[test]
+    fn given_a51011_telegram_then_decode_humidity_and_slide_switch() {
+        let erp = erp1(&[0xa5, 0, 125, 250, 0x09, 1, 2, 3, 4, 0]);
+        let panel = RoomPanel::decode(&erp, 0x11).unwrap();
+        assert_eq!(panel.humidity, Some(RelativeHumidity(50.0)));
+        assert_eq!(panel.temperature, Temperature(40.0));
+        assert_eq!(panel.slide_switch, Some(SlideSwitch::Day));
+        assert_eq!(panel.fan_speed, None);
+    }
+
+    #[test]
+    fn given_a51016_telegram_then_decode_10bit_temperature() {
+        let erp = erp1(&[0xa5, 0, 0xff, 0xff, 0x09, 1, 2, 3, 4, 0]);
+        let panel = RoomPanel::decode(&erp, 0x16).unwrap();
+        assert_eq!(panel.set_point, Some(1.0));
+        assert_eq!(panel.temperature, Temperature(-10.0));
+        assert_eq!(panel.occupancy, Some(false));
+    }
+
+    #[test]
+    fn given_a51018_telegram_then_decode_fan_enumeration() {
+        let erp = erp1(&[0xa5, 250, 0, 255, 0x78, 1, 2, 3, 4, 0]);
+        let panel = RoomPanel::decode(&erp, 0x18).unwrap();
+        assert_eq!(panel.illuminance, Some(Illuminance(1000.0)));
+        assert_eq!(panel.fan_speed, Some(FanSpeed::Off));
+        assert_eq!(panel.temperature, Temperature(0.0));
+    }
+}
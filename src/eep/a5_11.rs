@@ -0,0 +1,260 @@
+//! A5-11 : Controller status
+//!
+//! Feedback sent by controllers and actuators about their own state, as opposed to the
+//! sensor data they act upon.
+//!
+//! | Type | Device                          | DB3               | DB2                | DB1                 |
+//! |------|---------------------------------|-------------------|--------------------|---------------------|
+//! | 0x01 | Lighting controller             | Illuminance       | Illuminance target | Dimming output      |
+//! | 0x02 | Temperature controller          | Control variable  | Fan stage          | Set point           |
+//! | 0x03 | Blind status                    | Position          | Angle              | Flags and states    |
+//! | 0x04 | Extended lighting status        | Parameter 1       | Parameter 2        | Parameter 3         |
+//! | 0x05 | Dual-channel switch actuator    | Relay states      | -                  | -                   |
+//!
+//! Flags are carried in DB0, see each variant of [`Status`].
+
+use super::quantity::{Illuminance, Temperature};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// Mode of a temperature controller (A5-11-02, DB0.6..5)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ControllerMode {
+    Heating,
+    Cooling,
+    Off,
+}
+
+/// Room occupancy as seen by a temperature controller (A5-11-02, DB0.1..0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum RoomOccupancy {
+    Occupied,
+    Unoccupied,
+    StandBy,
+    Frost,
+}
+
+/// Motion of a blind (A5-11-03, DB1.1..0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum BlindMotion {
+    Stopped,
+    Opening,
+    Closing,
+}
+
+/// End position of a blind (A5-11-03, DB1.3..2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum EndPosition {
+    NotReached,
+    Open,
+    Closed,
+}
+
+/// Parameters of an extended lighting status (A5-11-04), selected by DB0.2..1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum LightingParameters {
+    /// Dimmer value 0..255, and lamp operating hours
+    Dimming { value: u8, operating_hours: Option<u16> },
+    Rgb { red: u8, green: u8, blue: u8 },
+    /// Raw energy metering value, and unit code in DB1 (see the EEP specification)
+    Energy { value: u16, unit: u8 },
+}
+
+/// A controller status telegram (A5-11-01 to A5-11-05)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Status {
+    /// A5-11-01
+    Lighting {
+        illuminance: Illuminance,
+        /// Illuminance set point, 0..255
+        set_point: u8,
+        /// Dimming output level, 0..255
+        output: u8,
+        repeater: bool,
+        power_relay_timer: bool,
+        daylight_harvesting: bool,
+        dimming: bool,
+        magnet_contact: bool,
+        occupancy: bool,
+        power_relay: bool,
+    },
+    /// A5-11-02
+    TemperatureController {
+        /// Control variable, in % (raw 0..255)
+        control_variable: f32,
+        /// Fan stage, `None` for automatic
+        fan_stage: Option<u8>,
+        set_point: Temperature,
+        alarm: bool,
+        mode: Option<ControllerMode>,
+        /// The controller is overridden
+        override_active: bool,
+        energy_hold_off: bool,
+        occupancy: RoomOccupancy,
+    },
+    /// A5-11-03
+    Blind {
+        /// Position, in %, when reported
+        position: Option<u8>,
+        /// Angle, in degrees, when reported
+        angle: Option<i16>,
+        end_position: Option<EndPosition>,
+        motion: Option<BlindMotion>,
+        /// Error state, 0 = no error
+        error: u8,
+        service_mode: bool,
+        inverse_mode: bool,
+    },
+    /// A5-11-04
+    ExtendedLighting {
+        parameters: LightingParameters,
+        service_mode: bool,
+        /// Error state, 0 = no error
+        error: u8,
+        on: bool,
+    },
+    /// A5-11-05
+    DualChannelSwitch {
+        relay1: bool,
+        relay2: bool,
+        /// Working mode, 1..4
+        working_mode: u8,
+    },
+}
+
+impl Status {
+    /// Decode an A5-11 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        if !(0x01..=0x05).contains(&eep_type) {
+            return Err(DecodeError::UnsupportedType(eep_type));
+        }
+        let db = data_4bs(erp)?;
+        let flag = |byte: usize, bit: u8| db[byte] & (1 << bit) != 0;
+
+        match eep_type {
+            0x01 => Ok(Self::Lighting {
+                illuminance: Illuminance(linear(db[0] as u16, 255, 0.0, 510.0)),
+                set_point: db[1],
+                output: db[2],
+                repeater: flag(3, 7),
+                power_relay_timer: flag(3, 6),
+                daylight_harvesting: flag(3, 5),
+                dimming: flag(3, 4),
+                magnet_contact: flag(3, 2),
+                occupancy: flag(3, 1),
+                power_relay: flag(3, 0),
+            }),
+            0x02 => Ok(Self::TemperatureController {
+                control_variable: linear(db[0] as u16, 255, 0.0, 100.0),
+                fan_stage: match db[1] {
+                    0..=3 => Some(db[1]),
+                    _ => None,
+                },
+                set_point: Temperature(linear(db[2] as u16, 255, 0.0, 51.2)),
+                alarm: flag(3, 7),
+                mode: match (db[3] >> 5) & 0x03 {
+                    1 => Some(ControllerMode::Heating),
+                    2 => Some(ControllerMode::Cooling),
+                    3 => Some(ControllerMode::Off),
+                    _ => None,
+                },
+                override_active: flag(3, 4),
+                energy_hold_off: flag(3, 2),
+                occupancy: match db[3] & 0x03 {
+                    0 => RoomOccupancy::Occupied,
+                    1 => RoomOccupancy::Unoccupied,
+                    2 => RoomOccupancy::StandBy,
+                    _ => RoomOccupancy::Frost,
+                },
+            }),
+            0x03 => {
+                let angle = (db[1] & 0x7f).min(90) as i16 * 2;
+                Ok(Self::Blind {
+                    position: flag(2, 7).then_some(db[0].min(100)),
+                    angle: flag(2, 6).then_some(if flag(1, 7) { -angle } else { angle }),
+                    end_position: match (db[2] >> 2) & 0x03 {
+                        1 => Some(EndPosition::NotReached),
+                        2 => Some(EndPosition::Open),
+                        3 => Some(EndPosition::Closed),
+                        _ => None,
+                    },
+                    motion: match db[2] & 0x03 {
+                        1 => Some(BlindMotion::Stopped),
+                        2 => Some(BlindMotion::Opening),
+                        3 => Some(BlindMotion::Closing),
+                        _ => None,
+                    },
+                    error: (db[2] >> 4) & 0x03,
+                    service_mode: flag(3, 7),
+                    inverse_mode: flag(3, 6),
+                })
+            }
+            0x04 => Ok(Self::ExtendedLighting {
+                parameters: match (db[3] >> 1) & 0x03 {
+                    0 => LightingParameters::Dimming {
+                        value: db[0],
+                        operating_hours: flag(3, 6).then_some(u16::from_be_bytes([db[1], db[2]])),
+                    },
+                    1 => LightingParameters::Rgb { red: db[0], green: db[1], blue: db[2] },
+                    2 => LightingParameters::Energy { value: u16::from_be_bytes([db[0], db[1]]), unit: db[2] },
+                    mode => return Err(DecodeError::InvalidValue(mode)),
+                },
+                service_mode: flag(3, 7),
+                error: (db[3] >> 4) & 0x03,
+                on: flag(3, 0),
+            }),
+            _ => Ok(Self::DualChannelSwitch {
+                relay1: flag(0, 0),
+                relay2: flag(0, 1),
+                working_mode: db[3] & 0x07,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a51102_telegram_then_decode_controller_status() {
+        let erp = erp1(&[0xa5, 102, 2, 110, 0xaa, 1, 2, 3, 4, 0]);
+        let Status::TemperatureController { control_variable, fan_stage, set_point, mode, occupancy, .. } =
+            Status::decode(&erp, 0x02).unwrap() else { panic!("wrong variant") };
+        assert!((control_variable - 40.0).abs() < 0.01);
+        assert_eq!(fan_stage, Some(2));
+        assert!((set_point.0 - 22.08).abs() < 0.01);
+        assert_eq!(mode, Some(ControllerMode::Heating));
+        assert_eq!(occupancy, RoomOccupancy::StandBy);
+    }
+
+    #[test]
+    fn given_a51103_telegram_then_decode_blind_status() {
+        let erp = erp1(&[0xa5, 60, 0x8f, 0xc6, 0x08, 1, 2, 3, 4, 0]);
+        assert_eq!(Status::decode(&erp, 0x03).unwrap(), Status::Blind {
+            position: Some(60), angle: Some(-30), end_position: Some(EndPosition::NotReached),
+            motion: Some(BlindMotion::Opening), error: 0, service_mode: false, inverse_mode: false,
+        });
+    }
+
+    #[test]
+    fn given_a51104_rgb_telegram_then_decode_parameters() {
+        let erp = erp1(&[0xa5, 255, 128, 0, 0x0b, 1, 2, 3, 4, 0]);
+        assert!(matches!(Status::decode(&erp, 0x04).unwrap(), Status::ExtendedLighting {
+            parameters: LightingParameters::Rgb { red: 255, green: 128, blue: 0 }, on: true, ..
+        }));
+    }
+}
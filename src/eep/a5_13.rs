@@ -0,0 +1,168 @@
+//! A5-13 : Environmental applications
+//!
+//! A weather station sends several kinds of telegrams under one profile. The kind of
+//! each telegram is given by the identifier in DB0.7..4, which matches the type number
+//! of the corresponding profile (1 = weather, 2 = sun intensity, ..., 7 = sun position).
+//! Devices commonly declare A5-13-01 and send both identifiers 1 and 2, so the
+//! identifier, not the declared type, selects the decoding.
+
+use super::quantity::{Illuminance, Speed, Temperature};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// Source of date and time information
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum TimeSource {
+    RealTimeClock,
+    Gps,
+}
+
+/// One telegram of a weather station (A5-13-01 to A5-13-07)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Message {
+    /// Identifier 1
+    Weather {
+        /// Dawn sensor
+        dawn: Illuminance,
+        /// Outdoor temperature
+        temperature: Temperature,
+        wind_speed: Speed,
+        night: bool,
+        rain: bool,
+    },
+    /// Identifier 2, in klx
+    SunIntensity {
+        west: f32,
+        south: f32,
+        east: f32,
+        southern_hemisphere: bool,
+    },
+    /// Identifier 3
+    Date { day: u8, month: u8, year: u16, source: TimeSource },
+    /// Identifier 4
+    Time {
+        /// 1 = Monday .. 7 = Sunday
+        weekday: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        /// The hour is in 12-hour format; `pm` tells which half of the day
+        twelve_hour: bool,
+        pm: bool,
+        source: TimeSource,
+    },
+    /// Identifier 5, in degrees
+    Direction { elevation: f32, azimuth: u16 },
+    /// Identifier 6, in degrees
+    Position { latitude: f32, longitude: f32 },
+    /// Identifier 7
+    SunPosition {
+        /// Elevation of the sun, in degrees
+        elevation: u8,
+        /// Azimuth of the sun, in degrees, -90 (east) .. +90 (west)
+        azimuth: f32,
+        /// Solar radiation, in W/m²
+        radiation: u16,
+        night: bool,
+    },
+}
+
+impl Message {
+    /// Decode an A5-13 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        if !(0x01..=0x07).contains(&eep_type) {
+            return Err(DecodeError::UnsupportedType(eep_type));
+        }
+        let db = data_4bs(erp)?;
+        let source = if db[3] & 0x01 != 0 { TimeSource::Gps } else { TimeSource::RealTimeClock };
+
+        match db[3] >> 4 {
+            1 => Ok(Self::Weather {
+                dawn: Illuminance(linear(db[0] as u16, 255, 0.0, 999.0)),
+                temperature: Temperature(linear(db[1] as u16, 255, -40.0, 80.0)),
+                wind_speed: Speed(linear(db[2] as u16, 255, 0.0, 70.0)),
+                night: db[3] & 0x04 != 0,
+                rain: db[3] & 0x02 != 0,
+            }),
+            2 => Ok(Self::SunIntensity {
+                west: linear(db[0] as u16, 255, 0.0, 150.0),
+                south: linear(db[1] as u16, 255, 0.0, 150.0),
+                east: linear(db[2] as u16, 255, 0.0, 150.0),
+                southern_hemisphere: db[3] & 0x04 != 0,
+            }),
+            3 => Ok(Self::Date {
+                day: db[0] & 0x1f,
+                month: db[1] & 0x0f,
+                year: 2000 + (db[2] & 0x7f) as u16,
+                source,
+            }),
+            4 => Ok(Self::Time {
+                weekday: db[0] >> 5,
+                hour: db[0] & 0x1f,
+                minute: db[1] & 0x3f,
+                second: db[2] & 0x3f,
+                twelve_hour: db[3] & 0x04 != 0,
+                pm: db[3] & 0x02 != 0,
+                source,
+            }),
+            5 => Ok(Self::Direction {
+                elevation: db[0].min(180) as f32 - 90.0,
+                azimuth: ((db[1] & 0x01) as u16) << 8 | db[2] as u16,
+            }),
+            6 => {
+                let latitude = ((db[2] >> 4) as u16) << 8 | db[0] as u16;
+                let longitude = ((db[2] & 0x0f) as u16) << 8 | db[1] as u16;
+                Ok(Self::Position {
+                    latitude: linear(latitude, 4095, -90.0, 90.0),
+                    longitude: linear(longitude, 4095, -180.0, 180.0),
+                })
+            }
+            7 => Ok(Self::SunPosition {
+                elevation: db[0] & 0x7f,
+                azimuth: db[1].min(180) as f32 - 90.0,
+                radiation: ((db[2] as u16) << 3 | (db[3] & 0x07) as u16).min(2000),
+                night: db[0] & 0x80 != 0,
+            }),
+            id => Err(DecodeError::InvalidValue(id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a51301_weather_and_sun_telegrams_then_use_identifier() {
+        let weather = erp1(&[0xa5, 255, 85, 0, 0x1e, 1, 2, 3, 4, 0]);
+        assert_eq!(Message::decode(&weather, 0x01).unwrap(), Message::Weather {
+            dawn: Illuminance(999.0), temperature: Temperature(0.0), wind_speed: Speed(0.0), night: true, rain: true,
+        });
+        let sun = erp1(&[0xa5, 255, 0, 0, 0x28, 1, 2, 3, 4, 0]);
+        assert_eq!(Message::decode(&sun, 0x01).unwrap(), Message::SunIntensity {
+            west: 150.0, south: 0.0, east: 0.0, southern_hemisphere: false,
+        });
+    }
+
+    #[test]
+    fn given_a51303_and_a51304_telegrams_then_decode_date_and_time() {
+        let date = erp1(&[0xa5, 14, 7, 24, 0x39, 1, 2, 3, 4, 0]);
+        assert_eq!(Message::decode(&date, 0x03).unwrap(), Message::Date {
+            day: 14, month: 7, year: 2024, source: TimeSource::Gps,
+        });
+        let time = erp1(&[0xa5, 0x4d, 30, 59, 0x48, 1, 2, 3, 4, 0]);
+        assert!(matches!(Message::decode(&time, 0x04).unwrap(),
+                         Message::Time { weekday: 2, hour: 13, minute: 30, second: 59, twelve_hour: false, .. }));
+    }
+
+    #[test]
+    fn given_unknown_identifier_then_return_error() {
+        let erp = erp1(&[0xa5, 0, 0, 0, 0x98, 1, 2, 3, 4, 0]);
+        assert!(matches!(Message::decode(&erp, 0x01), Err(DecodeError::InvalidValue(9))));
+    }
+}
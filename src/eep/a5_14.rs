@@ -0,0 +1,120 @@
+//! A5-14 : Multi-function sensors
+//!
+//! Window and door sensors reporting their supply voltage (DB3, 0..250 → 0..5 V) along
+//! with a combination of the following fields:
+//!
+//! | Type | Contact             | Illuminance (DB2, 0..1000 lx) | Vibration |
+//! |------|---------------------|-------------------------------|-----------|
+//! | 0x01 | DB0.0, 1 = closed   | -                             | -         |
+//! | 0x02 | DB0.0               | yes                           | -         |
+//! | 0x03 | DB0.0               | -                             | DB0.1     |
+//! | 0x04 | DB0.0               | yes                           | DB0.1     |
+//! | 0x05 | -                   | -                             | DB0.1     |
+//! | 0x06 | -                   | yes                           | DB0.1     |
+//! | 0x07 | Door DB0.2, lock DB0.1 (1 = open, 1 = locked) | -   | -         |
+//! | 0x08 | Door DB0.2, lock DB0.1                        | -   | DB0.0     |
+//! | 0x09 | Window DB0.2..1, 0 closed / 1 tilted / 2 open | -   | -         |
+//! | 0x0A | Window DB0.2..1                               | -   | DB0.0     |
+
+use super::d5_00::ContactState;
+use super::quantity::{Illuminance, Voltage};
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// State of a window (A5-14-09, A5-14-0A)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum WindowState {
+    Closed,
+    Tilted,
+    Open,
+}
+
+/// A multi-function sensor report (A5-14-01 to A5-14-0A)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reading {
+    pub supply_voltage: Voltage,
+    /// Window or door contact
+    pub contact: Option<ContactState>,
+    /// `true` when the door lock is engaged (A5-14-07, A5-14-08)
+    pub locked: Option<bool>,
+    pub window: Option<WindowState>,
+    pub illuminance: Option<Illuminance>,
+    /// Vibration (break-in attempt) detected
+    pub vibration: Option<bool>,
+}
+
+impl Reading {
+    /// Decode an A5-14 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        if !(0x01..=0x0A).contains(&eep_type) {
+            return Err(DecodeError::UnsupportedType(eep_type));
+        }
+        let db = data_4bs(erp)?;
+        let flag = |bit: u8| db[3] & (1 << bit) != 0;
+        let contact = |closed: bool| if closed { ContactState::Closed } else { ContactState::Open };
+
+        let mut reading = Self {
+            supply_voltage: Voltage(linear(db[0].min(250) as u16, 250, 0.0, 5.0)),
+            contact: None,
+            locked: None,
+            window: None,
+            illuminance: matches!(eep_type, 0x02 | 0x04 | 0x06)
+                .then(|| Illuminance(linear(db[1].min(250) as u16, 250, 0.0, 1000.0))),
+            vibration: None,
+        };
+
+        match eep_type {
+            0x01..=0x04 => {
+                reading.contact = Some(contact(flag(0)));
+                reading.vibration = (eep_type >= 0x03).then_some(flag(1));
+            }
+            0x05 | 0x06 => reading.vibration = Some(flag(1)),
+            0x07 | 0x08 => {
+                reading.contact = Some(contact(!flag(2)));
+                reading.locked = Some(flag(1));
+                reading.vibration = (eep_type == 0x08).then_some(flag(0));
+            }
+            _ => {
+                reading.window = Some(match (db[3] >> 1) & 0x03 {
+                    0 => WindowState::Closed,
+                    1 => WindowState::Tilted,
+                    2 => WindowState::Open,
+                    _ => return Err(DecodeError::InvalidValue(db[3])),
+                });
+                reading.vibration = (eep_type == 0x0A).then_some(flag(0));
+            }
+        }
+        Ok(reading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a51404_telegram_then_decode_contact_illuminance_and_vibration() {
+        let erp = erp1(&[0xa5, 150, 125, 0, 0x0b, 1, 2, 3, 4, 0]);
+        assert_eq!(Reading::decode(&erp, 0x04).unwrap(), Reading {
+            supply_voltage: Voltage(3.0), contact: Some(ContactState::Closed), locked: None, window: None,
+            illuminance: Some(Illuminance(500.0)), vibration: Some(true),
+        });
+    }
+
+    #[test]
+    fn given_a51408_and_a5140a_telegrams_then_decode_door_and_window() {
+        let door = Reading::decode(&erp1(&[0xa5, 250, 0, 0, 0x0a, 1, 2, 3, 4, 0]), 0x08).unwrap();
+        assert_eq!(door.contact, Some(ContactState::Closed));
+        assert_eq!(door.locked, Some(true));
+        assert_eq!(door.vibration, Some(false));
+
+        let window = Reading::decode(&erp1(&[0xa5, 250, 0, 0, 0x0d, 1, 2, 3, 4, 0]), 0x0A).unwrap();
+        assert_eq!(window.window, Some(WindowState::Open));
+        assert_eq!(window.vibration, Some(true));
+    }
+}
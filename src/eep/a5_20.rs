@@ -0,0 +1,297 @@
+//! A5-20 : HVAC components
+//!
+//! A5-20-01 is a bidirectional profile: the battery powered valve actuator sends its
+//! [`Status`], and the controller answers within the short receive window that follows
+//! with a [`Command`].
+//!
+//! Build the answer to a status telegram:
+//! ```
+//! use enocean::eep::a5_20::{Command, SetPoint};
+//!
+//! let command = Command {
+//!     set_point: SetPoint::Temperature(21.0),
+//!     room_temperature: Some(19.5),
+//!     summer_mode: false,
+//! };
+//! let user_data = command.encode();
+//! assert_eq!(user_data[3] & 0x08, 0x08); // Data telegram
+//! ```
+//!
+//! A5-20-04 follows the same exchange pattern for the newer radiator actuators with a
+//! local display, see [`RadiatorStatus`] and [`RadiatorCommand`].
+
+use super::quantity::Temperature;
+use super::{data_4bs, linear, DecodeError};
+use crate::packet::RadioErp1;
+
+/// Status of an A5-20-01 valve actuator
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Status {
+    /// Current valve position, in %
+    pub valve_position: u8,
+    pub service_on: bool,
+    pub energy_input_enabled: bool,
+    pub energy_storage_charged: bool,
+    /// Battery capacity will need to be changed in the next months
+    pub battery_low: bool,
+    pub cover_open: bool,
+    pub temperature_sensor_failure: bool,
+    pub window_open: bool,
+    pub actuator_obstructed: bool,
+    /// Temperature measured by the actuator
+    pub temperature: Temperature,
+}
+
+impl Status {
+    /// Decode an A5-20-01 status telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        let db = data_4bs(erp)?;
+        let flag = |bit: u8| db[1] & (1 << bit) != 0;
+
+        Ok(Self {
+            valve_position: db[0].min(100),
+            service_on: flag(7),
+            energy_input_enabled: flag(6),
+            energy_storage_charged: flag(5),
+            battery_low: flag(4),
+            cover_open: flag(3),
+            temperature_sensor_failure: flag(2),
+            window_open: flag(1),
+            actuator_obstructed: flag(0),
+            temperature: Temperature(linear(db[2] as u16, 255, 0.0, 40.0)),
+        })
+    }
+}
+
+/// What the actuator should regulate on
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum SetPoint {
+    /// Valve position, in %
+    ValvePosition(u8),
+    /// Temperature set point, in °C (0..40)
+    Temperature(f32),
+}
+
+/// Command from the controller to an A5-20-01 valve actuator
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Command {
+    pub set_point: SetPoint,
+    /// Room temperature measured by the controller, in °C (0..40). Without it, the actuator
+    /// regulates on its own sensor.
+    pub room_temperature: Option<f32>,
+    /// Summer mode: the actuator reduces its wake-up rate
+    pub summer_mode: bool,
+}
+
+/// Util : inverse of the linear mapping, clamped to the raw range
+fn to_raw(value: f32, min: f32, max: f32) -> u8 {
+    // Rounded to the nearest; `f32::round` is not available without `std`
+    ((value.clamp(min, max) - min) * 255.0 / (max - min) + 0.5) as u8
+}
+
+impl Command {
+    /// Encode the 4BS user data (DB3..DB0) of the command telegram
+    pub fn encode(&self) -> [u8; 4] {
+        let (db3, set_point_selection) = match self.set_point {
+            SetPoint::ValvePosition(position) => (position.min(100), 0),
+            SetPoint::Temperature(celsius) => (to_raw(celsius, 0.0, 40.0), 1 << 2),
+        };
+        // The room temperature is transmitted inverted: 255 = 0 °C. DB1.0 tells the actuator
+        // whether DB2 holds it; when clear, DB2 is ignored.
+        let (db2, temperature_from_rcu) = match self.room_temperature {
+            Some(t) => (255 - to_raw(t, 0.0, 40.0), 1),
+            None => (0, 0),
+        };
+        let db1 = set_point_selection | if self.summer_mode { 1 << 3 } else { 0 } | temperature_from_rcu;
+        let db0 = 0x08; // LRN bit: data telegram
+
+        [db3, db2, db1, db0]
+    }
+}
+
+/// Failure reported by an A5-20-04 actuator in place of the feed temperature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum FailureCode {
+    MeasurementError,
+    BatteryEmpty,
+    FrostProtection,
+    BlockedValve,
+    EndPointDetectionError,
+    NoValve,
+    NotTaughtIn,
+    NoResponseFromController,
+    TeachInError,
+    Other(u8),
+}
+
+impl From<u8> for FailureCode {
+    fn from(code: u8) -> Self {
+        match code {
+            17 => Self::MeasurementError,
+            18 => Self::BatteryEmpty,
+            20 => Self::FrostProtection,
+            33 => Self::BlockedValve,
+            36 => Self::EndPointDetectionError,
+            40 => Self::NoValve,
+            49 => Self::NotTaughtIn,
+            53 => Self::NoResponseFromController,
+            54 => Self::TeachInError,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Status of an A5-20-04 radiator valve actuator
+///
+/// | Byte | Content                                                          |
+/// |------|------------------------------------------------------------------|
+/// | DB3  | Valve position, 0..100 %                                         |
+/// | DB2  | Local temperature set point, 10..30 °C                           |
+/// | DB1  | Feed temperature 20..80 °C, or failure code if DB0.0 is set      |
+/// | DB0  | .7 button lock, .4 window open, .2 radio error, .1 weak signal   |
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RadiatorStatus {
+    /// Current valve position, in %
+    pub valve_position: u8,
+    /// Set point adjusted on the device
+    pub local_set_point: Temperature,
+    /// Feed temperature, or the failure reported by the actuator
+    pub feed_temperature: Result<Temperature, FailureCode>,
+    pub button_locked: bool,
+    pub window_open: bool,
+    pub radio_error: bool,
+    pub radio_signal_weak: bool,
+}
+
+impl RadiatorStatus {
+    /// Decode an A5-20-04 status telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        let db = data_4bs(erp)?;
+        let flag = |bit: u8| db[3] & (1 << bit) != 0;
+
+        Ok(Self {
+            valve_position: db[0].min(100),
+            local_set_point: Temperature(linear(db[1] as u16, 255, 10.0, 30.0)),
+            feed_temperature: if flag(0) { Err(FailureCode::from(db[2])) }
+                              else { Ok(Temperature(linear(db[2] as u16, 255, 20.0, 80.0))) },
+            button_locked: flag(7),
+            window_open: flag(4),
+            radio_error: flag(2),
+            radio_signal_weak: flag(1),
+        })
+    }
+}
+
+/// Orientation of the A5-20-04 display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DisplayOrientation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// Command from the controller to an A5-20-04 radiator valve actuator
+///
+/// | Byte | Content                                                                  |
+/// |------|--------------------------------------------------------------------------|
+/// | DB3  | Valve position 0..100 %, or temperature set point 10..30 °C              |
+/// | DB2  | Room temperature from the controller, 10..30 °C                          |
+/// | DB1  | .7 measurement disabled, .5..0 wake-up cycle                             |
+/// | DB0  | .5..4 display orientation, .2 button lock, .1 set point selection        |
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RadiatorCommand {
+    pub set_point: SetPoint,
+    /// Room temperature measured by the controller, in °C (10..30)
+    pub room_temperature: Option<f32>,
+    /// Let the actuator measure the feed temperature
+    pub measurement_enabled: bool,
+    /// Raw wake-up cycle code (0..63), see the EEP specification for durations
+    pub wake_up_cycle: u8,
+    pub display_orientation: DisplayOrientation,
+    pub button_lock: bool,
+}
+
+impl RadiatorCommand {
+    /// Encode the 4BS user data (DB3..DB0) of the command telegram
+    pub fn encode(&self) -> [u8; 4] {
+        let (db3, set_point_selection) = match self.set_point {
+            SetPoint::ValvePosition(position) => (position.min(100), 0),
+            SetPoint::Temperature(celsius) => (to_raw(celsius, 10.0, 30.0), 1 << 1),
+        };
+        let db2 = self.room_temperature.map(|t| to_raw(t, 10.0, 30.0)).unwrap_or(0);
+        let db1 = if self.measurement_enabled { 0 } else { 1 << 7 } | self.wake_up_cycle.min(63);
+        let db0 = (self.display_orientation as u8) << 4
+                | if self.button_lock { 1 << 2 } else { 0 }
+                | set_point_selection
+                | 0x08; // LRN bit: data telegram
+
+        [db3, db2, db1, db0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a52001_status_telegram_then_decode_it() {
+        let erp = erp1(&[0xa5, 50, 0x22, 255, 0x08, 1, 2, 3, 4, 0]);
+        let status = Status::decode(&erp).unwrap();
+        assert_eq!(status.valve_position, 50);
+        assert!(status.energy_storage_charged);
+        assert!(status.window_open);
+        assert!(!status.actuator_obstructed);
+        assert_eq!(status.temperature, Temperature(40.0));
+    }
+
+    #[test]
+    fn given_a52001_command_then_encode_it() {
+        let command = Command { set_point: SetPoint::ValvePosition(80), room_temperature: None, summer_mode: true };
+        assert_eq!(command.encode(), [80, 0, 0x08, 0x08]);
+
+        let command = Command { set_point: SetPoint::Temperature(20.0), room_temperature: Some(40.0), summer_mode: false };
+        assert_eq!(command.encode(), [128, 0, 0x05, 0x08]);
+    }
+
+    #[test]
+    fn given_a52004_status_telegrams_then_decode_them() {
+        let status = RadiatorStatus::decode(&erp1(&[0xa5, 30, 128, 255, 0x18, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(status.valve_position, 30);
+        assert!((status.local_set_point.0 - 20.0).abs() < 0.1);
+        assert_eq!(status.feed_temperature, Ok(Temperature(80.0)));
+        assert!(status.window_open);
+
+        let status = RadiatorStatus::decode(&erp1(&[0xa5, 0, 0, 33, 0x09, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(status.feed_temperature, Err(FailureCode::BlockedValve));
+    }
+
+    #[test]
+    fn given_a52004_command_then_encode_it() {
+        let command = RadiatorCommand {
+            set_point: SetPoint::Temperature(30.0),
+            room_temperature: Some(10.0),
+            measurement_enabled: true,
+            wake_up_cycle: 19,
+            display_orientation: DisplayOrientation::Deg180,
+            button_lock: true,
+        };
+        assert_eq!(command.encode(), [255, 0, 19, 0x2e]);
+    }
+}
@@ -0,0 +1,102 @@
+//! A5-38 : Central command
+//!
+//! A5-38-08 is the gateway profile used to drive switching and dimming actuators.
+//! DB3 holds the command ID; the meaning of the other bytes depends on it.
+//!
+//! | Command       | DB2             | DB1                 | DB0.2       | DB0.1        | DB0.0  |
+//! |---------------|-----------------|---------------------|-------------|--------------|--------|
+//! | 0x01 Switching| Time (MSB)      | Time (LSB), 0.1 s   | Lock        | Delay        | On/Off |
+//! | 0x02 Dimming  | Dimming value   | Ramping time, s     | Relative    | Store value  | On/Off |
+//!
+//! ```
+//! use enocean::eep::a5_38::CentralCommand;
+//!
+//! let dim = CentralCommand::Dimming { on: true, value: 80, relative: true, ramp: 2, store: false };
+//! assert_eq!(dim.encode(), [0x02, 80, 2, 0x0d]);
+//! ```
+
+use super::{data_4bs, DecodeError};
+use crate::packet::RadioErp1;
+
+/// A5-38-08 command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum CentralCommand {
+    Switching {
+        on: bool,
+        /// Duration or delay, in 1/10 s. 0 means no time limit.
+        time: u16,
+        /// The time is a delay before switching, instead of a duration
+        delay: bool,
+        /// Lock the actuator in this state for the duration
+        lock: bool,
+    },
+    Dimming {
+        on: bool,
+        /// Dimming value, 0..255, or 0..100 % when `relative` is set
+        value: u8,
+        relative: bool,
+        /// Ramping time, in s. 0 means no ramping.
+        ramp: u8,
+        /// Store the final value in the actuator
+        store: bool,
+    },
+}
+
+impl CentralCommand {
+    /// Encode the 4BS user data (DB3..DB0), with the LRN bit set for a data telegram
+    pub fn encode(&self) -> [u8; 4] {
+        let bit = |set: bool, n: u8| if set { 1 << n } else { 0 };
+        match *self {
+            Self::Switching { on, time, delay, lock } => {
+                let [time_msb, time_lsb] = time.to_be_bytes();
+                [0x01, time_msb, time_lsb, 0x08 | bit(lock, 2) | bit(delay, 1) | bit(on, 0)]
+            }
+            Self::Dimming { on, value, relative, ramp, store } => {
+                let value = if relative { value.min(100) } else { value };
+                [0x02, value, ramp, 0x08 | bit(relative, 2) | bit(store, 1) | bit(on, 0)]
+            }
+        }
+    }
+
+    /// Decode an A5-38-08 telegram, e.g. one sent by another controller
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        let db = data_4bs(erp)?;
+        let bit = |n: u8| db[3] & (1 << n) != 0;
+        match db[0] {
+            0x01 => Ok(Self::Switching {
+                on: bit(0),
+                time: u16::from_be_bytes([db[1], db[2]]),
+                delay: bit(1),
+                lock: bit(2),
+            }),
+            0x02 => Ok(Self::Dimming { on: bit(0), value: db[1], relative: bit(2), ramp: db[2], store: bit(1) }),
+            command => Err(DecodeError::InvalidValue(command)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_switching_command_then_encode_and_decode_it() {
+        let command = CentralCommand::Switching { on: true, time: 600, delay: false, lock: true };
+        let user_data = command.encode();
+        assert_eq!(user_data, [0x01, 0x02, 0x58, 0x0d]);
+
+        let mut data = vec![0xa5];
+        data.extend_from_slice(&user_data);
+        data.extend_from_slice(&[1, 2, 3, 4, 0]);
+        assert_eq!(CentralCommand::decode(&erp1(&data)).unwrap(), command);
+    }
+
+    #[test]
+    fn given_relative_dimming_command_then_clamp_value() {
+        let command = CentralCommand::Dimming { on: true, value: 200, relative: true, ramp: 0, store: true };
+        assert_eq!(command.encode(), [0x02, 100, 0, 0x0f]);
+    }
+}
@@ -0,0 +1,283 @@
+//! A5-3F-7F: universal profile, decoded with a mapping given by the user
+//!
+//! Devices sending A5-3F-7F define the meaning of their 4 data bytes themselves. Rather than a
+//! decoder in code, each field is described by a [`FieldMapping`], written in a short
+//! notation that fits configuration files, e.g. the mapping of a device in the registry:
+//!
+//! | Notation                   | Field                                                |
+//! |----------------------------|------------------------------------------------------|
+//! | `24:4`                     | bits 24 to 27, as its raw value                      |
+//! | `16:8 255..0 = 0..40 °C`   | bits 16 to 23, raw 255..0 scaled to 0..40, in °C     |
+//! | `0:10 0..1000 = -20.5..80` | bits 0 to 9, raw 0..1000 scaled to -20.5..80         |
+//!
+//! Offsets count bits from the MSB of DB3, as in [`field`](super::field).
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use enocean::eep::a5_3f::{decode, FieldMapping};
+//! # use enocean::packet::RadioErp1;
+//! # use enocean::enocean::Rorg;
+//!
+//! let mut mapping = BTreeMap::new();
+//! mapping.insert("temperature".to_string(), "16:8 255..0 = 0..40 °C".parse::<FieldMapping>()?);
+//! # let erp = RadioErp1::builder().choice(Rorg::Bs4).user_data(&[0x00, 0x00, 0x80, 0x08]).sender("0180998e".parse()?).build()?;
+//! let values = decode(&mapping, &erp)?;
+//! assert_eq!((values[0].name, values[0].unit), ("temperature", "°C"));
+//! assert!((values[0].value - 19.92).abs() < 0.01);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::num::{ParseFloatError, ParseIntError};
+use core::str::FromStr;
+
+use super::field::DataField;
+use super::{data_4bs, DecodeError};
+use crate::packet::{EEPProfileCode, RadioErp1};
+
+/// The universal profile
+pub const EEP: EEPProfileCode = EEPProfileCode::new(0xA5, 0x3F, 0x7F);
+
+/// A field of the 4 data bytes, with the linear mapping of its raw value
+#[derive(Debug, Clone)]
+pub struct FieldMapping {
+    /// Position of the first bit, from the MSB of DB3
+    pub offset: usize,
+    pub size: usize,
+    /// Raw values mapped to the ends of `scale`
+    pub range: (u32, u32),
+    /// Physical values corresponding to the ends of `range`
+    pub scale: (f32, f32),
+    pub unit: String,
+}
+
+impl FieldMapping {
+    /// A field decoded as its raw value
+    pub fn raw(offset: usize, size: usize) -> Self {
+        let field = DataField::new(offset, size);
+        Self { offset, size, range: field.range, scale: field.scale, unit: String::new() }
+    }
+
+    /// Set the linear mapping and unit of the field
+    pub fn scaled(self, range: (u32, u32), scale: (f32, f32), unit: &str) -> Self {
+        Self { range, scale, unit: unit.to_string(), ..self }
+    }
+
+    /// The bit-field engine view of this mapping. The unit stays in the mapping.
+    pub fn data_field(&self) -> DataField {
+        DataField::new(self.offset, self.size).scaled(self.range, self.scale, "")
+    }
+
+    /// Whether the raw value is used as is
+    fn is_raw(&self) -> bool {
+        let field = DataField::new(self.offset, self.size);
+        self.range == field.range && self.scale == field.scale
+    }
+}
+
+/// Scales are compared bit for bit, so that mappings can be kept in registries
+impl PartialEq for FieldMapping {
+    fn eq(&self, other: &Self) -> bool {
+        (self.offset, self.size, self.range, &self.unit) == (other.offset, other.size, other.range, &other.unit)
+            && (self.scale.0.to_bits(), self.scale.1.to_bits()) == (other.scale.0.to_bits(), other.scale.1.to_bits())
+    }
+}
+
+impl Eq for FieldMapping {}
+
+impl fmt::Display for FieldMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.offset, self.size)?;
+        if !self.is_raw() {
+            write!(f, " {}..{} = {}..{}", self.range.0, self.range.1, self.scale.0, self.scale.1)?;
+        }
+        if !self.unit.is_empty() {
+            write!(f, " {}", self.unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors parsing a [`FieldMapping`], or adding it to a mapping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseMappingError {
+    /// The offset or size is not a number
+    Bits(ParseIntError),
+    /// The field does not fit in the 4 data bytes
+    OutOfData { offset: usize, size: usize },
+    Range(ParseIntError),
+    Scale(ParseFloatError),
+    /// A scale bound is not finite
+    NotFinite,
+    /// The raw range has a single value, and maps to no scale
+    EmptyRange,
+    /// The text does not follow the notation
+    Syntax,
+    /// The mapping already has [`MAX_FIELDS`] fields
+    TooManyFields,
+}
+
+impl fmt::Display for ParseMappingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bits(_)                    => write!(f, "Invalid field offset or size"),
+            Self::OutOfData { offset, size } => write!(f, "Field of {} bits at bit {} out of the data", size, offset),
+            Self::Range(_)                   => write!(f, "Invalid raw range"),
+            Self::Scale(_)                   => write!(f, "Invalid scale"),
+            Self::NotFinite                  => write!(f, "Scale bounds must be finite"),
+            Self::EmptyRange                 => write!(f, "Raw range bounds must differ"),
+            Self::Syntax                     => write!(f, "Expected `offset:size [min..max = min..max] [unit]`"),
+            Self::TooManyFields              => write!(f, "A mapping has at most {} fields", MAX_FIELDS),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseMappingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Bits(e) | Self::Range(e) => Some(e),
+            Self::Scale(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for FieldMapping {
+    type Err = ParseMappingError;
+
+    /// Parses `offset:size`, optionally followed by `min..max = min..max` and a unit
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (bits, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let (offset, size) = bits.split_once(':').ok_or(ParseMappingError::Syntax)?;
+        let (offset, size) = (offset.parse().map_err(ParseMappingError::Bits)?, size.parse().map_err(ParseMappingError::Bits)?);
+        if size == 0 || offset + size > 32 {
+            return Err(ParseMappingError::OutOfData { offset, size });
+        }
+        let mapping = Self::raw(offset, size);
+
+        let Some((range, rest)) = rest.split_once('=') else {
+            return Ok(Self { unit: rest.trim().to_string(), ..mapping });
+        };
+        let rest = rest.trim_start();
+        let (scale, unit) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let (lo, hi) = range.trim().split_once("..").ok_or(ParseMappingError::Syntax)?;
+        let range: (u32, u32) = (lo.parse().map_err(ParseMappingError::Range)?, hi.parse().map_err(ParseMappingError::Range)?);
+        if range.0 == range.1 {
+            return Err(ParseMappingError::EmptyRange);
+        }
+        let (lo, hi) = scale.split_once("..").ok_or(ParseMappingError::Syntax)?;
+        let scale: (f32, f32) = (lo.parse().map_err(ParseMappingError::Scale)?, hi.parse().map_err(ParseMappingError::Scale)?);
+        if !scale.0.is_finite() || !scale.1.is_finite() {
+            return Err(ParseMappingError::NotFinite);
+        }
+        Ok(mapping.scaled(range, scale, unit.trim()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FieldMapping {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FieldMapping {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A field decoded from a telegram
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MappedValue<'a> {
+    pub name: &'a str,
+    pub value: f32,
+    pub unit: &'a str,
+}
+
+/// Fields of a mapping decoded into a [`MappedReading`]
+pub const MAX_FIELDS: usize = 8;
+
+/// The values of the fields of a mapping, in the order of their names, as handed out by
+/// gateways in [`Decoded::Mapped`](super::Decoded::Mapped). The names and units are kept by
+/// the mapping, see [`Self::named`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct MappedReading {
+    len: u8,
+    values: [f32; MAX_FIELDS],
+}
+
+impl MappedReading {
+    pub fn values(&self) -> &[f32] {
+        &self.values[..(self.len as usize).min(MAX_FIELDS)]
+    }
+
+    /// The values with the names and units of the mapping they were decoded with
+    pub fn named<'a>(&self, mapping: &'a BTreeMap<String, FieldMapping>) -> Vec<MappedValue<'a>> {
+        mapping.iter()
+            .zip(self.values())
+            .map(|((name, field), value)| MappedValue { name, value: *value, unit: &field.unit })
+            .collect()
+    }
+}
+
+/// Serialized as the list of values
+#[cfg(feature = "serde")]
+impl serde::Serialize for MappedReading {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.values())
+    }
+}
+
+/// Decode the fields of a 4BS data telegram with `mapping`, by field name
+pub fn decode<'a>(mapping: &'a BTreeMap<String, FieldMapping>, erp: &RadioErp1) -> Result<Vec<MappedValue<'a>>, DecodeError> {
+    Ok(decode_reading(mapping, erp)?.named(mapping))
+}
+
+/// Decode the fields of a 4BS data telegram with `mapping`, of at most [`MAX_FIELDS`] fields
+pub fn decode_reading(mapping: &BTreeMap<String, FieldMapping>, erp: &RadioErp1) -> Result<MappedReading, DecodeError> {
+    if mapping.len() > MAX_FIELDS {
+        return Err(DecodeError::UnsupportedProfile(EEP));
+    }
+    let data = data_4bs(erp)?;
+    let mut reading = MappedReading::default();
+    for field in mapping.values() {
+        reading.values[reading.len as usize] = field.data_field().value(&data)?;
+        reading.len += 1;
+    }
+    Ok(reading)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_field_notation_then_parse_and_decode() {
+        let level: FieldMapping = "0:10 0..1000 = -20.5..80 %".parse().unwrap();
+        assert_eq!(level, FieldMapping::raw(0, 10).scaled((0, 1000), (-20.5, 80.0), "%"));
+        assert_eq!(level.to_string(), "0:10 0..1000 = -20.5..80 %");
+        assert_eq!("24:4".parse::<FieldMapping>().unwrap().to_string(), "24:4");
+        assert!(matches!("30:4".parse::<FieldMapping>(), Err(ParseMappingError::OutOfData { offset: 30, size: 4 })));
+        assert!(matches!("0:8 0..255 = 0..x".parse::<FieldMapping>(), Err(ParseMappingError::Scale(_))));
+        assert!(matches!("0-8".parse::<FieldMapping>(), Err(ParseMappingError::Syntax)));
+        assert!(matches!("0:8 10..10 = 0..100".parse::<FieldMapping>(), Err(ParseMappingError::EmptyRange)));
+
+        let mapping = BTreeMap::from([("level".to_string(), level), ("mode".to_string(), "24:4".parse().unwrap())]);
+        let values = decode(&mapping, &erp1(&[0xa5, 0xfa, 0x00, 0x00, 0x3a, 1, 2, 3, 4, 0x00])).unwrap();
+        assert_eq!(values, vec![
+            MappedValue { name: "level", value: 80.0, unit: "%" },
+            MappedValue { name: "mode", value: 3.0, unit: "" },
+        ]);
+        assert!(matches!(decode(&mapping, &erp1(&[0xa5, 0xfa, 0x00, 0x00, 0x30, 1, 2, 3, 4, 0x00])), Err(DecodeError::TeachIn)));
+    }
+}
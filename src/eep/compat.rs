@@ -0,0 +1,109 @@
+//! JSON compatible with the Python `enocean` and node `enocean-js` libraries (feature `loader`)
+//!
+//! Telegrams are represented the way these libraries print parsed packets, so that consumers
+//! written for them keep working. Fields are keyed by their shortcut, from the runtime
+//! definitions of [`super::loader`]:
+//!
+//! ```json
+//! {
+//!   "packet_type": 1, "rorg": 165, "rorg_func": 2, "rorg_type": 5,
+//!   "sender": "01:80:99:8E", "dBm": -45, "status": 0, "learn": false,
+//!   "parsed": {
+//!     "TMP": { "description": "Temperature", "unit": "°C", "value": 19.92, "raw_value": 128 }
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::loader::Definitions;
+use super::DecodeError;
+use crate::enocean::Rorg;
+use crate::packet::{EEPProfileCode, RadioErp1};
+
+/// A field, as in the `parsed` object
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompatField {
+    pub description: String,
+    pub unit: String,
+    pub value: f32,
+    pub raw_value: u32,
+}
+
+/// A telegram, as parsed by the Python and node libraries
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompatTelegram {
+    pub packet_type: u8,
+    pub rorg: u8,
+    pub rorg_func: u8,
+    pub rorg_type: u8,
+    /// Sender address, as `01:80:99:8E`
+    pub sender: String,
+    #[serde(rename = "dBm")]
+    pub dbm: Option<i16>,
+    pub status: u8,
+    /// Teach-in telegram; its fields are left out
+    pub learn: bool,
+    pub parsed: BTreeMap<String, CompatField>,
+}
+
+impl CompatTelegram {
+    /// Represent a telegram from a device of profile `eep`, decoding its fields with `definitions`
+    pub fn decode(definitions: &Definitions, eep: EEPProfileCode, erp: &RadioErp1) -> Result<Self, DecodeError> {
+        let profile = definitions.get(eep).ok_or(DecodeError::UnsupportedProfile(eep))?;
+        if u8::from(erp.choice) != eep.rorg() {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        // 1BS and 4BS teach-in telegrams have the LRN bit (DB0.3) cleared
+        let learn = matches!(erp.choice, Rorg::Bs1 | Rorg::Bs4)
+            && erp.user_data.last().is_some_and(|db0| db0 & 0x08 == 0);
+
+        let mut parsed = BTreeMap::new();
+        if !learn {
+            for definition in &profile.fields {
+                let field = definition.data_field();
+                parsed.insert(definition.shortcut.clone(), CompatField {
+                    description: definition.description.clone(),
+                    unit: definition.unit.clone(),
+                    value: field.value(&erp.user_data)?,
+                    raw_value: field.raw(&erp.user_data)?,
+                });
+            }
+        }
+
+        let sender = erp.sender_id.to_string().to_uppercase();
+        Ok(Self {
+            packet_type: 0x01,
+            rorg: eep.rorg(),
+            rorg_func: eep.func(),
+            rorg_type: eep.eep_type(),
+            sender: [&sender[0..2], &sender[2..4], &sender[4..6], &sender[6..8]].join(":"),
+            dbm: erp.rssi_dbm(),
+            status: erp.status,
+            learn,
+            parsed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_a50205_telegram_then_serialize_like_python_enocean() {
+        let definitions = Definitions::from_json(r#"[{
+            "eep": "A5-02-05",
+            "fields": [{ "shortcut": "TMP", "description": "Temperature", "offset": 16, "size": 8,
+                         "range": [255, 0], "scale": [0.0, 40.0], "unit": "°C" }]
+        }]"#).unwrap();
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0x00, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00]);
+        let telegram = CompatTelegram::decode(&definitions, "A5-02-05".parse().unwrap(), &erp).unwrap();
+        assert_eq!(serde_json::to_string(&telegram).unwrap(), concat!(
+            r#"{"packet_type":1,"rorg":165,"rorg_func":2,"rorg_type":5,"sender":"01:80:99:8E","dBm":null,"status":0,"learn":false,"#,
+            r#""parsed":{"TMP":{"description":"Temperature","unit":"°C","value":40.0,"raw_value":0}}}"#));
+    }
+}
@@ -0,0 +1,225 @@
+//! D2-01 : Electronic switches and dimmers with energy measurement and local control
+//!
+//! VLD telegrams of this profile start with a command ID in the low nibble of the first byte.
+//! The controller sends [`Command`]s, the actuator answers with [`Message`]s.
+//!
+//! Switch on channel 0 of an actuator:
+//! ```
+//! use enocean::eep::d2_01::{Command, DimMode};
+//!
+//! let command = Command::SetOutput { channel: 0, value: 100, dim: DimMode::Immediate };
+//! assert_eq!(command.encode(), vec![0x01, 0x00, 0x64]);
+//! ```
+
+use alloc::vec::Vec;
+
+use super::quantity::{Energy, Power};
+use super::{vld_command, DecodeError};
+use crate::packet::RadioErp1;
+
+/// Channel number addressing all the outputs of an actuator
+pub const ALL_CHANNELS: u8 = 0x1E;
+/// Channel number addressing the input channel of an actuator
+pub const INPUT_CHANNEL: u8 = 0x1F;
+
+/// How the actuator reaches a new output value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DimMode {
+    Immediate = 0,
+    Timer1 = 1,
+    Timer2 = 2,
+    Timer3 = 3,
+    Stop = 4,
+}
+
+/// Commands sent to a D2-01 actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Command {
+    /// CMD 0x1 : set the output value, 0 = off, 1..100 %
+    SetOutput { channel: u8, value: u8, dim: DimMode },
+    /// CMD 0x3 : ask for an actuator status response
+    StatusQuery { channel: u8 },
+    /// CMD 0x6 : ask for a measurement response, of power if `power` is set, of energy otherwise
+    MeasurementQuery { channel: u8, power: bool },
+}
+
+impl Command {
+    /// Encode the VLD user data
+    pub fn encode(&self) -> Vec<u8> {
+        match *self {
+            Self::SetOutput { channel, value, dim } => vec![0x01, (dim as u8) << 5 | (channel & 0x1f), value.min(100)],
+            Self::StatusQuery { channel } => vec![0x03, channel & 0x1f],
+            Self::MeasurementQuery { channel, power } => vec![0x06, (power as u8) << 5 | channel & 0x1f],
+        }
+    }
+
+    /// The channel addressed, possibly [`ALL_CHANNELS`]
+    pub fn channel(&self) -> u8 {
+        match *self {
+            Self::SetOutput { channel, .. } | Self::StatusQuery { channel } | Self::MeasurementQuery { channel, .. } => channel,
+        }
+    }
+}
+
+/// Error level reported by an actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ErrorLevel {
+    HardwareOk,
+    Warning,
+    Failure,
+    NotSupported,
+}
+
+/// CMD 0x4 : actuator status response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Status {
+    pub channel: u8,
+    /// Output value, 0 = off, 1..100 %. `None` if the output is not valid or not set.
+    pub value: Option<u8>,
+    pub power_failure_enabled: bool,
+    pub power_failure_detected: bool,
+    pub over_current: bool,
+    pub error_level: ErrorLevel,
+    pub local_control: bool,
+}
+
+/// Unit of a measurement response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum MeasurementUnit {
+    EnergyWs,
+    EnergyWh,
+    EnergyKWh,
+    PowerW,
+    PowerKW,
+}
+
+/// CMD 0x7 : actuator measurement response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Measurement {
+    pub channel: u8,
+    pub unit: MeasurementUnit,
+    pub value: u32,
+}
+
+impl Measurement {
+    /// The measured power, if this is a power measurement
+    pub fn power(&self) -> Option<Power> {
+        match self.unit {
+            MeasurementUnit::PowerW => Some(Power(self.value as f32)),
+            MeasurementUnit::PowerKW => Some(Power(self.value as f32 * 1000.0)),
+            _ => None,
+        }
+    }
+
+    /// The measured energy, if this is an energy measurement
+    pub fn energy(&self) -> Option<Energy> {
+        match self.unit {
+            MeasurementUnit::EnergyWs => Some(Energy(self.value as f32 / 3600.0)),
+            MeasurementUnit::EnergyWh => Some(Energy(self.value as f32)),
+            MeasurementUnit::EnergyKWh => Some(Energy(self.value as f32 * 1000.0)),
+            _ => None,
+        }
+    }
+}
+
+/// Telegrams sent by a D2-01 actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Message {
+    Status(Status),
+    Measurement(Measurement),
+}
+
+impl Message {
+    /// Decode a D2-01 actuator telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        let d = &erp.user_data[..];
+        match vld_command(erp, 1)? {
+            0x04 if d.len() >= 3 => Ok(Self::Status(Status {
+                channel: d[1] & 0x1f,
+                value: match d[2] & 0x7f {
+                    v @ 0..=100 => Some(v),
+                    _ => None,
+                },
+                power_failure_enabled: d[0] & 0x80 != 0,
+                power_failure_detected: d[0] & 0x40 != 0,
+                over_current: d[1] & 0x80 != 0,
+                error_level: match (d[1] >> 5) & 0x03 {
+                    0 => ErrorLevel::HardwareOk,
+                    1 => ErrorLevel::Warning,
+                    2 => ErrorLevel::Failure,
+                    _ => ErrorLevel::NotSupported,
+                },
+                local_control: d[2] & 0x80 != 0,
+            })),
+            0x07 if d.len() >= 6 => Ok(Self::Measurement(Measurement {
+                channel: d[1] & 0x1f,
+                unit: match d[1] >> 5 {
+                    0 => MeasurementUnit::EnergyWs,
+                    1 => MeasurementUnit::EnergyWh,
+                    2 => MeasurementUnit::EnergyKWh,
+                    3 => MeasurementUnit::PowerW,
+                    4 => MeasurementUnit::PowerKW,
+                    unit => return Err(DecodeError::InvalidValue(unit)),
+                },
+                value: u32::from_be_bytes(d[2..6].try_into().unwrap()),
+            })),
+            0x04 | 0x07 => Err(DecodeError::BadLength(d.len())),
+            command => Err(DecodeError::InvalidValue(command)),
+        }
+    }
+
+    /// The channel reported on
+    pub fn channel(&self) -> u8 {
+        match self {
+            Self::Status(status) => status.channel,
+            Self::Measurement(measurement) => measurement.channel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d2010e_measurement_report_then_decode_power() {
+        // Same report as the legacy parser test: 19 W on channel 0
+        let erp = erp1(&[0xd2, 0x07, 0x60, 0x00, 0x00, 0x00, 0x13, 0x05, 0x0a, 0x3d, 0x6a, 0x00]);
+        let measurement = Measurement { channel: 0, unit: MeasurementUnit::PowerW, value: 19 };
+        assert_eq!(Message::decode(&erp).unwrap(), Message::Measurement(measurement));
+        assert_eq!(measurement.power(), Some(Power(19.0)));
+        assert_eq!(measurement.energy(), None);
+    }
+
+    #[test]
+    fn given_d201_status_response_then_decode_it() {
+        let erp = erp1(&[0xd2, 0x84, 0x01, 0x64, 0x05, 0x0a, 0x3d, 0x6a, 0x00]);
+        let Message::Status(status) = Message::decode(&erp).unwrap() else { panic!("not a status") };
+        assert_eq!(status.channel, 1);
+        assert_eq!(status.value, Some(100));
+        assert!(status.power_failure_enabled);
+        assert_eq!(status.error_level, ErrorLevel::HardwareOk);
+    }
+
+    #[test]
+    fn given_d201_commands_then_encode_them() {
+        assert_eq!(Command::StatusQuery { channel: ALL_CHANNELS }.encode(), vec![0x03, 0x1e]);
+        assert_eq!(Command::MeasurementQuery { channel: 0, power: true }.encode(), vec![0x06, 0x20]);
+        assert_eq!(Command::SetOutput { channel: 2, value: 50, dim: DimMode::Timer2 }.encode(), vec![0x01, 0x42, 50]);
+    }
+}
@@ -0,0 +1,64 @@
+//! D2-03 : Light, switching and blind control
+//!
+//! D2-03-0A is a push button reporting the kind of press together with its battery level:
+//!
+//! | Byte | Field                                                                 |
+//! |------|-----------------------------------------------------------------------|
+//! | 0    | Battery autonomy, 1..100 %                                            |
+//! | 1    | Action: 1 single press, 2 double press, 3 long press, 4 long release  |
+
+use super::{check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// What the user did with the button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ButtonAction {
+    SinglePress,
+    DoublePress,
+    LongPress,
+    LongPressReleased,
+}
+
+/// A D2-03-0A telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PushButton {
+    pub action: ButtonAction,
+    /// Battery level, in %. `None` if out of range.
+    pub battery: Option<u8>,
+}
+
+impl PushButton {
+    /// Decode a D2-03-0A telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Vld, 2)?;
+        let d = &erp.user_data[..];
+        let action = match d[1] {
+            1 => ButtonAction::SinglePress,
+            2 => ButtonAction::DoublePress,
+            3 => ButtonAction::LongPress,
+            4 => ButtonAction::LongPressReleased,
+            action => return Err(DecodeError::InvalidValue(action)),
+        };
+        Ok(Self { action, battery: (1..=100).contains(&d[0]).then_some(d[0]) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d2030a_telegrams_then_decode_press_and_battery() {
+        let erp = erp1(&[0xd2, 87, 0x02, 1, 2, 3, 4, 0]);
+        assert_eq!(PushButton::decode(&erp).unwrap(), PushButton { action: ButtonAction::DoublePress, battery: Some(87) });
+        let erp = erp1(&[0xd2, 0, 0x03, 1, 2, 3, 4, 0]);
+        assert_eq!(PushButton::decode(&erp).unwrap(), PushButton { action: ButtonAction::LongPress, battery: None });
+        assert!(PushButton::decode(&erp1(&[0xd2, 50, 0x07, 1, 2, 3, 4, 0])).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! D2-05 : Blinds control for position and angle
+//!
+//! Unlike most VLD profiles, D2-05-00 carries the command ID in the low nibble of the
+//! *last* byte, together with the channel in the high nibble.
+//!
+//! ```
+//! use enocean::eep::d2_05::{Command, Repositioning, ALL_CHANNELS};
+//!
+//! let command = Command::GoTo { channel: ALL_CHANNELS, position: Some(50), angle: None, repositioning: Repositioning::Direct };
+//! assert_eq!(command.encode(), vec![50, 127, 0, 0xf1]);
+//! ```
+
+use alloc::vec::Vec;
+
+use super::DecodeError;
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Channel number addressing all the channels of an actuator
+pub const ALL_CHANNELS: u8 = 0x0F;
+
+/// Value of the position and angle fields meaning "no change" / "unknown"
+const NO_VALUE: u8 = 127;
+
+/// How the blind moves to a new position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Repositioning {
+    Direct = 0,
+    /// Go up first, then to the new position
+    UpFirst = 1,
+    /// Go down first, then to the new position
+    DownFirst = 2,
+}
+
+/// Locking mode of a blind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum LockingMode {
+    Normal,
+    Blockage,
+    Alarm,
+}
+
+/// Commands sent to a D2-05-00 actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Command {
+    /// CMD 0x1 : go to position and angle, in %. `None` leaves the value unchanged.
+    GoTo { channel: u8, position: Option<u8>, angle: Option<u8>, repositioning: Repositioning },
+    /// CMD 0x2 : stop moving
+    Stop { channel: u8 },
+    /// CMD 0x3 : ask for the current position and angle
+    Query { channel: u8 },
+}
+
+impl Command {
+    /// Encode the VLD user data
+    pub fn encode(&self) -> Vec<u8> {
+        let value = |v: Option<u8>| v.map(|v| v.min(100)).unwrap_or(NO_VALUE);
+        match *self {
+            Self::GoTo { channel, position, angle, repositioning } =>
+                vec![value(position), value(angle), (repositioning as u8) << 4, channel << 4 | 0x01],
+            Self::Stop { channel } => vec![channel << 4 | 0x02],
+            Self::Query { channel } => vec![channel << 4 | 0x03],
+        }
+    }
+
+    /// The channel addressed, possibly [`ALL_CHANNELS`]
+    pub fn channel(&self) -> u8 {
+        match *self {
+            Self::GoTo { channel, .. } | Self::Stop { channel } | Self::Query { channel } => channel,
+        }
+    }
+}
+
+/// CMD 0x4 : reply position and angle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reply {
+    pub channel: u8,
+    /// Position in %, `None` if unknown
+    pub position: Option<u8>,
+    /// Angle in %, `None` if unknown
+    pub angle: Option<u8>,
+    pub locking_mode: LockingMode,
+}
+
+impl Reply {
+    /// Decode a D2-05-00 reply telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::Vld {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.len() != 4 {
+            return Err(DecodeError::BadLength(d.len()));
+        }
+        if d[3] & 0x0f != 0x04 {
+            return Err(DecodeError::InvalidValue(d[3] & 0x0f));
+        }
+
+        let value = |v: u8| match v & 0x7f {
+            v @ 0..=100 => Some(v),
+            _ => None,
+        };
+        Ok(Self {
+            channel: d[3] >> 4,
+            position: value(d[0]),
+            angle: value(d[1]),
+            locking_mode: match d[2] & 0x07 {
+                1 => LockingMode::Blockage,
+                2 => LockingMode::Alarm,
+                _ => LockingMode::Normal,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d20500_commands_then_encode_them() {
+        assert_eq!(Command::Stop { channel: 0 }.encode(), vec![0x02]);
+        assert_eq!(Command::Query { channel: 1 }.encode(), vec![0x13]);
+        let go_to = Command::GoTo { channel: 0, position: Some(100), angle: Some(30), repositioning: Repositioning::UpFirst };
+        assert_eq!(go_to.encode(), vec![100, 30, 0x10, 0x01]);
+    }
+
+    #[test]
+    fn given_d20500_reply_then_decode_position() {
+        let erp = erp1(&[0xd2, 75, 127, 0x01, 0x04, 1, 2, 3, 4, 0]);
+        assert_eq!(Reply::decode(&erp).unwrap(),
+                   Reply { channel: 0, position: Some(75), angle: None, locking_mode: LockingMode::Blockage });
+    }
+}
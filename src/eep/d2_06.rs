@@ -0,0 +1,130 @@
+//! D2-06 : Multisensor window handle
+//!
+//! The first byte of a D2-06-01 telegram is the message type. Sensor values (type 0x00):
+//!
+//! | Byte | Bits | Field                                                           |
+//! |------|------|-----------------------------------------------------------------|
+//! | 1    | 7..4 | Handle position (1 up, 2 down, 3 left, 4 right)                 |
+//! | 1    | 3..0 | Window state (1 not tilted, 2 tilted)                           |
+//! | 2    | 7..4 | Burglary alarm (1 no alarm, 2 alarm)                            |
+//! | 2    | 3..0 | Protection plus alarm (1 no alarm, 2 alarm)                     |
+//! | 3    | 7..0 | Temperature, 0..250 → -20..+60 °C                               |
+//! | 4    | 7..0 | Humidity, 0..200 → 0..100 %                                     |
+//! | 5..6 |      | Illuminance, 0..60000 lx                                        |
+//! | 7    | 7..3 | Battery, 0..20 → 0..100 %                                       |
+//!
+//! Out of range values, and 0 in enumerations, mean the device does not support the field.
+
+use super::quantity::{Illuminance, RelativeHumidity, Temperature};
+use super::{linear, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Position of the handle, seen from inside the room
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum HandlePosition {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Sensor values message
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SensorValues {
+    pub handle: Option<HandlePosition>,
+    /// Window tilted
+    pub tilted: Option<bool>,
+    pub burglary_alarm: Option<bool>,
+    pub protection_alarm: Option<bool>,
+    pub temperature: Option<Temperature>,
+    pub humidity: Option<RelativeHumidity>,
+    pub illuminance: Option<Illuminance>,
+    /// Battery level, in %
+    pub battery: Option<u8>,
+}
+
+/// A D2-06-01 telegram
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Message {
+    SensorValues(SensorValues),
+    /// Configuration reports and log data, which are not decoded
+    Other { message_type: u8 },
+}
+
+impl Message {
+    /// Decode a D2-06-01 telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::Vld {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.is_empty() {
+            return Err(DecodeError::BadLength(0));
+        }
+        if d[0] != 0x00 {
+            return Ok(Self::Other { message_type: d[0] });
+        }
+        if d.len() < 8 {
+            return Err(DecodeError::BadLength(d.len()));
+        }
+
+        let flag = |nibble: u8| match nibble {
+            1 => Some(false),
+            2 => Some(true),
+            _ => None,
+        };
+        let illuminance = u16::from_be_bytes([d[5], d[6]]);
+
+        Ok(Self::SensorValues(SensorValues {
+            handle: match d[1] >> 4 {
+                1 => Some(HandlePosition::Up),
+                2 => Some(HandlePosition::Down),
+                3 => Some(HandlePosition::Left),
+                4 => Some(HandlePosition::Right),
+                _ => None,
+            },
+            tilted: flag(d[1] & 0x0f),
+            burglary_alarm: flag(d[2] >> 4),
+            protection_alarm: flag(d[2] & 0x0f),
+            temperature: (d[3] <= 250).then(|| Temperature(linear(d[3] as u16, 250, -20.0, 60.0))),
+            humidity: (d[4] <= 200).then(|| RelativeHumidity(linear(d[4] as u16, 200, 0.0, 100.0))),
+            illuminance: (illuminance <= 60000).then_some(Illuminance(illuminance as f32)),
+            battery: (d[7] >> 3 <= 20).then_some((d[7] >> 3) * 5),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d20601_sensor_values_then_decode_all_fields() {
+        let erp = erp1(&[0xd2, 0x00, 0x22, 0x12, 125, 100, 0x01, 0xf4, 0xa0, 0x00, 0x00, 1, 2, 3, 4, 0]);
+        let Message::SensorValues(values) = Message::decode(&erp).unwrap() else { panic!("not sensor values") };
+        assert_eq!(values.handle, Some(HandlePosition::Down));
+        assert_eq!(values.tilted, Some(true));
+        assert_eq!(values.burglary_alarm, Some(false));
+        assert_eq!(values.protection_alarm, Some(true));
+        assert_eq!(values.temperature, Some(Temperature(20.0)));
+        assert_eq!(values.humidity, Some(RelativeHumidity(50.0)));
+        assert_eq!(values.illuminance, Some(Illuminance(500.0)));
+        assert_eq!(values.battery, Some(100));
+    }
+
+    #[test]
+    fn given_d20601_unsupported_fields_then_return_none() {
+        let erp = erp1(&[0xd2, 0x00, 0x00, 0x00, 255, 255, 0xff, 0xff, 0xff, 1, 2, 3, 4, 0]);
+        assert_eq!(Message::decode(&erp).unwrap(), Message::SensorValues(SensorValues::default()));
+        let erp = erp1(&[0xd2, 0x01, 0x00, 1, 2, 3, 4, 0]);
+        assert_eq!(Message::decode(&erp).unwrap(), Message::Other { message_type: 1 });
+    }
+}
@@ -0,0 +1,131 @@
+//! D2-14 : Multi function sensors
+//!
+//! Bit layout of the supported types (offset:size, counted from the MSB of the first byte):
+//!
+//! | Field        | 0x30 / 0x31                | 0x40 / 0x41                       |
+//! |--------------|----------------------------|-----------------------------------|
+//! | Smoke alarm  | 0:1                        | -                                 |
+//! | Sensor fault | 1:1                        | -                                 |
+//! | Temperature  | 8:8, 0..250 → 0..+50 °C    | 0:10, 0..1000 → -40..+60 °C       |
+//! | Humidity     | 16:8, 0..200 → 0..100 %    | 10:8, 0..200 → 0..100 %           |
+//! | Illuminance  | -                          | 18:17, 0..100000 lx               |
+//! | Acceleration | -                          | 35:2 status, 37:10 ×3 → ±2.5 g    |
+//! | Contact      | -                          | 67:1 (0x41 only)                  |
+
+use super::quantity::{Illuminance, RelativeHumidity, Temperature};
+use super::{bits, linear, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Why the acceleration was reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum AccelerationStatus {
+    PeriodicUpdate,
+    Threshold1Exceeded,
+    Threshold2Exceeded,
+}
+
+/// Acceleration on the three axes, in g
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Acceleration {
+    pub status: AccelerationStatus,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// A multisensor report. Fields the type does not carry are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Reading {
+    pub temperature: Option<Temperature>,
+    pub humidity: Option<RelativeHumidity>,
+    pub illuminance: Option<Illuminance>,
+    pub acceleration: Option<Acceleration>,
+    /// Contact closed
+    pub contact: Option<bool>,
+    pub smoke_alarm: Option<bool>,
+    pub sensor_fault: Option<bool>,
+}
+
+impl Reading {
+    /// Decode a D2-14 telegram
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        let length = match eep_type {
+            0x30 | 0x31 => 3,
+            0x40 | 0x41 => 9,
+            _ => return Err(DecodeError::UnsupportedType(eep_type)),
+        };
+        if erp.choice != Rorg::Vld {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.len() < length {
+            return Err(DecodeError::BadLength(d.len()));
+        }
+
+        if length == 3 {
+            return Ok(Self {
+                smoke_alarm: Some(bits(d, 0, 1) != 0),
+                sensor_fault: Some(bits(d, 1, 1) != 0),
+                temperature: Some(Temperature(linear(bits(d, 8, 8).min(250) as u16, 250, 0.0, 50.0))),
+                humidity: Some(RelativeHumidity(linear(bits(d, 16, 8).min(200) as u16, 200, 0.0, 100.0))),
+                ..Self::default()
+            });
+        }
+
+        let axis = |offset| linear(bits(d, offset, 10).min(1000) as u16, 1000, -2.5, 2.5);
+        Ok(Self {
+            temperature: Some(Temperature(linear(bits(d, 0, 10).min(1000) as u16, 1000, -40.0, 60.0))),
+            humidity: Some(RelativeHumidity(linear(bits(d, 10, 8).min(200) as u16, 200, 0.0, 100.0))),
+            illuminance: Some(Illuminance(bits(d, 18, 17).min(100000) as f32)),
+            acceleration: Some(Acceleration {
+                status: match bits(d, 35, 2) {
+                    1 => AccelerationStatus::Threshold1Exceeded,
+                    2 => AccelerationStatus::Threshold2Exceeded,
+                    _ => AccelerationStatus::PeriodicUpdate,
+                },
+                x: axis(37),
+                y: axis(47),
+                z: axis(57),
+            }),
+            contact: (eep_type == 0x41).then(|| bits(d, 67, 1) != 0),
+            ..Self::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d21441_telegram_then_decode_all_fields() {
+        // TMP = 600 (20 °C), HUM = 100 (50 %), ILL = 500 lx, status 0, X = Y = 500 (0 g), Z = 900 (2 g), contact closed
+        let erp = erp1(&[0xd2, 0x96, 0x19, 0x00, 0x3e, 0x83, 0xe8, 0xfa, 0x70, 0x90, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x41).unwrap();
+        assert_eq!(reading.temperature, Some(Temperature(20.0)));
+        assert_eq!(reading.humidity, Some(RelativeHumidity(50.0)));
+        assert_eq!(reading.illuminance, Some(Illuminance(500.0)));
+        let acceleration = reading.acceleration.unwrap();
+        assert_eq!(acceleration.status, AccelerationStatus::PeriodicUpdate);
+        assert_eq!((acceleration.x, acceleration.y, acceleration.z), (0.0, 0.0, 2.0));
+        assert_eq!(reading.contact, Some(true));
+    }
+
+    #[test]
+    fn given_d21430_telegram_then_decode_smoke_alarm() {
+        let erp = erp1(&[0xd2, 0x80, 125, 100, 1, 2, 3, 4, 0]);
+        let reading = Reading::decode(&erp, 0x30).unwrap();
+        assert_eq!(reading.smoke_alarm, Some(true));
+        assert_eq!(reading.sensor_fault, Some(false));
+        assert_eq!(reading.temperature, Some(Temperature(25.0)));
+        assert_eq!(reading.acceleration, None);
+    }
+}
@@ -0,0 +1,84 @@
+//! D2-15 : People activity
+//!
+//! D2-15-00 desk occupancy sensors report in the first byte:
+//!
+//! | Bits | Field                                                            |
+//! |------|------------------------------------------------------------------|
+//! | 7..6 | Presence: 0 present, 1 not present, 2 not detectable             |
+//! | 5..4 | Energy storage: 0 high, 1 medium, 2 low, 3 critical              |
+
+use super::{bits, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Presence state reported by the sensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Presence {
+    Present,
+    NotPresent,
+    /// The sensor cannot currently detect presence
+    NotDetectable,
+}
+
+/// Charge level of the energy storage of a self-powered sensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum EnergyStorage {
+    High,
+    Medium,
+    Low,
+    Critical,
+}
+
+/// A D2-15-00 telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Activity {
+    pub presence: Presence,
+    pub energy_storage: EnergyStorage,
+}
+
+impl Activity {
+    /// Decode a D2-15-00 telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::Vld {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.is_empty() {
+            return Err(DecodeError::BadLength(0));
+        }
+        let presence = match bits(d, 0, 2) {
+            0 => Presence::Present,
+            1 => Presence::NotPresent,
+            2 => Presence::NotDetectable,
+            _ => return Err(DecodeError::InvalidValue(d[0])),
+        };
+        let energy_storage = match bits(d, 2, 2) {
+            0 => EnergyStorage::High,
+            1 => EnergyStorage::Medium,
+            2 => EnergyStorage::Low,
+            _ => EnergyStorage::Critical,
+        };
+        Ok(Self { presence, energy_storage })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d21500_telegrams_then_decode_presence_and_energy() {
+        let erp = erp1(&[0xd2, 0x10, 1, 2, 3, 4, 0]);
+        assert_eq!(Activity::decode(&erp).unwrap(), Activity { presence: Presence::Present, energy_storage: EnergyStorage::Medium });
+        let erp = erp1(&[0xd2, 0x70, 1, 2, 3, 4, 0]);
+        assert_eq!(Activity::decode(&erp).unwrap(), Activity { presence: Presence::NotPresent, energy_storage: EnergyStorage::Critical });
+        assert!(Activity::decode(&erp1(&[0xd2, 0xc0, 1, 2, 3, 4, 0])).is_err());
+    }
+}
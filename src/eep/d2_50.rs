@@ -0,0 +1,206 @@
+//! D2-50 : Heat recovery ventilation
+//!
+//! The message type is given by the 3 most significant bits of the first byte:
+//!
+//! | MT | Direction           | Message                      |
+//! |----|---------------------|------------------------------|
+//! | 0  | controller → unit   | Remote transmission request  |
+//! | 1  | controller → unit   | Ventilation control          |
+//! | 2  | unit → controller   | Basic status                 |
+//!
+//! Basic status layout (offset:size, from the MSB of the first byte):
+//!
+//! | Field                       | Bits   | Encoding                        |
+//! |-----------------------------|--------|---------------------------------|
+//! | Operation mode              | 3:4    | see [`Mode`]                    |
+//! | Safety mode                 | 7:1    |                                 |
+//! | Heat exchanger bypass open  | 8:1    |                                 |
+//! | Defrost mode                | 11:1   |                                 |
+//! | Filter maintenance required | 17:1   |                                 |
+//! | Outdoor air temperature     | 32:7   | raw - 64, in °C                 |
+//! | Supply air temperature      | 39:7   | raw - 64, in °C                 |
+//! | Indoor air temperature      | 46:7   | raw - 64, in °C                 |
+//! | Exhaust air temperature     | 53:7   | raw - 64, in °C                 |
+//! | Supply air flow             | 60:10  | m³/h                            |
+//! | Exhaust air flow            | 70:10  | m³/h                            |
+//! | Supply fan speed            | 80:12  | rpm                             |
+//! | Exhaust fan speed           | 92:12  | rpm                             |
+
+use alloc::vec::Vec;
+
+use super::{bits, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Operation mode of the ventilation unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Mode {
+    /// Fan stage 0 (off) to 4
+    Level(u8),
+    Automatic,
+    AutomaticOnDemand,
+    SupplyAirOnly,
+    ExhaustAirOnly,
+}
+
+impl Mode {
+    fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0..=4 => Some(Self::Level(raw)),
+            0x0B => Some(Self::Automatic),
+            0x0C => Some(Self::AutomaticOnDemand),
+            0x0D => Some(Self::SupplyAirOnly),
+            0x0E => Some(Self::ExhaustAirOnly),
+            _ => None,
+        }
+    }
+
+    fn to_raw(self) -> u8 {
+        match self {
+            Self::Level(level) => level.min(4),
+            Self::Automatic => 0x0B,
+            Self::AutomaticOnDemand => 0x0C,
+            Self::SupplyAirOnly => 0x0D,
+            Self::ExhaustAirOnly => 0x0E,
+        }
+    }
+}
+
+/// Commands sent to a D2-50 ventilation unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Command {
+    /// MT 0 : ask the unit to send its basic status
+    StatusRequest,
+    /// MT 1 : set the operation mode. `None` leaves it unchanged.
+    Control { mode: Option<Mode> },
+}
+
+impl Command {
+    /// Encode the VLD user data
+    pub fn encode(&self) -> Vec<u8> {
+        match *self {
+            Self::StatusRequest => vec![0x00],
+            Self::Control { mode } => {
+                let mode = mode.map(Mode::to_raw).unwrap_or(0x0F);
+                vec![0x01 << 5 | mode << 1, 0x00, 0x00, 0x00]
+            }
+        }
+    }
+}
+
+/// MT 2 : basic status of the ventilation unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Status {
+    pub mode: Option<Mode>,
+    pub safety_mode: bool,
+    pub bypass_open: bool,
+    pub defrost: bool,
+    pub filter_maintenance: bool,
+    /// Temperatures, in °C
+    pub outdoor_air_temperature: i8,
+    pub supply_air_temperature: i8,
+    pub indoor_air_temperature: i8,
+    pub exhaust_air_temperature: i8,
+    /// Air flows, in m³/h
+    pub supply_air_flow: u16,
+    pub exhaust_air_flow: u16,
+    /// Fan speeds, in rpm
+    pub supply_fan_speed: u16,
+    pub exhaust_fan_speed: u16,
+}
+
+/// A D2-50 telegram, in either direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Message {
+    Command(Command),
+    Status(Status),
+}
+
+impl Message {
+    /// Decode a D2-50 telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::Vld {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.is_empty() {
+            return Err(DecodeError::BadLength(0));
+        }
+        let temperature = |offset| bits(d, offset, 7) as i8 - 64;
+
+        match d[0] >> 5 {
+            0 => Ok(Self::Command(Command::StatusRequest)),
+            1 => Ok(Self::Command(Command::Control { mode: Mode::from_raw(bits(d, 3, 4) as u8) })),
+            2 if d.len() >= 13 => Ok(Self::Status(Status {
+                mode: Mode::from_raw(bits(d, 3, 4) as u8),
+                safety_mode: bits(d, 7, 1) != 0,
+                bypass_open: bits(d, 8, 1) != 0,
+                defrost: bits(d, 11, 1) != 0,
+                filter_maintenance: bits(d, 17, 1) != 0,
+                outdoor_air_temperature: temperature(32),
+                supply_air_temperature: temperature(39),
+                indoor_air_temperature: temperature(46),
+                exhaust_air_temperature: temperature(53),
+                supply_air_flow: bits(d, 60, 10) as u16,
+                exhaust_air_flow: bits(d, 70, 10) as u16,
+                supply_fan_speed: bits(d, 80, 12) as u16,
+                exhaust_fan_speed: bits(d, 92, 12) as u16,
+            })),
+            2 => Err(DecodeError::BadLength(d.len())),
+            message_type => Err(DecodeError::InvalidValue(message_type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d250_commands_then_encode_and_decode_them() {
+        let command = Command::Control { mode: Some(Mode::Level(2)) };
+        let user_data = command.encode();
+        assert_eq!(user_data, vec![0x24, 0, 0, 0]);
+
+        let mut data = vec![0xd2];
+        data.extend_from_slice(&user_data);
+        data.extend_from_slice(&[1, 2, 3, 4, 0]);
+        assert_eq!(Message::decode(&erp1(&data)).unwrap(), Message::Command(command));
+    }
+
+    #[test]
+    fn given_d250_basic_status_then_decode_it() {
+        let fields = [(2, 3), (0x0B, 4), (0, 1), (1, 1), (0, 23), (64 + 5, 7), (64 + 18, 7), (64 + 21, 7),
+                      (64 + 8, 7), (150, 10), (140, 10), (1200, 12), (1100, 12)];
+        let mut d = vec![0u8; 13];
+        let mut offset = 0;
+        for (value, size) in fields {
+            for i in 0..size {
+                if (value >> (size - 1 - i)) & 1 == 1 {
+                    d[(offset + i) / 8] |= 0x80 >> ((offset + i) % 8);
+                }
+            }
+            offset += size;
+        }
+        let mut data = vec![0xd2];
+        data.extend_from_slice(&d);
+        data.extend_from_slice(&[1, 2, 3, 4, 0]);
+
+        let Message::Status(status) = Message::decode(&erp1(&data)).unwrap() else { panic!("not a status") };
+        assert_eq!(status.mode, Some(Mode::Automatic));
+        assert!(status.bypass_open);
+        assert_eq!(status.outdoor_air_temperature, 5);
+        assert_eq!(status.indoor_air_temperature, 21);
+        assert_eq!(status.supply_air_flow, 150);
+        assert_eq!(status.exhaust_fan_speed, 1100);
+    }
+}
@@ -0,0 +1,59 @@
+//! D5-00 : Contacts and switches
+//!
+//! | Bit  | DB0.3                           | DB0.0                    |
+//! |------|---------------------------------|--------------------------|
+//! | Name | LRN (0 = teach-in, 1 = data)    | CO (0 = open, 1 = closed)|
+
+use super::{check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// State of a single input contact (D5-00-01)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ContactState {
+    Open,
+    Closed,
+}
+
+impl ContactState {
+    /// Decode a D5-00-01 data telegram, rejecting teach-in telegrams
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Bs1, 1)?;
+        if erp.user_data[0] & 0x08 == 0 {
+            return Err(DecodeError::TeachIn);
+        }
+        match erp.user_data[0] & 1 {
+            1 => Ok(Self::Closed),
+            _ => Ok(Self::Open),
+        }
+    }
+}
+
+/// True if the telegram is a 1BS teach-in telegram (LRN bit cleared)
+pub fn is_teach_in(erp: &RadioErp1) -> bool {
+    erp.choice == Rorg::Bs1 && erp.user_data.len() == 1 && erp.user_data[0] & 0x08 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_d50001_telegrams_then_decode_contact_state() {
+        let closed = erp1(&[0xd5, 0x09, 0x01, 0x92, 0x3d, 0xa8, 0x00]);
+        let open = erp1(&[0xd5, 0x08, 0x01, 0x92, 0x3d, 0xa8, 0x00]);
+        assert_eq!(ContactState::decode(&closed).unwrap(), ContactState::Closed);
+        assert_eq!(ContactState::decode(&open).unwrap(), ContactState::Open);
+        assert!(!is_teach_in(&closed));
+    }
+
+    #[test]
+    fn given_d50001_teach_in_telegram_then_recognize_it() {
+        let teach_in = erp1(&[0xd5, 0x00, 0x01, 0x92, 0x3d, 0xa8, 0x00]);
+        assert!(is_teach_in(&teach_in));
+        assert!(matches!(ContactState::decode(&teach_in), Err(DecodeError::TeachIn)));
+    }
+}
@@ -0,0 +1,252 @@
+//! Eltako series 14 actuators
+//!
+//! Series 14 actuators sit on the RS485 bus of a FAM14, where each channel has a bus
+//! address from 1 to 127. The FAM14 relays their telegrams on air from its base ID plus
+//! the bus address, see [`Fam14`]; on the bus itself, e.g. through a FGW14-USB, they are
+//! sent from the bus address, see [`bus_address`].
+//!
+//! The actuators are commanded with 4BS telegrams, from a sender ID taught in as a controller:
+//! a gateway usually sends from a distinct address of its base ID range per actuator
+//! channel, see [`IdBase::sender`]. Eltako reads the bytes of A5-38-08 its own way: the dimming
+//! value is always in %, DB1 is a dimming speed and DB0.2 locks the actuator. The FSB14
+//! shutter actuator uses the Eltako profile A5-3F-7F.
+//!
+//! ```
+//! use enocean::eep::eltako::{Actuator, Direction, Dimming, Shutter};
+//!
+//! assert_eq!(Actuator::Fud14.teach_in(), [0xe0, 0x40, 0x0d, 0x80]);
+//! assert_eq!(Dimming { value: 40, speed: 0, lock: false }.encode(), [0x02, 40, 0x00, 0x09]);
+//! let up = Shutter { direction: Direction::Up, runtime: Some(150), lock: false };
+//! assert_eq!(up.encode(), [0x00, 150, 0x01, 0x0a]);
+//! ```
+//!
+//! Actuators confirm their state with the telegrams decoded by [`Actuator::decode`]: RPS
+//! telegrams for the FSR14 and FSB14, and A5-38-08 dimming telegrams for the FUD14.
+
+use super::a5_38::CentralCommand;
+use super::teach_in::TeachIn4BS;
+use super::{check_telegram, data_4bs, DecodeError};
+use crate::enocean::Rorg;
+use crate::manufacturer::Manufacturer;
+use crate::packet::{Address, EEPProfileCode, IdBase, RadioErp1};
+
+/// Highest bus address of a series 14 device
+pub const MAX_BUS_ADDRESS: u8 = 127;
+
+/// The sender ID of the telegrams of bus device `device` on the bus itself
+pub fn bus_address(device: u8) -> Address {
+    Address::new(device as u32)
+}
+
+/// The bus device of a telegram sent on the bus itself, from its sender ID
+pub fn bus_device(address: Address) -> Option<u8> {
+    u32::from(address).try_into().ok().filter(|device| (1..=MAX_BUS_ADDRESS).contains(device))
+}
+
+/// A FAM14 bus gateway, relaying the telegrams of the bus devices on air from its base ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fam14 {
+    pub base: IdBase,
+}
+
+impl Fam14 {
+    pub fn new(base: IdBase) -> Self {
+        Self { base }
+    }
+
+    /// The address the telegrams of bus device `device` are relayed from
+    pub fn radio_address(&self, device: u8) -> Option<Address> {
+        (1..=MAX_BUS_ADDRESS).contains(&device).then(|| self.base.sender(device)).flatten()
+    }
+
+    /// The bus device a relayed telegram comes from, from its sender ID
+    pub fn bus_device(&self, address: Address) -> Option<u8> {
+        address.offset_from(&self.base).filter(|device| *device >= 1)
+    }
+}
+
+/// FSR14 relay command: A5-38-08 switching, without time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Switching {
+    pub on: bool,
+    /// Lock the relay in this state
+    pub lock: bool,
+}
+
+impl Switching {
+    /// Encode the 4BS user data (DB3..DB0)
+    pub fn encode(&self) -> [u8; 4] {
+        CentralCommand::Switching { on: self.on, time: 0, delay: false, lock: self.lock }.encode()
+    }
+}
+
+/// FUD14 dimmer command: A5-38-08 dimming, the Eltako way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Dimming {
+    /// Dimming value, 0..100 %, 0 switching off
+    pub value: u8,
+    /// Dimming speed, from 1 (fastest) to 255 (slowest); 0 keeps the setting of the dimmer
+    pub speed: u8,
+    /// Lock the dimmer at this value
+    pub lock: bool,
+}
+
+impl Dimming {
+    /// Encode the 4BS user data (DB3..DB0)
+    pub fn encode(&self) -> [u8; 4] {
+        let value = self.value.min(100);
+        [0x02, value, self.speed, 0x08 | if self.lock { 0x04 } else { 0 } | (value > 0) as u8]
+    }
+}
+
+/// Direction of a shutter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Direction {
+    Stop = 0x00,
+    Up = 0x01,
+    Down = 0x02,
+}
+
+/// FSB14 shutter command (A5-3F-7F)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Shutter {
+    pub direction: Direction,
+    /// How long to move, in 1/10 s. `None` moves for the runtime set on the actuator.
+    pub runtime: Option<u16>,
+    /// Lock the actuator
+    pub lock: bool,
+}
+
+impl Shutter {
+    /// Encode the 4BS user data (DB3..DB0). DB0.1 gives the runtime in 1/10 s rather than s.
+    pub fn encode(&self) -> [u8; 4] {
+        let [msb, lsb] = self.runtime.unwrap_or(0).to_be_bytes();
+        let tenths = if self.runtime.is_some() { 0x02 } else { 0 };
+        [msb, lsb, self.direction as u8, 0x08 | if self.lock { 0x04 } else { 0 } | tenths]
+    }
+}
+
+/// State reported by a shutter actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ShutterState {
+    /// Started moving
+    Moving(Direction),
+    /// Reached the top end position
+    Top,
+    /// Reached the bottom end position
+    Bottom,
+    /// Stopped between the end positions, after moving for `runtime` 1/10 s
+    Stopped { direction: Direction, runtime: u16 },
+}
+
+/// State reported by a series 14 actuator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Status {
+    Switch { on: bool },
+    Dimmer { on: bool, value: u8 },
+    Shutter(ShutterState),
+}
+
+/// The series 14 actuators supported here
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Actuator {
+    /// Relay
+    Fsr14,
+    /// Universal dimmer
+    Fud14,
+    /// Shutter
+    Fsb14,
+}
+
+impl Actuator {
+    /// The profile the actuator is commanded with
+    pub fn eep(&self) -> EEPProfileCode {
+        match self {
+            Self::Fsr14 | Self::Fud14 => EEPProfileCode::new(0xA5, 0x38, 0x08),
+            Self::Fsb14 => EEPProfileCode::new(0xA5, 0x3F, 0x7F),
+        }
+    }
+
+    /// The 4BS teach-in telegram to send, from the controller address, while the actuator
+    /// is in learn mode
+    pub fn teach_in(&self) -> [u8; 4] {
+        TeachIn4BS { eep: Some(self.eep()), manufacturer: Some(Manufacturer::Eltako.into()), response: None }.encode()
+    }
+
+    /// Decode the state reported by the actuator
+    pub fn decode(&self, erp: &RadioErp1) -> Result<Status, DecodeError> {
+        match self {
+            Self::Fsr14 => match rps(erp)? {
+                0x70 => Ok(Status::Switch { on: true }),
+                0x50 => Ok(Status::Switch { on: false }),
+                data => Err(DecodeError::InvalidValue(data)),
+            },
+            Self::Fud14 => match CentralCommand::decode(erp)? {
+                CentralCommand::Dimming { on, value, .. } => Ok(Status::Dimmer { on, value: value.min(100) }),
+                CentralCommand::Switching { .. } => Err(DecodeError::InvalidValue(0x01)),
+            },
+            Self::Fsb14 if erp.choice == Rorg::Bs4 => {
+                let db = data_4bs(erp)?;
+                let direction = match db[2] {
+                    0x01 => Direction::Up,
+                    0x02 => Direction::Down,
+                    direction => return Err(DecodeError::InvalidValue(direction)),
+                };
+                Ok(Status::Shutter(ShutterState::Stopped { direction, runtime: u16::from_be_bytes([db[0], db[1]]) }))
+            }
+            Self::Fsb14 => match rps(erp)? {
+                0x01 => Ok(Status::Shutter(ShutterState::Moving(Direction::Up))),
+                0x02 => Ok(Status::Shutter(ShutterState::Moving(Direction::Down))),
+                0x70 => Ok(Status::Shutter(ShutterState::Top)),
+                0x50 => Ok(Status::Shutter(ShutterState::Bottom)),
+                data => Err(DecodeError::InvalidValue(data)),
+            },
+        }
+    }
+}
+
+/// Util : the data byte of an RPS telegram
+fn rps(erp: &RadioErp1) -> Result<u8, DecodeError> {
+    check_telegram(erp, Rorg::Rps, 1)?;
+    Ok(erp.user_data[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_fam14_then_translate_bus_and_radio_addresses() {
+        let fam14 = Fam14::new(IdBase("ff9b1280".parse().unwrap()));
+        assert_eq!(fam14.radio_address(5), Some("ff9b1285".parse().unwrap()));
+        assert_eq!(fam14.bus_device("ff9b1285".parse().unwrap()), Some(5));
+        assert_eq!((fam14.radio_address(0), fam14.bus_device("ff9b1280".parse().unwrap())), (None, None));
+        assert_eq!(bus_device(bus_address(12)), Some(12));
+        assert_eq!(bus_device("0180998e".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn given_actuator_telegrams_then_decode_status() {
+        assert_eq!(Actuator::Fsr14.decode(&erp1(&[0xf6, 0x70, 0x00, 0x00, 0x00, 0x05, 0x30])).unwrap(), Status::Switch { on: true });
+        assert_eq!(Actuator::Fsb14.decode(&erp1(&[0xf6, 0x50, 0x00, 0x00, 0x00, 0x06, 0x30])).unwrap(), Status::Shutter(ShutterState::Bottom));
+        assert_eq!(
+            Actuator::Fsb14.decode(&erp1(&[0xa5, 0x00, 0x4b, 0x02, 0x0a, 0x00, 0x00, 0x00, 0x06, 0x00])).unwrap(),
+            Status::Shutter(ShutterState::Stopped { direction: Direction::Down, runtime: 75 })
+        );
+        let dimmer = erp1(&[0xa5, 0x02, 0x28, 0x00, 0x09, 0x00, 0x00, 0x00, 0x07, 0x00]);
+        assert_eq!(Actuator::Fud14.decode(&dimmer).unwrap(), Status::Dimmer { on: true, value: 40 });
+        assert!(matches!(Actuator::Fsr14.decode(&dimmer), Err(DecodeError::WrongRorg(Rorg::Bs4))));
+
+        assert_eq!(Actuator::Fsb14.teach_in(), [0xff, 0xf8, 0x0d, 0x80]);
+        assert_eq!(Switching { on: false, lock: true }.encode(), [0x01, 0x00, 0x00, 0x0c]);
+        assert_eq!(Dimming { value: 0, speed: 10, lock: false }.encode(), [0x02, 0, 10, 0x08]);
+    }
+}
@@ -0,0 +1,133 @@
+//! F6-02 : Light and blind control (rocker switches)
+//!
+//! Rocker switch modules (PTM 200) send one RPS telegram when buttons are pressed and
+//! another one when they are released. The meaning of the data byte depends on the NU
+//! bit of the status byte:
+//!
+//! | NU | DB0.7..5         | DB0.4        | DB0.3..1          | DB0.0               |
+//! |----|------------------|--------------|-------------------|---------------------|
+//! | 1  | First button     | Energy bow   | Second button     | Second action valid |
+//! | 0  | Number of buttons| Energy bow   | -                 | -                   |
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::eep::f6_02::{Button, RockerEvent};
+//!
+//! let data = [0xf6, 0x70, 0xfe, 0xf5, 0x8f, 0xf5, 0x30];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! assert_eq!(RockerEvent::decode(&erp)?, RockerEvent::Pressed { first: Button::B0, second: None });
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`RockerEvent::encode`] gives the telegrams of a module in return, to emulate a switch
+//! toward actuators that only learn RPS switches. A press is always followed by a release.
+
+use super::{check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// A rocker button. The letter names the rocker, the digit its side (`0` = O, `1` = I).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Button {
+    A1, A0, B1, B0,
+    C1, C0, D1, D0,
+}
+
+impl Button {
+    const ALL: [Self; 8] = [Self::A1, Self::A0, Self::B1, Self::B0, Self::C1, Self::C0, Self::D1, Self::D0];
+}
+
+/// What happened on a rocker switch module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum RockerEvent {
+    /// One or two buttons pressed
+    Pressed { first: Button, second: Option<Button> },
+    /// Three or four buttons pressed at once; they are not individually identified
+    MultiplePressed,
+    /// All buttons released
+    Released,
+}
+
+impl RockerEvent {
+    /// Decode an F6-02-01/02 telegram (two rockers)
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        decode_rockers(erp, 2)
+    }
+
+    /// The data byte and the status byte of the event, as sent by a PTM 2xx module: T21 is
+    /// set, and NU when the buttons are identified
+    pub fn encode(&self) -> (u8, u8) {
+        match *self {
+            Self::Pressed { first, second } => {
+                let second = second.map_or(0, |button| (button as u8) << 1 | 0x01);
+                ((first as u8) << 5 | 0x10 | second, 0x30)
+            }
+            Self::MultiplePressed => (0x70, 0x20),
+            Self::Released => (0x00, 0x20),
+        }
+    }
+}
+
+/// Decode a rocker telegram from a module with `rockers` rockers (2 for F6-02, 4 for F6-03)
+pub(crate) fn decode_rockers(erp: &RadioErp1, rockers: u8) -> Result<RockerEvent, DecodeError> {
+    check_telegram(erp, Rorg::Rps, 1)?;
+    let byte = erp.user_data[0];
+    let pressed = byte & 0x10 != 0;
+    let button = |raw: u8| match Button::ALL.get(raw as usize) {
+        Some(&b) if raw < 2 * rockers => Ok(b),
+        _ => Err(DecodeError::InvalidValue(byte)),
+    };
+
+    if !erp.status_byte().nu() {
+        return match (byte >> 5, pressed) {
+            (0, false) => Ok(RockerEvent::Released),
+            (3, true) => Ok(RockerEvent::MultiplePressed),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        };
+    }
+
+    if !pressed {
+        return Ok(RockerEvent::Released);
+    }
+    Ok(RockerEvent::Pressed {
+        first: button(byte >> 5)?,
+        second: if byte & 0x01 != 0 { Some(button((byte >> 1) & 0x07)?) } else { None },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_f602_telegrams_then_decode_rocker_events() {
+        let event = |byte, status| RockerEvent::decode(&erp1(&[0xf6, byte, 1, 2, 3, 4, status]));
+        assert_eq!(event(0x10, 0x30).unwrap(), RockerEvent::Pressed { first: Button::A1, second: None });
+        assert_eq!(event(0x75, 0x30).unwrap(), RockerEvent::Pressed { first: Button::B0, second: Some(Button::B1) });
+        assert_eq!(event(0x00, 0x20).unwrap(), RockerEvent::Released);
+        assert_eq!(event(0x70, 0x20).unwrap(), RockerEvent::MultiplePressed);
+        assert!(event(0x90, 0x30).is_err());
+    }
+
+    #[test]
+    fn given_rocker_events_then_encode_as_ptm200() {
+        let events = [
+            RockerEvent::Pressed { first: Button::B0, second: None },
+            RockerEvent::Pressed { first: Button::A0, second: Some(Button::B1) },
+            RockerEvent::MultiplePressed,
+            RockerEvent::Released,
+        ];
+        for event in events {
+            let (byte, status) = event.encode();
+            assert_eq!(RockerEvent::decode(&erp1(&[0xf6, byte, 1, 2, 3, 4, status])).unwrap(), event);
+        }
+        assert_eq!(events[0].encode(), (0x70, 0x30));
+        assert_eq!(events[3].encode(), (0x00, 0x20));
+    }
+}
@@ -0,0 +1,32 @@
+//! F6-03 : Light and blind control, four rockers
+//!
+//! Same telegrams as [F6-02](super::f6_02), with the 3-bit button fields covering the
+//! rockers C and D as well:
+//!
+//! | DB0.7..5 / DB0.3..1 | 0  | 1  | 2  | 3  | 4  | 5  | 6  | 7  |
+//! |---------------------|----|----|----|----|----|----|----|----|
+//! | Button              | A1 | A0 | B1 | B0 | C1 | C0 | D1 | D0 |
+
+use super::f6_02::{decode_rockers, RockerEvent};
+use super::DecodeError;
+use crate::packet::RadioErp1;
+
+/// Decode an F6-03-01/02 telegram (four rockers)
+pub fn decode(erp: &RadioErp1) -> Result<RockerEvent, DecodeError> {
+    decode_rockers(erp, 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::f6_02::Button;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_f603_telegrams_then_decode_all_four_rockers() {
+        let event = |byte| decode(&erp1(&[0xf6, byte, 1, 2, 3, 4, 0x30])).unwrap();
+        assert_eq!(event(0x90), RockerEvent::Pressed { first: Button::C1, second: None });
+        assert_eq!(event(0xfb), RockerEvent::Pressed { first: Button::D0, second: Some(Button::C0) });
+        assert_eq!(event(0x00), RockerEvent::Released);
+    }
+}
@@ -0,0 +1,59 @@
+//! F6-04 : Key card activated switch
+//!
+//! Hotel key card holders report whether a card is currently inserted. The two
+//! variants of the profile encode this differently:
+//!
+//!  - F6-04-01 sends `0x70` when a card is inserted and `0x00` when it is taken out.
+//!  - F6-04-02 carries the state of the card in DB0.2, and the energy bow in DB0.7.
+
+use super::{check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// State of a key card holder (F6-04-01, F6-04-02)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum KeyCardState {
+    Inserted,
+    Removed,
+}
+
+impl KeyCardState {
+    /// Decode an F6-04 telegram. `eep_type` selects between the `0x01` and `0x02` variants.
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Rps, 1)?;
+        let byte = erp.user_data[0];
+        match eep_type {
+            0x01 => match byte {
+                0x70 => Ok(Self::Inserted),
+                0x00 => Ok(Self::Removed),
+                _ => Err(DecodeError::InvalidValue(byte)),
+            },
+            0x02 => match (byte >> 2) & 1 {
+                1 => Ok(Self::Inserted),
+                _ => Ok(Self::Removed),
+            },
+            _ => Err(DecodeError::UnsupportedType(eep_type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_f60401_telegrams_then_decode_card_state() {
+        assert_eq!(KeyCardState::decode(&erp1(&[0xf6, 0x70, 1, 2, 3, 4, 0x30]), 0x01).unwrap(), KeyCardState::Inserted);
+        assert_eq!(KeyCardState::decode(&erp1(&[0xf6, 0x00, 1, 2, 3, 4, 0x20]), 0x01).unwrap(), KeyCardState::Removed);
+        assert!(KeyCardState::decode(&erp1(&[0xf6, 0x04, 1, 2, 3, 4, 0x20]), 0x01).is_err());
+    }
+
+    #[test]
+    fn given_f60402_telegrams_then_decode_card_state() {
+        assert_eq!(KeyCardState::decode(&erp1(&[0xf6, 0x84, 1, 2, 3, 4, 0x20]), 0x02).unwrap(), KeyCardState::Inserted);
+        assert_eq!(KeyCardState::decode(&erp1(&[0xf6, 0x00, 1, 2, 3, 4, 0x20]), 0x02).unwrap(), KeyCardState::Removed);
+    }
+}
@@ -0,0 +1,61 @@
+//! F6-05 : Detectors
+//!
+//! Simple RPS detectors reporting a single state byte:
+//!
+//! | Value  | F6-05-00 (wind)      | F6-05-01 (leakage)   | F6-05-02 (smoke)     |
+//! |--------|----------------------|----------------------|----------------------|
+//! | `0x00` | Wind below threshold | -                    | No smoke             |
+//! | `0x10` | Wind above threshold | -                    | Smoke alarm          |
+//! | `0x11` | -                    | Water detected       | -                    |
+//! | `0x30` | Energy low           | -                    | Energy low           |
+//!
+//! The leakage sensor only sends `0x11`; any other value means no water.
+
+use super::{check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// State of an F6-05 detector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DetectorState {
+    /// Wind above threshold, water detected, or smoke detected
+    Alarm,
+    NoAlarm,
+    /// The detector's energy storage is running low
+    EnergyLow,
+}
+
+impl DetectorState {
+    /// Decode an F6-05 telegram. `eep_type` selects the kind of detector.
+    pub fn decode(erp: &RadioErp1, eep_type: u8) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Rps, 1)?;
+        let byte = erp.user_data[0];
+        match (eep_type, byte) {
+            (0x01, 0x11) => Ok(Self::Alarm),
+            (0x01, _) => Ok(Self::NoAlarm),
+            (0x00 | 0x02, 0x00) => Ok(Self::NoAlarm),
+            (0x00 | 0x02, 0x10) => Ok(Self::Alarm),
+            (0x00 | 0x02, 0x30) => Ok(Self::EnergyLow),
+            (0x00 | 0x02, _) => Err(DecodeError::InvalidValue(byte)),
+            _ => Err(DecodeError::UnsupportedType(eep_type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_f605_telegrams_then_decode_detector_state() {
+        assert_eq!(DetectorState::decode(&erp1(&[0xf6, 0x10, 1, 2, 3, 4, 0x30]), 0x00).unwrap(), DetectorState::Alarm);
+        assert_eq!(DetectorState::decode(&erp1(&[0xf6, 0x30, 1, 2, 3, 4, 0x30]), 0x02).unwrap(), DetectorState::EnergyLow);
+        assert_eq!(DetectorState::decode(&erp1(&[0xf6, 0x00, 1, 2, 3, 4, 0x20]), 0x02).unwrap(), DetectorState::NoAlarm);
+        assert_eq!(DetectorState::decode(&erp1(&[0xf6, 0x11, 1, 2, 3, 4, 0x30]), 0x01).unwrap(), DetectorState::Alarm);
+        assert_eq!(DetectorState::decode(&erp1(&[0xf6, 0x00, 1, 2, 3, 4, 0x20]), 0x01).unwrap(), DetectorState::NoAlarm);
+        assert!(DetectorState::decode(&erp1(&[0xf6, 0x11, 1, 2, 3, 4, 0x30]), 0x02).is_err());
+    }
+}
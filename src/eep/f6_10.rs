@@ -0,0 +1,75 @@
+//! F6-10 : Mechanical handle
+//!
+//! Window handles report their position in the upper nibble of the single data byte:
+//!
+//! | DB0.7..DB0.4 | Position             |
+//! |--------------|----------------------|
+//! | 0b11x0       | Horizontal (open)    |
+//! | 0b1101       | Up (tilted)          |
+//! | 0b1111       | Down (closed)        |
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::eep::f6_10::HandlePosition;
+//!
+//! let data = [0xf6, 0xf0, 0x01, 0x92, 0x3d, 0xa8, 0x20];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! assert_eq!(HandlePosition::decode(&erp)?, HandlePosition::Closed);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use super::{check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Position of a window handle (F6-10-00, F6-10-01)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum HandlePosition {
+    /// Handle pointing down
+    Closed,
+    /// Handle horizontal
+    Open,
+    /// Handle pointing up
+    Tilted,
+}
+
+impl HandlePosition {
+    /// Decode the handle position from an RPS telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Rps, 1)?;
+        Self::from_byte(erp.user_data[0])
+    }
+
+    /// Decode the handle position from the raw data byte
+    pub fn from_byte(byte: u8) -> Result<Self, DecodeError> {
+        match byte >> 4 {
+            0b1100 | 0b1110 => Ok(Self::Open),
+            0b1101 => Ok(Self::Tilted),
+            0b1111 => Ok(Self::Closed),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_f610_telegrams_then_decode_handle_position() {
+        assert_eq!(HandlePosition::decode(&erp1(&[0xf6, 0xc0, 1, 2, 3, 4, 0x20])).unwrap(), HandlePosition::Open);
+        assert_eq!(HandlePosition::decode(&erp1(&[0xf6, 0xe0, 1, 2, 3, 4, 0x20])).unwrap(), HandlePosition::Open);
+        assert_eq!(HandlePosition::decode(&erp1(&[0xf6, 0xd0, 1, 2, 3, 4, 0x20])).unwrap(), HandlePosition::Tilted);
+        assert_eq!(HandlePosition::decode(&erp1(&[0xf6, 0xf0, 1, 2, 3, 4, 0x20])).unwrap(), HandlePosition::Closed);
+    }
+
+    #[test]
+    fn given_invalid_f610_telegram_then_return_error() {
+        assert!(matches!(HandlePosition::decode(&erp1(&[0xf6, 0x30, 1, 2, 3, 4, 0x20])), Err(DecodeError::InvalidValue(0x30))));
+        assert!(matches!(HandlePosition::decode(&erp1(&[0xd5, 0x09, 1, 2, 3, 4, 0x00])), Err(DecodeError::WrongRorg(Rorg::Bs1))));
+    }
+}
@@ -0,0 +1,188 @@
+//! Generic bit-field extraction and linear scaling
+//!
+//! These are the building blocks of the profile decoders of this crate. They are public
+//! so that profiles the crate does not support yet can be decoded directly from the
+//! EEP specification tables.
+//!
+//! Offsets are counted in bits from the MSB of the first user data byte, as in the
+//! specification, where `DB3.7` of a 4BS telegram is bit 0 and `DB0.0` is bit 31.
+//!
+//! ```
+//! use enocean::eep::field::DataField;
+//!
+//! // A5-02-05 temperature: DB1, 255..0 → 0..40 °C
+//! const TEMPERATURE: DataField = DataField::new(16, 8).scaled((255, 0), (0.0, 40.0), "°C");
+//!
+//! let user_data = [0x00, 0x00, 0x80, 0x08];
+//! let value = TEMPERATURE.value(&user_data)?;
+//! assert!((value - 19.92).abs() < 0.01);
+//! assert_eq!(TEMPERATURE.unit, "°C");
+//! # Ok::<(), enocean::eep::DecodeError>(())
+//! ```
+//!
+//! Fields are written back the same way, to build telegrams from physical values:
+//!
+//! ```
+//! use enocean::eep::field::{encode_4bs, DataField};
+//!
+//! const TEMPERATURE: DataField = DataField::new(16, 8).scaled((255, 0), (0.0, 40.0), "°C");
+//!
+//! assert_eq!(encode_4bs(&[(TEMPERATURE, 19.92)])?, [0x00, 0x00, 0x80, 0x08]);
+//! # Ok::<(), enocean::eep::DecodeError>(())
+//! ```
+
+use super::DecodeError;
+
+/// A field of the user data, with the linear mapping from its raw value to a physical value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataField {
+    /// Position of the first bit, from the MSB of the first byte
+    pub offset_bits: usize,
+    pub size_bits: usize,
+    /// Raw values mapped to the ends of `scale`. The first may be greater than the second.
+    pub range: (u32, u32),
+    /// Physical values corresponding to the ends of `range`
+    pub scale: (f32, f32),
+    pub unit: &'static str,
+}
+
+impl DataField {
+    /// A raw field, whose value is its unscaled raw value
+    pub const fn new(offset_bits: usize, size_bits: usize) -> Self {
+        let max = if size_bits >= 32 { u32::MAX } else { (1 << size_bits) - 1 };
+        Self { offset_bits, size_bits, range: (0, max), scale: (0.0, max as f32), unit: "" }
+    }
+
+    /// Set the linear mapping and unit of the field
+    pub const fn scaled(self, range: (u32, u32), scale: (f32, f32), unit: &'static str) -> Self {
+        Self { range, scale, unit, ..self }
+    }
+
+    /// Extract the raw value of the field
+    pub fn raw(&self, data: &[u8]) -> Result<u32, DecodeError> {
+        if self.size_bits > 32 || (self.offset_bits + self.size_bits).div_ceil(8) > data.len() {
+            return Err(DecodeError::BadLength(data.len()));
+        }
+        Ok(bits(data, self.offset_bits, self.size_bits))
+    }
+
+    /// Extract the field and map it to its physical value. Raw values outside `range` are clamped.
+    pub fn value(&self, data: &[u8]) -> Result<f32, DecodeError> {
+        let (lo, hi) = self.range;
+        Ok(self.scale_raw(self.raw(data)?.clamp(lo.min(hi), lo.max(hi))))
+    }
+
+    /// Map a raw value to its physical value, extrapolating the mapping outside `range`
+    pub fn scale_raw(&self, raw: u32) -> f32 {
+        scale(raw as f32, self.range.0 as f32, self.range.1 as f32, self.scale.0, self.scale.1)
+    }
+
+    /// Write the raw value of the field, truncated to its size
+    pub fn write_raw(&self, data: &mut [u8], raw: u32) -> Result<(), DecodeError> {
+        if self.size_bits > 32 || (self.offset_bits + self.size_bits).div_ceil(8) > data.len() {
+            return Err(DecodeError::BadLength(data.len()));
+        }
+        set_bits(data, self.offset_bits, self.size_bits, raw);
+        Ok(())
+    }
+
+    /// Map a physical value to the nearest raw value, and write it. Values outside `scale`
+    /// are clamped.
+    pub fn write_value(&self, data: &mut [u8], value: f32) -> Result<(), DecodeError> {
+        let (lo, hi) = self.range;
+        let raw = scale(value, self.scale.0, self.scale.1, lo as f32, hi as f32)
+            .clamp(lo.min(hi) as f32, lo.max(hi) as f32);
+        self.write_raw(data, (raw + 0.5) as u32)
+    }
+}
+
+/// LRN bit of 4BS telegrams (DB0.3): set on data telegrams, clear on teach-in telegrams
+pub const LRN_4BS: DataField = DataField::new(28, 1);
+
+/// Build the 4 data bytes of a 4BS data telegram from the values of its fields. The LRN bit
+/// is set, the bits of no field are left to 0.
+pub fn encode_4bs(values: &[(DataField, f32)]) -> Result<[u8; 4], DecodeError> {
+    let mut data = [0; 4];
+    for (field, value) in values {
+        field.write_value(&mut data, *value)?;
+    }
+    LRN_4BS.write_raw(&mut data, 1)?;
+    Ok(data)
+}
+
+/// Extract `size` bits starting at bit `offset`, counted from the MSB of the first byte.
+///
+/// Panics if the field is out of `data`; see [`DataField::raw`] for a checked version.
+pub fn bits(data: &[u8], offset: usize, size: usize) -> u32 {
+    (offset..offset + size).fold(0, |acc, i| acc << 1 | ((data[i / 8] >> (7 - i % 8)) & 1) as u32)
+}
+
+/// Write the low `size` bits of `value` starting at bit `offset`, counted from the MSB of the
+/// first byte.
+///
+/// Panics if the field is out of `data`; see [`DataField::write_raw`] for a checked version.
+pub fn set_bits(data: &mut [u8], offset: usize, size: usize, value: u32) {
+    for i in offset..offset + size {
+        let bit = (value >> (offset + size - 1 - i)) & 1;
+        let mask = 1 << (7 - i % 8);
+        data[i / 8] = if bit != 0 { data[i / 8] | mask } else { data[i / 8] & !mask };
+    }
+}
+
+/// Linear mapping of a raw value in 0..=raw_max to min..=max
+pub fn linear(raw: u16, raw_max: u16, min: f32, max: f32) -> f32 {
+    scale(raw as f32, 0.0, raw_max as f32, min, max)
+}
+
+/// Linear mapping of `raw` from raw_min..=raw_max to min..=max. An empty raw range maps
+/// everything to `min`.
+pub fn scale(raw: f32, raw_min: f32, raw_max: f32, min: f32, max: f32) -> f32 {
+    if raw_max == raw_min {
+        return min;
+    }
+    min + (raw - raw_min) * (max - min) / (raw_max - raw_min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_unaligned_field_then_extract_and_scale_it() {
+        // 10 bits straddling two bytes: 0b11_1111_1111 = 1023
+        let field = DataField::new(6, 10).scaled((0, 1023), (-10.0, 10.0), "V");
+        assert_eq!(field.raw(&[0x03, 0xff]).unwrap(), 1023);
+        assert_eq!(field.value(&[0x03, 0xff]).unwrap(), 10.0);
+        assert_eq!(DataField::new(4, 4).value(&[0x0a]).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn given_field_out_of_data_then_return_bad_length() {
+        assert!(matches!(DataField::new(12, 8).raw(&[0, 0]), Err(DecodeError::BadLength(2))));
+        assert!(matches!(DataField::new(12, 8).write_raw(&mut [0, 0], 1), Err(DecodeError::BadLength(2))));
+    }
+
+    #[test]
+    fn given_values_then_write_fields_back() {
+        let field = DataField::new(6, 10).scaled((0, 1023), (-10.0, 10.0), "V");
+        let mut data = [0xfc, 0x00];
+        field.write_value(&mut data, 10.0).unwrap();
+        assert_eq!(data, [0xff, 0xff]);
+        field.write_value(&mut data, -20.0).unwrap();
+        assert_eq!(data, [0xfc, 0x00]);
+
+        let inverted = DataField::new(16, 8).scaled((255, 0), (0.0, 40.0), "°C");
+        assert_eq!(encode_4bs(&[(inverted, 0.0)]).unwrap(), [0x00, 0x00, 0xff, 0x08]);
+        assert!((inverted.value(&encode_4bs(&[(inverted, 21.5)]).unwrap()).unwrap() - 21.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn given_degenerate_mapping_then_never_produce_nan() {
+        let constant = DataField::new(0, 8).scaled((10, 10), (0.0, 100.0), "%");
+        assert_eq!(constant.value(&[0x42]).unwrap(), 0.0);
+        let flat = DataField::new(0, 8).scaled((0, 255), (5.0, 5.0), "%");
+        let mut data = [0xff];
+        flat.write_value(&mut data, 5.0).unwrap();
+        assert_eq!(data, [0x00]);
+    }
+}
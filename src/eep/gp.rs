@@ -0,0 +1,290 @@
+//! Generic Profiles (GP, RORG B0..B3)
+//!
+//! Instead of referring to a profile of the specification, a GP device describes its data
+//! channels in its teach-in request (GP_TI, RORG B0). The data telegrams are then decoded
+//! with these descriptions: complete data (GP_CD, RORG B2) carries all the channels in
+//! order, selective data (GP_SD, RORG B3) only some of them.
+//!
+//! Teach-in request bit stream:
+//!
+//! | Bits | Field                                                                 |
+//! |------|-----------------------------------------------------------------------|
+//! | 11   | Manufacturer ID                                                       |
+//! | 1    | Direction (0 = inbound, 1 = outbound)                                 |
+//! | 2    | Purpose (0 = teach-in, 1 = teach-out)                                 |
+//! | 2    | Reserved                                                              |
+//! | ...  | Channels: type (2), signal type (8), value type (2), then per type:   |
+//! |      | data: resolution (4), engineering min (8), scaling min (4),           |
+//! |      |       engineering max (8), scaling max (4)                            |
+//! |      | enumeration: resolution (4)                                           |
+//! |      | flag: nothing                                                         |
+//! |      | teach-in information: nothing, the channel is skipped                 |
+//!
+//! Selective data starts with the number of channels (4 bits), then for each channel its
+//! index (6 bits) and its value.
+
+use alloc::vec::Vec;
+
+use super::field::{bits, scale};
+use super::DecodeError;
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+/// Bits per value, by resolution code
+const RESOLUTIONS: [usize; 13] = [0, 2, 3, 4, 5, 6, 8, 10, 12, 16, 20, 24, 32];
+
+/// Scaling factors, by scaling code
+const SCALINGS: [f32; 13] = [0.0, 1.0, 10.0, 100.0, 1e3, 1e4, 1e5, 1e6, 0.1, 0.01, 0.001, 1e-6, 1e-9];
+
+/// What a channel value means
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ValueType {
+    Reserved,
+    Current,
+    SetPointAbsolute,
+    SetPointRelative,
+}
+
+/// Kind of a channel, with what is needed to decode its values
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum ChannelKind {
+    /// Raw values in 0..2^bits are mapped linearly to min..=max
+    Data { bits: usize, min: f32, max: f32 },
+    Flag,
+    Enumeration { bits: usize },
+}
+
+/// A channel described in a teach-in request
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Channel {
+    pub kind: ChannelKind,
+    /// Signal type code, see the GP specification
+    pub signal_type: u8,
+    pub value_type: ValueType,
+}
+
+impl Channel {
+    /// Size of the channel values, in bits
+    pub fn size_bits(&self) -> usize {
+        match self.kind {
+            ChannelKind::Data { bits, .. } | ChannelKind::Enumeration { bits } => bits,
+            ChannelKind::Flag => 1,
+        }
+    }
+
+    fn value(&self, raw: u32) -> Value {
+        match self.kind {
+            ChannelKind::Data { bits, min, max } => {
+                let raw_max = if bits == 32 { u32::MAX } else { (1 << bits) - 1 };
+                Value::Data(scale(raw as f32, 0.0, raw_max as f32, min, max))
+            }
+            ChannelKind::Flag => Value::Flag(raw != 0),
+            ChannelKind::Enumeration { .. } => Value::Enumeration(raw),
+        }
+    }
+}
+
+/// A decoded channel value
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Value {
+    Data(f32),
+    Flag(bool),
+    Enumeration(u32),
+}
+
+/// A value along with the channel it was read from
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ChannelValue {
+    pub index: usize,
+    pub signal_type: u8,
+    pub value: Value,
+}
+
+/// Description of a GP device, from its teach-in request
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct GenericProfile {
+    pub manufacturer: u16,
+    pub outbound: bool,
+    pub teach_out: bool,
+    pub channels: Vec<Channel>,
+}
+
+impl GenericProfile {
+    /// Decode a GP teach-in request
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::GpTi {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        let total = d.len() * 8;
+        if total < 16 {
+            return Err(DecodeError::BadLength(d.len()));
+        }
+
+        let mut channels = Vec::new();
+        let mut offset = 16;
+        // Anything shorter than a flag channel is padding
+        while offset + 12 <= total {
+            let kind = bits(d, offset, 2);
+            let signal_type = bits(d, offset + 2, 8) as u8;
+            let value_type = match bits(d, offset + 10, 2) {
+                1 => ValueType::Current,
+                2 => ValueType::SetPointAbsolute,
+                3 => ValueType::SetPointRelative,
+                _ => ValueType::Reserved,
+            };
+            offset += 12;
+
+            let field_size = match kind {
+                1 => 28,
+                3 => 4,
+                _ => 0,
+            };
+            if offset + field_size > total {
+                return Err(DecodeError::BadLength(d.len()));
+            }
+            let resolution = || {
+                let code = bits(d, offset, 4) as u8;
+                RESOLUTIONS.get(code as usize).copied().filter(|bits| *bits > 0).ok_or(DecodeError::InvalidValue(code))
+            };
+            let engineering = |at: usize| -> Result<f32, DecodeError> {
+                let code = bits(d, at + 8, 4) as u8;
+                let factor = SCALINGS.get(code as usize).copied().filter(|f| *f > 0.0).ok_or(DecodeError::InvalidValue(code))?;
+                Ok(bits(d, at, 8) as u8 as i8 as f32 * factor)
+            };
+            let kind = match kind {
+                1 => ChannelKind::Data { bits: resolution()?, min: engineering(offset + 4)?, max: engineering(offset + 16)? },
+                2 => ChannelKind::Flag,
+                3 => ChannelKind::Enumeration { bits: resolution()? },
+                // Teach-in information channels carry nothing for data telegrams: skip their
+                // header, the next channel follows
+                _ => continue,
+            };
+            offset += field_size;
+            channels.push(Channel { kind, signal_type, value_type });
+        }
+
+        Ok(Self {
+            manufacturer: bits(d, 0, 11) as u16,
+            outbound: bits(d, 11, 1) != 0,
+            teach_out: bits(d, 12, 2) == 1,
+            channels,
+        })
+    }
+
+    /// Decode a complete (GP_CD) or selective (GP_SD) data telegram of the device
+    pub fn decode_data(&self, erp: &RadioErp1) -> Result<Vec<ChannelValue>, DecodeError> {
+        let d = &erp.user_data[..];
+        let total = d.len() * 8;
+        let mut values = Vec::new();
+        let mut read = |index: usize, offset: usize| -> Result<usize, DecodeError> {
+            let channel = self.channels.get(index).ok_or(DecodeError::InvalidValue(index as u8))?;
+            let size = channel.size_bits();
+            if offset + size > total {
+                return Err(DecodeError::BadLength(d.len()));
+            }
+            values.push(ChannelValue { index, signal_type: channel.signal_type, value: channel.value(bits(d, offset, size)) });
+            Ok(offset + size)
+        };
+
+        match erp.choice {
+            Rorg::GpCd => {
+                let mut offset = 0;
+                for index in 0..self.channels.len() {
+                    offset = read(index, offset)?;
+                }
+            }
+            Rorg::GpSd => {
+                if d.is_empty() {
+                    return Err(DecodeError::BadLength(0));
+                }
+                let mut offset = 4;
+                for _ in 0..bits(d, 0, 4) {
+                    if offset + 6 > total {
+                        return Err(DecodeError::BadLength(d.len()));
+                    }
+                    let index = bits(d, offset, 6) as usize;
+                    offset = read(index, offset + 6)?;
+                }
+            }
+            rorg => return Err(DecodeError::WrongRorg(rorg)),
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    /// Util : pack (value, size in bits) pairs, MSB first
+    fn pack(fields: &[(u32, usize)]) -> Vec<u8> {
+        let total: usize = fields.iter().map(|(_, size)| size).sum();
+        let mut data = vec![0u8; total.div_ceil(8)];
+        let mut offset = 0;
+        for &(value, size) in fields {
+            for bit in 0..size {
+                if value >> (size - 1 - bit) & 1 != 0 {
+                    data[(offset + bit) / 8] |= 0x80 >> ((offset + bit) % 8);
+                }
+            }
+            offset += size;
+        }
+        data
+    }
+
+    fn telegram(rorg: u8, data: &[u8]) -> Vec<u8> {
+        [&[rorg], data, &[0x01, 0x02, 0x03, 0x04, 0x00]].concat()
+    }
+
+    #[test]
+    fn given_teach_in_then_decode_data_telegrams() {
+        // Eltako, inbound teach-in: a 10-bit data channel 0..100 (x1), and a flag
+        let teach_in = pack(&[
+            (0x00d, 11), (0, 1), (0, 2), (0, 2),
+            (1, 2), (0x06, 8), (1, 2), (7, 4), (0, 8), (1, 4), (100, 8), (1, 4),
+            (2, 2), (0x30, 8), (1, 2),
+        ]);
+        let profile = GenericProfile::decode(&erp1(&telegram(0xb0, &teach_in))).unwrap();
+        assert_eq!(profile.manufacturer, 0x00d);
+        assert_eq!(profile.channels.len(), 2);
+        assert_eq!(profile.channels[0].kind, ChannelKind::Data { bits: 10, min: 0.0, max: 100.0 });
+        assert_eq!(profile.channels[1].kind, ChannelKind::Flag);
+
+        let complete = pack(&[(1023, 10), (1, 1)]);
+        let values = profile.decode_data(&erp1(&telegram(0xb2, &complete))).unwrap();
+        assert_eq!(values[0].value, Value::Data(100.0));
+        assert_eq!(values[1].value, Value::Flag(true));
+
+        let selective = pack(&[(1, 4), (1, 6), (0, 1)]);
+        let values = profile.decode_data(&erp1(&telegram(0xb3, &selective))).unwrap();
+        assert_eq!(values, vec![ChannelValue { index: 1, signal_type: 0x30, value: Value::Flag(false) }]);
+    }
+
+    #[test]
+    fn given_teach_in_information_channel_then_decode_the_next_channels() {
+        let teach_in = pack(&[
+            (0x00d, 11), (0, 1), (0, 2), (0, 2),
+            (0, 2), (0x01, 8), (0, 2),
+            (2, 2), (0x30, 8), (1, 2),
+            (3, 2), (0x40, 8), (1, 2), (3, 4),
+        ]);
+        let profile = GenericProfile::decode(&erp1(&telegram(0xb0, &teach_in))).unwrap();
+        assert_eq!(profile.channels.iter().map(|channel| channel.kind).collect::<Vec<_>>(), vec![
+            ChannelKind::Flag, ChannelKind::Enumeration { bits: 4 },
+        ]);
+    }
+}
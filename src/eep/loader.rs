@@ -0,0 +1,150 @@
+//! Runtime EEP definitions (feature `loader`)
+//!
+//! Loads profile definitions from JSON and decodes telegrams generically through
+//! [`DataField`], for profiles without a dedicated decoder in this crate. The format
+//! follows the structure of the EEP specification tables; the published EEP XML can be
+//! converted to it field by field.
+//!
+//! ```json
+//! [
+//!   {
+//!     "eep": "A5-02-05",
+//!     "title": "Temperature Sensor Range 0°C to +40°C",
+//!     "fields": [
+//!       { "shortcut": "TMP", "description": "Temperature", "offset": 16, "size": 8,
+//!         "range": [255, 0], "scale": [0.0, 40.0], "unit": "°C" }
+//!     ]
+//!   }
+//! ]
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::field::DataField;
+use super::DecodeError;
+use crate::packet::{EEPProfileCode, RadioErp1};
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Invalid JSON")]            Json(#[from] serde_json::Error),
+    #[error("Invalid profile code")]    ProfileCode(String),
+    #[error("Empty raw range")]         EmptyRange(String),
+}
+
+/// A field of a profile definition
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldDefinition {
+    pub shortcut: String,
+    #[serde(default)]
+    pub description: String,
+    pub offset: usize,
+    pub size: usize,
+    /// Raw range, defaults to the full range of the field
+    pub range: Option<(u32, u32)>,
+    /// Physical range, defaults to the raw range
+    pub scale: Option<(f32, f32)>,
+    #[serde(default)]
+    pub unit: String,
+}
+
+impl FieldDefinition {
+    /// The bit-field engine view of this field. The unit stays in the definition.
+    pub fn data_field(&self) -> DataField {
+        let field = DataField::new(self.offset, self.size);
+        let range = self.range.unwrap_or(field.range);
+        let scale = self.scale.unwrap_or((range.0 as f32, range.1 as f32));
+        field.scaled(range, scale, "")
+    }
+}
+
+/// A profile definition
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileDefinition {
+    /// Profile code, as `A5-02-05`
+    pub eep: String,
+    #[serde(default)]
+    pub title: String,
+    pub fields: Vec<FieldDefinition>,
+}
+
+/// A field decoded from a telegram
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldValue<'a> {
+    pub shortcut: &'a str,
+    pub value: f32,
+    pub unit: &'a str,
+}
+
+/// A set of profile definitions, used as a generic decoder
+#[derive(Debug, Clone, Default)]
+pub struct Definitions {
+    profiles: HashMap<EEPProfileCode, ProfileDefinition>,
+}
+
+impl Definitions {
+    /// Load a JSON array of profile definitions. Later definitions replace earlier ones.
+    pub fn from_json(json: &str) -> Result<Self, LoadError> {
+        let mut definitions = Self::default();
+        definitions.extend_from_json(json)?;
+        Ok(definitions)
+    }
+
+    /// Add the profile definitions of a JSON array. Nothing is added if one of them is
+    /// invalid, e.g. has a field whose raw range bounds are equal.
+    pub fn extend_from_json(&mut self, json: &str) -> Result<(), LoadError> {
+        let profiles: Vec<ProfileDefinition> = serde_json::from_str(json)?;
+        let profiles = profiles.into_iter()
+            .map(|profile| {
+                let eep = profile.eep.parse().map_err(|_| LoadError::ProfileCode(profile.eep.clone()))?;
+                if let Some(field) = profile.fields.iter().find(|field| field.range.is_some_and(|(lo, hi)| lo == hi)) {
+                    return Err(LoadError::EmptyRange(field.shortcut.clone()));
+                }
+                Ok((eep, profile))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.profiles.extend(profiles);
+        Ok(())
+    }
+
+    pub fn get(&self, eep: EEPProfileCode) -> Option<&ProfileDefinition> {
+        self.profiles.get(&eep)
+    }
+
+    /// Decode all the fields of a telegram with the definition of its profile
+    pub fn decode(&self, eep: EEPProfileCode, erp: &RadioErp1) -> Result<Vec<FieldValue<'_>>, DecodeError> {
+        let profile = self.get(eep).ok_or(DecodeError::UnsupportedProfile(eep))?;
+        if u8::from(erp.choice) != eep.rorg() {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        profile.fields.iter()
+            .map(|field| field.data_field().value(&erp.user_data)
+                .map(|value| FieldValue { shortcut: &field.shortcut, value, unit: &field.unit }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_json_definition_then_decode_telegram_generically() {
+        let definitions = Definitions::from_json(r#"[{
+            "eep": "A5-02-05",
+            "fields": [{ "shortcut": "TMP", "offset": 16, "size": 8, "range": [255, 0], "scale": [0.0, 40.0], "unit": "°C" }]
+        }]"#).unwrap();
+
+        let values = definitions.decode("A5-02-05".parse().unwrap(), &erp1(&[0xa5, 0, 0, 0, 0x08, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(values, vec![FieldValue { shortcut: "TMP", value: 40.0, unit: "°C" }]);
+    }
+
+    #[test]
+    fn given_empty_raw_range_then_reject_definition() {
+        let json = r#"[{ "eep": "A5-02-05", "fields": [{ "shortcut": "TMP", "offset": 16, "size": 8, "range": [0, 0] }] }]"#;
+        assert!(matches!(Definitions::from_json(json), Err(LoadError::EmptyRange(shortcut)) if shortcut == "TMP"));
+    }
+}
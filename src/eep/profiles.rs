@@ -0,0 +1,152 @@
+//! Profile decoders provided by applications
+//!
+//! The typed decoders of this crate cover the published profiles. Proprietary or
+//! pre-release profiles are decoded by [`Profile`] implementations that applications
+//! register in a [`ProfileDecoders`] table, for a profile code or for all the types of a
+//! RORG and FUNC. A table of [`Decoded`] values decodes the telegrams received by a
+//! gateway, see `device::Gateway::with_profiles`. The table produces values of an
+//! application defined type `T`; when `T` is built from [`Decoded`], it falls back to the
+//! built-in decoders:
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::eep::{DecodeError, Decoded};
+//! use enocean::eep::profiles::ProfileDecoders;
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Values {
+//!     Builtin(Decoded),
+//!     Flow(u8),
+//! }
+//!
+//! impl From<Decoded> for Values {
+//!     fn from(decoded: Decoded) -> Self { Values::Builtin(decoded) }
+//! }
+//!
+//! let mut decoders = ProfileDecoders::with_builtin();
+//! decoders.register("A5-FF-01".parse()?, |_, erp: &RadioErp1| {
+//!     erp.user_data.first().map(|flow| Values::Flow(*flow)).ok_or(DecodeError::BadLength(0))
+//! });
+//!
+//! let data = [0xa5, 0x2a, 0x00, 0x00, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! assert_eq!(decoders.decode("A5-FF-01".parse()?, &erp)?, Values::Flow(0x2a));
+//! assert!(matches!(decoders.decode("A5-02-05".parse()?, &erp)?, Values::Builtin(Decoded::Temperature(_))));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+use super::{decode, DecodeError, Decoded};
+use crate::packet::{EEPProfileCode, RadioErp1};
+
+/// A profile decoder, producing values of type `T`. Implemented by closures.
+pub trait Profile<T>: Send + Sync {
+    /// Decode a telegram of the profile `eep`
+    fn decode(&self, eep: EEPProfileCode, erp: &RadioErp1) -> Result<T, DecodeError>;
+}
+
+impl<T, F> Profile<T> for F
+where
+    F: Fn(EEPProfileCode, &RadioErp1) -> Result<T, DecodeError> + Send + Sync,
+{
+    fn decode(&self, eep: EEPProfileCode, erp: &RadioErp1) -> Result<T, DecodeError> {
+        self(eep, erp)
+    }
+}
+
+type Builtin<T> = fn(EEPProfileCode, &RadioErp1) -> Result<T, DecodeError>;
+
+/// Registered profile decoders, by profile code or by RORG and FUNC
+pub struct ProfileDecoders<T> {
+    profiles: BTreeMap<EEPProfileCode, Box<dyn Profile<T>>>,
+    families: BTreeMap<(u8, u8), Box<dyn Profile<T>>>,
+    builtin: Option<Builtin<T>>,
+}
+
+impl<T> Default for ProfileDecoders<T> {
+    fn default() -> Self {
+        Self { profiles: BTreeMap::new(), families: BTreeMap::new(), builtin: None }
+    }
+}
+
+impl<T> fmt::Debug for ProfileDecoders<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfileDecoders")
+            .field("profiles", &self.profiles.keys())
+            .field("families", &self.families.keys())
+            .field("builtin", &self.builtin.is_some())
+            .finish()
+    }
+}
+
+impl<T> ProfileDecoders<T> {
+    /// A table without any decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A table falling back to the built-in decoders, see [`super::decode`]
+    pub fn with_builtin() -> Self
+    where
+        T: From<Decoded>,
+    {
+        Self { builtin: Some(|eep, erp| decode(eep, erp).map(T::from)), ..Self::default() }
+    }
+
+    /// Register the decoder of a profile, replacing any previous one
+    pub fn register(&mut self, eep: EEPProfileCode, profile: impl Profile<T> + 'static) {
+        self.profiles.insert(eep, Box::new(profile));
+    }
+
+    /// Register the decoder of all the types of a RORG and FUNC, replacing any previous one.
+    /// Decoders registered for a profile code take precedence.
+    pub fn register_family(&mut self, rorg: u8, func: u8, profile: impl Profile<T> + 'static) {
+        self.families.insert((rorg, func), Box::new(profile));
+    }
+
+    pub fn unregister(&mut self, eep: EEPProfileCode) -> bool {
+        self.profiles.remove(&eep).is_some()
+    }
+
+    /// Whether an application decoder is registered for `eep`, built-in decoders aside
+    pub fn is_registered(&self, eep: EEPProfileCode) -> bool {
+        self.profiles.contains_key(&eep) || self.families.contains_key(&(eep.rorg(), eep.func()))
+    }
+
+    /// Decode a telegram with the decoder registered for `eep`, or the built-in one
+    pub fn decode(&self, eep: EEPProfileCode, erp: &RadioErp1) -> Result<T, DecodeError> {
+        let registered = self.profiles.get(&eep).or_else(|| self.families.get(&(eep.rorg(), eep.func())));
+        match (registered, self.builtin) {
+            (Some(profile), _) => profile.decode(eep, erp),
+            (None, Some(builtin)) => builtin(eep, erp),
+            (None, None) => Err(DecodeError::UnsupportedProfile(eep)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_registered_profiles_then_take_precedence_over_builtin() {
+        let erp = erp1(&[0xa5, 0x00, 0x00, 0x80, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00]);
+        let decoders: ProfileDecoders<Decoded> = ProfileDecoders::new();
+        assert!(matches!(decoders.decode("A5-02-05".parse().unwrap(), &erp), Err(DecodeError::UnsupportedProfile(_))));
+
+        let mut decoders = ProfileDecoders::with_builtin();
+        assert!(matches!(decoders.decode("A5-02-05".parse().unwrap(), &erp), Ok(Decoded::Temperature(_))));
+        decoders.register_family(0xA5, 0x02, |_, _: &RadioErp1| Err(DecodeError::InvalidValue(0)));
+        decoders.register("A5-02-05".parse().unwrap(), |eep: EEPProfileCode, erp: &RadioErp1| decode(eep, erp));
+        assert!(decoders.is_registered("A5-02-01".parse().unwrap()));
+        assert!(matches!(decoders.decode("A5-02-05".parse().unwrap(), &erp), Ok(Decoded::Temperature(_))));
+        assert!(matches!(decoders.decode("A5-02-01".parse().unwrap(), &erp), Err(DecodeError::InvalidValue(0))));
+        assert!(decoders.unregister("A5-02-05".parse().unwrap()));
+        assert!(matches!(decoders.decode("A5-02-05".parse().unwrap(), &erp), Err(DecodeError::InvalidValue(0))));
+    }
+}
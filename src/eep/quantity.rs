@@ -0,0 +1,68 @@
+//! Dimensioned values returned by the profile decoders
+//!
+//! Each quantity wraps an `f32` in a fixed unit, so that values of different kinds
+//! cannot be mixed up:
+//!
+//! ```
+//! use enocean::eep::quantity::Temperature;
+//!
+//! let t = Temperature(21.5);
+//! assert_eq!(t.to_string(), "21.5 °C");
+//! assert_eq!(f32::from(t), 21.5);
+//! assert_eq!(Temperature::UNIT, "°C");
+//! ```
+
+use core::fmt::Display;
+
+macro_rules! quantity {
+    ($(#[$doc:meta])* $name:ident, $unit:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+        #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+        pub struct $name(pub f32);
+
+        impl $name {
+            pub const UNIT: &'static str = $unit;
+
+            /// The value, in [`Self::UNIT`]
+            pub fn value(self) -> f32 { self.0 }
+        }
+
+        impl From<$name> for f32 {
+            fn from(quantity: $name) -> f32 { quantity.0 }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{} {}", self.0, Self::UNIT)
+            }
+        }
+
+        /// Serialized as `{"value": 21.5, "unit": "°C"}`
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStruct;
+                let mut s = serializer.serialize_struct(stringify!($name), 2)?;
+                s.serialize_field("value", &self.0)?;
+                s.serialize_field("unit", Self::UNIT)?;
+                s.end()
+            }
+        }
+    };
+}
+
+quantity!(/// Temperature, in °C
+          Temperature, "°C");
+quantity!(/// Relative humidity, in %
+          RelativeHumidity, "%");
+quantity!(/// Illuminance, in lx
+          Illuminance, "lx");
+quantity!(/// Electric potential, in V
+          Voltage, "V");
+quantity!(/// Speed, in m/s
+          Speed, "m/s");
+quantity!(/// Power, in W
+          Power, "W");
+quantity!(/// Energy, in Wh
+          Energy, "Wh");
@@ -0,0 +1,154 @@
+//! Tables of the built-in profiles
+//!
+//! The profiles decoded by [`decode`](super::decode) are listed in [`BUILTIN`], by RORG and
+//! FUNC with their supported types, so that tools can tell which devices are supported
+//! without decoding a telegram. The families with a linear scaling per type keep it in a
+//! `const` table of [`DataField`]s, e.g. [`a5_02::FIELDS`](super::a5_02::FIELDS), built at
+//! compile time and read by their decoders; [`scaled_fields`] gathers them by profile.
+//! Nothing here allocates, so the tables are available without `std`.
+//!
+//! ```
+//! use enocean::eep::tables::{is_builtin, scaled_fields, BUILTIN};
+//!
+//! assert!(is_builtin("A5-02-05".parse()?));
+//! assert!(!is_builtin("A5-02-0C".parse()?));
+//! assert!(BUILTIN.iter().any(|family| family.title == "Room operating panels"));
+//!
+//! let (name, temperature) = scaled_fields("A5-02-05".parse()?).next().unwrap();
+//! assert_eq!((name, temperature.scale, temperature.unit), ("temperature", (0.0, 40.0), "°C"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use super::field::DataField;
+use super::{a5_02, a5_04, a5_08, a5_10};
+use crate::packet::EEPProfileCode;
+
+/// A family of built-in profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Family {
+    pub rorg: u8,
+    pub func: u8,
+    /// The types decoded
+    pub types: &'static [u8],
+    pub title: &'static str,
+}
+
+impl Family {
+    const fn new(rorg: u8, func: u8, types: &'static [u8], title: &'static str) -> Self {
+        Self { rorg, func, types, title }
+    }
+
+    pub fn contains(&self, eep: EEPProfileCode) -> bool {
+        (eep.rorg(), eep.func()) == (self.rorg, self.func) && self.types.contains(&eep.eep_type())
+    }
+
+    /// The profiles of the family
+    pub fn profiles(&self) -> impl Iterator<Item = EEPProfileCode> + '_ {
+        self.types.iter().map(|eep_type| EEPProfileCode::new(self.rorg, self.func, *eep_type))
+    }
+}
+
+/// The families decoded by [`decode`](super::decode)
+pub const BUILTIN: [Family; 25] = [
+    Family::new(0xA5, 0x02, &a5_02::TYPES, "Temperature sensors"),
+    Family::new(0xA5, 0x04, &[0x01, 0x02, 0x03], "Temperature and humidity sensors"),
+    Family::new(0xA5, 0x06, &[0x01, 0x02, 0x03], "Light sensors"),
+    Family::new(0xA5, 0x07, &[0x01, 0x02, 0x03], "Occupancy sensors"),
+    Family::new(0xA5, 0x08, &[0x01, 0x02, 0x03], "Light, temperature and occupancy sensors"),
+    Family::new(0xA5, 0x09, &[0x02, 0x04, 0x05, 0x08], "Gas sensors"),
+    Family::new(0xA5, 0x10, &a5_10::TYPES, "Room operating panels"),
+    Family::new(0xA5, 0x11, &[0x01, 0x02, 0x03, 0x04, 0x05], "Controller status"),
+    Family::new(0xA5, 0x13, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07], "Environmental applications"),
+    Family::new(0xA5, 0x14, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A], "Multi-function sensors"),
+    Family::new(0xA5, 0x20, &[0x01, 0x04], "HVAC components"),
+    Family::new(0xA5, 0x38, &[0x08], "Central command"),
+    Family::new(0xD2, 0x01, &[
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A,
+        0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14,
+    ], "Electronic switches and dimmers"),
+    Family::new(0xD2, 0x03, &[0x0A], "Light, switching and blind control"),
+    Family::new(0xD2, 0x05, &[0x00], "Blinds control for position and angle"),
+    Family::new(0xD2, 0x06, &[0x01], "Multisensor window handle"),
+    Family::new(0xD2, 0x14, &[0x30, 0x31, 0x40, 0x41], "Multi function sensors"),
+    Family::new(0xD2, 0x15, &[0x00], "People activity"),
+    Family::new(0xD2, 0x50, &[0x00, 0x01, 0x10, 0x11], "Heat recovery ventilation"),
+    Family::new(0xD5, 0x00, &[0x01], "Contacts and switches"),
+    Family::new(0xF6, 0x02, &[0x01, 0x02], "Light and blind control"),
+    Family::new(0xF6, 0x03, &[0x01, 0x02], "Light and blind control, four rockers"),
+    Family::new(0xF6, 0x04, &[0x01, 0x02], "Key card activated switch"),
+    Family::new(0xF6, 0x05, &[0x00, 0x01, 0x02], "Detectors"),
+    Family::new(0xF6, 0x10, &[0x00, 0x01], "Mechanical handle"),
+];
+
+/// Whether [`decode`](super::decode) decodes the telegrams of `eep`
+pub fn is_builtin(eep: EEPProfileCode) -> bool {
+    BUILTIN.iter().any(|family| family.contains(eep))
+}
+
+/// Every profile decoded by [`decode`](super::decode)
+pub fn builtin_profiles() -> impl Iterator<Item = EEPProfileCode> {
+    BUILTIN.iter().flat_map(Family::profiles)
+}
+
+/// The linearly scaled fields of a profile, by name. Empty for the profiles without a
+/// scaling table.
+pub fn scaled_fields(eep: EEPProfileCode) -> impl Iterator<Item = (&'static str, DataField)> {
+    let eep_type = eep.eep_type();
+    let fields = match (eep.rorg(), eep.func()) {
+        (0xA5, 0x02) => match a5_02::field(eep_type) {
+            Ok(temperature) => [Some(("temperature", temperature)), None, None],
+            Err(_) => [None; 3],
+        },
+        (0xA5, 0x04) => match a5_04::fields(eep_type) {
+            Ok(fields) => [Some(("humidity", fields.humidity)), Some(("temperature", fields.temperature)), None],
+            Err(_) => [None; 3],
+        },
+        (0xA5, 0x08) => match a5_08::fields(eep_type) {
+            Ok(fields) => [
+                Some(("supply_voltage", fields.supply_voltage)),
+                Some(("illuminance", fields.illuminance)),
+                Some(("temperature", fields.temperature)),
+            ],
+            Err(_) => [None; 3],
+        },
+        _ => [None; 3],
+    };
+    fields.into_iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+    use crate::eep::{decode, DecodeError};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn given_builtin_tables_then_match_the_decoders() {
+        let telegram = |rorg: u8| match rorg {
+            0xA5 => erp1(&[0xa5, 0x00, 0x00, 0x80, 0x08, 1, 2, 3, 4, 0x00]).into_owned(),
+            0xD2 => erp1(&[0xd2, 0x04, 0x01, 0x32, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 1, 2, 3, 4, 0x00]).into_owned(),
+            _ => erp1(&[rorg, 0x00, 1, 2, 3, 4, 0x30]).into_owned(),
+        };
+        let unsupported = |eep| matches!(decode(eep, &telegram(eep.rorg())), Err(DecodeError::UnsupportedProfile(_) | DecodeError::UnsupportedType(_)));
+
+        assert_eq!(builtin_profiles().count(), BUILTIN.iter().map(|family| family.types.len()).sum::<usize>());
+        for eep in builtin_profiles() {
+            assert!(!unsupported(eep), "{eep} listed but not decoded");
+        }
+        for family in &BUILTIN {
+            let eep = EEPProfileCode::new(family.rorg, family.func, 0x7F);
+            assert!(!is_builtin(eep) && unsupported(eep), "{eep} decoded but not listed");
+        }
+    }
+
+    #[test]
+    fn given_scaling_tables_then_decoders_use_them() {
+        assert_eq!(a5_02::TYPES.len(), a5_02::FIELDS.len());
+        assert_eq!(a5_02::FIELDS[23], (0x20, DataField::new(14, 10).scaled((1023, 0), (-10.0, 41.2), "°C")));
+        let names: Vec<_> = scaled_fields(EEPProfileCode::new(0xA5, 0x08, 0x03)).map(|(name, field)| (name, field.scale)).collect();
+        assert_eq!(names, [("supply_voltage", (0.0, 5.1)), ("illuminance", (0.0, 1530.0)), ("temperature", (-30.0, 50.0))]);
+        assert_eq!(scaled_fields(EEPProfileCode::new(0xA5, 0x04, 0x04)).count(), 0);
+        assert_eq!(scaled_fields(EEPProfileCode::new(0xD2, 0x01, 0x12)).count(), 0);
+    }
+}
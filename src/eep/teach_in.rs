@@ -0,0 +1,325 @@
+//! Teach-in telegrams
+//!
+//! Before a receiver can interpret the data telegrams of a device, it has to learn the
+//! profile of the device from its teach-in telegram. [`TeachIn::detect`] recognizes the
+//! teach-in telegrams of all kinds among received telegrams.
+//!
+//! 1BS teach-in telegrams only have their LRN bit (DB0.3) cleared. They carry no profile;
+//! 1BS devices are D5-00-01 contacts.
+//!
+//! 4BS teach-in telegrams have the LRN bit (DB0.3) cleared. With DB0.7 set, they carry
+//! the profile and manufacturer of the device (variations 2 and 3):
+//!
+//! | Bits         | Field                                              |
+//! |--------------|----------------------------------------------------|
+//! | DB3.7..2     | FUNC                                               |
+//! | DB3.1..DB2.3 | TYPE                                               |
+//! | DB2.2..DB1.0 | Manufacturer ID                                    |
+//! | DB0.7        | LRN type (1 = with EEP)                            |
+//! | DB0.6        | EEP result (response only, 1 = EEP supported)      |
+//! | DB0.5        | LRN result (response only, 1 = learned)            |
+//! | DB0.4        | LRN status (0 = query, 1 = response)               |
+//!
+//! VLD devices use the Universal Teach-in (UTE, RORG D4) handshake instead. The device
+//! sends a [`UteRequest`], and the receiver answers with a [`UteResponse`] addressed to it:
+//!
+//! | Byte | Request                                         | Response                 |
+//! |------|-------------------------------------------------|--------------------------|
+//! | DB6  | .7 bidirectional, .6 no response expected       | .7 bidirectional         |
+//! |      | .5..4 request type, .3..0 CMD = 0               | .5..4 result, CMD = 1    |
+//! | DB5  | Number of channels to teach (0xFF = all)        | Echoed                   |
+//! | DB4  | Manufacturer ID, bits 7..0                      | Echoed                   |
+//! | DB3  | Manufacturer ID, bits 10..8 in .2..0            | Echoed                   |
+//! | DB2  | TYPE                                            | Echoed                   |
+//! | DB1  | FUNC                                            | Echoed                   |
+//! | DB0  | RORG                                            | Echoed                   |
+
+use super::{bits, check_telegram, DecodeError};
+use crate::enocean::Rorg;
+use crate::manufacturer::Manufacturer;
+use crate::packet::{EEPProfileCode, RadioErp1};
+
+use num_enum::TryFromPrimitive;
+
+/// A teach-in telegram of any kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum TeachIn {
+    /// 1BS teach-in, from a D5-00-01 contact
+    OneBS,
+    FourBS(TeachIn4BS),
+    Ute(UteRequest),
+}
+
+impl TeachIn {
+    /// Recognize a teach-in telegram, returning `None` for data telegrams
+    pub fn detect(erp: &RadioErp1) -> Option<Self> {
+        match erp.choice {
+            Rorg::Bs1 if super::d5_00::is_teach_in(erp) => Some(Self::OneBS),
+            Rorg::Bs4 => TeachIn4BS::decode(erp).ok().map(Self::FourBS),
+            Rorg::Ute => UteRequest::decode(erp).ok().map(Self::Ute),
+            _ => None,
+        }
+    }
+
+    /// The profile announced by the teach-in telegram, when it carries one
+    pub fn eep(&self) -> Option<EEPProfileCode> {
+        match self {
+            Self::OneBS => Some(EEPProfileCode::new(0xD5, 0x00, 0x01)),
+            Self::FourBS(teach_in) => teach_in.eep,
+            Self::Ute(request) => Some(request.eep),
+        }
+    }
+}
+
+/// Result carried by a 4BS teach-in response (variation 3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TeachInResult4BS {
+    pub eep_supported: bool,
+    pub learned: bool,
+}
+
+/// A 4BS teach-in telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TeachIn4BS {
+    /// Profile of the device, absent for teach-in telegrams without EEP (variation 1)
+    pub eep: Option<EEPProfileCode>,
+    /// 11-bit manufacturer ID, absent for teach-in telegrams without EEP
+    pub manufacturer: Option<u16>,
+    /// Set for a teach-in response, `None` for a query
+    pub response: Option<TeachInResult4BS>,
+}
+
+impl TeachIn4BS {
+    /// Decode a 4BS teach-in telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Bs4, 4)?;
+        let d = &erp.user_data[..];
+        if d[3] & 0x08 != 0 {
+            return Err(DecodeError::NotTeachIn);
+        }
+        if d[3] & 0x80 == 0 {
+            return Ok(Self { eep: None, manufacturer: None, response: None });
+        }
+
+        Ok(Self {
+            eep: Some(EEPProfileCode::new(0xA5, bits(d, 0, 6) as u8, bits(d, 6, 7) as u8)),
+            manufacturer: Some(bits(d, 13, 11) as u16),
+            response: (d[3] & 0x10 != 0).then_some(TeachInResult4BS {
+                eep_supported: d[3] & 0x40 != 0,
+                learned: d[3] & 0x20 != 0,
+            }),
+        })
+    }
+
+    /// The manufacturer, if it is registered
+    pub fn known_manufacturer(&self) -> Option<Manufacturer> {
+        self.manufacturer.and_then(|id| Manufacturer::try_from_primitive(id).ok())
+    }
+
+    /// Build the response to this query (variation 3), echoing its profile and manufacturer
+    pub fn response(&self, result: TeachInResult4BS) -> Self {
+        Self { response: Some(result), ..*self }
+    }
+
+    /// Encode the 4BS user data (DB3..DB0) of the telegram.
+    ///
+    /// Send a response back to the device address, from the sender ID the device will
+    /// be controlled with, see [`RadioErp1::addressed`].
+    pub fn encode(&self) -> [u8; 4] {
+        let (func, eep_type) = self.eep.map(|eep| (eep.func(), eep.eep_type())).unwrap_or((0, 0));
+        let manufacturer = self.manufacturer.unwrap_or(0) & 0x7ff;
+        let mut db0 = if self.eep.is_some() { 0x80 } else { 0 };
+        if let Some(result) = self.response {
+            db0 |= 0x10
+                | if result.eep_supported { 0x40 } else { 0 }
+                | if result.learned { 0x20 } else { 0 };
+        }
+
+        [
+            (func & 0x3f) << 2 | (eep_type >> 5) & 0x03,
+            (eep_type & 0x1f) << 3 | (manufacturer >> 8) as u8,
+            manufacturer as u8,
+            db0,
+        ]
+    }
+}
+
+/// What a UTE request asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum UteRequestType {
+    TeachIn,
+    Deletion,
+    /// Teach-in, or deletion if the device is already known
+    Unspecified,
+}
+
+/// A UTE teach-in query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct UteRequest {
+    pub eep: EEPProfileCode,
+    /// 11-bit manufacturer ID
+    pub manufacturer: u16,
+    pub bidirectional: bool,
+    pub response_expected: bool,
+    pub request_type: UteRequestType,
+    /// Number of channels to teach, 0xFF for all of them
+    pub channels: u8,
+}
+
+impl UteRequest {
+    /// Decode a UTE teach-in query
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        check_telegram(erp, Rorg::Ute, 7)?;
+        let d = &erp.user_data[..];
+        if d[0] & 0x0f != 0 {
+            return Err(DecodeError::InvalidValue(d[0]));
+        }
+
+        Ok(Self {
+            eep: EEPProfileCode::new(d[6], d[5], d[4]),
+            manufacturer: ((d[3] & 0x07) as u16) << 8 | d[2] as u16,
+            bidirectional: d[0] & 0x80 != 0,
+            response_expected: d[0] & 0x40 == 0,
+            request_type: match (d[0] >> 4) & 0x03 {
+                0 => UteRequestType::TeachIn,
+                1 => UteRequestType::Deletion,
+                2 => UteRequestType::Unspecified,
+                _ => return Err(DecodeError::InvalidValue(d[0])),
+            },
+            channels: d[1],
+        })
+    }
+
+    /// The manufacturer, if it is registered
+    pub fn known_manufacturer(&self) -> Option<Manufacturer> {
+        Manufacturer::try_from_primitive(self.manufacturer).ok()
+    }
+
+    /// Encode the UTE user data (DB6..DB0) of the request, as sent by the device
+    pub fn encode(&self) -> [u8; 7] {
+        let request_type = match self.request_type {
+            UteRequestType::TeachIn => 0,
+            UteRequestType::Deletion => 1,
+            UteRequestType::Unspecified => 2,
+        };
+        [
+            if self.bidirectional { 0x80 } else { 0 } | if self.response_expected { 0 } else { 0x40 } | request_type << 4,
+            self.channels,
+            self.manufacturer as u8,
+            (self.manufacturer >> 8) as u8 & 0x07,
+            self.eep.eep_type(),
+            self.eep.func(),
+            self.eep.rorg(),
+        ]
+    }
+
+    /// Build the response to this request
+    pub fn response(&self, result: UteResult) -> UteResponse {
+        UteResponse { request: *self, result }
+    }
+}
+
+/// Outcome of a UTE teach-in request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum UteResult {
+    Refused = 0,
+    Accepted = 1,
+    Deleted = 2,
+    EepNotSupported = 3,
+}
+
+/// A UTE teach-in response, sent back to the address of the requesting device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct UteResponse {
+    pub request: UteRequest,
+    pub result: UteResult,
+}
+
+impl UteResponse {
+    /// Encode the UTE user data (DB6..DB0) of the response telegram
+    pub fn encode(&self) -> [u8; 7] {
+        let request = &self.request;
+        let bidirectional = if request.bidirectional { 0x80 } else { 0 };
+        [
+            bidirectional | (self.result as u8) << 4 | 0x01,
+            request.channels,
+            request.manufacturer as u8,
+            (request.manufacturer >> 8) as u8 & 0x07,
+            request.eep.eep_type(),
+            request.eep.func(),
+            request.eep.rorg(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_4bs_teach_in_with_eep_then_extract_profile_and_manufacturer() {
+        // A5-02-05, manufacturer 0x00D (Eltako), query
+        let erp = erp1(&[0xa5, 0x08, 0x28, 0x0d, 0x80, 1, 2, 3, 4, 0]);
+        let teach_in = TeachIn4BS::decode(&erp).unwrap();
+        assert_eq!(teach_in.eep, Some(EEPProfileCode::new(0xA5, 0x02, 0x05)));
+        assert_eq!(teach_in.known_manufacturer(), Some(Manufacturer::Eltako));
+        assert_eq!(teach_in.response, None);
+    }
+
+    #[test]
+    fn given_4bs_teach_in_without_eep_or_data_telegram_then_handle_them() {
+        let erp = erp1(&[0xa5, 0, 0, 0, 0x00, 1, 2, 3, 4, 0]);
+        assert_eq!(TeachIn4BS::decode(&erp).unwrap(), TeachIn4BS { eep: None, manufacturer: None, response: None });
+        let erp = erp1(&[0xa5, 0, 0, 0, 0x08, 1, 2, 3, 4, 0]);
+        assert!(matches!(TeachIn4BS::decode(&erp), Err(DecodeError::NotTeachIn)));
+    }
+
+    #[test]
+    fn given_ute_request_then_decode_it_and_build_response() {
+        // D2-01-0E smart plug from NodOn, bidirectional teach-in request
+        let erp = erp1(&[0xd4, 0xa0, 0x01, 0x46, 0x00, 0x0e, 0x01, 0xd2, 5, 10, 61, 106, 0]);
+        let request = UteRequest::decode(&erp).unwrap();
+        assert_eq!(request.eep, EEPProfileCode::new(0xD2, 0x01, 0x0E));
+        assert_eq!(request.known_manufacturer(), Some(Manufacturer::NodOn));
+        assert!(request.bidirectional && request.response_expected);
+        assert_eq!(request.request_type, UteRequestType::Unspecified);
+        assert_eq!(request.encode(), [0xa0, 0x01, 0x46, 0x00, 0x0e, 0x01, 0xd2]);
+
+        assert_eq!(request.response(UteResult::Accepted).encode(), [0x91, 0x01, 0x46, 0x00, 0x0e, 0x01, 0xd2]);
+    }
+
+    #[test]
+    fn given_received_telegrams_then_detect_teach_in() {
+        let contact = erp1(&[0xd5, 0x00, 1, 2, 3, 4, 0]);
+        assert_eq!(TeachIn::detect(&contact), Some(TeachIn::OneBS));
+        assert_eq!(TeachIn::detect(&contact).unwrap().eep(), Some(EEPProfileCode::new(0xD5, 0x00, 0x01)));
+        assert_eq!(TeachIn::detect(&erp1(&[0xd5, 0x09, 1, 2, 3, 4, 0])), None);
+        assert_eq!(TeachIn::detect(&erp1(&[0xa5, 0, 0, 0, 0x08, 1, 2, 3, 4, 0])), None);
+    }
+
+    #[test]
+    fn given_4bs_teach_in_query_then_encode_response() {
+        let query = TeachIn4BS::decode(&erp1(&[0xa5, 0x80, 0x08, 0x0d, 0x80, 1, 2, 3, 4, 0])).unwrap();
+        assert_eq!(query.eep, Some(EEPProfileCode::new(0xA5, 0x20, 0x01)));
+        assert_eq!(query.encode(), [0x80, 0x08, 0x0d, 0x80]);
+
+        let response = query.response(TeachInResult4BS { eep_supported: true, learned: true });
+        assert_eq!(response.encode(), [0x80, 0x08, 0x0d, 0xf0]);
+        assert_eq!(TeachIn4BS::decode(&erp1(&[0xa5, 0x80, 0x08, 0x0d, 0xf0, 1, 2, 3, 4, 0])).unwrap(), response);
+    }
+}
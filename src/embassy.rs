@@ -0,0 +1,165 @@
+//! Async link to an ESP3 device, for Embassy (feature `embassy`)
+//!
+//! The async counterpart of [`crate::embedded::EmbeddedPort`], over the `embedded-io-async`
+//! traits the Embassy HALs implement for their UARTs. It runs on any executor, and does not
+//! depend on tokio. Builds without `std`.
+//!
+//! ```ignore
+//! #[embassy_executor::task]
+//! async fn gateway(uart: BufferedUart<'static, UART1>) {
+//!     let mut port = AsyncPort::new(uart);
+//!     let base = port.read_id_base().await.unwrap();
+//!     loop {
+//!         let frame = port.read_frame().await.unwrap();
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use core::borrow::Borrow;
+
+use embedded_io_async::{ErrorType, Read, Write};
+
+use crate::embedded::TransportError;
+use crate::frame::ESP3Frame;
+use crate::link::{Link, RawFrame, READ_BUFFER};
+use crate::packet::{CommonCommand, IdBase, Packet, Response, VersionResponse};
+use crate::FrameReadError;
+
+/// An ESP3 device on an async serial link
+pub struct AsyncPort<T> {
+    serial: T,
+    /// Frames received and not yet returned, e.g. while waiting for a response
+    link: Link,
+}
+
+type Error<T> = TransportError<<T as ErrorType>::Error>;
+
+impl<T: Read + Write> AsyncPort<T> {
+    pub fn new(serial: T) -> Self {
+        Self { serial, link: Link::new() }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.serial
+    }
+
+    /// Hand every frame read or written to `tap` as raw bytes, including frames failing
+    /// their CRC. See [`crate::link`].
+    pub fn set_tap(&mut self, tap: impl FnMut(&RawFrame) + Send + 'static) {
+        self.link.set_tap(tap);
+    }
+
+    pub fn clear_tap(&mut self) {
+        self.link.clear_tap();
+    }
+
+    pub async fn read_version_information(&mut self) -> Result<VersionResponse, Error<T>> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadVersion)).await?;
+        Ok(VersionResponse::decode(&response)?)
+    }
+
+    pub async fn read_id_base(&mut self) -> Result<IdBase, Error<T>> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadIdBase)).await?;
+        Ok(IdBase::decode(&response)?)
+    }
+
+    /// Read the bytes available from the serial link
+    async fn receive(&mut self) -> Result<(), Error<T>> {
+        let mut buffer = [0; READ_BUFFER];
+        let n = self.serial.read(&mut buffer).await.map_err(TransportError::Io)?;
+        if n == 0 {
+            return Err(FrameReadError::EOF.into());
+        }
+        self.link.feed(&buffer[..n]);
+        Ok(())
+    }
+
+    /// Read the next frame, starting with the frames set aside while waiting for a response.
+    pub async fn read_frame(&mut self) -> Result<ESP3Frame, Error<T>> {
+        loop {
+            if let Some(frame) = self.link.next_frame() {
+                return Ok(frame?);
+            }
+            self.receive().await?;
+        }
+    }
+
+    pub async fn write_frame(&mut self, frame: &ESP3Frame) -> Result<(), Error<T>> {
+        self.serial.write_all(frame.borrow()).await.map_err(TransportError::Io)?;
+        self.serial.flush().await.map_err(TransportError::Io)?;
+        self.link.sent(frame);
+        Ok(())
+    }
+
+    /// Send a packet and wait for the response of the device
+    pub async fn write_packet(&mut self, packet: Packet<'_>) -> Result<Response, Error<T>> {
+        self.write_frame(&packet.encode()).await?;
+
+        loop {
+            if let Some(reply) = self.link.next_response() {
+                return Ok(Response::decode(reply?.as_ref())?);
+            }
+            self.receive().await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::VecDeque;
+    use core::convert::Infallible;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// A serial link replaying received bytes, and recording the sent ones. Always ready.
+    struct Link {
+        rx: VecDeque<u8>,
+        tx: Vec<u8>,
+    }
+
+    impl ErrorType for Link {
+        type Error = Infallible;
+    }
+
+    impl Read for Link {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let n = buf.len().min(self.rx.len());
+            for (b, r) in buf.iter_mut().zip(self.rx.drain(..n)) {
+                *b = r;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for Link {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.tx.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    /// Util : run a future that never waits
+    fn ready<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is pending"),
+        }
+    }
+
+    #[test]
+    fn given_telegram_before_response_then_queue_it() {
+        let telegram = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let response = ESP3Frame::assemble(0x02, &[0x00, 0xff, 0x80, 0x00, 0x00], &[]);
+        let mut rx = VecDeque::new();
+        rx.extend(Borrow::<[u8]>::borrow(&telegram));
+        rx.extend(Borrow::<[u8]>::borrow(&response));
+        let mut port = AsyncPort::new(Link { rx, tx: Vec::new() });
+
+        assert_eq!(ready(port.read_id_base()).unwrap(), IdBase("ff800000".parse().unwrap()));
+        assert_eq!(ready(port.read_frame()).unwrap().data(), telegram.data());
+        assert!(matches!(ready(port.read_frame()), Err(TransportError::Frame(FrameReadError::EOF))));
+    }
+}
@@ -0,0 +1,188 @@
+//! Link to an ESP3 device over `embedded-io` (feature `embedded-io`)
+//!
+//! The counterpart of [`crate::port::Port`] for microcontrollers: any UART implementing the
+//! blocking `embedded_io::Read` and `embedded_io::Write` traits, e.g. from a HAL, carries
+//! the frames. Builds without `std`.
+//!
+//! ```ignore
+//! let mut port = EmbeddedPort::new(uart);
+//! let base = port.read_id_base()?;
+//! loop {
+//!     let frame = port.read_frame()?;
+//!     if let Ok(Packet::RadioErp1(erp)) = Packet::decode(frame.as_ref()) {
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use core::borrow::Borrow;
+use core::fmt;
+
+use embedded_io::{Read, Write};
+
+use crate::frame::ESP3Frame;
+use crate::link::{Link, RawFrame, READ_BUFFER};
+use crate::packet::{CommonCommand, IdBase, Packet, ParseError, Response, VersionResponse};
+use crate::FrameReadError;
+
+/// Errors of an [`EmbeddedPort`], over the errors `E` of its serial link
+#[derive(Debug)]
+pub enum TransportError<E> {
+    Io(E),
+    Frame(FrameReadError),
+    Parse(ParseError),
+}
+
+impl<E: fmt::Debug> fmt::Display for TransportError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e)    => write!(f, "IO Error: {:?}", e),
+            Self::Frame(_) => write!(f, "Could not read frame"),
+            Self::Parse(_) => write!(f, "Could not parse frame"),
+        }
+    }
+}
+
+impl<E> From<FrameReadError> for TransportError<E> {
+    fn from(e: FrameReadError) -> Self {
+        Self::Frame(e)
+    }
+}
+
+impl<E> From<ParseError> for TransportError<E> {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// An ESP3 device on a serial link
+pub struct EmbeddedPort<T> {
+    serial: T,
+    /// Frames received and not yet returned, e.g. while waiting for a response
+    link: Link,
+}
+
+impl<T: Read + Write> EmbeddedPort<T> {
+    pub fn new(serial: T) -> Self {
+        Self { serial, link: Link::new() }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.serial
+    }
+
+    /// Hand every frame read or written to `tap` as raw bytes, including frames failing
+    /// their CRC. See [`crate::link`].
+    pub fn set_tap(&mut self, tap: impl FnMut(&RawFrame) + Send + 'static) {
+        self.link.set_tap(tap);
+    }
+
+    pub fn clear_tap(&mut self) {
+        self.link.clear_tap();
+    }
+
+    pub fn read_version_information(&mut self) -> Result<VersionResponse, TransportError<T::Error>> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadVersion))?;
+        Ok(VersionResponse::decode(&response)?)
+    }
+
+    pub fn read_id_base(&mut self) -> Result<IdBase, TransportError<T::Error>> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadIdBase))?;
+        Ok(IdBase::decode(&response)?)
+    }
+
+    /// Read the bytes available from the serial link
+    fn receive(&mut self) -> Result<(), TransportError<T::Error>> {
+        let mut buffer = [0; READ_BUFFER];
+        let n = self.serial.read(&mut buffer).map_err(TransportError::Io)?;
+        if n == 0 {
+            return Err(FrameReadError::EOF.into());
+        }
+        self.link.feed(&buffer[..n]);
+        Ok(())
+    }
+
+    /// Read the next frame, starting with the frames set aside while waiting for a response.
+    pub fn read_frame(&mut self) -> Result<ESP3Frame, TransportError<T::Error>> {
+        loop {
+            if let Some(frame) = self.link.next_frame() {
+                return Ok(frame?);
+            }
+            self.receive()?;
+        }
+    }
+
+    pub fn write_frame(&mut self, frame: &ESP3Frame) -> Result<(), TransportError<T::Error>> {
+        self.serial.write_all(frame.borrow()).map_err(TransportError::Io)?;
+        self.serial.flush().map_err(TransportError::Io)?;
+        self.link.sent(frame);
+        Ok(())
+    }
+
+    /// Send a packet and wait for the response of the device
+    pub fn write_packet(&mut self, packet: Packet) -> Result<Response, TransportError<T::Error>> {
+        self.write_frame(&packet.encode())?;
+
+        loop {
+            if let Some(reply) = self.link.next_response() {
+                return Ok(Response::decode(reply?.as_ref())?);
+            }
+            self.receive()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::VecDeque;
+    use core::convert::Infallible;
+
+    /// A serial link replaying received bytes, and recording the sent ones
+    struct Link {
+        rx: VecDeque<u8>,
+        tx: Vec<u8>,
+    }
+
+    impl embedded_io::ErrorType for Link {
+        type Error = Infallible;
+    }
+
+    impl Read for Link {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let n = buf.len().min(self.rx.len());
+            for (b, r) in buf.iter_mut().zip(self.rx.drain(..n)) {
+                *b = r;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for Link {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.tx.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn given_telegram_before_response_then_queue_it() {
+        let telegram = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let response = ESP3Frame::assemble(0x02, &[0x00, 0xff, 0x80, 0x00, 0x00], &[]);
+        let mut rx = VecDeque::new();
+        rx.extend(Borrow::<[u8]>::borrow(&telegram));
+        rx.extend(Borrow::<[u8]>::borrow(&response));
+        let mut port = EmbeddedPort::new(Link { rx, tx: Vec::new() });
+
+        assert_eq!(port.read_id_base().unwrap(), IdBase("ff800000".parse().unwrap()));
+        assert_eq!(port.read_frame().unwrap().data(), telegram.data());
+        assert!(matches!(port.read_frame(), Err(TransportError::Frame(FrameReadError::EOF))));
+
+        let sent = Packet::CommonCommand(CommonCommand::ReadIdBase).encode();
+        assert_eq!(port.into_inner().tx, Borrow::<[u8]>::borrow(&sent));
+    }
+}
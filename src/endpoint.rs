@@ -0,0 +1,123 @@
+//! Channels of multi-channel devices
+//!
+//! Many D2 actuators switch several loads behind a single address, e.g. a 2-channel relay.
+//! An [`Endpoint`] names one channel of a device, so that applications handle each channel
+//! as a device of its own:
+//!
+//! - the registry names the channels of a device, and lists its endpoints
+//!   (`DeviceRegistry::endpoints`);
+//! - decoded values tell the channel they are about ([`Decoded::channel`](crate::eep::Decoded::channel));
+//! - the gateway hands out a handle per endpoint, commanding and reading a single channel
+//!   (`Gateway::endpoint`).
+//!
+//! Endpoints are displayed and parsed as `05123456/1`. Single channel devices have the only
+//! endpoint `05123456/0`, also parsed from the bare address.
+
+use core::fmt;
+use core::num::ParseIntError;
+use core::str::FromStr;
+
+use crate::packet::Address;
+
+/// A channel of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Endpoint {
+    pub address: Address,
+    pub channel: u8,
+}
+
+impl Endpoint {
+    pub const fn new(address: Address, channel: u8) -> Self {
+        Self { address, channel }
+    }
+}
+
+/// The first channel of a device
+impl From<Address> for Endpoint {
+    fn from(address: Address) -> Self {
+        Self::new(address, 0)
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.channel)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Endpoint {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}/{=u8}", self.address, self.channel)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Endpoint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Endpoint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        alloc::string::String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Errors parsing an [`Endpoint`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseEndpointError {
+    Address(hex::FromHexError),
+    Channel(ParseIntError),
+}
+
+impl fmt::Display for ParseEndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(_) => write!(f, "Invalid address"),
+            Self::Channel(_) => write!(f, "Invalid channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEndpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Address(e) => Some(e),
+            Self::Channel(e) => Some(e),
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = ParseEndpointError;
+
+    /// Parses both `05123456/1` and `05123456`, the latter as channel 0
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, channel) = s.split_once('/').unwrap_or((s, "0"));
+        Ok(Self {
+            address: address.parse().map_err(ParseEndpointError::Address)?,
+            channel: channel.parse().map_err(ParseEndpointError::Channel)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn given_endpoint_then_display_and_parse() {
+        let endpoint = Endpoint::new(Address::new(0x05123456), 1);
+        assert_eq!(endpoint.to_string(), "05123456/1");
+        assert_eq!("05123456/1".parse(), Ok(endpoint));
+        assert_eq!("05123456".parse(), Ok(Endpoint::from(Address::new(0x05123456))));
+        assert!(matches!("05123456/x".parse::<Endpoint>(), Err(ParseEndpointError::Channel(_))));
+        assert!(matches!("0512/1".parse::<Endpoint>(), Err(ParseEndpointError::Address(_))));
+    }
+}
@@ -37,7 +37,7 @@
 //! See [LICENSE-APACHE](LICENSE-APACHE) and [LICENSE-MIT](LICENSE-MIT) for details.
 //!
 
-use num_enum::{TryFromPrimitive, IntoPrimitive};
+use num_enum::{FromPrimitive, TryFromPrimitive, IntoPrimitive};
 
 use crate::*;
 
@@ -153,7 +153,7 @@ impl From<&ESP3> for Vec<u8> {
             status,
             payload,
         } => {
-            esp3_vector.push(*rorg as u8);
+            esp3_vector.push(u8::from(*rorg));
             esp3_vector.extend_from_slice(&payload);
             esp3_vector.extend_from_slice(sender_id);
             esp3_vector.push(*status);
@@ -258,9 +258,11 @@ fn get_packet_type(em: &[u8]) -> ParseEspResult<PacketType> {
         })
 }
 
-/// Simple implementation of possible Radio Organization for a Radio ERP1 packet (from EnOcean ESP3)
-#[derive(PartialEq, Debug, Clone, Copy, IntoPrimitive, TryFromPrimitive)]
+/// Radio Organization (choice byte) of a Radio ERP1 packet. Choice bytes without a
+/// defined RORG decode as [`Rorg::Vendor`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy, IntoPrimitive, FromPrimitive)]
 #[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Rorg {
     Undefined = 0xFF,
     Rps = 0xF6,
@@ -276,10 +278,54 @@ pub enum Rorg {
     SysEx = 0xC5,
     Sec = 0x30,
     SecEncaps = 0x31,
+    NonSec = 0x32,
+    SecCdm = 0x33,
+    SecTi = 0x35,
+    Cdm = 0x40,
+    GpTi = 0xB0,
+    GpTr = 0xB1,
+    GpCd = 0xB2,
+    GpSd = 0xB3,
+    Signal = 0xD0,
+    /// A choice byte without a defined RORG
+    #[num_enum(catch_all)]
+    Vendor(u8) = 0x00,
+}
+
+impl fmt::Display for Rorg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Undefined => write!(f, "Undefined"),
+            Self::Rps       => write!(f, "RPS"),
+            Self::Bs1       => write!(f, "1BS"),
+            Self::Bs4       => write!(f, "4BS"),
+            Self::Vld       => write!(f, "VLD"),
+            Self::Msc       => write!(f, "MSC"),
+            Self::Adt       => write!(f, "ADT"),
+            Self::Ute       => write!(f, "UTE"),
+            Self::SmLrnReq  => write!(f, "SM_LRN_REQ"),
+            Self::SmLrnAns  => write!(f, "SM_LRN_ANS"),
+            Self::SmRec     => write!(f, "SM_REC"),
+            Self::SysEx     => write!(f, "SYS_EX"),
+            Self::Sec       => write!(f, "SEC"),
+            Self::SecEncaps => write!(f, "SEC_ENCAPS"),
+            Self::NonSec    => write!(f, "NON_SEC"),
+            Self::SecCdm    => write!(f, "SEC_CDM"),
+            Self::SecTi     => write!(f, "SEC_TI"),
+            Self::Cdm       => write!(f, "CDM"),
+            Self::GpTi      => write!(f, "GP_TI"),
+            Self::GpTr      => write!(f, "GP_TR"),
+            Self::GpCd      => write!(f, "GP_CD"),
+            Self::GpSd      => write!(f, "GP_SD"),
+            Self::Signal    => write!(f, "SIGNAL"),
+            Self::Vendor(choice) => write!(f, "RORG {:02X}", choice),
+        }
+    }
 }
 /// Simple implementation of possible Return codes for a response packet (from EnOcean ESP3)
 #[derive(Debug, PartialEq, Clone, Copy, IntoPrimitive, TryFromPrimitive)]
 #[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ReturnCode {
     Ok = 0x00,
     Error = 0x01,
@@ -289,16 +335,85 @@ pub enum ReturnCode {
     LockSet = 0x05,
     BufferTooSmall = 0x06,
     NoFreeBuffer = 0x07,
+    FlashHwError = 0x82,
+    BaseIdOutOfRange = 0x90,
+    BaseIdMaxReached = 0x91,
     Undefined = 0xff,
 }
 
+impl ReturnCode {
+    /// `Ok(())` for [`ReturnCode::Ok`], the failure it reports otherwise
+    pub fn result(self) -> Result<(), CommandError> {
+        Err(match self {
+            Self::Ok               => return Ok(()),
+            Self::Error            => CommandError::Failed,
+            Self::NotSupported     => CommandError::NotSupported,
+            Self::WrongParam       => CommandError::WrongParam,
+            Self::OperationDenied  => CommandError::Denied,
+            Self::LockSet          => CommandError::Locked,
+            Self::BufferTooSmall   => CommandError::BufferTooSmall,
+            Self::NoFreeBuffer     => CommandError::NoFreeBuffer,
+            Self::FlashHwError     => CommandError::Flash,
+            Self::BaseIdOutOfRange => CommandError::BaseIdOutOfRange,
+            Self::BaseIdMaxReached => CommandError::BaseIdMaxReached,
+            Self::Undefined        => CommandError::Undefined,
+        })
+    }
+}
+
+/// A command refused by the device, from the return code of its response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommandError {
+    /// The command failed, for no specific reason
+    Failed,
+    /// The command, or the requested setting such as a baud rate, is not supported
+    NotSupported,
+    WrongParam,
+    /// The device denied the operation, e.g. a memory write to a protected area, or a
+    /// telegram while the duty cycle limit is reached
+    Denied,
+    /// The setting is locked, and cannot be changed until the device restarts
+    Locked,
+    BufferTooSmall,
+    NoFreeBuffer,
+    /// Writing to the flash memory failed
+    Flash,
+    /// The new base ID is outside of `ff800000`..`ffffff80`
+    BaseIdOutOfRange,
+    /// The base ID can no longer be changed
+    BaseIdMaxReached,
+    Undefined,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Failed           => write!(f, "Command failed"),
+            Self::NotSupported     => write!(f, "Command not supported"),
+            Self::WrongParam       => write!(f, "Wrong command parameter"),
+            Self::Denied           => write!(f, "Operation denied"),
+            Self::Locked           => write!(f, "Setting locked"),
+            Self::BufferTooSmall   => write!(f, "Buffer too small"),
+            Self::NoFreeBuffer     => write!(f, "No free buffer"),
+            Self::Flash            => write!(f, "Flash memory error"),
+            Self::BaseIdOutOfRange => write!(f, "Base ID out of range"),
+            Self::BaseIdMaxReached => write!(f, "Base ID changes exhausted"),
+            Self::Undefined        => write!(f, "Undefined error"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CommandError {}
+
 fn get_return_code(rc_byte: u8) -> ReturnCode {
     ReturnCode::try_from_primitive(rc_byte).unwrap_or(ReturnCode::Undefined)
 }
 
 /// Given an u8 byte containing Rorg indicator, return the corresponding Rorg variant
 fn get_radio_organization(rorg_byte: u8) -> Rorg {
-    Rorg::try_from_primitive(rorg_byte).unwrap_or(Rorg::Undefined)
+    Rorg::from(rorg_byte)
 }
 
 pub use crc8::compute_crc8;
@@ -0,0 +1,130 @@
+//! Software receive filter
+//!
+//! A [`ReceiveFilter`] drops the telegrams an application is not interested in, e.g. those
+//! of the neighbours' devices in a dense building, before they are decoded and dispatched.
+//! Unlike the filters of the transceiver, it can be changed at any time without a command
+//! to the device, and has no limit on its number of entries.
+//!
+//! A telegram is accepted if its sender is not blocked, if the sender and RORG allowlists
+//! are empty or contain it, and if its signal is strong enough.
+//!
+//! ```
+//! use enocean::enocean::Rorg;
+//! use enocean::filter::ReceiveFilter;
+//!
+//! let mut filter = ReceiveFilter::new();
+//! filter.allow_sender("0180998e".parse()?);
+//! filter.allow_rorg(Rorg::Bs4);
+//! filter.set_min_rssi(Some(-85));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::collections::BTreeSet;
+
+use crate::enocean::Rorg;
+use crate::packet::{Address, RadioErp1};
+
+/// Senders, RORGs and signal strength of the telegrams to accept
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceiveFilter {
+    allowed_senders: BTreeSet<Address>,
+    blocked_senders: BTreeSet<Address>,
+    allowed_rorgs: BTreeSet<u8>,
+    min_rssi: Option<i16>,
+}
+
+impl ReceiveFilter {
+    /// A filter accepting all telegrams
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept the telegrams of `address`; once a sender is allowed, the others are dropped
+    pub fn allow_sender(&mut self, address: Address) {
+        self.allowed_senders.insert(address);
+    }
+
+    /// Drop the telegrams of `address`
+    pub fn block_sender(&mut self, address: Address) {
+        self.blocked_senders.insert(address);
+    }
+
+    /// Remove `address` from the allowlist and the blocklist
+    pub fn forget_sender(&mut self, address: &Address) {
+        self.allowed_senders.remove(address);
+        self.blocked_senders.remove(address);
+    }
+
+    /// Accept the telegrams of `rorg`; once a RORG is allowed, the others are dropped
+    pub fn allow_rorg(&mut self, rorg: Rorg) {
+        self.allowed_rorgs.insert(rorg.into());
+    }
+
+    pub fn forget_rorg(&mut self, rorg: Rorg) {
+        self.allowed_rorgs.remove(&rorg.into());
+    }
+
+    /// Drop the telegrams received with a signal weaker than `dbm`, e.g. -85. Telegrams
+    /// without signal strength are accepted.
+    pub fn set_min_rssi(&mut self, dbm: Option<i16>) {
+        self.min_rssi = dbm;
+    }
+
+    pub fn allowed_senders(&self) -> impl Iterator<Item = &Address> {
+        self.allowed_senders.iter()
+    }
+
+    pub fn blocked_senders(&self) -> impl Iterator<Item = &Address> {
+        self.blocked_senders.iter()
+    }
+
+    pub fn allowed_rorgs(&self) -> impl Iterator<Item = Rorg> + '_ {
+        self.allowed_rorgs.iter().map(|&rorg| Rorg::from(rorg))
+    }
+
+    pub fn min_rssi(&self) -> Option<i16> {
+        self.min_rssi
+    }
+
+    /// Accept all telegrams again
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn accepts(&self, erp: &RadioErp1) -> bool {
+        !self.blocked_senders.contains(&erp.sender_id)
+            && (self.allowed_senders.is_empty() || self.allowed_senders.contains(&erp.sender_id))
+            && (self.allowed_rorgs.is_empty() || self.allowed_rorgs.contains(&erp.choice.into()))
+            && self.min_rssi.zip(erp.rssi_dbm()).is_none_or(|(min, rssi)| rssi >= min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ESP3Frame;
+
+    #[test]
+    fn given_filter_then_drop_other_telegrams() {
+        let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x80, 0x99, 0x8e, 0x30], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x50, 0x00]);
+        let erp = RadioErp1::decode(frame.as_ref()).unwrap();
+        let mut filter = ReceiveFilter::new();
+        assert!(filter.accepts(&erp));
+
+        filter.allow_rorg(Rorg::Bs4);
+        assert!(!filter.accepts(&erp));
+        filter.allow_rorg(Rorg::Rps);
+        filter.allow_sender("0180998e".parse().unwrap());
+        assert!(filter.accepts(&erp));
+
+        filter.set_min_rssi(Some(-70));
+        assert!(!filter.accepts(&erp));
+        filter.set_min_rssi(Some(-85));
+        assert!(filter.accepts(&erp));
+
+        filter.block_sender("0180998e".parse().unwrap());
+        assert!(!filter.accepts(&erp));
+        filter.clear();
+        assert!(filter.accepts(&erp));
+    }
+}
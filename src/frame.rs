@@ -46,15 +46,31 @@
 //! # assert_eq!(&serial_port[..], &[85, 0, 10, 7, 1, 235, 165, 16, 8, 70, 128, 5, 17, 114, 247, 0, 1, 255, 255, 255, 255, 55, 0, 55]);
 //! ```
 //!
+//! Without `std`, feed the received bytes to a [`FrameDecoder`], e.g. from a UART interrupt:
+//! ```
+//! # use enocean::frame::*;
+//! # let received = [0, 85, 0, 7, 7, 1, 122, 246, 80, 1, 2, 3, 4, 48, 1, 255, 255, 255, 255, 45, 0, 249];
+//! let mut decoder = FrameDecoder::new();
+//! let frames: Vec<ESP3Frame> = received.iter().filter_map(|&byte| decoder.push(byte)).collect::<Result<_, _>>().unwrap();
+//!
+//! assert_eq!(frames.len(), 1);
+//! assert_eq!(frames[0].data(), &[246, 80, 1, 2, 3, 4, 48]);
+//! ```
+//!
 
-use std::borrow::Borrow;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+#[cfg(feature = "std")]
 use std::io::Read;
 
 use crate::FrameReadError;
 use crate::crc8::{compute_crc8, CRC8};
 
+pub mod fixed;
+
 /// An owned ESP3 frame that has been CRC-checked. Backed by a single `Vec<u8>`,  Includes synchronization byte and CRCs.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ESP3Frame {
     packet_type: u8,
     data_length: usize,
@@ -63,6 +79,7 @@ pub struct ESP3Frame {
 }
 
 /// Borrowed contents of an ESP3 frame. Can also be used to assemble a new frame.
+#[derive(Clone, Copy)]
 pub struct ESP3FrameRef<'a> {
     /// The packet type. See ESP3 specification, section 1.8
     pub packet_type: u8,
@@ -84,6 +101,7 @@ impl ESP3Frame {
     }
 
     /// Read a frame from a buffered reader. Will perform header synchronization. Allocates exactly the space needed.
+    #[cfg(feature = "std")]
     pub fn read_from(reader: &mut impl Read) -> Result<Self, FrameReadError> {
 
         let mut header = [0; 6];
@@ -105,24 +123,27 @@ impl ESP3Frame {
         };
 
         // The frame is now synchronized and the header CRC is valid
-        // decode the header
-        let data_length = ((header[1] as usize) << 8) + (header[2] as usize);
-        let optional_data_length = header[3] as usize;
-        let packet_type = header[4];
-
         // Allocate an appropriate buffer
-        let total_length = 6 + data_length + optional_data_length + 1;
-        let mut frame = vec![0; total_length];
+        let mut frame = vec![0; frame_length(&header)];
 
         frame[0..6].copy_from_slice(&header);
         reader.read_exact(&mut frame[6..])?;
 
+        Self::checked(frame)
+
+    }
+
+    /// Decode a complete frame with a valid header, checking its data CRC
+    fn checked(frame: Vec<u8>) -> Result<Self, FrameReadError> {
+        let data_length = ((frame[1] as usize) << 8) + (frame[2] as usize);
+        let optional_data_length = frame[3] as usize;
+        let packet_type = frame[4];
+
         // Check the Data CRC
         let data_crc = compute_crc8(&frame[6..]);
         if data_crc != 0 { return Err(FrameReadError::DataCRC{ frame, data_crc }) }
 
         Ok(ESP3Frame { frame, packet_type, data_length, optional_data_length })
-
     }
 
     /// The packet type, as a single byte
@@ -149,6 +170,7 @@ impl ESP3Frame {
     }
 
     /// Writes the complete frame
+    #[cfg(feature = "std")]
     pub fn write_to(&self, writer: &mut impl std::io::Write) -> Result<(), std::io::Error> {
         writer.write_all(&self.frame)
     }
@@ -164,37 +186,60 @@ impl<'a> From<ESP3FrameRef<'a>> for ESP3Frame {
     fn from(value: ESP3FrameRef<'a>) -> Self { value.to_owned() }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ESP3Frame {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(u.arbitrary::<ESP3FrameRef>()?.to_owned())
+    }
+}
+
+/// Contents of any packet type, with data and optional data truncated to the lengths the
+/// header can hold
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ESP3FrameRef<'a> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let packet_type = u.arbitrary()?;
+        let data: &[u8] = u.arbitrary()?;
+        let optional_data: &[u8] = u.arbitrary()?;
+        Ok(Self { packet_type, data: &data[..data.len().min(0xffff)], optional_data: &optional_data[..optional_data.len().min(0xff)] })
+    }
+}
+
 impl<'a> ESP3FrameRef<'a> {
 
     /// Generate and write a frame
+    #[cfg(feature = "std")]
     pub fn write_to(&self, writer: &mut impl std::io::Write) -> Result<(), std::io::Error> {
+        writer.write_all(&self.header())?;
+        writer.write_all(self.data)?;
+        writer.write_all(self.optional_data)?;
+        writer.write_all(&[self.data_crc()])
+    }
 
-        // Build the header
+    /// Build the header, with its CRC
+    fn header(&self) -> [u8; 6] {
         let data_len = self.data.len() as u16;
         let data_high = (data_len >> 8) as u8;
         let data_low = (data_len & 0xff) as u8;
         let opt_len = self.optional_data.len() as u8;
 
         let mut header = [0x55, data_high, data_low, opt_len, self.packet_type, 0];
-
-        // CRC the header
         header[5] = CRC8::from(&header[1..5]).into();
-        writer.write_all(&header[..])?;
-
-        // CRC the payload
-        let data_crc = CRC8::from(self.data).extend(self.optional_data).into();
-
-        // Build the payload
-        writer.write_all(self.data)?;
-        writer.write_all(self.optional_data)?;
-        writer.write_all(&[data_crc])
+        header
+    }
 
+    /// CRC the payload
+    fn data_crc(&self) -> u8 {
+        CRC8::from(self.data).extend(self.optional_data).into()
     }
 
     // Copies the pieces of a constructed ESP3FrameRef into a single-buffer owned ESP3Frame
     pub fn to_owned(&self) -> ESP3Frame {
         let mut frame = Vec::with_capacity(6 + self.data.len() + self.optional_data.len() + 1);
-        self.write_to(&mut frame).unwrap();   // Writing to a preallocated Vec should never fail
+        frame.extend_from_slice(&self.header());
+        frame.extend_from_slice(self.data);
+        frame.extend_from_slice(self.optional_data);
+        frame.push(self.data_crc());
 
         ESP3Frame { packet_type: self.packet_type,
                     data_length: self.data.len(),
@@ -203,4 +248,103 @@ impl<'a> ESP3FrameRef<'a> {
 
     }
 
-}
\ No newline at end of file
+}
+/// Util : total length of a frame, from its header
+fn frame_length(header: &[u8]) -> usize {
+    let data_length = ((header[1] as usize) << 8) + (header[2] as usize);
+    6 + data_length + header[3] as usize + 1
+}
+
+/// Parse all the complete frames of `bytes` in one pass, e.g. a capture file or a drained
+/// serial buffer. Returns the frames, and the number of bytes consumed; the rest starts
+/// an incomplete frame, to parse again with the next bytes. Bytes out of sync are skipped;
+/// on a bad data CRC, only the sync byte is, and the search resumes right after it so that
+/// frames within the corrupt one are kept.
+///
+/// Unlike [`FrameDecoder`], it looks for sync bytes and copies frames a whole slice at a time.
+///
+/// ```
+/// # use enocean::frame::*;
+/// let bytes = [0, 85, 0, 7, 7, 1, 122, 246, 80, 1, 2, 3, 4, 48, 1, 255, 255, 255, 255, 45, 0, 249, 85, 0, 7];
+/// let (frames, consumed) = parse_all(&bytes);
+///
+/// assert_eq!(frames.len(), 1);
+/// assert_eq!(frames[0].data(), &[246, 80, 1, 2, 3, 4, 48]);
+/// assert_eq!(&bytes[consumed..], &[85, 0, 7]);
+/// ```
+pub fn parse_all(bytes: &[u8]) -> (Vec<ESP3Frame>, usize) {
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = bytes[pos..].iter().position(|&byte| byte == 0x55) {
+        pos += start;
+        let rest = &bytes[pos..];
+        if rest.len() < 6 {
+            return (frames, pos);
+        }
+        if compute_crc8(&rest[1..6]) != 0 {
+            pos += 1;
+            continue;
+        }
+        let length = frame_length(rest);
+        if rest.len() < length {
+            return (frames, pos);
+        }
+        match ESP3Frame::checked(rest[..length].to_vec()) {
+            Ok(frame) => {
+                frames.push(frame);
+                pos += length;
+            }
+            Err(_) => pos += 1,
+        }
+    }
+    (frames, bytes.len())
+}
+
+/// Decodes frames from bytes received one at a time, without `std::io`. Performs header
+/// synchronization like [`ESP3Frame::read_from`].
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next received byte. Returns the frame it completes, if any.
+    pub fn push(&mut self, byte: u8) -> Option<Result<ESP3Frame, FrameReadError>> {
+        self.buffer.push(byte);
+
+        // Drop bytes until the buffer starts with a sync byte followed by a valid header
+        while !self.buffer.is_empty()
+            && (self.buffer[0] != 0x55 || (self.buffer.len() >= 6 && compute_crc8(&self.buffer[1..6]) != 0)) {
+            self.buffer.remove(0);
+        }
+
+        if self.buffer.len() < 6 || self.buffer.len() < frame_length(&self.buffer) {
+            return None;
+        }
+        Some(ESP3Frame::checked(core::mem::take(&mut self.buffer)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::borrow::Borrow;
+
+    #[test]
+    fn given_bad_data_crc_then_resync_within_the_frame() {
+        let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let long = ESP3Frame::assemble(0x01, &[0; 20], &[]);
+        let long: &[u8] = long.borrow();
+        // The header of a long frame, cut short by a complete frame
+        let bytes = [&long[..6], frame.borrow(), &[0; 7]].concat();
+
+        let (frames, consumed) = parse_all(&bytes);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].data(), frame.data());
+        assert_eq!(consumed, bytes.len());
+    }
+}
@@ -0,0 +1,185 @@
+//! Frames in fixed-size buffers, without heap allocation
+//!
+//! A [`FixedFrame<N>`] holds a frame of up to `N` bytes, sync byte, header and CRCs
+//! included, i.e. up to `N - 7` bytes of data and optional data. The ERP1 telegrams of
+//! the common profiles fit in 40 bytes. Frames are read with a [`FixedFrameDecoder<N>`],
+//! and decoded into packets through [`FixedFrame::as_ref`], like [`ESP3Frame`]s.
+//!
+//! ```
+//! # use enocean::frame::fixed::*;
+//! # use enocean::packet::Packet;
+//! # let received = [0, 85, 0, 7, 7, 1, 122, 246, 48, 0, 49, 192, 249, 48, 1, 255, 255, 255, 255, 51, 0, 144];
+//! let mut decoder = FixedFrameDecoder::<40>::new();
+//! for byte in received {
+//!     if let Some(Ok(frame)) = decoder.push(byte) {
+//!         assert_eq!(frame.packet_type(), 0x01);
+//!         let packet = Packet::decode(frame.as_ref());
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+use super::{frame_length, ESP3Frame, ESP3FrameRef};
+use crate::crc8::compute_crc8;
+
+/// Errors of a [`FixedFrameDecoder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FixedFrameError {
+    /// The data CRC of the frame was incorrect
+    DataCRC { data_crc: u8 },
+    /// The frame does not fit in the buffer; it is skipped
+    TooLong { length: usize },
+}
+
+impl fmt::Display for FixedFrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DataCRC { .. }     => write!(f, "Bad CRC for data"),
+            Self::TooLong { length } => write!(f, "Frame of {} bytes too long for the buffer", length),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FixedFrameError {}
+
+/// A CRC-checked ESP3 frame of up to `N` bytes
+#[derive(Clone, Copy)]
+pub struct FixedFrame<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedFrame<N> {
+    /// Copy a frame, if it fits in `N` bytes
+    pub fn assemble(frame: ESP3FrameRef) -> Option<Self> {
+        let len = 6 + frame.data.len() + frame.optional_data.len() + 1;
+        if len > N {
+            return None;
+        }
+        let mut buffer = [0; N];
+        buffer[..6].copy_from_slice(&frame.header());
+        buffer[6..][..frame.data.len()].copy_from_slice(frame.data);
+        buffer[6 + frame.data.len()..len - 1].copy_from_slice(frame.optional_data);
+        buffer[len - 1] = frame.data_crc();
+        Some(Self { buffer, len })
+    }
+
+    pub fn packet_type(&self) -> u8 {
+        self.buffer[4]
+    }
+
+    fn data_length(&self) -> usize {
+        ((self.buffer[1] as usize) << 8) + (self.buffer[2] as usize)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.buffer[6..][..self.data_length()]
+    }
+
+    pub fn optional_data(&self) -> &[u8] {
+        &self.buffer[6 + self.data_length()..self.len - 1]
+    }
+
+    /// The complete frame, to be written to the link
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    pub fn as_ref(&self) -> ESP3FrameRef<'_> {
+        ESP3FrameRef { packet_type: self.packet_type(), data: self.data(), optional_data: self.optional_data() }
+    }
+
+    pub fn to_owned(&self) -> ESP3Frame {
+        self.as_ref().to_owned()
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedFrame<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FixedFrame").field("frame", &self.as_bytes()).finish()
+    }
+}
+
+/// Decodes frames of up to `N` bytes from bytes received one at a time, like
+/// [`super::FrameDecoder`], without allocating
+#[derive(Debug, Clone)]
+pub struct FixedFrameDecoder<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for FixedFrameDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> FixedFrameDecoder<N> {
+    /// A decoder for frames of up to `N` bytes. `N` must hold at least a frame without data.
+    pub fn new() -> Self {
+        assert!(N >= 7, "buffer too small for a frame");
+        Self { buffer: [0; N], len: 0 }
+    }
+
+    /// Feed the next received byte. Returns the frame it completes, if any.
+    pub fn push(&mut self, byte: u8) -> Option<Result<FixedFrame<N>, FixedFrameError>> {
+        self.buffer[self.len] = byte;
+        self.len += 1;
+
+        // Drop bytes until the buffer starts with a sync byte followed by a valid header
+        while self.len > 0
+            && (self.buffer[0] != 0x55 || (self.len >= 6 && compute_crc8(&self.buffer[1..6]) != 0)) {
+            self.buffer.copy_within(1..self.len, 0);
+            self.len -= 1;
+        }
+        if self.len < 6 {
+            return None;
+        }
+
+        let length = frame_length(&self.buffer);
+        if length > N {
+            self.len = 0;
+            return Some(Err(FixedFrameError::TooLong { length }));
+        }
+        if self.len < length {
+            return None;
+        }
+        self.len = 0;
+        let data_crc = compute_crc8(&self.buffer[6..length]);
+        if data_crc != 0 {
+            return Some(Err(FixedFrameError::DataCRC { data_crc }));
+        }
+        Some(Ok(FixedFrame { buffer: self.buffer, len: length }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use core::borrow::Borrow;
+
+    #[test]
+    fn given_frames_then_decode_them_in_place() {
+        let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x2d, 0x00]);
+        let long = ESP3Frame::assemble(0x01, &[0; 20], &[]);
+        let bytes: &[u8] = frame.borrow();
+
+        let mut decoder = FixedFrameDecoder::<24>::new();
+        let mut results = Vec::new();
+        for &byte in [&[0x00, 0x55][..], long.borrow(), bytes].concat().iter() {
+            results.extend(decoder.push(byte));
+        }
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], Err(FixedFrameError::TooLong { length: 27 })));
+        let decoded = results[1].unwrap();
+        assert_eq!(decoded.as_bytes(), bytes);
+        assert_eq!(decoded.optional_data(), frame.optional_data());
+        assert_eq!(FixedFrame::<24>::assemble(frame.as_ref()).unwrap().as_bytes(), bytes);
+        assert!(FixedFrame::<24>::assemble(long.as_ref()).is_none());
+    }
+}
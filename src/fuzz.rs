@@ -0,0 +1,115 @@
+//! Properties of the encoders and decoders, for fuzzing (feature `arbitrary`)
+//!
+//! Frames, packets and the values of the profile decoders implement
+//! [`arbitrary::Arbitrary`]. Each function below checks a property on such values, and
+//! panics if it does not hold. They can be called from `cargo fuzz` targets:
+//!
+//! ```ignore
+//! #![no_main]
+//! use enocean::packet::Packet;
+//!
+//! libfuzzer_sys::fuzz_target!(|packet: Packet| enocean::fuzz::packet_round_trip(&packet));
+//! ```
+
+use core::borrow::Borrow;
+
+use crate::eep::a5_38::CentralCommand;
+use crate::eep::{self, Decoded};
+use crate::enocean::Rorg;
+use crate::frame::{ESP3Frame, FrameDecoder};
+use crate::packet::{Address, EEPProfileCode, Packet, RadioErp1};
+
+/// A frame read back from its bytes is the frame written
+pub fn frame_round_trip(frame: &ESP3Frame) {
+    let bytes: &[u8] = frame.borrow();
+    let mut decoder = FrameDecoder::new();
+    let read: Vec<_> = bytes.iter().filter_map(|&byte| decoder.push(byte)).collect();
+    assert_eq!(read.len(), 1, "{:02x?}", bytes);
+    assert_eq!(Borrow::<[u8]>::borrow(read[0].as_ref().unwrap()), bytes);
+}
+
+/// A packet decoded from its encoding encodes the same
+pub fn packet_round_trip(packet: &Packet) {
+    let frame = packet.encode();
+    let decoded = Packet::decode(frame.as_ref()).unwrap_or_else(|e| panic!("{:?} does not decode: {}", packet, e));
+    assert_eq!(Borrow::<[u8]>::borrow(&decoded.encode()), Borrow::<[u8]>::borrow(&frame), "{:?}", packet);
+}
+
+/// The profile decoders return an error, without panicking, on the telegrams they do not expect
+pub fn profile_decode(profile: EEPProfileCode, packet: &Packet) -> Option<Decoded> {
+    match packet {
+        Packet::RadioErp1(erp) => eep::decode(profile, erp).ok(),
+        _ => None,
+    }
+}
+
+/// A central command is decoded as sent, with the relative dimming values limited to 100 %
+pub fn central_command_round_trip(command: CentralCommand) {
+    let user_data = command.encode();
+    let erp = RadioErp1::addressed(Rorg::Bs4, &user_data, Address::new(0), Address::new(0));
+    let expected = match command {
+        CentralCommand::Dimming { on, value, relative: true, ramp, store } =>
+            CentralCommand::Dimming { on, value: value.min(100), relative: true, ramp, store },
+        command => command,
+    };
+    assert_eq!(CentralCommand::decode(&erp).ok(), Some(expected), "{:?}", command);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arbitrary::{Arbitrary, Unstructured};
+
+    /// Util : pseudo-random numbers (xorshift)
+    fn random() -> impl FnMut() -> u64 {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    #[test]
+    fn given_mutated_frames_then_round_trips_hold() {
+        let seeds = [
+            ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x2d, 0x00]),
+            ESP3Frame::assemble(0x01, &[0xa5, 0x00, 0x00, 0x55, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x2d, 0x00]),
+            ESP3Frame::assemble(0x01, &[0xd2, 0x04, 0x60, 0xe4, 0x05, 0x12, 0x34, 0x56, 0x00], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x40, 0x00]),
+            ESP3Frame::assemble(0x02, &[0x00, 0xff, 0x80, 0x00, 0x00], &[]),
+            ESP3Frame::assemble(0x04, &[0x04, 0x00], &[0x01]),
+        ];
+        let profiles = ["F6-02-01", "A5-02-05", "A5-38-08", "D2-01-12", "D2-05-00"].map(|p| p.parse().unwrap());
+        let mut next = random();
+
+        for seed in &seeds {
+            for _ in 0..1000 {
+                let mut bytes = [seed.data(), seed.optional_data()].concat();
+                let index = next() as usize % bytes.len();
+                bytes[index] = next() as u8;
+                let split = seed.data().len();
+                let frame = ESP3Frame::assemble(seed.packet_type(), &bytes[..split], &bytes[split..]);
+
+                frame_round_trip(&frame);
+                if let Ok(packet) = Packet::decode(frame.as_ref()) {
+                    packet_round_trip(&packet);
+                    for profile in profiles {
+                        let _ = profile_decode(profile, &packet);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn given_arbitrary_commands_then_round_trips_hold() {
+        let mut next = random();
+        for _ in 0..1000 {
+            let input = next().to_le_bytes();
+            if let Ok(command) = CentralCommand::arbitrary(&mut Unstructured::new(&input)) {
+                central_command_round_trip(command);
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Home Assistant MQTT discovery (feature `serde`)
+//!
+//! Builds the discovery config messages of the devices of a [`DeviceRegistry`], so that
+//! Home Assistant creates their entities. No MQTT client is bundled: the application publishes
+//! them, retained, with its own, and publishes the [`crate::eep::Report`] of each telegram as
+//! JSON on the state topic of its sender:
+//!
+//! | Topic                                                 | Payload                       |
+//! |-------------------------------------------------------|-------------------------------|
+//! | `homeassistant/sensor/enocean_0180998e/temperature/config` | [`DiscoveryConfig`], JSON |
+//! | `enocean/0180998e`                                    | `Report`, JSON                |
+//!
+//! Only profiles with a typed decoder and a matching Home Assistant entity are announced.
+//! Values a profile reports only sometimes are `null` in the other reports; their templates
+//! render `none` then, and Home Assistant shows the entity as unknown.
+
+use serde::Serialize;
+
+use crate::packet::{Address, EEPProfileCode};
+use crate::registry::DeviceRegistry;
+
+/// A Home Assistant entity of a profile
+struct Entity {
+    component: &'static str,
+    object: &'static str,
+    device_class: Option<&'static str>,
+    unit: Option<&'static str>,
+    /// Template over the JSON report
+    value_template: &'static str,
+}
+
+const fn sensor(object: &'static str, device_class: Option<&'static str>, unit: Option<&'static str>, value_template: &'static str) -> Entity {
+    Entity { component: "sensor", object, device_class, unit, value_template }
+}
+
+const fn binary_sensor(object: &'static str, device_class: &'static str, value_template: &'static str) -> Entity {
+    Entity { component: "binary_sensor", object, device_class: Some(device_class), unit: None, value_template }
+}
+
+const TEMPERATURE: Entity = sensor("temperature", Some("temperature"), Some("°C"), "{{ value_json.values.Temperature.value }}");
+
+const HUMIDITY_TEMPERATURE: Entity = sensor("temperature", Some("temperature"), Some("°C"), concat!(
+    "{{ value_json.values.TemperatureHumidity.temperature.value ",
+    "if value_json.values.TemperatureHumidity.temperature else none }}"));
+const HUMIDITY: Entity = sensor("humidity", Some("humidity"), Some("%"), "{{ value_json.values.TemperatureHumidity.humidity.value }}");
+
+const LIGHT: Entity = sensor("illuminance", Some("illuminance"), Some("lx"), "{{ value_json.values.Light.illuminance.value }}");
+
+const OCCUPANCY: Entity = binary_sensor("motion", "motion", "{{ 'ON' if value_json.values.Occupancy.motion else 'OFF' }}");
+
+const MULTI_ILLUMINANCE: Entity = sensor("illuminance", Some("illuminance"), Some("lx"),
+    "{{ value_json.values.LightTemperatureOccupancy.illuminance.value }}");
+const MULTI_TEMPERATURE: Entity = sensor("temperature", Some("temperature"), Some("°C"),
+    "{{ value_json.values.LightTemperatureOccupancy.temperature.value }}");
+const MULTI_MOTION: Entity = binary_sensor("motion", "motion",
+    "{{ 'ON' if value_json.values.LightTemperatureOccupancy.motion else 'OFF' }}");
+
+const CONTACT: Entity = binary_sensor("contact", "door", "{{ 'ON' if value_json.values.Contact == 'Open' else 'OFF' }}");
+
+const HANDLE: Entity = sensor("handle", None, None, "{{ value_json.values.HandlePosition }}");
+
+/// Entities announced for a profile
+fn entities(eep: EEPProfileCode) -> &'static [Entity] {
+    match (eep.rorg(), eep.func(), eep.eep_type()) {
+        (0xA5, 0x02, _)    => &[TEMPERATURE],
+        (0xA5, 0x04, _)    => &[HUMIDITY_TEMPERATURE, HUMIDITY],
+        (0xA5, 0x06, _)    => &[LIGHT],
+        (0xA5, 0x07, _)    => &[OCCUPANCY],
+        (0xA5, 0x08, _)    => &[MULTI_ILLUMINANCE, MULTI_TEMPERATURE, MULTI_MOTION],
+        (0xD5, 0x00, 0x01) => &[CONTACT],
+        (0xF6, 0x10, _)    => &[HANDLE],
+        _ => &[],
+    }
+}
+
+/// The device an entity belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiscoveryDevice {
+    pub identifiers: Vec<String>,
+    pub name: String,
+    pub model: String,
+    pub manufacturer: String,
+}
+
+/// Payload of a discovery message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiscoveryConfig {
+    pub name: String,
+    pub unique_id: String,
+    pub state_topic: String,
+    pub value_template: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_of_measurement: Option<String>,
+    pub device: DiscoveryDevice,
+}
+
+/// A discovery config message, to be published retained
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryMessage {
+    pub topic: String,
+    pub config: DiscoveryConfig,
+}
+
+/// Topics used for discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovery {
+    discovery_prefix: String,
+    state_prefix: String,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Discovery {
+    /// Discovery under `homeassistant`, states under `enocean`
+    pub fn new() -> Self {
+        Self { discovery_prefix: "homeassistant".into(), state_prefix: "enocean".into() }
+    }
+
+    pub fn with_discovery_prefix(mut self, prefix: &str) -> Self {
+        self.discovery_prefix = prefix.into();
+        self
+    }
+
+    pub fn with_state_prefix(mut self, prefix: &str) -> Self {
+        self.state_prefix = prefix.into();
+        self
+    }
+
+    /// Topic to publish the reports of a device on
+    pub fn state_topic(&self, address: &Address) -> String {
+        format!("{}/{}", self.state_prefix, address)
+    }
+
+    /// Discovery messages of all the devices of `registry` with a supported profile
+    pub fn messages(&self, registry: &DeviceRegistry) -> Vec<DiscoveryMessage> {
+        let mut messages = Vec::new();
+        for (address, entry) in registry.iter() {
+            let node = format!("enocean_{}", address);
+            let name = entry.name.clone().unwrap_or_else(|| format!("EnOcean {}", address));
+            for entity in entities(entry.eep) {
+                messages.push(DiscoveryMessage {
+                    topic: format!("{}/{}/{}/{}/config", self.discovery_prefix, entity.component, node, entity.object),
+                    config: DiscoveryConfig {
+                        name: format!("{} {}", name, entity.object),
+                        unique_id: format!("{}_{}", node, entity.object),
+                        state_topic: self.state_topic(address),
+                        value_template: entity.value_template.into(),
+                        device_class: entity.device_class.map(Into::into),
+                        unit_of_measurement: entity.unit.map(Into::into),
+                        device: DiscoveryDevice {
+                            identifiers: vec![node.clone()],
+                            name: name.clone(),
+                            model: entry.eep.to_string(),
+                            manufacturer: "EnOcean".into(),
+                        },
+                    },
+                });
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::DeviceEntry;
+
+    #[test]
+    fn given_registry_then_announce_supported_devices() {
+        let mut registry = DeviceRegistry::new();
+        registry.insert("0180998e".parse().unwrap(), DeviceEntry { name: Some("Bathroom".into()), ..DeviceEntry::new("A5-04-01".parse().unwrap()) });
+        registry.insert("fef58ff5".parse().unwrap(), DeviceEntry::new("F6-02-01".parse().unwrap()));
+
+        let messages = Discovery::new().messages(&registry);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].topic, "homeassistant/sensor/enocean_0180998e/humidity/config");
+        assert_eq!(messages[1].config.state_topic, "enocean/0180998e");
+        assert_eq!(serde_json::to_string(&messages[1].config).unwrap(), concat!(
+            r#"{"name":"Bathroom humidity","unique_id":"enocean_0180998e_humidity","state_topic":"enocean/0180998e","#,
+            r#""value_template":"{{ value_json.values.TemperatureHumidity.humidity.value }}","device_class":"humidity","#,
+            r#""unit_of_measurement":"%","device":{"identifiers":["enocean_0180998e"],"name":"Bathroom","model":"A5-04-01","manufacturer":"EnOcean"}}"#));
+        assert_eq!(messages[0].config.value_template,
+            "{{ value_json.values.TemperatureHumidity.temperature.value if value_json.values.TemperatureHumidity.temperature else none }}");
+    }
+}
@@ -0,0 +1,108 @@
+//! InfluxDB line protocol (feature `influxdb`)
+//!
+//! A [`LineProtocol`] renders each [`Report`] as a line, with a measurement per profile,
+//! the sender (and the gateway, if given) as tags, and a field per value, suffixed with its
+//! unit:
+//!
+//! ```text
+//! enocean_a5_04_01,sender=0180998e,gateway=ff9b1280 humidity[%]=45.2,temperature[°C]=21.5 1697371200123000000
+//! ```
+//!
+//! The lines are written to the InfluxDB HTTP API (`/api/v2/write`) or to Telegraf by the
+//! application. Reports without numeric values give no line.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::eep::{DataPoint, Report};
+use crate::packet::{Address, EEPProfileCode};
+
+/// Formatter of reports as InfluxDB lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineProtocol {
+    prefix: String,
+    gateway: Option<Address>,
+}
+
+impl Default for LineProtocol {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineProtocol {
+    /// Measurements named `enocean_<profile>`, without gateway tag
+    pub fn new() -> Self {
+        Self { prefix: "enocean".into(), gateway: None }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Tag the lines with the address of the gateway, e.g. its base ID
+    pub fn with_gateway(mut self, gateway: Address) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    /// The line of a report received at `timestamp`, `None` if it has no numeric value
+    pub fn line(&self, report: &Report, timestamp: SystemTime) -> Option<String> {
+        self.render(report.eep, report.sender, &report.data_points(), timestamp)
+    }
+
+    fn render(&self, eep: EEPProfileCode, sender: Address, points: &[DataPoint], timestamp: SystemTime) -> Option<String> {
+        if points.is_empty() {
+            return None;
+        }
+        let mut line = format!("{}_{:02x}_{:02x}_{:02x},sender={}", escape(&self.prefix, ", "), eep.rorg(), eep.func(), eep.eep_type(), sender);
+        if let Some(gateway) = self.gateway {
+            line += &format!(",gateway={}", gateway);
+        }
+        for (index, point) in points.iter().enumerate() {
+            let key = match &point.unit {
+                Some(unit) => format!("{}[{}]", point.field, unit),
+                None => point.field.clone(),
+            };
+            line += if index == 0 { " " } else { "," };
+            line += &format!("{}={}", escape(&key, ",= "), point.value);
+        }
+        let nanos = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        Some(format!("{} {}", line, nanos))
+    }
+}
+
+/// Util : backslash-escape the `special` characters of a name
+fn escape(name: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c == '\\' || special.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn given_data_points_then_render_a_line() {
+        let points = [
+            DataPoint { field: "humidity".into(), value: 45.2, unit: Some("%".into()) },
+            DataPoint { field: "temperature".into(), value: 21.5, unit: Some("°C".into()) },
+            DataPoint { field: "window open".into(), value: 1.0, unit: None },
+        ];
+        let timestamp = UNIX_EPOCH + Duration::from_millis(1697371200123);
+        let protocol = LineProtocol::new().with_gateway("ff9b1280".parse().unwrap());
+
+        assert_eq!(
+            protocol.render("A5-04-01".parse().unwrap(), "0180998e".parse().unwrap(), &points, timestamp).unwrap(),
+            "enocean_a5_04_01,sender=0180998e,gateway=ff9b1280 humidity[%]=45.2,temperature[°C]=21.5,window\\ open=1 1697371200123000000"
+        );
+        assert_eq!(protocol.render("A5-04-01".parse().unwrap(), "0180998e".parse().unwrap(), &[], timestamp), None);
+    }
+}
@@ -0,0 +1,163 @@
+//! Periodic re-sending of commands
+//!
+//! Some actuators fall back to a default unless they receive a command regularly: A5-20
+//! valve actuators expect an answer to each of their wake telegrams, and some D2 devices a
+//! command at least every few minutes. A [`KeepAlive`] remembers the last command sent to
+//! each scheduled device, and tells when to send it again: after an interval, or as soon as
+//! the device sends a telegram. It does no I/O; see `device::Gateway::with_keepalive` for
+//! the gateway sending the commands.
+//!
+//! ```
+//! use enocean::enocean::Rorg;
+//! use enocean::keepalive::{KeepAlive, Trigger};
+//! use std::time::{Duration, Instant};
+//!
+//! let valve = "05123456".parse()?;
+//! let mut keepalive = KeepAlive::new();
+//! keepalive.schedule(valve, Trigger::Interval(Duration::from_secs(600)));
+//!
+//! let start = Instant::now();
+//! keepalive.remember(valve, Rorg::Bs4, &[0x46, 0x00, 0x00, 0x08], start);
+//! assert!(keepalive.due(start).is_empty());
+//! assert_eq!(keepalive.due(start + Duration::from_secs(600))[0].destination, valve);
+//! keepalive.sent(&valve, start + Duration::from_secs(600));
+//! assert!(keepalive.due(start + Duration::from_secs(600)).is_empty());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::enocean::Rorg;
+use crate::packet::{Address, RadioErp1};
+
+/// When to send the last command again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// Once the interval elapsed since it was last sent
+    Interval(Duration),
+    /// Whenever the device sends a telegram, e.g. the status of an A5-20 actuator
+    OnTelegram,
+}
+
+/// A command to send again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resend {
+    pub destination: Address,
+    pub rorg: Rorg,
+    pub user_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    trigger: Trigger,
+    command: Option<(Rorg, Vec<u8>)>,
+    sent: Option<Instant>,
+}
+
+impl Entry {
+    fn resend(&self, destination: Address) -> Option<Resend> {
+        let (rorg, user_data) = self.command.clone()?;
+        Some(Resend { destination, rorg, user_data })
+    }
+}
+
+/// The last commands of the scheduled devices
+#[derive(Debug, Clone, Default)]
+pub struct KeepAlive {
+    entries: BTreeMap<Address, Entry>,
+}
+
+impl KeepAlive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the commands to `device` again on `trigger`, once one was sent
+    pub fn schedule(&mut self, device: Address, trigger: Trigger) {
+        self.entries.entry(device)
+            .and_modify(|entry| entry.trigger = trigger)
+            .or_insert(Entry { trigger, command: None, sent: None });
+    }
+
+    /// Stop sending commands to `device` again
+    pub fn unschedule(&mut self, device: &Address) {
+        self.entries.remove(device);
+    }
+
+    pub fn is_scheduled(&self, device: &Address) -> bool {
+        self.entries.contains_key(device)
+    }
+
+    /// Record a command sent to `device` at `now`, if scheduled
+    pub fn remember(&mut self, device: Address, rorg: Rorg, user_data: &[u8], now: Instant) {
+        if let Some(entry) = self.entries.get_mut(&device) {
+            entry.command = Some((rorg, user_data.to_vec()));
+            entry.sent = Some(now);
+        }
+    }
+
+    /// Record the command of `device` sent again at `now`
+    pub fn sent(&mut self, device: &Address, now: Instant) {
+        if let Some(entry) = self.entries.get_mut(device) {
+            entry.sent = Some(now);
+        }
+    }
+
+    /// The commands whose interval elapsed at `now`. They stay due until recorded with
+    /// [`Self::sent`].
+    pub fn due(&self, now: Instant) -> Vec<Resend> {
+        self.entries.iter()
+            .filter(|(_, entry)| match (entry.trigger, entry.sent) {
+                (Trigger::Interval(interval), Some(sent)) => now.saturating_duration_since(sent) >= interval,
+                _ => false,
+            })
+            .filter_map(|(address, entry)| entry.resend(*address))
+            .collect()
+    }
+
+    /// The command to answer a telegram with, if its sender is scheduled on
+    /// [`Trigger::OnTelegram`]
+    pub fn on_telegram(&self, erp: &RadioErp1) -> Option<Resend> {
+        let entry = self.entries.get(&erp.sender_id)?;
+        if entry.trigger != Trigger::OnTelegram {
+            return None;
+        }
+        entry.resend(erp.sender_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_scheduled_devices_then_resend_last_command() {
+        let start = Instant::now();
+        let (valve, relay) = ("05123456".parse().unwrap(), "0180998e".parse().unwrap());
+        let mut keepalive = KeepAlive::new();
+        keepalive.schedule(valve, Trigger::OnTelegram);
+        keepalive.schedule(relay, Trigger::Interval(Duration::from_secs(60)));
+
+        let status = erp1(&[0xa5, 0x32, 0x00, 0x80, 0x08, 0x05, 0x12, 0x34, 0x56, 0x00]);
+        assert_eq!(keepalive.on_telegram(&status), None);
+
+        keepalive.remember(valve, Rorg::Bs4, &[0x46, 0x00, 0x00, 0x08], start);
+        keepalive.remember(relay, Rorg::Vld, &[0x01, 0x1e, 0x64], start);
+        keepalive.remember("fefa1234".parse().unwrap(), Rorg::Vld, &[0x01, 0x1e, 0x00], start);
+        assert_eq!(
+            keepalive.on_telegram(&status),
+            Some(Resend { destination: valve, rorg: Rorg::Bs4, user_data: vec![0x46, 0x00, 0x00, 0x08] })
+        );
+
+        assert!(keepalive.due(start + Duration::from_secs(59)).is_empty());
+        let due = keepalive.due(start + Duration::from_secs(60));
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].destination, due[0].rorg), (relay, Rorg::Vld));
+        // Not sent, e.g. refused by the transceiver: still due
+        assert_eq!(keepalive.due(start + Duration::from_secs(61)), due);
+        keepalive.sent(&relay, start + Duration::from_secs(61));
+        assert!(keepalive.due(start + Duration::from_secs(90)).is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+//! Learning devices
+//!
+//! A [`LearnManager`] opens a learn window, recognizes teach-in telegrams of all kinds,
+//! builds the responses bidirectional devices wait for, and reports each new device once.
+//!
+//! ```no_run
+//! use enocean::learn::LearnManager;
+//! use enocean::port::Port;
+//! use std::time::Duration;
+//!
+//! let mut port = Port::open("/dev/ttyUSB0")?;
+//! let mut learn = LearnManager::new("ff9b1280".parse()?);
+//! learn.start(Duration::from_secs(30));
+//! while let Some(device) = learn.run(&mut port)? {
+//!     println!("Learned {} ({:?})", device.address, device.eep);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! RPS devices (rocker switches, window handles, ...) have no teach-in telegram: any
+//! telegram received while learn mode is active is a candidate for enrollment.
+//! [`RpsCandidate`] classifies such a telegram from its status byte, so that the
+//! application can confirm which profile to enroll the device with:
+//!
+//! | Status bit | Name | Meaning                                                       |
+//! |------------|------|---------------------------------------------------------------|
+//! | 5          | T21  | 1 = PTM 2xx module (two rockers), 0 = PTM 1xx module          |
+//! | 4          | NU   | 1 = N-message (identified buttons), 0 = U-message             |
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::eep::teach_in::{TeachIn, TeachInResult4BS, UteRequestType, UteResult};
+use crate::enocean::Rorg;
+use crate::frame::ESP3Frame;
+use crate::packet::{Address, EEPProfileCode, Event, RadioErp1};
+use crate::smart_ack::{LearnDecision, Postmaster};
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+use crate::{packet::Packet, port::Port, FrameReadError, PacketError};
+
+/// An RPS telegram received in learn mode, considered as a teach-in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpsCandidate {
+    pub sender: Address,
+    /// T21 : sent by a PTM 2xx module
+    pub ptm2xx: bool,
+    /// NU : N-message, whose data identifies the buttons
+    pub n_message: bool,
+    pub data: u8,
+}
+
+impl RpsCandidate {
+    /// Classify an RPS telegram, returning `None` for other RORGs.
+    ///
+    /// Only meaningful while learn mode is active: outside of it, RPS telegrams are data.
+    pub fn classify(erp: &RadioErp1) -> Option<Self> {
+        if erp.choice != Rorg::Rps || erp.user_data.len() != 1 {
+            return None;
+        }
+        Some(Self {
+            sender: erp.sender_id,
+            ptm2xx: erp.status_byte().t21(),
+            n_message: erp.status_byte().nu(),
+            data: erp.user_data[0],
+        })
+    }
+
+    /// The most likely profile of the sender, if the telegram is characteristic enough
+    pub fn likely_profile(&self) -> Option<EEPProfileCode> {
+        match (self.ptm2xx, self.n_message) {
+            // A button pressed on a rocker switch
+            (true, true) if self.data & 0x10 != 0 => Some(EEPProfileCode::new(0xF6, 0x02, 0x01)),
+            // Window handle positions
+            (true, false) if self.data & 0x0f == 0 && self.data >> 6 == 0b11 => Some(EEPProfileCode::new(0xF6, 0x10, 0x00)),
+            (false, _) => Some(EEPProfileCode::new(0xF6, 0x01, 0x01)),
+            _ => None,
+        }
+    }
+}
+
+/// A device enrolled during a learn window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceLearned {
+    pub address: Address,
+    /// Profile of the device. For RPS devices, this is only a guess.
+    pub eep: Option<EEPProfileCode>,
+    /// 11-bit manufacturer ID, when announced
+    pub manufacturer: Option<u16>,
+}
+
+/// What to do with a telegram received in learn mode
+#[derive(Debug, Clone, Default)]
+pub struct LearnOutcome {
+    pub learned: Option<DeviceLearned>,
+    /// Teach-in response to send back to the device
+    pub response: Option<ESP3Frame>,
+}
+
+/// Learn mode state machine
+#[derive(Debug, Clone)]
+pub struct LearnManager {
+    sender_id: Address,
+    deadline: Option<Instant>,
+    learned: HashSet<Address>,
+    postmaster: Option<Postmaster>,
+}
+
+impl LearnManager {
+    /// `sender_id` is the address responses are sent from, and that learned devices will
+    /// accept commands from (base ID or chip ID of the transceiver).
+    pub fn new(sender_id: Address) -> Self {
+        Self { sender_id, deadline: None, learned: HashSet::new(), postmaster: None }
+    }
+
+    /// Also learn Smart Ack clients, through the given postmaster.
+    /// The transceiver learn mode must be enabled separately with `SA_WR_LEARNMODE`.
+    pub fn with_postmaster(mut self, postmaster: Postmaster) -> Self {
+        self.postmaster = Some(postmaster);
+        self
+    }
+
+    pub fn postmaster(&self) -> Option<&Postmaster> {
+        self.postmaster.as_ref()
+    }
+
+    /// Open a learn window
+    pub fn start(&mut self, timeout: Duration) {
+        self.deadline = Some(Instant::now() + timeout);
+        self.learned.clear();
+    }
+
+    /// Close the learn window
+    pub fn stop(&mut self) {
+        self.deadline = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() < deadline)
+    }
+
+    /// Process a received telegram. Outside of the learn window, it is ignored.
+    pub fn handle(&mut self, erp: &RadioErp1) -> LearnOutcome {
+        let mut outcome = LearnOutcome::default();
+        if !self.is_active() {
+            return outcome;
+        }
+
+        let address = erp.sender_id;
+        let mut learned = DeviceLearned { address, eep: None, manufacturer: None };
+        match TeachIn::detect(erp) {
+            Some(TeachIn::FourBS(query)) => {
+                learned.eep = query.eep;
+                learned.manufacturer = query.manufacturer;
+                if query.eep.is_some() && query.response.is_none() {
+                    let result = TeachInResult4BS { eep_supported: true, learned: true };
+                    outcome.response = Some(self.respond(Rorg::Bs4, &query.response(result).encode(), address));
+                }
+            }
+            Some(TeachIn::Ute(request)) => {
+                learned.eep = Some(request.eep);
+                learned.manufacturer = Some(request.manufacturer);
+                let result = match request.request_type {
+                    UteRequestType::Deletion => UteResult::Deleted,
+                    _ => UteResult::Accepted,
+                };
+                if request.response_expected {
+                    outcome.response = Some(self.respond(Rorg::Ute, &request.response(result).encode(), address));
+                }
+                if result == UteResult::Deleted {
+                    return outcome;
+                }
+            }
+            Some(teach_in @ TeachIn::OneBS) => learned.eep = teach_in.eep(),
+            None => match RpsCandidate::classify(erp) {
+                Some(candidate) => learned.eep = candidate.likely_profile(),
+                None => return outcome,
+            },
+        }
+
+        if self.learned.insert(address) {
+            outcome.learned = Some(learned);
+        }
+        outcome
+    }
+
+    /// Process an event from the transceiver. Smart Ack learn requests are answered by the
+    /// postmaster even outside of the learn window, but only reported within it.
+    pub fn handle_event(&mut self, event: &Event) -> LearnOutcome {
+        let mut outcome = LearnOutcome::default();
+        let Some(postmaster) = &mut self.postmaster else { return outcome };
+
+        let result = postmaster.handle_event(event);
+        outcome.response = result.response.map(|response| response.encode());
+        if let Some(LearnDecision::LearnedIn(mailbox)) = result.decision {
+            if self.is_active() && self.learned.insert(mailbox.client) {
+                outcome.learned = Some(DeviceLearned {
+                    address: mailbox.client,
+                    eep: Some(mailbox.eep),
+                    manufacturer: Some(mailbox.manufacturer),
+                });
+            }
+        }
+        outcome
+    }
+
+    fn respond(&self, rorg: Rorg, user_data: &[u8], device: Address) -> ESP3Frame {
+        RadioErp1::addressed(rorg, user_data, self.sender_id, device).encode()
+    }
+
+    /// Receive telegrams from `port` until a device is learned or the learn window closes,
+    /// sending teach-in responses as needed. Returns `None` once the window is closed.
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    pub fn run(&mut self, port: &mut Port) -> Result<Option<DeviceLearned>, PacketError> {
+        while self.is_active() {
+            let frame = match port.read_frame() {
+                Ok(frame) => frame,
+                Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let outcome = match Packet::decode(frame.as_ref()) {
+                Ok(Packet::RadioErp1(erp)) => self.handle(&erp),
+                Ok(Packet::Event(event)) => self.handle_event(&event),
+                _ => continue,
+            };
+            if let Some(response) = outcome.response {
+                port.write_frame(&response)?;
+            }
+            if outcome.learned.is_some() {
+                return Ok(outcome.learned);
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_rps_telegrams_in_learn_mode_then_classify_them() {
+        let rocker = RpsCandidate::classify(&erp1(&[0xf6, 0x30, 0xfe, 0xf5, 0x8f, 0xf5, 0x30])).unwrap();
+        assert!(rocker.ptm2xx && rocker.n_message);
+        assert_eq!(rocker.sender.to_string(), "fef58ff5");
+        assert_eq!(rocker.likely_profile(), Some(EEPProfileCode::new(0xF6, 0x02, 0x01)));
+
+        let handle = RpsCandidate::classify(&erp1(&[0xf6, 0xd0, 1, 2, 3, 4, 0x20])).unwrap();
+        assert_eq!(handle.likely_profile(), Some(EEPProfileCode::new(0xF6, 0x10, 0x00)));
+
+        let released = RpsCandidate::classify(&erp1(&[0xf6, 0x00, 1, 2, 3, 4, 0x20])).unwrap();
+        assert_eq!(released.likely_profile(), None);
+        assert_eq!(RpsCandidate::classify(&erp1(&[0xd5, 0x09, 1, 2, 3, 4, 0])), None);
+    }
+
+    #[test]
+    fn given_learn_window_then_learn_each_device_once_and_respond() {
+        let mut learn = LearnManager::new("ff9b1280".parse().unwrap());
+        let query = erp1(&[0xa5, 0x80, 0x08, 0x0d, 0x80, 1, 2, 3, 4, 0]);
+        assert!(learn.handle(&query).learned.is_none());
+
+        learn.start(Duration::from_secs(60));
+        let outcome = learn.handle(&query);
+        let learned = outcome.learned.unwrap();
+        assert_eq!(learned.eep, Some(EEPProfileCode::new(0xA5, 0x20, 0x01)));
+        assert_eq!(learned.manufacturer, Some(0x00D));
+
+        let response = outcome.response.unwrap();
+        assert_eq!(response.data(), &[0xa5, 0x80, 0x08, 0x0d, 0xf0, 0xff, 0x9b, 0x12, 0x80, 0x00]);
+        assert_eq!(&response.optional_data()[1..5], &[1, 2, 3, 4]);
+
+        assert!(learn.handle(&query).learned.is_none());
+        learn.stop();
+        assert!(!learn.is_active());
+    }
+
+    #[test]
+    fn given_ute_deletion_request_then_respond_without_learning() {
+        let mut learn = LearnManager::new("ff9b1280".parse().unwrap());
+        learn.start(Duration::from_secs(60));
+        let outcome = learn.handle(&erp1(&[0xd4, 0x90, 0x01, 0x46, 0x00, 0x0e, 0x01, 0xd2, 5, 10, 61, 106, 0]));
+        assert!(outcome.learned.is_none());
+        assert_eq!(outcome.response.unwrap().data()[1], 0xa1);
+    }
+}
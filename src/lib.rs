@@ -1,21 +1,97 @@
+//! Without the default `std` feature, the protocol core (frames, packets, EEP decoders)
+//! builds with `no_std` + `alloc`; frames are then read with [`frame::FrameDecoder`].
+//!
+//! The serial link is blocking with the default `sync` feature ([`port::Port`]), or async
+//! with the `tokio` feature (`tokio_port::TokioPort`); both are built on [`link::Link`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate alloc;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
 extern crate serialport;
 
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::error::Error as StdError;
-use std::fmt;
-
-use thiserror::Error;
 
 // Differents file which should be linked
+#[cfg(feature = "std")]
+pub mod actuator;
+#[cfg(feature = "std")]
+pub mod anomaly;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod capture;
+#[cfg(feature = "std")]
+pub mod cdm;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+pub mod config;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
 pub mod communicator;
 pub mod crc8;
+#[cfg(feature = "std")]
+pub mod deadband;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+pub mod device;
 pub mod eep;
+#[cfg(feature = "embassy")]
+pub mod embassy;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+pub mod endpoint;
 pub mod enocean;
+pub mod filter;
 pub mod frame;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(all(feature = "std", feature = "serde"))]
+pub mod homeassistant;
+#[cfg(feature = "influxdb")]
+pub mod influxdb;
+#[cfg(feature = "std")]
+pub mod keepalive;
+#[cfg(feature = "std")]
+pub mod learn;
+pub mod link;
+pub mod manufacturer;
+pub mod msc;
 pub mod packet;
+#[cfg(feature = "std")]
+pub mod path;
+#[cfg(feature = "std")]
+pub mod poll;
+#[cfg(feature = "std")]
+pub mod presence;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
 pub mod port;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod quality;
+#[cfg(feature = "std")]
+pub mod registry;
+pub mod reman;
+#[cfg(feature = "security")]
+pub mod security;
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+pub mod sim;
+pub mod signal;
+pub mod smart_ack;
+#[cfg(feature = "tokio")]
+pub mod tokio_port;
+#[cfg(feature = "std")]
+pub mod traffic;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-/// Custom Result type = std::result::Result<T, ParseEspError>
-type ParseEspResult<T> = std::result::Result<T, ParseEspError>;
+/// Custom Result type = core::result::Result<T, ParseEspError>
+type ParseEspResult<T> = core::result::Result<T, ParseEspError>;
 
 /// Custom error type (eg. allow to see corresponding packet / byte index )
 #[derive(Debug, Clone)]
@@ -42,21 +118,116 @@ pub enum ParseEspErrorKind {
 /// There is no variant for sync byte or header CRC errors; these are
 /// treated as synchronization failures and just cause the reader to
 /// try to resync.
-#[derive(Debug, Error)]
+#[derive(Debug)]
 pub enum FrameReadError {
     /// The reader returned an IO Error
-    #[error("IO Error")]            IOError(#[from] std::io::Error),
+    #[cfg(feature = "std")]
+    IOError(std::io::Error),
     /// The reader reached end of stream before delivering a complete packet
-    #[error("End of Stream")]       EOF,
+    EOF,
     /// The data CRC of the packet was incorrect
-    #[error("Bad CRC for data")]    DataCRC{ frame: Vec<u8>, data_crc: u8 },
+    DataCRC{ frame: Vec<u8>, data_crc: u8 },
 }
 
-#[derive(Debug,Error)]
+impl fmt::Display for FrameReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::IOError(_)     => write!(f, "IO Error"),
+            Self::EOF            => write!(f, "End of Stream"),
+            Self::DataCRC { .. } => write!(f, "Bad CRC for data"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for FrameReadError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            #[cfg(feature = "std")]
+            Self::IOError(_)               => defmt::write!(f, "IO Error"),
+            Self::EOF                      => defmt::write!(f, "End of Stream"),
+            Self::DataCRC { data_crc, .. } => defmt::write!(f, "Bad CRC for data ({=u8:#x})", *data_crc),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl StdError for FrameReadError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::IOError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for FrameReadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+#[derive(Debug)]
 pub enum PacketError {
-    #[error("Could not read frame")]  FrameError(#[from] FrameReadError),
-    #[error("Could not parse frame")] ParseError(#[from] packet::ParseError),
-    #[error("IO Error")]              IOError(#[from] std::io::Error),
+    FrameError(FrameReadError),
+    ParseError(packet::ParseError),
+    #[cfg(feature = "std")]
+    IOError(std::io::Error),
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::FrameError(_) => write!(f, "Could not read frame"),
+            Self::ParseError(_) => write!(f, "Could not parse frame"),
+            #[cfg(feature = "std")]
+            Self::IOError(_)    => write!(f, "IO Error"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PacketError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::FrameError(e) => defmt::write!(f, "Could not read frame: {}", e),
+            Self::ParseError(e) => defmt::write!(f, "Could not parse frame: {}", e),
+            #[cfg(feature = "std")]
+            Self::IOError(_)    => defmt::write!(f, "IO Error"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl StdError for PacketError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::FrameError(e) => Some(e),
+            Self::ParseError(e) => Some(e),
+            Self::IOError(e)    => Some(e),
+        }
+    }
+}
+
+impl From<FrameReadError> for PacketError {
+    fn from(e: FrameReadError) -> Self {
+        Self::FrameError(e)
+    }
+}
+
+impl From<packet::ParseError> for PacketError {
+    fn from(e: packet::ParseError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for PacketError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
 }
 
 impl fmt::Display for ParseEspError {
@@ -77,6 +248,7 @@ impl fmt::Display for ParseEspError {
         }
     }
 }
+#[cfg(feature = "std")]
 impl StdError for ParseEspError {
     fn description(&self) -> &str {
         &self.message
@@ -0,0 +1,223 @@
+//! Framing and correlation shared by the serial links
+//!
+//! Every transport, blocking ([`crate::port::Port`], [`crate::embedded::EmbeddedPort`]) or
+//! async ([`crate::tokio_port::TokioPort`], [`crate::embassy::AsyncPort`]), reads bytes from
+//! its serial link into a [`Link`], and takes complete frames out of it. While waiting for
+//! the response to a command, or for the report on a telegram sent, the other frames
+//! received are set aside, and returned by the next reads. The transports only differ by
+//! how they read and write bytes:
+//!
+//! ```ignore
+//! pub fn write_packet(&mut self, packet: Packet) -> Result<Response, Error> {
+//!     self.write_frame(&packet.encode())?;
+//!     loop {
+//!         if let Some(frame) = self.link.next_response() {
+//!             return Ok(Response::decode(frame?.as_ref())?);
+//!         }
+//!         let n = self.serial.read(&mut buffer)?;
+//!         self.link.feed(&buffer[..n]);
+//!     }
+//! }
+//! ```
+//!
+//! A tap set on the link ([`Link::set_tap`]) sees every complete frame as raw bytes,
+//! received or sent, including those failing their data CRC, without taking them from the
+//! decode path: capture tools and debugging proxies are layered on a transport this way.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use core::borrow::Borrow;
+use core::fmt;
+
+use crate::enocean::CommandError;
+use crate::frame::{ESP3Frame, FrameDecoder};
+use crate::packet::Event;
+use crate::FrameReadError;
+
+/// Size of the read buffers of the transports
+pub const READ_BUFFER: usize = 64;
+
+/// Outcome of sending a radio telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TxStatus {
+    /// The telegram left the air interface (CO_TX_DONE)
+    Sent,
+    /// The telegram could not be sent (CO_TX_FAILED), e.g. because the channel was busy
+    Failed { cause: u8 },
+    /// The transceiver refused the telegram
+    Refused(CommandError),
+}
+
+/// Direction of a frame on the link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// From the transceiver
+    Rx,
+    /// To the transceiver
+    Tx,
+}
+
+/// A complete frame on the link, as seen by a tap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawFrame<'a> {
+    pub direction: Direction,
+    /// The frame, from sync byte to data CRC
+    pub bytes: &'a [u8],
+    /// Whether the data CRC is correct. Bytes without a valid header are dropped while
+    /// synchronizing, and never seen as frames.
+    pub valid: bool,
+}
+
+type Tap = Box<dyn FnMut(&RawFrame) + Send>;
+
+/// Frames received from a serial link, not yet returned
+#[derive(Default)]
+pub struct Link {
+    decoder: FrameDecoder,
+    received: VecDeque<Result<ESP3Frame, FrameReadError>>,
+    tap: Option<Tap>,
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Link")
+            .field("decoder", &self.decoder)
+            .field("received", &self.received)
+            .field("tap", &self.tap.is_some())
+            .finish()
+    }
+}
+
+impl Link {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hand every frame received or sent to `tap`, before it is decoded
+    pub fn set_tap(&mut self, tap: impl FnMut(&RawFrame) + Send + 'static) {
+        self.tap = Some(Box::new(tap));
+    }
+
+    pub fn clear_tap(&mut self) {
+        self.tap = None;
+    }
+
+    /// Feed bytes read from the link
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if let Some(frame) = self.decoder.push(byte) {
+                if let Some(tap) = self.tap.as_mut() {
+                    match &frame {
+                        Ok(frame) => tap(&RawFrame { direction: Direction::Rx, bytes: frame.borrow(), valid: true }),
+                        Err(FrameReadError::DataCRC { frame, .. }) => tap(&RawFrame { direction: Direction::Rx, bytes: frame, valid: false }),
+                        Err(_) => {}
+                    }
+                }
+                self.received.push_back(frame);
+            }
+        }
+    }
+
+    /// Report a frame written to the link to the tap, if any
+    pub fn sent(&mut self, frame: &ESP3Frame) {
+        if let Some(tap) = self.tap.as_mut() {
+            tap(&RawFrame { direction: Direction::Tx, bytes: frame.borrow(), valid: true });
+        }
+    }
+
+    /// Whether a tap is set, e.g. to skip assembling a frame only the tap would see
+    pub fn tapped(&self) -> bool {
+        self.tap.is_some()
+    }
+
+    /// The next frame received, or error reading one
+    pub fn next_frame(&mut self) -> Option<Result<ESP3Frame, FrameReadError>> {
+        self.received.pop_front()
+    }
+
+    /// The next response received, or error reading a frame. The other frames are kept,
+    /// for [`Self::next_frame`].
+    pub fn next_response(&mut self) -> Option<Result<ESP3Frame, FrameReadError>> {
+        self.take(|frame| frame.packet_type() == 0x02)
+    }
+
+    /// The next report on a telegram sent, or error reading a frame. The other frames are
+    /// kept, for [`Self::next_frame`].
+    pub fn next_tx_status(&mut self) -> Option<Result<TxStatus, FrameReadError>> {
+        let frame = self.take(|frame| frame.packet_type() == 0x04 && matches!(frame.data().first(), Some(0x07 | 0x08)));
+        Some(frame?.map(|frame| match Event::decode(frame.as_ref()) {
+            Ok(Event::COTXFailed { cause }) => TxStatus::Failed { cause },
+            _ => TxStatus::Sent,
+        }))
+    }
+
+    /// Util : remove the first error, or frame matching `wanted`
+    fn take(&mut self, wanted: impl Fn(&ESP3Frame) -> bool) -> Option<Result<ESP3Frame, FrameReadError>> {
+        let index = self.received.iter().position(|frame| frame.as_ref().map_or(true, &wanted))?;
+        self.received.remove(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::borrow::Borrow;
+
+    #[test]
+    fn given_telegram_before_response_then_set_it_aside() {
+        let telegram = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let response = ESP3Frame::assemble(0x02, &[0x00], &[]);
+        let bytes = [Borrow::<[u8]>::borrow(&telegram), Borrow::<[u8]>::borrow(&response)].concat();
+
+        let mut link = Link::new();
+        link.feed(&bytes[..10]);
+        assert!(link.next_response().is_none());
+        link.feed(&bytes[10..]);
+        assert_eq!(link.next_response().unwrap().unwrap().data(), &[0x00]);
+        assert_eq!(link.next_frame().unwrap().unwrap().data(), telegram.data());
+        assert!(link.next_frame().is_none());
+    }
+
+    #[test]
+    fn given_tx_events_then_report_status() {
+        let ready = ESP3Frame::assemble(0x04, &[0x04, 0x00], &[]);
+        let failed = ESP3Frame::assemble(0x04, &[0x07, 0x01], &[]);
+        let done = ESP3Frame::assemble(0x04, &[0x08], &[]);
+        let bytes = [&ready, &failed, &done].map(|frame| Borrow::<[u8]>::borrow(frame).to_vec()).concat();
+
+        let mut link = Link::new();
+        link.feed(&bytes);
+        assert_eq!(link.next_tx_status().unwrap().unwrap(), TxStatus::Failed { cause: 1 });
+        assert_eq!(link.next_tx_status().unwrap().unwrap(), TxStatus::Sent);
+        assert!(link.next_tx_status().is_none());
+        assert_eq!(link.next_frame().unwrap().unwrap().data(), ready.data());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn given_tap_then_see_every_frame_without_taking_it() {
+        use alloc::sync::Arc;
+        use alloc::vec::Vec;
+        use std::sync::Mutex;
+
+        let response = ESP3Frame::assemble(0x02, &[0x00], &[]);
+        let mut corrupt = Borrow::<[u8]>::borrow(&ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[])).to_vec();
+        *corrupt.last_mut().unwrap() ^= 0xff;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let tap = seen.clone();
+
+        let mut link = Link::new();
+        link.set_tap(move |raw| tap.lock().unwrap().push((raw.direction, raw.bytes.to_vec(), raw.valid)));
+        link.sent(&ESP3Frame::assemble(0x05, &[0x08], &[]));
+        link.feed(&[&corrupt[..], response.borrow()].concat());
+        assert!(matches!(link.next_frame(), Some(Err(FrameReadError::DataCRC { .. }))));
+        assert_eq!(link.next_frame().unwrap().unwrap().data(), &[0x00]);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.iter().map(|(direction, _, valid)| (*direction, *valid)).collect::<Vec<_>>(),
+            [(Direction::Tx, true), (Direction::Rx, false), (Direction::Rx, true)]);
+        assert_eq!(seen[1].1, corrupt);
+    }
+}
@@ -0,0 +1,193 @@
+//! EnOcean Alliance manufacturer IDs
+//!
+//! Manufacturer IDs are 11-bit values carried in teach-in telegrams and remote management
+//! messages.
+//!
+//! ```
+//! use enocean::manufacturer::Manufacturer;
+//! use num_enum::TryFromPrimitive;
+//!
+//! let manufacturer = Manufacturer::try_from_primitive(0x00D).unwrap();
+//! assert_eq!(manufacturer, Manufacturer::Eltako);
+//! assert_eq!(manufacturer.to_string(), "Eltako");
+//! assert_eq!(Manufacturer::describe(0x5A5), "unknown manufacturer 0x5a5");
+//! ```
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use core::fmt::Display;
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+macro_rules! manufacturers {
+    ($($(#[$doc:meta])* $variant:ident = $id:literal, $name:literal;)*) => {
+        /// A manufacturer registered with the EnOcean Alliance
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, IntoPrimitive, TryFromPrimitive)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+        #[repr(u16)]
+        pub enum Manufacturer {
+            $($(#[$doc])* $variant = $id,)*
+        }
+
+        impl Manufacturer {
+            /// Human-readable name of the manufacturer
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+manufacturers! {
+    Reserved = 0x000, "Reserved";
+    Peha = 0x001, "Peha";
+    Thermokon = 0x002, "Thermokon";
+    Servodan = 0x003, "Servodan";
+    EchoflexSolutions = 0x004, "Echoflex Solutions";
+    Omnio = 0x005, "Omnio";
+    HardmeierElectronics = 0x006, "Hardmeier Electronics";
+    Regulvar = 0x007, "Regulvar";
+    AdHocElectronics = 0x008, "Ad Hoc Electronics";
+    DistechControls = 0x009, "Distech Controls";
+    KiebackPeter = 0x00A, "Kieback + Peter";
+    EnOcean = 0x00B, "EnOcean";
+    Vicos = 0x00C, "Vicos";
+    Eltako = 0x00D, "Eltako";
+    Leviton = 0x00E, "Leviton";
+    Honeywell = 0x00F, "Honeywell";
+    SpartanPeripheralDevices = 0x010, "Spartan Peripheral Devices";
+    Siemens = 0x011, "Siemens";
+    TMac = 0x012, "T-Mac";
+    ReliableControls = 0x013, "Reliable Controls";
+    ElsnerElektronik = 0x014, "Elsner Elektronik";
+    DiehlControls = 0x015, "Diehl Controls";
+    BscComputer = 0x016, "BSC Computer";
+    SSRegeltechnik = 0x017, "S+S Regeltechnik";
+    ZenoControls = 0x018, "Zeno Controls";
+    IntesisSoftware = 0x019, "Intesis Software";
+    Viessmann = 0x01A, "Viessmann";
+    LutuoTechnology = 0x01B, "Lutuo Technology";
+    Can2Go = 0x01C, "CAN2GO";
+    Sauter = 0x01D, "Sauter";
+    BootUp = 0x01E, "Boot-Up";
+    OsramSylvania = 0x01F, "Osram Sylvania";
+    Unotech = 0x020, "Unotech";
+    DeltaControls = 0x021, "Delta Controls";
+    Unitronic = 0x022, "Unitronic";
+    Nanosense = 0x023, "NanoSense";
+    TheS4Group = 0x024, "The S4 Group";
+    MsrSolutions = 0x025, "MSR Solutions";
+    Ge = 0x026, "GE";
+    Maico = 0x027, "Maico";
+    Ruskin = 0x028, "Ruskin";
+    MagnumEnergySolutions = 0x029, "Magnum Energy Solutions";
+    KmcControls = 0x02A, "KMC Controls";
+    EcologixControls = 0x02B, "Ecologix Controls";
+    Trio2Sys = 0x02C, "Trio2Sys";
+    AfrisoEuroIndex = 0x02D, "Afriso-Euro-Index";
+    Waldmann = 0x02E, "Waldmann";
+    NecPlatforms = 0x030, "NEC Platforms";
+    Itec = 0x031, "ITEC";
+    Simicx = 0x032, "SimiCX";
+    Permundo = 0x033, "Permundo";
+    Eurotronic = 0x034, "Eurotronic Technology";
+    ArtJapan = 0x035, "Art Japan";
+    TiansuAutomation = 0x036, "Tiansu Automation Control System";
+    Weinzierl = 0x037, "Weinzierl Engineering";
+    GruppoGiordano = 0x038, "Gruppo Giordano Idea";
+    Alphaeos = 0x039, "alphaEOS";
+    TagTechnologies = 0x03A, "Tag Technologies";
+    Wattstopper = 0x03B, "Wattstopper";
+    Pressac = 0x03C, "Pressac Communications";
+    GigaConcept = 0x03E, "Giga Concept";
+    Sensortec = 0x03F, "Sensortec";
+    JaegerDirekt = 0x040, "Jäger Direkt";
+    AirSystemComponents = 0x041, "Air System Components";
+    Ermine = 0x042, "Ermine";
+    Soda = 0x043, "SODA";
+    EkeAutomation = 0x044, "EKE Automation";
+    HolterRegelarmaturen = 0x045, "Holter Regelarmaturen";
+    /// Formerly ID-RF
+    NodOn = 0x046, "NodOn";
+    DeutaControls = 0x047, "Deuta Controls";
+    Ewattch = 0x048, "Ewattch";
+    Micropelt = 0x049, "Micropelt";
+    Caleffi = 0x04A, "Caleffi";
+    DigitalConcepts = 0x04B, "Digital Concepts";
+    EmersonClimate = 0x04C, "Emerson Climate Technologies";
+    AdeeElectronic = 0x04D, "ADEE Electronic";
+    Altecon = 0x04E, "Altecon";
+    NanjingPutian = 0x04F, "Nanjing Putian Telecommunications";
+    Terralux = 0x050, "Terralux";
+    Menred = 0x051, "Menred";
+    Iexergy = 0x052, "iEXERGY";
+    Oventrop = 0x053, "Oventrop";
+    BuildingAutomationProducts = 0x054, "Building Automation Products";
+    FunctionalDevices = 0x055, "Functional Devices";
+    Ogga = 0x056, "OGGA";
+    IthoDaalderop = 0x057, "Itho Daalderop";
+    Resol = 0x058, "Resol";
+    AdvancedDevices = 0x059, "Advanced Devices";
+    Autani = 0x05A, "Autani";
+    DrRiedel = 0x05B, "Dr. Riedel";
+    Hoppe = 0x05C, "Hoppe";
+    Siegenia = 0x05D, "Siegenia-Aubi";
+    AdeoServices = 0x05E, "ADEO Services";
+    Eimsig = 0x05F, "Eimsig";
+    Vimar = 0x060, "Vimar";
+    GlenDimplex = 0x061, "Glen Dimplex";
+    Pmdm = 0x062, "PMDM";
+    HubbellLighting = 0x063, "Hubbell Lighting";
+    Debflex = 0x064, "Debflex";
+    Perifactory = 0x065, "Perifactory Sensorsystems";
+    Watty = 0x066, "Watty";
+    Wago = 0x067, "WAGO";
+    Kessel = 0x068, "Kessel";
+    AugWinkhaus = 0x069, "Aug. Winkhaus";
+    Decelect = 0x06A, "Decelect";
+    MstIndustries = 0x06B, "MST Industries";
+    BeckerAntriebe = 0x06C, "Becker Antriebe";
+    Nexelec = 0x06D, "Nexelec";
+    WielandElectric = 0x06E, "Wieland Electric";
+    Advisen = 0x06F, "Advisen";
+    CwsBoco = 0x070, "CWS-boco International";
+    RotoFrank = 0x071, "Roto Frank";
+    AlmControls = 0x072, "ALM Controls";
+    TommasoTechnologies = 0x073, "Tommaso Technologies";
+    Rehau = 0x074, "Rehau";
+    InabaDenkiSangyo = 0x075, "Inaba Denki Sangyo";
+    HagerControls = 0x076, "Hager Controls";
+    /// Shared ID for devices using manufacturer-specific sub-IDs
+    MultiUser = 0x7FF, "Multi user";
+}
+
+impl Manufacturer {
+    /// Name of a manufacturer ID, falling back to the hex ID for unregistered values
+    pub fn describe(id: u16) -> String {
+        match Self::try_from_primitive(id) {
+            Ok(manufacturer) => manufacturer.name().to_owned(),
+            Err(_) => format!("unknown manufacturer {:#05x}", id),
+        }
+    }
+}
+
+impl Display for Manufacturer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_manufacturer_id_then_convert_both_ways() {
+        assert_eq!(Manufacturer::try_from_primitive(0x046).unwrap(), Manufacturer::NodOn);
+        assert_eq!(u16::from(Manufacturer::Thermokon), 0x002);
+        assert!(Manufacturer::try_from_primitive(0x7FE).is_err());
+        assert_eq!(Manufacturer::describe(0x00D), "Eltako");
+    }
+}
@@ -0,0 +1,129 @@
+//! Manufacturer specific telegrams (MSC, RORG D1)
+//!
+//! The first 11 bits of an MSC telegram are the manufacturer ID; the rest is defined by
+//! the manufacturer. Applications register decoders for the manufacturers they know in a
+//! [`MscDecoders`] table:
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::manufacturer::Manufacturer;
+//! use enocean::eep::DecodeError;
+//! use enocean::msc::{Msc, MscDecoders};
+//!
+//! let mut decoders = MscDecoders::new();
+//! decoders.register(Manufacturer::Eltako.into(), |msc: &Msc| {
+//!     msc.bits(5, 8).ok_or(DecodeError::BadLength(msc.raw.len()))
+//! });
+//!
+//! let data = [0xd1, 0x01, 0xa4, 0x20, 0x01, 0x80, 0x99, 0x8e, 0x00];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! assert_eq!(decoders.decode(&erp)?, 0x20);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+use crate::eep::field::bits;
+use crate::eep::DecodeError;
+use crate::enocean::Rorg;
+use crate::manufacturer::Manufacturer;
+use crate::packet::RadioErp1;
+
+/// View over an MSC telegram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Msc<'a> {
+    pub manufacturer: u16,
+    /// User data, including the manufacturer ID
+    pub raw: &'a [u8],
+}
+
+impl<'a> Msc<'a> {
+    pub fn decode(erp: &'a RadioErp1<'_>) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::Msc {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        if erp.user_data.len() < 2 {
+            return Err(DecodeError::BadLength(erp.user_data.len()));
+        }
+        Ok(Self { manufacturer: bits(&erp.user_data, 0, 11) as u16, raw: &erp.user_data })
+    }
+
+    /// Field of the manufacturer data, with `offset` counted in bits after the manufacturer ID.
+    /// `None` if the field goes past the end of the telegram, or is wider than 32 bits.
+    pub fn bits(&self, offset: usize, size: usize) -> Option<u32> {
+        (size <= 32 && offset + size <= self.payload_bits()).then(|| bits(self.raw, 11 + offset, size))
+    }
+
+    /// Size of the manufacturer data, in bits
+    pub fn payload_bits(&self) -> usize {
+        self.raw.len() * 8 - 11
+    }
+}
+
+impl fmt::Display for Msc<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MSC from {}: {}", Manufacturer::describe(self.manufacturer), hex::encode(self.raw))
+    }
+}
+
+type Decoder<T> = Box<dyn Fn(&Msc) -> Result<T, DecodeError> + Send + Sync>;
+
+/// Manufacturer specific decoders, producing values of an application defined type `T`
+pub struct MscDecoders<T> {
+    decoders: BTreeMap<u16, Decoder<T>>,
+}
+
+impl<T> Default for MscDecoders<T> {
+    fn default() -> Self {
+        Self { decoders: BTreeMap::new() }
+    }
+}
+
+impl<T> fmt::Debug for MscDecoders<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.decoders.keys()).finish()
+    }
+}
+
+impl<T> MscDecoders<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the decoder of a manufacturer, replacing any previous one
+    pub fn register(&mut self, manufacturer: u16, decoder: impl Fn(&Msc) -> Result<T, DecodeError> + Send + Sync + 'static) {
+        self.decoders.insert(manufacturer, Box::new(decoder));
+    }
+
+    /// Decode an MSC telegram with the decoder of its manufacturer
+    pub fn decode(&self, erp: &RadioErp1) -> Result<T, DecodeError> {
+        let msc = Msc::decode(erp)?;
+        let decoder = self.decoders.get(&msc.manufacturer).ok_or(DecodeError::UnknownManufacturer(msc.manufacturer))?;
+        decoder(&msc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+    use alloc::string::ToString;
+
+    #[test]
+    fn given_msc_telegram_then_extract_manufacturer_and_payload() {
+        let erp = erp1(&[0xd1, 0x08, 0xdf, 0xff, 0x01, 0x02, 0x03, 0x04, 0x00]);
+        let msc = Msc::decode(&erp).unwrap();
+        assert_eq!(msc.manufacturer, 0x046);
+        assert_eq!(msc.payload_bits(), 13);
+        assert_eq!(msc.bits(0, 13), Some(0x1fff));
+        assert_eq!(msc.bits(8, 6), None);
+        assert_eq!(msc.bits(14, 1), None);
+        assert_eq!(msc.to_string(), "MSC from NodOn: 08dfff");
+
+        let decoders: MscDecoders<u32> = MscDecoders::new();
+        assert!(matches!(decoders.decode(&erp), Err(DecodeError::UnknownManufacturer(0x046))));
+    }
+}
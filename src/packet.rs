@@ -1,26 +1,102 @@
 //! ESP3 packet encoding and decoding
+//!
+//! Responses to the commands without a typed decoder are read with [`view`].
+//!
+//! Decoding follows the specification by default, but firmware in the field does not always:
+//! [`DecodeOptions`] makes [`Packet::decode_with`] keep telegrams with unknown subtelegram
+//! or security values, as `Other`, or reject optional data of unexpected length.
 
+pub mod view;
 
-use std::{str::{Utf8Error, FromStr}, fmt::Display};
 
-use num_enum::{TryFromPrimitive, IntoPrimitive};
-use thiserror::Error;
+use alloc::{borrow::{Cow, ToOwned}, string::String, vec::Vec};
+use core::{str::{Utf8Error, FromStr}, fmt::Display};
 
-use crate::{frame::{ESP3Frame, ESP3FrameRef}, enocean::Rorg};
+use num_enum::{FromPrimitive, TryFromPrimitive, TryFromPrimitiveError, IntoPrimitive};
+use smallvec::SmallVec;
+
+use crate::{frame::{ESP3Frame, ESP3FrameRef}, enocean::Rorg, reman::RemoteManCommand, smart_ack::SmartAckCommand};
 
 pub type ResponseCode = crate::enocean::ReturnCode;
 
 #[derive(Debug,Clone,Copy,Eq,PartialEq,Hash,PartialOrd,Ord)]
-pub struct Address([u8; 4]);
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Address(pub(crate) [u8; 4]);
 
 pub const BROADCAST: Address = Address([0xff,0xff,0xff,0xff]);
 
+impl Address {
+    /// The address with the given numeric value, e.g. `Address::new(0x0180998e)`
+    pub const fn new(address: u32) -> Self {
+        Self(address.to_be_bytes())
+    }
+
+    /// The 4 bytes of the address, most significant first, as sent on air
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        *self == BROADCAST
+    }
+
+    /// Whether the address is one of the sender addresses of the base ID `base`
+    pub fn is_base_id(&self, base: &IdBase) -> bool {
+        base.range().contains(*self)
+    }
+
+    /// The offset of the address in the range of the base ID `base`, as given to
+    /// [`IdBase::sender`], if in range
+    pub fn offset_from(&self, base: &IdBase) -> Option<u8> {
+        base.range().offset_of(*self)
+    }
+}
+
+impl From<u32> for Address {
+    fn from(address: u32) -> Self {
+        Self::new(address)
+    }
+}
+
+impl From<Address> for u32 {
+    fn from(address: Address) -> Self {
+        u32::from_be_bytes(address.0)
+    }
+}
+
+impl From<[u8; 4]> for Address {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+}
+
 impl Display for Address {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:02x}{:02x}{:02x}{:02x}", self.0[0], self.0[1], self.0[2], self.0[3])
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for Address {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u8:02x}{=u8:02x}{=u8:02x}{=u8:02x}", self.0[0], self.0[1], self.0[2], self.0[3])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Address {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Address {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl FromStr for Address {
     type Err = hex::FromHexError;
 
@@ -32,52 +108,353 @@ impl FromStr for Address {
 }
 
 
+/// An EEP identifier (RORG, FUNC, TYPE), displayed as `A5-02-05`
+#[derive(Debug,Clone,Copy,Eq,PartialEq,Hash,PartialOrd,Ord)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct EEPProfileCode([u8; 3]);
 
-#[derive(Debug,Error)]
+impl EEPProfileCode {
+    pub const fn new(rorg: u8, func: u8, eep_type: u8) -> Self {
+        Self([rorg, func, eep_type])
+    }
+
+    pub fn rorg(&self) -> u8 { self.0[0] }
+    pub fn func(&self) -> u8 { self.0[1] }
+    pub fn eep_type(&self) -> u8 { self.0[2] }
+}
+
+impl Display for EEPProfileCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:02X}-{:02X}-{:02X}", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for EEPProfileCode {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u8:02X}-{=u8:02X}-{=u8:02X}", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for EEPProfileCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EEPProfileCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromStr for EEPProfileCode {
+    type Err = hex::FromHexError;
+
+    /// Parses both `A5-02-05` and `a50205`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut code = [0; 3];
+        hex::decode_to_slice(s.replace('-', ""), &mut code)?;
+        Ok(Self(code))
+    }
+}
+
+/// Errors decoding a packet. Offsets count from the start of the data, the optional data
+/// following it.
+#[derive(Debug)]
 pub enum ParseError {
-    #[error("Unsupported packet type")] UnsupportedPacketType,
-    #[error("Packet too short")]        PacketTooShort,
-    #[error("UTF8 decoding Error")]     UTF8(#[from] Utf8Error),
-    #[error("Invalid result code")]     InvalidResultCode(u8),
-    #[error("Invalid primitive")]       InvalidPrimitive,
+    UnsupportedPacketType,
+    /// The packet has only `length` bytes of data
+    PacketTooShort { packet_type: u8, length: usize },
+    UTF8(Utf8Error),
+    InvalidResultCode(u8),
+    /// The byte `value` at `offset` is not valid
+    InvalidPrimitive { packet_type: u8, offset: usize, value: u8 },
+    /// Remote management answer to an unknown function
+    UnknownFunction(u16),
+    /// Optional data of a length the packet type does not define, in strict mode
+    OptionalLength { packet_type: u8, length: usize },
+    /// An error from [`Packet::decode`], with a copy of the frame (feature `error-frames`)
+    #[cfg(feature = "error-frames")]
+    InFrame { error: alloc::boxed::Box<ParseError>, frame: ESP3Frame },
 }
 
-#[derive(Debug,Clone,Copy,PartialEq,Eq,TryFromPrimitive,IntoPrimitive)]
+impl ParseError {
+    /// The frame the error occurred in, if kept. Only [`Packet::decode`] keeps it, with the
+    /// `error-frames` feature.
+    pub fn source_frame(&self) -> Option<&ESP3Frame> {
+        match self {
+            #[cfg(feature = "error-frames")]
+            Self::InFrame { frame, .. } => Some(frame),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "error-frames")]
+    fn in_frame(self, frame: ESP3FrameRef) -> Self {
+        Self::InFrame { error: alloc::boxed::Box::new(self), frame: frame.to_owned() }
+    }
+
+    #[cfg(not(feature = "error-frames"))]
+    fn in_frame(self, _frame: ESP3FrameRef) -> Self {
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedPacketType => write!(f, "Unsupported packet type"),
+            Self::PacketTooShort { packet_type, length } =>
+                write!(f, "Packet of type {:#04x} too short ({} bytes)", packet_type, length),
+            Self::UTF8(_)               => write!(f, "UTF8 decoding Error"),
+            Self::InvalidResultCode(code) => write!(f, "Invalid result code {:#04x}", code),
+            Self::InvalidPrimitive { packet_type, offset, value } =>
+                write!(f, "Invalid value {:#04x} at offset {} of packet of type {:#04x}", value, offset, packet_type),
+            Self::UnknownFunction(function) => write!(f, "Unknown remote management function {:#05x}", function),
+            Self::OptionalLength { packet_type, length } =>
+                write!(f, "Unexpected optional data length {} for packet of type {:#04x}", length, packet_type),
+            #[cfg(feature = "error-frames")]
+            Self::InFrame { error, frame } =>
+                write!(f, "{} in frame {}", error, hex::encode(core::borrow::Borrow::<[u8]>::borrow(frame))),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ParseError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::UnsupportedPacketType   => defmt::write!(f, "Unsupported packet type"),
+            Self::PacketTooShort { packet_type, length } =>
+                defmt::write!(f, "Packet of type {=u8:#x} too short ({=usize} bytes)", *packet_type, *length),
+            Self::UTF8(_)                 => defmt::write!(f, "UTF8 decoding Error"),
+            Self::InvalidResultCode(code) => defmt::write!(f, "Invalid result code {=u8:#x}", *code),
+            Self::InvalidPrimitive { packet_type, offset, value } =>
+                defmt::write!(f, "Invalid value {=u8:#x} at offset {=usize} of packet of type {=u8:#x}", *value, *offset, *packet_type),
+            Self::UnknownFunction(function) => defmt::write!(f, "Unknown remote management function {=u16:#x}", *function),
+            Self::OptionalLength { packet_type, length } =>
+                defmt::write!(f, "Unexpected optional data length {=usize} for packet of type {=u8:#x}", *length, *packet_type),
+            #[cfg(feature = "error-frames")]
+            Self::InFrame { error, frame } => defmt::write!(f, "{} in frame {}", error, frame),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UTF8(e) => Some(e),
+            #[cfg(feature = "error-frames")]
+            Self::InFrame { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<Utf8Error> for ParseError {
+    fn from(e: Utf8Error) -> Self {
+        Self::UTF8(e)
+    }
+}
+
+/// Subtelegram number of a radio telegram: 3 when sending, and the number of subtelegrams
+/// heard, from 1, when receiving. Other counts decode as [`SubtelNum::Other`]; 0 is out of
+/// the specification, accepted with [`DecodeOptions::tolerate_unknown_enums`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,IntoPrimitive)]
 #[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SubtelNum {
     Send = 3, 
-    Receive = 0,
+    Receive = 1,
+    #[num_enum(catch_all)]
+    Other(u8) = 0xFF,
 }
 
-#[derive(Debug,Clone,Copy,PartialEq,Eq,TryFromPrimitive,IntoPrimitive)]
+impl SubtelNum {
+    /// Whether the specification defines the value
+    pub fn is_defined(&self) -> bool {
+        *self != Self::Other(0)
+    }
+}
+
+/// Fails on 0, out of the specification
+impl TryFromPrimitive for SubtelNum {
+    type Primitive = u8;
+    const NAME: &'static str = "SubtelNum";
+
+    fn try_from_primitive(number: u8) -> Result<Self, TryFromPrimitiveError<Self>> {
+        match number {
+            0 => Err(TryFromPrimitiveError { number }),
+            1 => Ok(Self::Receive),
+            3 => Ok(Self::Send),
+            count => Ok(Self::Other(count)),
+        }
+    }
+}
+
+impl TryFrom<u8> for SubtelNum {
+    type Error = TryFromPrimitiveError<Self>;
+
+    fn try_from(number: u8) -> Result<Self, Self::Error> {
+        Self::try_from_primitive(number)
+    }
+}
+
+/// Security level of a radio telegram. Values the specification does not define decode as
+/// [`Security::Other`], accepted with [`DecodeOptions::tolerate_unknown_enums`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,IntoPrimitive)]
 #[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Security {
     None = 0,
     Obsolete = 1,
     Decrypted = 2,
     Authenticated = 3,
     AuthAndDecrypted = 4,
+    #[num_enum(catch_all)]
+    Other(u8) = 0xFF,
 }
 
-#[derive(Debug,Clone,Copy)]
+/// Fails on the values out of the specification
+impl TryFromPrimitive for Security {
+    type Primitive = u8;
+    const NAME: &'static str = "Security";
+
+    fn try_from_primitive(number: u8) -> Result<Self, TryFromPrimitiveError<Self>> {
+        match number {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Obsolete),
+            2 => Ok(Self::Decrypted),
+            3 => Ok(Self::Authenticated),
+            4 => Ok(Self::AuthAndDecrypted),
+            _ => Err(TryFromPrimitiveError { number }),
+        }
+    }
+}
+
+impl TryFrom<u8> for Security {
+    type Error = TryFromPrimitiveError<Self>;
+
+    fn try_from(number: u8) -> Result<Self, Self::Error> {
+        Self::try_from_primitive(number)
+    }
+}
+
+/// How closely decoded packets must follow the specification
+///
+/// The default rejects unknown enumerated values, and accepts optional data of any length.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DecodeOptions {
+    /// Reject optional data of a length the packet type does not define, rather than
+    /// reading what is there
+    pub strict: bool,
+    /// Decode unknown subtelegram numbers and security levels as `Other`, rather than
+    /// rejecting the whole telegram
+    pub tolerate_unknown_enums: bool,
+}
+
+impl DecodeOptions {
+    /// Reject anything out of the specification
+    pub const STRICT: Self = Self { strict: true, tolerate_unknown_enums: false };
+    /// Keep whatever can be read, e.g. from firmware emitting out-of-spec values
+    pub const LENIENT: Self = Self { strict: false, tolerate_unknown_enums: true };
+}
+
+/// Integrity check of a telegram, from its status byte
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HashType {
+    Checksum,
+    Crc8,
+}
+
+/// Typed view of the status byte of a radio telegram
+///
+/// | Bit  | RPS telegrams                      | Other telegrams                   |
+/// |------|------------------------------------|-----------------------------------|
+/// | 7    | Reserved                           | Integrity: 0 = checksum, 1 = CRC8 |
+/// | 5    | T21: PTM type (0 = 1, 1 = 2)       | Reserved                          |
+/// | 4    | NU: N-message (1) or U-message (0) | Reserved                          |
+/// | 3..0 | Repeater hop count                 | Repeater hop count                |
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StatusByte(pub u8);
+
+impl StatusByte {
+    /// Number of repeaters the telegram went through
+    pub fn repeater_count(&self) -> u8 { self.0 & 0x0f }
+
+    /// Sent by a PTM 2xx module (RPS telegrams only)
+    pub fn t21(&self) -> bool { self.0 & 0x20 != 0 }
+
+    /// The data byte is an N-message, rather than a U-message (RPS telegrams only)
+    pub fn nu(&self) -> bool { self.0 & 0x10 != 0 }
+
+    /// Integrity check used by the telegram (non-RPS telegrams only)
+    pub fn hash_type(&self) -> HashType {
+        if self.0 & 0x80 != 0 { HashType::Crc8 } else { HashType::Checksum }
+    }
+}
+
+/// A radio telegram (packet type 0x01). The user data is borrowed from the frame when
+/// decoded, and may be owned when built: [`RadioErp1::into_owned`] detaches a decoded
+/// telegram from its frame, e.g. to move it to another task.
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RadioErp1<'a> {
     pub choice: Rorg,
-    pub user_data: &'a [u8],
+    pub user_data: Cow<'a, [u8]>,
     pub sender_id: Address,
     pub status: u8,
     pub subtel_num: Option<SubtelNum>,
     pub destination: Option<Address>,
+    /// Received signal strength, as reported: the negated dBm value. See [`Self::rssi_dbm`].
     pub rssi: Option<u8>,
     pub security: Option<Security>
 }
 
-#[derive(Debug,Clone,Copy)]
-// TODO parse details
+/// One of the subtelegrams of a received telegram
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Subtelegram {
+    /// Time since the previous subtelegram, in ms
+    pub tick: u8,
+    /// Negated dBm value
+    pub rssi: u8,
+    pub status: u8,
+}
+
+impl Subtelegram {
+    /// Received signal strength in dBm
+    pub fn rssi_dbm(&self) -> i16 {
+        -(self.rssi as i16)
+    }
+}
+
+/// A radio telegram along with the details of its subtelegrams (packet type 0x03)
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RadioSubTel<'a> {
+    pub erp: RadioErp1<'a>,
+    /// Timestamp of the first subtelegram, in ms
+    pub timestamp: u16,
+    pub subtelegrams: Vec<Subtelegram>,
+}
+
+/// Events reported by the transceiver (packet type 0x04).
+/// Smart Ack payloads are decoded by [`crate::smart_ack`].
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Event<'a> {
     SAReclaimUnsuccessful,
-    SAConfirmLearn       { data: &'a [u8; 17] }, 
-    SALearnAck           { data: &'a [u8; 3]},
+    SAConfirmLearn       { data: Cow<'a, [u8; 16]> },
+    SALearnAck           { data: Cow<'a, [u8; 3]> },
+    /// The transceiver (re)started; `wakeup` is a [`WakeupCause`]
     COReady              { wakeup: u8, mode: Option<u8> },
     COEventSecureDevices { cause: u8, device: Address },
     CODutyCycleLimit     { cause: u8},
@@ -86,13 +463,52 @@ pub enum Event<'a> {
     COLrnModeDisabled,
 }
 
+/// Why the transceiver started, as reported by CO_READY
+#[derive(Debug,Clone,Copy,PartialEq,Eq,TryFromPrimitive,IntoPrimitive)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum WakeupCause {
+    /// Power on, or supply voltage drop
+    VoltageSupplyDrop = 0x00,
+    ResetPin          = 0x01,
+    Watchdog          = 0x02,
+    Flywheel          = 0x03,
+    ParityError       = 0x04,
+    MemoryParityError = 0x05,
+    MemoryFault       = 0x06,
+    WakeupPin0        = 0x07,
+    WakeupPin1        = 0x08,
+    Unknown           = 0x09,
+    Uart              = 0x10,
+}
+
+impl WakeupCause {
+    /// Whether the transceiver was reset, losing its volatile state, rather than woken up
+    /// from sleep
+    pub fn is_reset(&self) -> bool {
+        !matches!(self, Self::WakeupPin0 | Self::WakeupPin1 | Self::Uart)
+    }
+}
+
+/// Payload of a [`Response`]. Payloads up to 32 bytes, like those of the common commands,
+/// are stored inline, without allocation.
+pub type ResponseData = SmallVec<[u8; 32]>;
+
 #[derive(Debug,Clone)]
 pub struct Response {
     pub code: ResponseCode,
-    pub data: Vec<u8>,
+    pub data: ResponseData,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Response {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Response {{ code: {}, data: {=[u8]:02x} }}", self.code, &self.data[..])
+    }
 }
 
 #[derive(Debug,Clone,Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Version {
     pub main: u8,
     pub beta: u8,
@@ -101,30 +517,46 @@ pub struct Version {
 }
 
 #[derive(Debug,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct VersionResponse {
     pub app: Version,
     pub api: Version,
     pub chip_id: Address,
     pub chip_version: [u8; 4],
+    /// Chip description, up to its first NUL byte. Read as Latin-1 when it is not valid UTF-8.
     pub description: String,
+    /// Chip description as sent, NUL-padded
+    pub description_raw: [u8; 16],
 }
 
-#[derive(Debug,Clone,Copy)]
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum CommonCommand<'a> {
     //Reset,
     ReadVersion,
     //ReadSystemLog,
+    /// CO_RD_IDBASE, see [`IdBase`] for the response
+    ReadIdBase,
+    /// CO_WR_SECUREDEVICE_ADD_PSK: pre-shared key of a device, for secure teach-in
+    WriteSecureDeviceAddPsk { device: Address, psk: [u8; 16] },
+    /// CO_WR_SECUREDEVICE_MAINTENANCEKEY
+    WriteSecureDeviceMaintenanceKey { device: Address, key: [u8; 16], number: u8 },
+    /// CO_RD_SECUREDEVICE_MAINTENANCEKEY
+    ReadSecureDeviceMaintenanceKey { index: u8 },
+    /// CO_GET_FREQUENCY_INFO, see [`FrequencyInfo`] for the response
+    GetFrequencyInfo,
 
-    Unknown { code: u8, data: &'a [u8], optional: &'a [u8] }
+    Unknown { code: u8, data: Cow<'a, [u8]>, optional: Cow<'a, [u8]> }
 }
 
 #[derive(Debug,Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Packet<'a> {
     RadioErp1(RadioErp1<'a>),
     Response(Response),
-    //Event(Event<'a>),
+    Event(Event<'a>),
     CommonCommand(CommonCommand<'a>),
-    //SmartAck,
+    SmartAckCommand(SmartAckCommand),
     //RemoteMan,
     //RadioMessage,
     //RadioErp2,
@@ -132,10 +564,20 @@ pub enum Packet<'a> {
     //RadioLRWPAN,
     //Command24GHz,
 
-    Unknown { packet_type: u8, data: &'a [u8], optional: &'a [u8] }
-    //RadioSubTel(RadioSubTel),
+    RadioSubTel(RadioSubTel<'a>),
+    RemoteManCommand(RemoteManCommand),
+
+    Unknown { packet_type: u8, data: Cow<'a, [u8]>, optional: Cow<'a, [u8]> }
 }
 
+// Owned packets can be handed to other threads, e.g. over a channel
+const _: () = {
+    const fn send_sync<T: Send + Sync>() {}
+    send_sync::<Packet<'static>>();
+    send_sync::<RadioErp1<'static>>();
+    send_sync::<ESP3Frame>();
+};
+
 impl VersionResponse {
     pub fn encode(&self) -> Response {
         todo!();
@@ -143,15 +585,18 @@ impl VersionResponse {
 
     pub fn decode(response: &Response) -> Result<Self, ParseError> {
 
-        fn fromcstr(s: &[u8]) -> Result<String, Utf8Error> {
-            let mut idx = 0;
-            while idx < s.len() && s[idx] == 0 { idx += 1 };
-            Ok(std::str::from_utf8(&s[..idx])?.to_owned())
+        fn fromcstr(s: &[u8]) -> String {
+            let s = &s[..s.iter().position(|&b| b == 0).unwrap_or(s.len())];
+            match core::str::from_utf8(s) {
+                Ok(s) => s.to_owned(),
+                Err(_) => s.iter().map(|&b| b as char).collect(),
+            }
         }
 
+        // Some firmware appends bytes after the description; they are ignored
         let d = &response.data;
-        if d.len() != 32 {
-            return Err(ParseError::PacketTooShort)
+        if d.len() < 32 {
+            return Err(ParseError::PacketTooShort { packet_type: 0x02, length: d.len() + 1 })
         }
 
         Ok(Self {
@@ -159,61 +604,534 @@ impl VersionResponse {
             api: Version { main: d[4], beta: d[5], alpha: d[6], build: d[7] },
             chip_id: Address(d[8..12].try_into().unwrap()),
             chip_version: d[12..16].try_into().unwrap(),
-            description: fromcstr(&d[16..32])?,
+            description: fromcstr(&d[16..32]),
+            description_raw: d[16..32].try_into().unwrap(),
         })
 
     }
 }
 
 impl Display for Version {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}.{}.{}.{}", self.main, self.beta, self.alpha, self.build)
     }
 }
 
 impl Display for VersionResponse {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{} (APP:{}, API:{}, Chip address:{}, version {:?}", self.description, self.app, self.api, self.chip_id, self.chip_version)
     }
 }
 
+/// Base ID of a transceiver: the first of the 128 addresses it can send telegrams from,
+/// besides its chip ID
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IdBase(pub Address);
+
+impl IdBase {
+    pub const RANGE: u8 = 128;
+
+    pub fn decode(response: &Response) -> Result<Self, ParseError> {
+        let d = response.data.get(..4).ok_or(ParseError::PacketTooShort { packet_type: 0x02, length: response.data.len() + 1 })?;
+        Ok(Self(Address(d.try_into().unwrap())))
+    }
+
+    /// Address number `offset` of the range, if in range
+    pub fn sender(&self, offset: u8) -> Option<Address> {
+        self.range().get(offset)
+    }
+
+    /// The addresses the transceiver can send from
+    pub fn range(&self) -> AddressRange {
+        AddressRange::new(self.0, Self::RANGE)
+    }
+}
+
+/// Radio frequency of a transceiver. Values of newer firmware decode as
+/// [`Frequency::Other`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,FromPrimitive,IntoPrimitive)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Frequency {
+    Mhz315   = 0x00,
+    Mhz868_3 = 0x01,
+    Mhz902_875 = 0x02,
+    Mhz925   = 0x03,
+    Mhz928   = 0x04,
+    Ghz2_4   = 0x20,
+    #[num_enum(catch_all)]
+    Other(u8) = 0xFF,
+}
+
+impl Display for Frequency {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Mhz315     => "315 MHz",
+            Self::Mhz868_3   => "868.3 MHz",
+            Self::Mhz902_875 => "902.875 MHz",
+            Self::Mhz925     => "925 MHz",
+            Self::Mhz928     => "928 MHz",
+            Self::Ghz2_4     => "2.4 GHz",
+            Self::Other(value) => return write!(f, "Unknown frequency {:#04x}", value),
+        })
+    }
+}
+
+/// Radio protocol of a transceiver. Values of newer firmware decode as
+/// [`RadioProtocol::Other`].
+#[derive(Debug,Clone,Copy,PartialEq,Eq,FromPrimitive,IntoPrimitive)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum RadioProtocol {
+    Erp1      = 0x00,
+    Erp2      = 0x01,
+    Ieee802154 = 0x10,
+    Bluetooth = 0x20,
+    LongRange = 0x30,
+    #[num_enum(catch_all)]
+    Other(u8) = 0xFF,
+}
+
+/// Response to CO_GET_FREQUENCY_INFO
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrequencyInfo {
+    pub frequency: Frequency,
+    pub protocol: RadioProtocol,
+}
+
+impl FrequencyInfo {
+    pub fn decode(response: &Response) -> Result<Self, ParseError> {
+        let d = response.data.get(..2).ok_or(ParseError::PacketTooShort { packet_type: 0x02, length: response.data.len() + 1 })?;
+        Ok(Self { frequency: Frequency::from(d[0]), protocol: RadioProtocol::from(d[1]) })
+    }
+}
+
+/// A window of `len` consecutive addresses, such as the range of a base ID
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AddressRange {
+    start: Address,
+    len: u8,
+}
+
+impl AddressRange {
+    /// The range of `len` addresses from `start`, truncated at `ffffffff`
+    pub fn new(start: Address, len: u8) -> Self {
+        let available = (u32::MAX - u32::from(start)).saturating_add(1);
+        Self { start, len: available.min(len as u32) as u8 }
+    }
+
+    pub fn start(&self) -> Address {
+        self.start
+    }
+
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, address: Address) -> bool {
+        self.offset_of(address).is_some()
+    }
+
+    /// Address number `offset` of the range, if in range
+    pub fn get(&self, offset: u8) -> Option<Address> {
+        (offset < self.len).then(|| Address::new(u32::from(self.start) + offset as u32))
+    }
+
+    /// The offset of `address` in the range, if in range
+    pub fn offset_of(&self, address: Address) -> Option<u8> {
+        let offset = u32::from(address).checked_sub(u32::from(self.start))?;
+        (offset < self.len as u32).then_some(offset as u8)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Address> {
+        let start = u32::from(self.start);
+        (0..self.len as u32).map(move |offset| Address::new(start + offset))
+    }
+}
+
 impl<'a> RadioErp1<'a> {
+    /// A builder for a telegram to send
+    pub fn builder() -> RadioErp1Builder<'a> {
+        RadioErp1Builder::default()
+    }
+
+    /// Typed view of the status byte
+    pub fn status_byte(&self) -> StatusByte {
+        StatusByte(self.status)
+    }
+
+    /// Received signal strength in dBm, e.g. -45 for a reported value of 0x2D.
+    /// Telegrams being sent report 0xFF, which gives `None`.
+    pub fn rssi_dbm(&self) -> Option<i16> {
+        self.rssi.filter(|rssi| *rssi != 0xff).map(|rssi| -(rssi as i16))
+    }
+
+    /// A telegram addressed to a single device, as used for teach-in responses and commands
+    pub fn addressed(choice: Rorg, user_data: impl Into<Cow<'a, [u8]>>, sender_id: Address, destination: Address) -> Self {
+        Self {
+            choice,
+            user_data: user_data.into(),
+            sender_id,
+            status: 0,
+            subtel_num: Some(SubtelNum::Send),
+            destination: Some(destination),
+            rssi: None,
+            security: None,
+        }
+    }
+
     pub fn encode(&self) -> ESP3Frame {
-        todo!()
+        let packet_type = 0x01;
+        let mut data = Vec::with_capacity(6 + self.user_data.len());
+        data.push(self.choice.into());
+        data.extend_from_slice(&self.user_data);
+        data.extend_from_slice(&self.sender_id.0);
+        data.push(self.status);
+
+        // Optional data is all-or-nothing; missing fields get their default value for sending
+        let has_optional = self.subtel_num.is_some() || self.destination.is_some()
+                        || self.rssi.is_some() || self.security.is_some();
+        let mut optional = Vec::with_capacity(7);
+        if has_optional {
+            optional.push(self.subtel_num.unwrap_or(SubtelNum::Send).into());
+            optional.extend_from_slice(&self.destination.unwrap_or(BROADCAST).0);
+            optional.push(self.rssi.unwrap_or(0xff));
+            optional.push(self.security.unwrap_or(Security::None).into());
+        }
+
+        ESP3Frame::assemble(packet_type, &data, &optional)
+    }
 
+    /// Encode the telegram encapsulated in an addressed telegram (ADT, RORG A6), so that only
+    /// its destination processes it. Fails if no destination is set.
+    pub fn encode_adt(&self) -> Result<ESP3Frame, BuildError> {
+        let destination = self.destination.ok_or(BuildError::Missing("destination"))?;
+        let user_data = [&[self.choice.into()], &self.user_data[..], &destination.0].concat();
+        Ok(RadioErp1 {
+            choice: Rorg::Adt,
+            user_data: Cow::Owned(user_data),
+            sender_id: self.sender_id,
+            status: self.status,
+            subtel_num: self.subtel_num,
+            destination: self.destination,
+            rssi: self.rssi,
+            security: self.security,
+        }.encode())
     }
 
+    /// Decode a telegram. Addressed telegrams (ADT, RORG A6) are unwrapped: the inner
+    /// telegram is returned, with the encapsulated destination.
     pub fn decode(frame: ESP3FrameRef<'a>) -> Result<Self, ParseError> {
+        Self::decode_with(frame, DecodeOptions::default())
+    }
+
+    /// Decode a telegram, as strictly as `options` say
+    pub fn decode_with(frame: ESP3FrameRef<'a>, options: DecodeOptions) -> Result<Self, ParseError> {
+        let erp = Self::decode_raw(frame, options)?;
+        if erp.choice != Rorg::Adt {
+            return Ok(erp);
+        }
+        let d = &frame.data[1..frame.data.len() - 5];
+        if d.len() < 5 {
+            return Err(ParseError::PacketTooShort { packet_type: frame.packet_type, length: frame.data.len() });
+        }
+        Ok(Self {
+            choice: Rorg::from(d[0]),
+            user_data: Cow::Borrowed(&d[1..d.len() - 4]),
+            destination: Some(Address(d[d.len() - 4..].try_into().unwrap())),
+            ..erp
+        })
+    }
+
+    fn decode_raw(frame: ESP3FrameRef<'a>, options: DecodeOptions) -> Result<Self, ParseError> {
+        if frame.data.len() < 6 {
+            return Err(ParseError::PacketTooShort { packet_type: frame.packet_type, length: frame.data.len() });
+        }
         let payload_len = frame.data.len() - 6;
         let opt_len = frame.optional_data.len();
-        Ok(Self { choice: Rorg::try_from_primitive(frame.data[0]).map_err(|_| ParseError::UnsupportedPacketType)?,
-                  user_data: &frame.data[1..][..payload_len],
+        if options.strict && frame.packet_type == 0x01 && !matches!(opt_len, 0 | 7) {
+            return Err(ParseError::OptionalLength { packet_type: frame.packet_type, length: opt_len });
+        }
+        let invalid = |index: usize| ParseError::InvalidPrimitive {
+            packet_type: frame.packet_type,
+            offset: frame.data.len() + index,
+            value: frame.optional_data[index],
+        };
+        let subtel_num = match frame.optional_data.first().map(|&b| SubtelNum::try_from_primitive(b)) {
+            Some(Err(_)) if !options.tolerate_unknown_enums => return Err(invalid(0)),
+            subtel_num => subtel_num.map(|subtel_num| subtel_num.unwrap_or_else(|e| SubtelNum::Other(e.number))),
+        };
+        let security = match frame.optional_data.get(6).map(|&b| Security::try_from_primitive(b)) {
+            Some(Err(_)) if !options.tolerate_unknown_enums => return Err(invalid(6)),
+            security => security.map(|security| security.unwrap_or_else(|e| Security::Other(e.number))),
+        };
+        Ok(Self { choice: Rorg::from(frame.data[0]),
+                  user_data: Cow::Borrowed(&frame.data[1..][..payload_len]),
                   sender_id: Address(frame.data[1+payload_len..][..4].try_into().unwrap()),
                   status: frame.data[5+payload_len],
-                  subtel_num: if opt_len >= 1 { Some(SubtelNum::try_from_primitive(frame.optional_data[0]).map_err(|_| ParseError::InvalidPrimitive)?) } 
-                              else { None },
+                  subtel_num,
                   destination: if opt_len >= 5 { Some(Address(frame.optional_data[1..5].try_into().unwrap())) } else { None },
                   rssi: if opt_len >= 6 { Some(frame.optional_data[5]) } else { None },
-                  security: if opt_len >= 7 { Some(Security::try_from_primitive(frame.optional_data[6]).map_err(|_| ParseError::InvalidPrimitive)?) } else { None }
+                  security,
         })
     }
+
+    /// The telegram with its user data owned, independent of the frame it was decoded from
+    pub fn into_owned(self) -> RadioErp1<'static> {
+        RadioErp1 { user_data: Cow::Owned(self.user_data.into_owned()), ..self }
+    }
+
+    /// An owned copy of the telegram, e.g. to keep it past the frame it was decoded from
+    pub fn to_owned(&self) -> RadioErp1<'static> {
+        self.clone().into_owned()
+    }
+}
+
+/// Errors of a [`RadioErp1Builder`]
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BuildError {
+    /// A required field was not set
+    Missing(&'static str),
+    /// The user data length does not suit the RORG
+    DataLength { rorg: Rorg, len: usize },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing(field)           => write!(f, "Missing {}", field),
+            Self::DataLength { rorg, len } => write!(f, "Invalid user data length {} for RORG {:?}", len, rorg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// Builds a telegram to send, checking the length of its user data
+///
+/// ```
+/// # use enocean::{enocean::Rorg, packet::{Address, RadioErp1}};
+/// let erp = RadioErp1::builder()
+///     .choice(Rorg::Bs4)
+///     .user_data(&[0x00, 0x00, 0x80, 0x08])
+///     .sender(Address::new(0xff9b1280))
+///     .build()
+///     .unwrap();
+/// let frame = erp.encode();
+/// ```
+///
+/// The user data is borrowed or owned, e.g. `.user_data(vec![0x00, 0x00, 0x80, 0x08])` for a
+/// telegram outliving the buffer it was encoded in.
+#[derive(Debug,Clone,Default)]
+pub struct RadioErp1Builder<'a> {
+    choice: Option<Rorg>,
+    user_data: Cow<'a, [u8]>,
+    sender_id: Option<Address>,
+    status: u8,
+    destination: Option<Address>,
+    security: Option<Security>,
+}
+
+impl<'a> RadioErp1Builder<'a> {
+    pub fn choice(mut self, choice: Rorg) -> Self {
+        self.choice = Some(choice);
+        self
+    }
+
+    pub fn user_data(mut self, user_data: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.user_data = user_data.into();
+        self
+    }
+
+    pub fn sender(mut self, sender_id: Address) -> Self {
+        self.sender_id = Some(sender_id);
+        self
+    }
+
+    pub fn status(mut self, status: u8) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Address the telegram to a single device, rather than broadcasting it
+    pub fn destination(mut self, destination: Address) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn security(mut self, security: Security) -> Self {
+        self.security = Some(security);
+        self
+    }
+
+    /// The telegram, with the optional data for sending: subtelegram number 3, and 0xFF for
+    /// the signal strength
+    pub fn build(self) -> Result<RadioErp1<'a>, BuildError> {
+        let choice = self.choice.ok_or(BuildError::Missing("RORG"))?;
+        let sender_id = self.sender_id.ok_or(BuildError::Missing("sender"))?;
+        let len = self.user_data.len();
+        let valid = match choice {
+            Rorg::Rps | Rorg::Bs1 => len == 1,
+            Rorg::Bs4 => len == 4,
+            Rorg::Vld => (1..=14).contains(&len),
+            _ => len > 0,
+        };
+        if !valid {
+            return Err(BuildError::DataLength { rorg: choice, len });
+        }
+        Ok(RadioErp1 {
+            choice,
+            user_data: self.user_data,
+            sender_id,
+            status: self.status,
+            subtel_num: Some(SubtelNum::Send),
+            destination: Some(self.destination.unwrap_or(BROADCAST)),
+            rssi: Some(0xff),
+            security: Some(self.security.unwrap_or(Security::None)),
+        })
+    }
+}
+
+impl<'a> RadioSubTel<'a> {
+
+    /// Strongest signal among the subtelegrams, in dBm, or the one of the telegram if no
+    /// subtelegram was reported
+    pub fn best_rssi_dbm(&self) -> Option<i16> {
+        self.subtelegrams.iter().map(Subtelegram::rssi_dbm).max().or(self.erp.rssi_dbm())
+    }
+
+    pub fn encode(&self) -> ESP3Frame {
+        let erp = self.erp.encode();
+        let mut optional = erp.optional_data().to_vec();
+        optional.resize(7, 0);
+        optional.extend_from_slice(&self.timestamp.to_be_bytes());
+        for sub in &self.subtelegrams {
+            optional.extend_from_slice(&[sub.tick, sub.rssi, sub.status]);
+        }
+        ESP3Frame::assemble(0x03, erp.data(), &optional)
+    }
+
+    pub fn decode(frame: ESP3FrameRef<'a>) -> Result<Self, ParseError> {
+        Self::decode_with(frame, DecodeOptions::default())
+    }
+
+    /// Decode a telegram with its subtelegrams, as strictly as `options` say
+    pub fn decode_with(frame: ESP3FrameRef<'a>, options: DecodeOptions) -> Result<Self, ParseError> {
+        let optional = frame.optional_data;
+        if options.strict && !(optional.is_empty() || optional.get(9..).is_some_and(|subtelegrams| subtelegrams.chunks_exact(3).remainder().is_empty())) {
+            return Err(ParseError::OptionalLength { packet_type: frame.packet_type, length: optional.len() });
+        }
+        let erp = RadioErp1::decode_with(frame, options)?;
+        let timestamp = match optional.get(7..9) {
+            Some(bytes) => u16::from_be_bytes(bytes.try_into().unwrap()),
+            None => 0,
+        };
+        let subtelegrams = optional.get(9..).unwrap_or_default()
+            .chunks_exact(3)
+            .map(|sub| Subtelegram { tick: sub[0], rssi: sub[1], status: sub[2] })
+            .collect();
+        Ok(Self { erp, timestamp, subtelegrams })
+    }
+
+    pub fn into_owned(self) -> RadioSubTel<'static> {
+        RadioSubTel { erp: self.erp.into_owned(), timestamp: self.timestamp, subtelegrams: self.subtelegrams }
+    }
+
+    pub fn to_owned(&self) -> RadioSubTel<'static> {
+        self.clone().into_owned()
+    }
 }
 
 impl Response {
 
     pub fn encode(&self) -> ESP3Frame {
-        todo!()
+        let mut data = vec![self.code.into()];
+        data.extend_from_slice(&self.data);
+        ESP3Frame::assemble(0x02, &data, &[])
     }
 
     pub fn decode(frame: ESP3FrameRef) -> Result<Self, ParseError> {
-        let code = ResponseCode::try_from_primitive(frame.data[0])
-            .map_err(|_| ParseError::InvalidResultCode(frame.data[0]))?;
-        let data = frame.data[1..].into();
+        let code = *frame.data.first().ok_or(ParseError::PacketTooShort { packet_type: frame.packet_type, length: 0 })?;
+        let code = ResponseCode::try_from_primitive(code).map_err(|_| ParseError::InvalidResultCode(code))?;
+        let data = ResponseData::from_slice(&frame.data[1..]);
         Ok( Self { code, data })
     }
 
 }
 
+impl<'a> Event<'a> {
+
+    pub fn encode(&self) -> ESP3Frame {
+        let (code, data): (u8, &[u8]) = match self {
+            Self::SAReclaimUnsuccessful => (0x01, &[]),
+            Self::SAConfirmLearn { data } => (0x02, &data[..]),
+            Self::SALearnAck { data } => (0x03, &data[..]),
+            Self::COReady { wakeup, .. } => (0x04, core::slice::from_ref(wakeup)),
+            Self::COEventSecureDevices { cause, device } =>
+                return ESP3Frame::assemble(0x04, &[0x05, *cause, device.0[0], device.0[1], device.0[2], device.0[3]], &[]),
+            Self::CODutyCycleLimit { cause } => (0x06, core::slice::from_ref(cause)),
+            Self::COTXFailed { cause } => (0x07, core::slice::from_ref(cause)),
+            Self::COTXDone => (0x08, &[]),
+            Self::COLrnModeDisabled => (0x09, &[]),
+        };
+        let optional = match self {
+            Self::COReady { mode: Some(mode), .. } => core::slice::from_ref(mode),
+            _ => &[],
+        };
+        let mut frame_data = vec![code];
+        frame_data.extend_from_slice(data);
+        ESP3Frame::assemble(0x04, &frame_data, optional)
+    }
+
+    pub fn decode(frame: ESP3FrameRef<'a>) -> Result<Self, ParseError> {
+        let d = frame.data;
+        let too_short = || ParseError::PacketTooShort { packet_type: frame.packet_type, length: d.len() };
+        let arg = |idx: usize| d.get(idx).copied().ok_or_else(too_short);
+        match *d.first().ok_or_else(too_short)? {
+            0x01 => Ok(Self::SAReclaimUnsuccessful),
+            0x02 => Ok(Self::SAConfirmLearn { data: Cow::Borrowed(d.get(1..17).ok_or_else(too_short)?.try_into().unwrap()) }),
+            0x03 => Ok(Self::SALearnAck { data: Cow::Borrowed(d.get(1..4).ok_or_else(too_short)?.try_into().unwrap()) }),
+            0x04 => Ok(Self::COReady { wakeup: arg(1)?, mode: frame.optional_data.first().copied() }),
+            0x05 => Ok(Self::COEventSecureDevices {
+                cause: arg(1)?,
+                device: Address(d.get(2..6).ok_or_else(too_short)?.try_into().unwrap()),
+            }),
+            0x06 => Ok(Self::CODutyCycleLimit { cause: arg(1)? }),
+            0x07 => Ok(Self::COTXFailed { cause: arg(1)? }),
+            0x08 => Ok(Self::COTXDone),
+            0x09 => Ok(Self::COLrnModeDisabled),
+            code => Err(ParseError::InvalidPrimitive { packet_type: frame.packet_type, offset: 0, value: code }),
+        }
+    }
+
+    pub fn into_owned(self) -> Event<'static> {
+        match self {
+            Self::SAReclaimUnsuccessful => Event::SAReclaimUnsuccessful,
+            Self::SAConfirmLearn { data } => Event::SAConfirmLearn { data: Cow::Owned(data.into_owned()) },
+            Self::SALearnAck { data } => Event::SALearnAck { data: Cow::Owned(data.into_owned()) },
+            Self::COReady { wakeup, mode } => Event::COReady { wakeup, mode },
+            Self::COEventSecureDevices { cause, device } => Event::COEventSecureDevices { cause, device },
+            Self::CODutyCycleLimit { cause } => Event::CODutyCycleLimit { cause },
+            Self::COTXFailed { cause } => Event::COTXFailed { cause },
+            Self::COTXDone => Event::COTXDone,
+            Self::COLrnModeDisabled => Event::COLrnModeDisabled,
+        }
+    }
+
+    pub fn to_owned(&self) -> Event<'static> {
+        self.clone().into_owned()
+    }
+}
+
 impl<'a> CommonCommand<'a> {
 
     fn assemble(code: u8, data: &[u8], optional: &[u8]) -> ESP3Frame {
@@ -223,12 +1141,49 @@ impl<'a> CommonCommand<'a> {
         ESP3Frame::assemble(packet_type, &frame_data, optional)
     }
 
+    /// The command code, first byte of the data
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Unknown { code, .. } => *code,
+            Self::ReadVersion => 0x03,
+            Self::ReadIdBase => 0x08,
+            Self::WriteSecureDeviceAddPsk { .. } => 0x1f,
+            Self::WriteSecureDeviceMaintenanceKey { .. } => 0x3c,
+            Self::ReadSecureDeviceMaintenanceKey { .. } => 0x3d,
+            Self::GetFrequencyInfo => 0x25,
+        }
+    }
+
     fn encode(&self) -> ESP3Frame {
         match self {
-            &Self::Unknown { code, data, optional } => CommonCommand::assemble(code, data, optional),
+            Self::Unknown { code, data, optional } => CommonCommand::assemble(*code, data, optional),
             &Self::ReadVersion => CommonCommand::assemble(0x03, &[], &[]),
+            &Self::ReadIdBase => CommonCommand::assemble(0x08, &[], &[]),
+            &Self::WriteSecureDeviceAddPsk { device, psk } => CommonCommand::assemble(0x1f, &[&device.0[..], &psk].concat(), &[]),
+            &Self::WriteSecureDeviceMaintenanceKey { device, key, number } =>
+                CommonCommand::assemble(0x3c, &[&device.0[..], &key, &[number]].concat(), &[]),
+            &Self::ReadSecureDeviceMaintenanceKey { index } => CommonCommand::assemble(0x3d, &[index], &[]),
+            &Self::GetFrequencyInfo => CommonCommand::assemble(0x25, &[], &[]),
         }
     }
+
+    pub fn into_owned(self) -> CommonCommand<'static> {
+        match self {
+            Self::ReadVersion => CommonCommand::ReadVersion,
+            Self::ReadIdBase => CommonCommand::ReadIdBase,
+            Self::WriteSecureDeviceAddPsk { device, psk } => CommonCommand::WriteSecureDeviceAddPsk { device, psk },
+            Self::WriteSecureDeviceMaintenanceKey { device, key, number } =>
+                CommonCommand::WriteSecureDeviceMaintenanceKey { device, key, number },
+            Self::ReadSecureDeviceMaintenanceKey { index } => CommonCommand::ReadSecureDeviceMaintenanceKey { index },
+            Self::GetFrequencyInfo => CommonCommand::GetFrequencyInfo,
+            Self::Unknown { code, data, optional } =>
+                CommonCommand::Unknown { code, data: Cow::Owned(data.into_owned()), optional: Cow::Owned(optional.into_owned()) },
+        }
+    }
+
+    pub fn to_owned(&self) -> CommonCommand<'static> {
+        self.clone().into_owned()
+    }
 }
 
 impl<'a> Packet<'a> {
@@ -239,17 +1194,284 @@ impl<'a> Packet<'a> {
             &RadioErp1(erp) => erp.encode(),
             &CommonCommand(cmd) => cmd.encode(),
             &Response(resp) => resp.encode(),
+            &Event(event) => event.encode(),
+            &SmartAckCommand(cmd) => cmd.encode(),
+            &RadioSubTel(sub) => sub.encode(),
+            &RemoteManCommand(cmd) => cmd.encode(),
             &Unknown { packet_type, data, optional } => ESP3Frame::assemble(*packet_type, data, optional),
         }       
     }
 
+    /// Decode a packet. Packets of types without a decoder are returned as [`Packet::Unknown`];
+    /// errors are kept for malformed packets.
     pub fn decode(frame: ESP3FrameRef<'a>) -> Result<Self, ParseError> {
+        Self::decode_with(frame, DecodeOptions::default())
+    }
+
+    /// Decode a packet, as strictly as `options` say
+    pub fn decode_with(frame: ESP3FrameRef<'a>, options: DecodeOptions) -> Result<Self, ParseError> {
+        Self::decode_packet(frame, options).map_err(|e| e.in_frame(frame))
+    }
+
+    fn decode_packet(frame: ESP3FrameRef<'a>, options: DecodeOptions) -> Result<Self, ParseError> {
         match frame.packet_type {
-            0x01 => Ok(Self::RadioErp1(RadioErp1::decode(frame)?)),
+            0x01 => Ok(Self::RadioErp1(RadioErp1::decode_with(frame, options)?)),
             0x02 => Ok(Self::Response(Response::decode(frame)?)),
-            _    => Err(ParseError::UnsupportedPacketType),
+            0x03 => Ok(Self::RadioSubTel(RadioSubTel::decode_with(frame, options)?)),
+            0x04 => Ok(Self::Event(Event::decode(frame)?)),
+            0x07 => Ok(Self::RemoteManCommand(RemoteManCommand::decode(frame)?)),
+            packet_type => Ok(Self::Unknown { packet_type, data: Cow::Borrowed(frame.data), optional: Cow::Borrowed(frame.optional_data) }),
+        }
+    }
+
+    /// The packet with its payloads owned, independent of the frame it was decoded from,
+    /// e.g. to send it to another task
+    pub fn into_owned(self) -> Packet<'static> {
+        match self {
+            Self::RadioErp1(erp) => Packet::RadioErp1(erp.into_owned()),
+            Self::Response(response) => Packet::Response(response),
+            Self::Event(event) => Packet::Event(event.into_owned()),
+            Self::CommonCommand(cmd) => Packet::CommonCommand(cmd.into_owned()),
+            Self::SmartAckCommand(cmd) => Packet::SmartAckCommand(cmd),
+            Self::RadioSubTel(sub) => Packet::RadioSubTel(sub.into_owned()),
+            Self::RemoteManCommand(cmd) => Packet::RemoteManCommand(cmd),
+            Self::Unknown { packet_type, data, optional } =>
+                Packet::Unknown { packet_type, data: Cow::Owned(data.into_owned()), optional: Cow::Owned(optional.into_owned()) },
+        }
+    }
+
+    /// An owned copy of the packet
+    pub fn to_owned(&self) -> Packet<'static> {
+        self.clone().into_owned()
+    }
+
+}
+
+/// A packet decoded from arbitrary frame contents. Contents that do not decode are rejected,
+/// so that fuzzers explore the packets the decoders accept.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Packet<'a> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Self::decode(u.arbitrary()?).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use core::borrow::Borrow;
+
+    #[test]
+    fn given_addressed_telegram_then_encapsulate_and_unwrap_it() {
+        let destination: Address = "01a2b3c4".parse().unwrap();
+        let erp = RadioErp1::addressed(Rorg::Vld, &[0x01, 0x1e, 0x64], "ff9b1280".parse().unwrap(), destination);
+        let frame = erp.encode_adt().unwrap();
+        assert_eq!(frame.data()[..5], [0xa6, 0xd2, 0x01, 0x1e, 0x64]);
+        assert_eq!(frame.data()[5..9], [0x01, 0xa2, 0xb3, 0xc4]);
+
+        let decoded = RadioErp1::decode(frame.as_ref()).unwrap();
+        assert_eq!(decoded.choice, Rorg::Vld);
+        assert_eq!(decoded.user_data[..], [0x01, 0x1e, 0x64]);
+        assert_eq!(decoded.destination, Some(destination));
+
+        let broadcast = RadioErp1 { destination: None, ..erp };
+        assert_eq!(broadcast.encode_adt().err(), Some(BuildError::Missing("destination")));
+    }
+
+    #[test]
+    fn given_status_byte_then_decode_flags() {
+        let rps = StatusByte(0x32);
+        assert!(rps.t21());
+        assert!(rps.nu());
+        assert_eq!(rps.repeater_count(), 2);
+        assert_eq!(StatusByte(0x80).hash_type(), HashType::Crc8);
+        assert_eq!(StatusByte(0x01).hash_type(), HashType::Checksum);
+    }
+
+    #[test]
+    fn given_id_base_response_then_compute_senders() {
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&[0xff, 0x9b, 0x12, 0x80]) };
+        let base = IdBase::decode(&response).unwrap();
+        assert_eq!(base.sender(0x7f), Some("ff9b12ff".parse().unwrap()));
+        assert_eq!(base.sender(0x80), None);
+    }
+
+    #[test]
+    fn given_decoded_packet_then_own_it_past_its_frame() {
+        let packet = {
+            let frame = ESP3Frame::assemble(0x01, &[0xd2, 0x01, 0x1e, 0x64, 0xff, 0x9b, 0x12, 0x80, 0x00], &[]);
+            let packet = Packet::decode(frame.as_ref()).unwrap();
+            assert!(matches!(&packet, Packet::RadioErp1(RadioErp1 { user_data: Cow::Borrowed(_), .. })));
+            assert!(matches!(packet.to_owned(), Packet::RadioErp1(RadioErp1 { user_data: Cow::Owned(_), .. })));
+            packet.into_owned()
+        };
+        let Packet::RadioErp1(erp) = packet else { panic!("not a telegram") };
+        assert!(matches!(erp.user_data, Cow::Owned(_)));
+        assert_eq!(erp.user_data[..], [0x01, 0x1e, 0x64]);
+
+        let built = RadioErp1::builder().choice(Rorg::Vld).user_data(vec![0x01, 0x1e, 0x64]).sender(erp.sender_id).build().unwrap();
+        assert_eq!(built.encode().data(), erp.encode().data());
+    }
+
+    #[test]
+    fn given_co_ready_event_then_tell_reset_from_wakeup() {
+        let frame = ESP3Frame::assemble(0x04, &[0x04, 0x02], &[0x00]);
+        let Ok(Packet::Event(Event::COReady { wakeup, mode: Some(0) })) = Packet::decode(frame.as_ref()) else { panic!("not CO_READY") };
+        assert_eq!(WakeupCause::try_from(wakeup), Ok(WakeupCause::Watchdog));
+        assert!(WakeupCause::Watchdog.is_reset());
+        assert!(!WakeupCause::Uart.is_reset());
+    }
+
+    #[test]
+    fn given_frequency_info_response_then_decode_it() {
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&[0x01, 0x00]) };
+        let info = FrequencyInfo::decode(&response).unwrap();
+        assert_eq!(info, FrequencyInfo { frequency: Frequency::Mhz868_3, protocol: RadioProtocol::Erp1 });
+        assert_eq!(info.frequency.to_string(), "868.3 MHz");
+
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&[0x07, 0x20]) };
+        let info = FrequencyInfo::decode(&response).unwrap();
+        assert_eq!((info.frequency, info.protocol), (Frequency::Other(0x07), RadioProtocol::Bluetooth));
+        assert_eq!(info.frequency.to_string(), "Unknown frequency 0x07");
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&[0x01]) };
+        assert!(matches!(FrequencyInfo::decode(&response), Err(ParseError::PacketTooShort { .. })));
+        assert_eq!(Packet::CommonCommand(CommonCommand::GetFrequencyInfo).encode().data(), &[0x25]);
+    }
+
+    #[test]
+    fn given_version_response_then_decode_padded_description() {
+        let mut data = vec![2, 11, 1, 0, 2, 6, 3, 0, 0x04, 0x01, 0x8d, 0xe1, 0x45, 0x4f, 0x03, 0x00];
+        data.extend_from_slice(b"GATEWAYCTRL\0\0\0\0\0");
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&data) };
+        let version = VersionResponse::decode(&response).unwrap();
+        assert_eq!(version.description, "GATEWAYCTRL");
+        assert_eq!(version.chip_id, Address::new(0x04018de1));
+
+        // Extra bytes are ignored, Latin-1 descriptions are kept
+        data[16..22].copy_from_slice(b"Pr\xe9t\0\0");
+        data.extend_from_slice(&[0xff, 0xff]);
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&data) };
+        let version = VersionResponse::decode(&response).unwrap();
+        assert_eq!(version.description, "Prét");
+        assert_eq!(&version.description_raw[..6], b"Pr\xe9t\0\0");
+
+        let response = Response { code: ResponseCode::Ok, data: ResponseData::from_slice(&data[..31]) };
+        assert!(matches!(VersionResponse::decode(&response), Err(ParseError::PacketTooShort { .. })));
+    }
+
+    #[test]
+    fn given_integer_address_then_convert_both_ways() {
+        let address = Address::new(0x0180998e);
+        assert_eq!(address, "0180998e".parse().unwrap());
+        assert_eq!(address, Address::from([0x01, 0x80, 0x99, 0x8e]));
+        assert_eq!(address.as_bytes(), &[0x01, 0x80, 0x99, 0x8e]);
+        assert_eq!(u32::from(address), 0x0180998e);
+        assert_eq!(Address::from(0xffffffff), BROADCAST);
+    }
+
+    #[test]
+    fn given_unknown_packet_type_then_decode_as_unknown() {
+        let frame = ESP3Frame::assemble(0x0a, &[0x01, 0x02], &[0x03]);
+        let packet = Packet::decode(frame.as_ref()).unwrap();
+        assert!(matches!(&packet, Packet::Unknown { packet_type: 0x0a, data, optional } if data[..] == [0x01, 0x02] && optional[..] == [0x03]));
+        assert_eq!(packet.encode().optional_data(), frame.optional_data());
+
+        let truncated = ESP3Frame::assemble(0x01, &[0xf6, 0x50], &[]);
+        assert!(matches!(RadioErp1::decode(truncated.as_ref()), Err(ParseError::PacketTooShort { packet_type: 0x01, length: 2 })));
+    }
+
+    #[test]
+    fn given_uncommon_choice_byte_then_decode_as_vendor_rorg() {
+        let frame = ESP3Frame::assemble(0x01, &[0x62, 0x12, 0x01, 0x02, 0x03, 0x04, 0x00], &[]);
+        let Packet::RadioErp1(erp) = Packet::decode(frame.as_ref()).unwrap() else { panic!() };
+        assert_eq!(erp.choice, Rorg::Vendor(0x62));
+        assert_eq!(erp.choice.to_string(), "RORG 62");
+        assert_eq!(Rorg::from(0xd5).to_string(), "1BS");
+        assert_eq!(erp.encode().data(), frame.data());
+    }
+
+    #[test]
+    fn given_error_response_then_report_command_error() {
+        let frame = ESP3Frame::assemble(0x02, &[0x82], &[]);
+        let response = Response::decode(frame.as_ref()).unwrap();
+        assert_eq!(response.code, ResponseCode::FlashHwError);
+        assert_eq!(response.code.result(), Err(crate::enocean::CommandError::Flash));
+        assert_eq!(ResponseCode::Ok.result(), Ok(()));
+    }
+
+    #[test]
+    fn given_invalid_optional_data_then_report_offset_and_value() {
+        let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x00]);
+        let error = Packet::decode(frame.as_ref()).unwrap_err();
+        let expected = "Invalid value 0x00 at offset 7 of packet of type 0x01";
+        if cfg!(feature = "error-frames") {
+            assert_eq!(Borrow::<[u8]>::borrow(error.source_frame().unwrap()), Borrow::<[u8]>::borrow(&frame));
+            assert!(error.to_string().starts_with(expected));
+        } else {
+            assert!(error.source_frame().is_none());
+            assert_eq!(error.to_string(), expected);
         }
     }
 
+    #[test]
+    fn given_out_of_spec_optional_data_then_decode_as_options_say() {
+        let received = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x40, 0x00]);
+        assert_eq!(RadioErp1::decode(received.as_ref()).unwrap().subtel_num, Some(SubtelNum::Receive));
+        let twice = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x02, 0xff, 0xff, 0xff, 0xff, 0x40, 0x00]);
+        assert_eq!(RadioErp1::decode(twice.as_ref()).unwrap().subtel_num, Some(SubtelNum::Other(0x02)));
+
+        let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x00, 0xff, 0xff, 0xff, 0xff, 0x40, 0x09]);
+        assert!(RadioErp1::decode(frame.as_ref()).is_err());
+        let erp = RadioErp1::decode_with(frame.as_ref(), DecodeOptions::LENIENT).unwrap();
+        assert_eq!((erp.subtel_num, erp.security), (Some(SubtelNum::Other(0x00)), Some(Security::Other(0x09))));
+        assert_eq!(u8::from(erp.security.unwrap()), 0x09);
+        assert!(SubtelNum::try_from_primitive(0x00).is_err() && Security::try_from_primitive(0x09).is_err());
+        assert_eq!(Security::try_from_primitive(0x03), Ok(Security::Authenticated));
+
+        let short = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[0x01, 0xff]);
+        assert!(RadioErp1::decode(short.as_ref()).is_ok());
+        assert!(matches!(RadioErp1::decode_with(short.as_ref(), DecodeOptions::STRICT), Err(ParseError::OptionalLength { length: 2, .. })));
+    }
+
+    #[test]
+    fn given_builder_then_check_user_data_length() {
+        let sender = Address::new(0xff9b1280);
+        let erp = RadioErp1::builder().choice(Rorg::Rps).user_data(&[0x30]).sender(sender).build().unwrap();
+        assert_eq!(erp.encode().optional_data(), &[0x03, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+
+        let short = RadioErp1::builder().choice(Rorg::Bs4).user_data(&[0x00, 0x08]).sender(sender).build();
+        assert_eq!(short.unwrap_err(), BuildError::DataLength { rorg: Rorg::Bs4, len: 2 });
+        let long = RadioErp1::builder().choice(Rorg::Vld).user_data(&[0; 15]).sender(sender).build();
+        assert!(long.is_err());
+        assert_eq!(RadioErp1::builder().choice(Rorg::Rps).user_data(&[0x30]).build().unwrap_err(), BuildError::Missing("sender"));
+    }
+
+    #[test]
+    fn given_base_id_then_locate_addresses_in_its_range() {
+        let base = IdBase(Address::new(0xff9b1280));
+        assert!(Address::new(0xff9b12ff).is_base_id(&base));
+        assert!(!Address::new(0xff9b127f).is_base_id(&base));
+        assert_eq!(Address::new(0xff9b1290).offset_from(&base), Some(0x10));
+        assert_eq!(Address::new(0xff9b1300).offset_from(&base), None);
+        assert_eq!(base.range().iter().last(), Some(Address::new(0xff9b12ff)));
+        assert!(BROADCAST.is_broadcast());
+
+        let last = AddressRange::new(Address::new(0xffffff80), 200);
+        assert_eq!(last.len(), 128);
+        assert_eq!(last.get(127), Some(BROADCAST));
+    }
+
+    #[test]
+    fn given_subtelegrams_then_report_best_rssi_in_dbm() {
+        let optional = [0x01, 0xff, 0xff, 0xff, 0xff, 0x2d, 0x00, 0x01, 0x00, 0x00, 0x3a, 0x00, 0x05, 0x2d, 0x00];
+        let frame = ESP3Frame::assemble(0x03, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &optional);
+        let Packet::RadioSubTel(sub) = Packet::decode(frame.as_ref()).unwrap() else { panic!() };
+        assert_eq!(sub.erp.rssi_dbm(), Some(-45));
+        assert_eq!(sub.timestamp, 0x0100);
+        assert_eq!(sub.subtelegrams.len(), 2);
+        assert_eq!(sub.best_rssi_dbm(), Some(-45));
+        assert_eq!(sub.encode().optional_data(), &optional);
+    }
 }
 
@@ -0,0 +1,255 @@
+//! Structured access to response payloads
+//!
+//! The payload of a [`Response`] depends on the command it answers, which the response does
+//! not repeat. For the commands without a typed decoder, e.g. those sent as
+//! [`CommonCommand::Unknown`], a [`ResponseView`] reads the payload by field name, from a
+//! declarative [`ResponseLayout`]: the layouts of the common commands are in [`LAYOUTS`],
+//! and firmware-specific commands are described the same way.
+//!
+//! ```
+//! use std::borrow::Cow;
+//! use enocean::enocean::ReturnCode;
+//! use enocean::packet::view::{FieldKind, FieldValue, ResponseField, ResponseLayout, ResponseView};
+//! use enocean::packet::{CommonCommand, Response, ResponseData};
+//!
+//! // CO_RD_REPEATER
+//! let response = Response { code: ReturnCode::Ok, data: ResponseData::from_slice(&[0x01, 0x02]) };
+//! let command = CommonCommand::Unknown { code: 0x0a, data: Cow::Borrowed(&[]), optional: Cow::Borrowed(&[]) };
+//! let view = ResponseView::for_command(&command, &response).unwrap();
+//! assert_eq!(view.get("level"), Some(FieldValue::U8(2)));
+//!
+//! // A vendor command answering a 16 bit counter and a status byte
+//! const COUNTER: ResponseLayout = ResponseLayout::new(0x80, "VENDOR_RD_COUNTER", &[
+//!     ResponseField::new("count", 0, FieldKind::U16),
+//!     ResponseField::new("status", 2, FieldKind::U8),
+//! ]);
+//! let response = Response { code: ReturnCode::Ok, data: ResponseData::from_slice(&[0x01, 0x2c, 0x00]) };
+//! assert_eq!(ResponseView::new(&COUNTER, &response).get("count"), Some(FieldValue::U16(300)));
+//! ```
+
+use core::fmt;
+
+use super::{CommonCommand, Response};
+use self::FieldKind::*;
+
+/// How the bytes of a field are read. Integers are big-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    U8,
+    U16,
+    U32,
+    Address,
+    /// `len` raw bytes
+    Bytes(usize),
+    /// Text of up to `len` bytes, NUL-padded
+    Text(usize),
+}
+
+impl FieldKind {
+    /// Number of bytes of the field
+    pub const fn size(&self) -> usize {
+        match *self {
+            Self::U8 => 1,
+            Self::U16 => 2,
+            Self::U32 | Self::Address => 4,
+            Self::Bytes(len) | Self::Text(len) => len,
+        }
+    }
+}
+
+/// A named field of a response payload, at a byte offset. Offsets count from the first byte
+/// after the return code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseField {
+    pub name: &'static str,
+    pub offset: usize,
+    pub kind: FieldKind,
+}
+
+impl ResponseField {
+    pub const fn new(name: &'static str, offset: usize, kind: FieldKind) -> Self {
+        Self { name, offset, kind }
+    }
+}
+
+/// The payload layout of the response to a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseLayout {
+    /// Code of the command answered
+    pub code: u8,
+    pub name: &'static str,
+    pub fields: &'static [ResponseField],
+}
+
+impl ResponseLayout {
+    pub const fn new(code: u8, name: &'static str, fields: &'static [ResponseField]) -> Self {
+        Self { code, name, fields }
+    }
+
+    /// The layout of the response to common command `code`, if known
+    pub fn of(code: u8) -> Option<&'static ResponseLayout> {
+        LAYOUTS.iter().find(|layout| layout.code == code)
+    }
+}
+
+/// Response layouts of the common commands with a fixed size payload
+pub const LAYOUTS: &[ResponseLayout] = &[
+    ResponseLayout::new(0x03, "CO_RD_VERSION", &[
+        ResponseField::new("app_version", 0, Bytes(4)),
+        ResponseField::new("api_version", 4, Bytes(4)),
+        ResponseField::new("chip_id", 8, Address),
+        ResponseField::new("chip_version", 12, U32),
+        ResponseField::new("app_description", 16, Text(16)),
+    ]),
+    ResponseLayout::new(0x08, "CO_RD_IDBASE", &[
+        ResponseField::new("base_id", 0, Address),
+    ]),
+    ResponseLayout::new(0x0a, "CO_RD_REPEATER", &[
+        ResponseField::new("enabled", 0, U8),
+        ResponseField::new("level", 1, U8),
+    ]),
+    ResponseLayout::new(0x1d, "CO_RD_NUMSECUREDEVICES", &[
+        ResponseField::new("count", 0, U8),
+    ]),
+    ResponseLayout::new(0x23, "CO_RD_DUTYCYCLE_LIMIT", &[
+        ResponseField::new("available", 0, U8),
+        ResponseField::new("slots", 1, U8),
+        ResponseField::new("slot_period", 2, U16),
+        ResponseField::new("slot_left", 4, U16),
+        ResponseField::new("load_after_actual", 6, U8),
+    ]),
+    ResponseLayout::new(0x25, "CO_GET_FREQUENCY_INFO", &[
+        ResponseField::new("frequency", 0, U8),
+        ResponseField::new("protocol", 1, U8),
+    ]),
+    ResponseLayout::new(0x27, "CO_GET_STEPCODE", &[
+        ResponseField::new("step_code", 0, U8),
+        ResponseField::new("status_code", 1, U8),
+    ]),
+    ResponseLayout::new(0x3d, "CO_RD_SECUREDEVICE_MAINTENANCEKEY", &[
+        ResponseField::new("device", 0, Address),
+        ResponseField::new("key", 4, Bytes(16)),
+        ResponseField::new("number", 20, U8),
+    ]),
+];
+
+/// The value of a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldValue<'a> {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    Address(super::Address),
+    Bytes(&'a [u8]),
+    /// Text up to its first NUL byte, if valid UTF-8; [`FieldValue::Bytes`] otherwise
+    Text(&'a str),
+}
+
+impl FieldValue<'_> {
+    /// The value of an integer field
+    pub fn as_u32(&self) -> Option<u32> {
+        match *self {
+            Self::U8(v) => Some(v.into()),
+            Self::U16(v) => Some(v.into()),
+            Self::U32(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::U8(v) => write!(f, "{}", v),
+            Self::U16(v) => write!(f, "{}", v),
+            Self::U32(v) => write!(f, "{}", v),
+            Self::Address(address) => write!(f, "{}", address),
+            Self::Bytes(bytes) => bytes.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+            Self::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// A response payload, read with the layout of the command it answers
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseView<'a> {
+    pub layout: &'a ResponseLayout,
+    data: &'a [u8],
+}
+
+impl<'a> ResponseView<'a> {
+    pub fn new(layout: &'a ResponseLayout, response: &'a Response) -> Self {
+        Self { layout, data: &response.data }
+    }
+
+    /// View the response to common command `code`, if its layout is in [`LAYOUTS`]
+    pub fn for_code(code: u8, response: &'a Response) -> Option<Self> {
+        Some(Self::new(ResponseLayout::of(code)?, response))
+    }
+
+    /// View the response to `command`, if its layout is in [`LAYOUTS`]
+    pub fn for_command(command: &CommonCommand, response: &'a Response) -> Option<Self> {
+        Self::for_code(command.code(), response)
+    }
+
+    /// The value of the field `name`. `None` if the layout has no such field, or the payload
+    /// ends before it.
+    pub fn get(&self, name: &str) -> Option<FieldValue<'a>> {
+        self.layout.fields.iter().find(|field| field.name == name).and_then(|field| self.read(field))
+    }
+
+    /// The fields of the layout with their values, `None` past the end of the payload
+    pub fn fields(&self) -> impl Iterator<Item = (&'a str, Option<FieldValue<'a>>)> + '_ {
+        self.layout.fields.iter().map(|field| (field.name, self.read(field)))
+    }
+
+    /// Payload bytes after the last field of the layout, e.g. appended by newer firmware
+    pub fn trailing(&self) -> &'a [u8] {
+        let end = self.layout.fields.iter().map(|field| field.offset + field.kind.size()).max().unwrap_or(0);
+        self.data.get(end..).unwrap_or_default()
+    }
+
+    /// Util : read a field, if within the payload
+    fn read(&self, field: &ResponseField) -> Option<FieldValue<'a>> {
+        let d = self.data.get(field.offset..field.offset + field.kind.size())?;
+        Some(match field.kind {
+            U8 => FieldValue::U8(d[0]),
+            U16 => FieldValue::U16(u16::from_be_bytes(d.try_into().unwrap())),
+            U32 => FieldValue::U32(u32::from_be_bytes(d.try_into().unwrap())),
+            Address => FieldValue::Address(super::Address(d.try_into().unwrap())),
+            Bytes(_) => FieldValue::Bytes(d),
+            Text(_) => {
+                let text = &d[..d.iter().position(|&b| b == 0).unwrap_or(d.len())];
+                core::str::from_utf8(text).map_or(FieldValue::Bytes(text), FieldValue::Text)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enocean::ReturnCode;
+    use crate::packet::ResponseData;
+    use alloc::string::ToString;
+
+    #[test]
+    fn given_version_response_then_read_fields_by_name() {
+        let mut data = vec![2, 11, 1, 0, 2, 6, 3, 0, 0x01, 0x80, 0x99, 0x8e, 0x45, 0x00, 0x01, 0x03];
+        data.extend_from_slice(b"GATEWAYCTRL\0\0\0\0\0\xaa");
+        let response = Response { code: ReturnCode::Ok, data: ResponseData::from_slice(&data) };
+        let view = ResponseView::for_command(&CommonCommand::ReadVersion, &response).unwrap();
+        assert_eq!(view.layout.name, "CO_RD_VERSION");
+        assert_eq!(view.get("chip_id"), Some(FieldValue::Address(crate::packet::Address::new(0x0180998e))));
+        assert_eq!(view.get("app_description"), Some(FieldValue::Text("GATEWAYCTRL")));
+        assert_eq!(view.get("app_version").unwrap().to_string(), "020b0100");
+        assert_eq!(view.get("chip_version").and_then(|v| v.as_u32()), Some(0x45000103));
+        assert_eq!(view.get("missing"), None);
+        assert_eq!(view.trailing(), &[0xaa]);
+
+        let short = Response { code: ReturnCode::Ok, data: ResponseData::from_slice(&data[..10]) };
+        let view = ResponseView::for_code(0x03, &short).unwrap();
+        assert_eq!(view.fields().filter(|(_, value)| value.is_some()).count(), 2);
+        assert!(ResponseView::for_code(0x42, &short).is_none());
+    }
+}
@@ -0,0 +1,228 @@
+//! Radio paths of telegrams, direct or through repeaters
+//!
+//! The status byte of a telegram counts the repeaters it went through. With the RADIO_SUB_TEL
+//! packets of the transceiver, each subtelegram received comes with its own status byte and
+//! signal strength, so a telegram heard both directly and from a repeater shows both paths.
+//! A [`PathInfo`] combines them for a telegram, and a [`PathTracker`] aggregates the last
+//! telegrams of each sender, to tell the sensors heard directly from those only reached
+//! through repeaters:
+//!
+//! ```
+//! # use enocean::frame::ESP3Frame;
+//! use enocean::packet::RadioSubTel;
+//! use enocean::path::{PathTracker, Route};
+//!
+//! // A 4BS telegram received directly at -80 dBm, and from a repeater at -52 dBm
+//! let frame = ESP3Frame::assemble(0x03, &[0xa5, 0x00, 0x00, 0x80, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x01],
+//!     &[0x01, 0xff, 0xff, 0xff, 0xff, 0x34, 0x00, 0x00, 0x10, 0x00, 0x50, 0x00, 0x12, 0x34, 0x01]);
+//! let sub = RadioSubTel::decode(frame.as_ref())?;
+//!
+//! let mut paths = PathTracker::default();
+//! let path = paths.record_subtel(&sub);
+//! assert_eq!((path.direct_rssi(), path.best_rssi()), (Some(-80), Some(-52)));
+//! assert_eq!(paths.get(&"0180998e".parse()?).unwrap().route(), Route::Direct);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Plain radio telegrams only report the copy the transceiver kept, see [`PathInfo::of`].
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::packet::{Address, RadioErp1, RadioSubTel, StatusByte};
+use crate::quality::{Stats, WINDOW};
+
+/// A copy of a telegram, as received
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Hop {
+    /// Repeaters the copy went through
+    pub hops: u8,
+    /// Signal strength in dBm, of the last hop
+    pub rssi: Option<i16>,
+}
+
+/// The paths a telegram was received through
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PathInfo {
+    pub sender: Address,
+    /// The copies received, in order
+    pub copies: Vec<Hop>,
+}
+
+impl PathInfo {
+    /// The path of a radio telegram: the single copy reported by the transceiver
+    pub fn of(erp: &RadioErp1) -> Self {
+        Self { sender: erp.sender_id, copies: vec![Hop { hops: erp.status_byte().repeater_count(), rssi: erp.rssi_dbm() }] }
+    }
+
+    /// The paths of a telegram, from its subtelegrams. Falls back to [`Self::of`] when the
+    /// transceiver reported no subtelegram.
+    pub fn of_subtel(sub: &RadioSubTel) -> Self {
+        if sub.subtelegrams.is_empty() {
+            return Self::of(&sub.erp);
+        }
+        let copies = sub.subtelegrams.iter()
+            .map(|subtelegram| Hop { hops: StatusByte(subtelegram.status).repeater_count(), rssi: Some(subtelegram.rssi_dbm()) })
+            .collect();
+        Self { sender: sub.erp.sender_id, copies }
+    }
+
+    /// Whether a copy was received straight from the sender
+    pub fn heard_directly(&self) -> bool {
+        self.copies.iter().any(|copy| copy.hops == 0)
+    }
+
+    /// Repeaters the longest path went through
+    pub fn max_hops(&self) -> u8 {
+        self.copies.iter().map(|copy| copy.hops).max().unwrap_or(0)
+    }
+
+    /// Strongest signal of the copies received straight from the sender, in dBm
+    pub fn direct_rssi(&self) -> Option<i16> {
+        self.copies.iter().filter(|copy| copy.hops == 0).filter_map(|copy| copy.rssi).max()
+    }
+
+    /// Strongest signal of all the copies, in dBm
+    pub fn best_rssi(&self) -> Option<i16> {
+        self.copies.iter().filter_map(|copy| copy.rssi).max()
+    }
+}
+
+/// How the telegrams of a sender reach the transceiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Route {
+    /// Every telegram was heard directly
+    Direct,
+    /// Some telegrams were only heard through repeaters
+    Mixed,
+    /// No telegram was heard directly
+    Repeated,
+}
+
+/// Summary of the paths of the last telegrams of a sender
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PathSummary {
+    pub telegrams: usize,
+    /// Telegrams with a copy received straight from the sender
+    pub direct: usize,
+    /// Signal strength of the direct copies, in dBm
+    pub direct_rssi: Option<Stats>,
+    /// Signal strength of the copies through repeaters, in dBm
+    pub repeated_rssi: Option<Stats>,
+    /// Repeaters the longest path went through
+    pub max_hops: u8,
+}
+
+impl PathSummary {
+    pub fn route(&self) -> Route {
+        match self.direct {
+            0 => Route::Repeated,
+            direct if direct == self.telegrams => Route::Direct,
+            _ => Route::Mixed,
+        }
+    }
+}
+
+/// Paths of the last telegrams, by sender
+#[derive(Debug, Clone)]
+pub struct PathTracker {
+    window: usize,
+    senders: BTreeMap<Address, VecDeque<PathInfo>>,
+}
+
+impl Default for PathTracker {
+    fn default() -> Self {
+        Self::new(WINDOW)
+    }
+}
+
+impl PathTracker {
+    /// Keep the last `window` telegrams of each sender
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), senders: BTreeMap::new() }
+    }
+
+    /// Record the paths of a telegram
+    pub fn record(&mut self, path: PathInfo) {
+        let paths = self.senders.entry(path.sender).or_default();
+        if paths.len() == self.window {
+            paths.pop_front();
+        }
+        paths.push_back(path);
+    }
+
+    /// Record a radio telegram, returning its path
+    pub fn record_erp(&mut self, erp: &RadioErp1) -> PathInfo {
+        let path = PathInfo::of(erp);
+        self.record(path.clone());
+        path
+    }
+
+    /// Record a telegram with its subtelegrams, returning its paths
+    pub fn record_subtel(&mut self, sub: &RadioSubTel) -> PathInfo {
+        let path = PathInfo::of_subtel(sub);
+        self.record(path.clone());
+        path
+    }
+
+    /// Summary of the last telegrams of `address`, if any was received
+    pub fn get(&self, address: &Address) -> Option<PathSummary> {
+        let paths = self.senders.get(address)?;
+        let copies = || paths.iter().flat_map(|path| &path.copies);
+        Some(PathSummary {
+            telegrams: paths.len(),
+            direct: paths.iter().filter(|path| path.heard_directly()).count(),
+            direct_rssi: Stats::of(copies().filter(|copy| copy.hops == 0).filter_map(|copy| copy.rssi)),
+            repeated_rssi: Stats::of(copies().filter(|copy| copy.hops > 0).filter_map(|copy| copy.rssi)),
+            max_hops: paths.iter().map(PathInfo::max_hops).max().unwrap_or(0),
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Address, PathSummary)> + '_ {
+        self.senders.keys().filter_map(|address| Some((*address, self.get(address)?)))
+    }
+
+    /// Forget the telegrams of a sender
+    pub fn clear(&mut self, address: &Address) {
+        self.senders.remove(address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ESP3Frame;
+
+    #[test]
+    fn given_repeated_telegrams_then_tell_the_route() {
+        let subtel = |subtelegrams: &[u8]| {
+            let mut optional = vec![0x01, 0xff, 0xff, 0xff, 0xff, 0x40, 0x00, 0x00, 0x10];
+            optional.extend_from_slice(subtelegrams);
+            let frame = ESP3Frame::assemble(0x03, &[0xf6, 0x50, 0xfe, 0xfa, 0x12, 0x34, 0x31], &optional);
+            RadioSubTel::decode(frame.as_ref()).unwrap().into_owned()
+        };
+        let mut paths = PathTracker::new(3);
+        let sender = "fefa1234".parse().unwrap();
+
+        let path = paths.record_subtel(&subtel(&[0x00, 0x48, 0x31, 0x12, 0x5a, 0x32]));
+        assert_eq!(path.copies, vec![Hop { hops: 1, rssi: Some(-0x48) }, Hop { hops: 2, rssi: Some(-0x5a) }]);
+        assert!(!path.heard_directly());
+        assert_eq!((path.max_hops(), path.direct_rssi()), (2, None));
+        assert_eq!(paths.get(&sender).unwrap().route(), Route::Repeated);
+
+        // Without subtelegrams, the hop count of the telegram
+        assert_eq!(paths.record_subtel(&subtel(&[])).copies, vec![Hop { hops: 1, rssi: Some(-0x40) }]);
+        paths.record_subtel(&subtel(&[0x00, 0x50, 0x30, 0x12, 0x44, 0x31]));
+        let summary = paths.get(&sender).unwrap();
+        assert_eq!((summary.telegrams, summary.direct, summary.max_hops, summary.route()), (3, 1, 2, Route::Mixed));
+        assert_eq!(summary.direct_rssi.map(|rssi| rssi.max), Some(-0x50));
+        assert_eq!(summary.repeated_rssi.map(|rssi| (rssi.min, rssi.max, rssi.samples)), Some((-0x5a, -0x40, 4)));
+
+        // The window drops the first telegram
+        paths.record_subtel(&subtel(&[0x00, 0x50, 0x30]));
+        assert_eq!(paths.get(&sender).unwrap().max_hops, 1);
+    }
+}
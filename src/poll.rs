@@ -0,0 +1,233 @@
+//! Periodic queries to bidirectional devices
+//!
+//! Some devices only report when asked: D2-01 actuators answer status and measurement
+//! queries, and some meters a reading request. A [`Poller`] sends a [`Query`] to each
+//! scheduled device at its interval, and matches the responses to the polls that asked for
+//! them. It does no I/O; see `device::Gateway::with_poller` for the gateway sending the polls.
+//!
+//! Polls are never closer than the spacing of the poller, [`POLL_SPACING`] by default: a
+//! poller of many devices spreads their polls over time rather than sending them in a burst,
+//! which keeps the transceiver within the duty cycle of its band and leaves the air to the
+//! devices. A device has at most one poll awaiting its response, until [`RESPONSE_TIMEOUT`].
+//!
+//! ```
+//! use enocean::poll::{Poller, Query};
+//! use std::time::{Duration, Instant};
+//!
+//! let (relay, plug) = ("05123456".parse()?, "0180998e".parse()?);
+//! let mut poller = Poller::new();
+//! poller.schedule(relay, Query::Status { channel: 0 }, Duration::from_secs(60));
+//! poller.schedule(plug, Query::Measurement { channel: 0, power: true }, Duration::from_secs(60));
+//!
+//! let start = Instant::now();
+//! assert_eq!(poller.next(start).unwrap().device, relay);
+//! assert_eq!(poller.next(start), None);
+//! assert_eq!(poller.next(start + Duration::from_secs(1)).unwrap().device, plug);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::eep::d2_01::{Command, Message, ALL_CHANNELS};
+use crate::enocean::Rorg;
+use crate::packet::{Address, RadioErp1};
+
+/// Shortest time between two polls
+pub const POLL_SPACING: Duration = Duration::from_secs(1);
+
+/// How long a poll awaits its response
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What to ask a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// D2-01 status query, answered by a status response for the channel
+    Status { channel: u8 },
+    /// D2-01 measurement query, of power if `power` is set, of energy otherwise, answered by
+    /// a measurement response for the channel
+    Measurement { channel: u8, power: bool },
+    /// A 4BS telegram, answered by the next 4BS telegram of the device, e.g. the reading
+    /// request of the A5-12 meters supporting one
+    Bs4([u8; 4]),
+}
+
+impl Query {
+    /// The RORG and user data of the telegram to send
+    pub fn telegram(&self) -> (Rorg, Vec<u8>) {
+        match *self {
+            Self::Status { channel } => (Rorg::Vld, Command::StatusQuery { channel }.encode()),
+            Self::Measurement { channel, power } => (Rorg::Vld, Command::MeasurementQuery { channel, power }.encode()),
+            Self::Bs4(data) => (Rorg::Bs4, data.to_vec()),
+        }
+    }
+
+    /// Whether a telegram of the device polled answers the query
+    pub fn is_answered_by(&self, erp: &RadioErp1) -> bool {
+        let for_channel = |queried: u8, channel: u8| queried == ALL_CHANNELS || queried == channel;
+        match (*self, erp.choice) {
+            (Self::Status { channel }, Rorg::Vld) => matches!(Message::decode(erp), Ok(Message::Status(status)) if for_channel(channel, status.channel)),
+            (Self::Measurement { channel, .. }, Rorg::Vld) => {
+                matches!(Message::decode(erp), Ok(Message::Measurement(measurement)) if for_channel(channel, measurement.channel))
+            }
+            (Self::Bs4(_), Rorg::Bs4) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A query sent to a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Poll {
+    pub device: Address,
+    pub query: Query,
+    pub sent: Instant,
+}
+
+/// How a poll ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    /// The device answered after `latency`
+    Answered { poll: Poll, latency: Duration },
+    /// No response within the timeout
+    Missed(Poll),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    device: Address,
+    query: Query,
+    interval: Duration,
+    /// When the query is next due, `None` for as soon as possible
+    due: Option<Instant>,
+    /// When the query awaiting its response was sent
+    sent: Option<Instant>,
+}
+
+impl Entry {
+    fn poll(&self, sent: Instant) -> Poll {
+        Poll { device: self.device, query: self.query, sent }
+    }
+}
+
+/// The queries of the scheduled devices, and the polls awaiting their response
+#[derive(Debug, Clone)]
+pub struct Poller {
+    spacing: Duration,
+    timeout: Duration,
+    last: Option<Instant>,
+    entries: Vec<Entry>,
+}
+
+impl Default for Poller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Poller {
+    /// A poller with the default spacing and response timeout
+    pub fn new() -> Self {
+        Self { spacing: POLL_SPACING, timeout: RESPONSE_TIMEOUT, last: None, entries: Vec::new() }
+    }
+
+    /// Send polls at least `spacing` apart
+    pub fn with_spacing(mut self, spacing: Duration) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Await responses for `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send `query` to `device` every `interval`, starting as soon as possible. Scheduling a
+    /// query again changes its interval.
+    pub fn schedule(&mut self, device: Address, query: Query, interval: Duration) {
+        match self.entries.iter_mut().find(|entry| entry.device == device && entry.query == query) {
+            Some(entry) => entry.interval = interval,
+            None => self.entries.push(Entry { device, query, interval, due: None, sent: None }),
+        }
+    }
+
+    /// Stop polling `device`
+    pub fn unschedule(&mut self, device: &Address) {
+        self.entries.retain(|entry| entry.device != *device);
+    }
+
+    pub fn is_scheduled(&self, device: &Address) -> bool {
+        self.entries.iter().any(|entry| entry.device == *device)
+    }
+
+    /// The poll to send at `now`, the most overdue one, unless the last poll was sent less
+    /// than the spacing ago. It is considered sent.
+    pub fn next(&mut self, now: Instant) -> Option<Poll> {
+        if self.last.is_some_and(|last| now.saturating_duration_since(last) < self.spacing) {
+            return None;
+        }
+        let awaiting: Vec<Address> = self.entries.iter().filter(|entry| entry.sent.is_some()).map(|entry| entry.device).collect();
+        let entry = self.entries.iter_mut()
+            .filter(|entry| entry.due.is_none_or(|due| due <= now) && !awaiting.contains(&entry.device))
+            .min_by_key(|entry| entry.due)?;
+        entry.due = Some(now + entry.interval);
+        entry.sent = Some(now);
+        self.last = Some(now);
+        Some(entry.poll(now))
+    }
+
+    /// Process a telegram received at `now`, returning the poll it answers, if any
+    pub fn handle(&mut self, erp: &RadioErp1, now: Instant) -> Option<PollOutcome> {
+        let entry = self.entries.iter_mut()
+            .find(|entry| entry.device == erp.sender_id && entry.sent.is_some() && entry.query.is_answered_by(erp))?;
+        let sent = entry.sent.take()?;
+        let poll = entry.poll(sent);
+        Some(PollOutcome::Answered { poll, latency: now.saturating_duration_since(poll.sent) })
+    }
+
+    /// The polls whose response timed out at `now`. Their devices can be polled again.
+    pub fn expired(&mut self, now: Instant) -> Vec<Poll> {
+        let timeout = self.timeout;
+        self.entries.iter_mut()
+            .filter(|entry| entry.sent.is_some_and(|sent| now.saturating_duration_since(sent) >= timeout))
+            .filter_map(|entry| entry.sent.take().map(|sent| entry.poll(sent)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_scheduled_queries_then_spread_polls_and_match_responses() {
+        let start = Instant::now();
+        let (relay, meter) = ("05123456".parse().unwrap(), "0180998e".parse().unwrap());
+        let mut poller = Poller::new().with_spacing(Duration::from_secs(2)).with_timeout(Duration::from_secs(3));
+        poller.schedule(relay, Query::Status { channel: 1 }, Duration::from_secs(30));
+        poller.schedule(relay, Query::Measurement { channel: 1, power: true }, Duration::from_secs(30));
+        poller.schedule(meter, Query::Bs4([0x00, 0x00, 0x00, 0x08]), Duration::from_secs(10));
+
+        let status = poller.next(start).unwrap();
+        assert_eq!((status.device, status.query.telegram()), (relay, (Rorg::Vld, vec![0x03, 0x01])));
+        assert_eq!(poller.next(start + Duration::from_secs(1)), None);
+        // The measurement of the relay waits for its status response
+        let reading = poller.next(start + Duration::from_secs(2)).unwrap();
+        assert_eq!(reading.device, meter);
+
+        // A status of another channel, then of the channel polled
+        assert_eq!(poller.handle(&erp1(&[0xd2, 0x04, 0x00, 0x32, 0x05, 0x12, 0x34, 0x56, 0x00]), start), None);
+        let at = start + Duration::from_millis(2500);
+        assert_eq!(
+            poller.handle(&erp1(&[0xd2, 0x04, 0x01, 0x32, 0x05, 0x12, 0x34, 0x56, 0x00]), at),
+            Some(PollOutcome::Answered { poll: status, latency: Duration::from_millis(2500) })
+        );
+        assert_eq!(poller.expired(start + Duration::from_secs(5)), vec![reading]);
+
+        let measurement = poller.next(start + Duration::from_secs(5)).unwrap();
+        assert_eq!(measurement.query.telegram(), (Rorg::Vld, vec![0x06, 0x21]));
+        poller.unschedule(&relay);
+        assert_eq!(poller.next(start + Duration::from_secs(12)).map(|poll| poll.device), Some(meter));
+    }
+}
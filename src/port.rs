@@ -1,16 +1,22 @@
 //! Stateful link to an ESP3 device
 
-use serialport::{self, SerialPort};
-use std::{collections::VecDeque, time::Duration};
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
 
-use crate::{frame::{ESP3Frame, ESP3FrameRef}, FrameReadError, packet::{Packet, CommonCommand, Response, VersionResponse}, PacketError};
+use crate::{frame::{ESP3Frame, ESP3FrameRef}, FrameReadError, packet::{Packet, CommonCommand, FrequencyInfo, IdBase, RadioErp1, Response, ResponseCode, VersionResponse}, PacketError};
+use crate::link::{Link, RawFrame, TxStatus, READ_BUFFER};
+
+/// A byte stream to an ESP3 device
+pub trait Serial: io::Read + io::Write + Send {}
+
+impl<T: io::Read + io::Write + Send> Serial for T {}
 
 /// An opened ESP3 device.
 pub struct Port {
-    port: Box<dyn SerialPort>,
+    port: Box<dyn Serial>,
 
-    /// In the future, this should store pending requests so that we can route the responses to the correct sender.
-    queue: VecDeque<ESP3Frame>
+    /// Frames received and not yet returned, e.g. while waiting for a response
+    link: Link,
 }
 
 impl Port {
@@ -29,9 +35,23 @@ impl Port {
             .flow_control(serialport::FlowControl::None)
             .open()?;
 
-        let queue = VecDeque::new();
+        Ok(Self { port: Box::new(port), link: Link::new() })
+    }
+
+    /// An ESP3 device over another byte stream, e.g. a TCP connection to a serial server or
+    /// a [`crate::sim::Transceiver`]. Reads should time out rather than block forever.
+    pub fn from_serial(serial: impl Serial + 'static) -> Self {
+        Self { port: Box::new(serial), link: Link::new() }
+    }
+
+    /// Hand every frame read or written to `tap` as raw bytes, including frames failing
+    /// their CRC, e.g. to capture the traffic of the port. See [`crate::link`].
+    pub fn set_tap(&mut self, tap: impl FnMut(&RawFrame) + Send + 'static) {
+        self.link.set_tap(tap);
+    }
 
-        Ok(Self { port, queue })
+    pub fn clear_tap(&mut self) {
+        self.link.clear_tap();
     }
 
     pub fn read_version_information(&mut self) -> Result<VersionResponse, PacketError> {
@@ -39,14 +59,46 @@ impl Port {
         Ok(VersionResponse::decode(&response)?)
     }
 
-    /// Read the next frame from the port.
+    pub fn read_id_base(&mut self) -> Result<IdBase, PacketError> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadIdBase))?;
+        Ok(IdBase::decode(&response)?)
+    }
+
+    /// The radio frequency and protocol, `None` if the firmware does not support the command
+    pub fn read_frequency_info(&mut self) -> Result<Option<FrequencyInfo>, PacketError> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::GetFrequencyInfo))?;
+        if response.code != ResponseCode::Ok {
+            return Ok(None);
+        }
+        Ok(Some(FrequencyInfo::decode(&response)?))
+    }
+
+    /// Read the next frame from the port, starting with the frames set aside while waiting for a response.
     pub fn read_frame(&mut self) -> Result<ESP3Frame, FrameReadError> {
-        ESP3Frame::read_from(&mut self.port)
+        loop {
+            if let Some(frame) = self.link.next_frame() {
+                return frame;
+            }
+            self.receive()?;
+        }
+    }
+
+    /// Read the bytes available from the port
+    fn receive(&mut self) -> Result<(), FrameReadError> {
+        let mut buffer = [0; READ_BUFFER];
+        let n = self.port.read(&mut buffer)?;
+        if n == 0 {
+            return Err(FrameReadError::EOF);
+        }
+        self.link.feed(&buffer[..n]);
+        Ok(())
     }
 
     /// Write a frame to the port.
     pub fn write_frame(&mut self, frame: &ESP3Frame) -> Result<(), std::io::Error> {
-        frame.write_to(&mut self.port)
+        frame.write_to(&mut self.port)?;
+        self.link.sent(frame);
+        Ok(())
     }
 
     /// Write a frame to the port.
@@ -54,24 +106,45 @@ impl Port {
     /// This performs a vectored write.
     /// If you already have a `&EPS3Frame`, use `write_frame` instead.
     pub fn write_frame_ref(&mut self, frame: ESP3FrameRef) -> Result<(), std::io::Error> {
-        frame.write_to(&mut self.port)
+        frame.write_to(&mut self.port)?;
+        if self.link.tapped() {
+            self.link.sent(&frame.to_owned());
+        }
+        Ok(())
     }
 
     pub fn write_packet(&mut self, packet: Packet) -> Result<Response, PacketError> {
         let frame = packet.encode();
         self.write_frame(&frame)?;
 
-        let reply = loop {
-            let frame = self.read_frame()?;
-            if frame.packet_type() != 0x02 {
-                self.queue.push_back(frame);
-            } else {
-                break frame;
+        loop {
+            if let Some(reply) = self.link.next_response() {
+                return Ok(Response::decode(reply?.as_ref())?);
             }
-        };
-
-        Ok(Response::decode(reply.as_ref())?)
+            self.receive()?;
+        }
+    }
 
+    /// Send a radio telegram, and wait up to `timeout` for the transceiver to report whether
+    /// it left the air interface. Frames received meanwhile are returned by the next reads.
+    pub fn send_confirmed(&mut self, erp: RadioErp1, timeout: Duration) -> Result<TxStatus, PacketError> {
+        let deadline = Instant::now() + timeout;
+        if let Err(e) = self.write_packet(Packet::RadioErp1(erp))?.code.result() {
+            return Ok(TxStatus::Refused(e));
+        }
+
+        loop {
+            if let Some(status) = self.link.next_tx_status() {
+                return Ok(status?);
+            }
+            if Instant::now() >= deadline {
+                return Err(PacketError::IOError(io::ErrorKind::TimedOut.into()));
+            }
+            match self.receive() {
+                Err(FrameReadError::IOError(e)) if e.kind() == io::ErrorKind::TimedOut => continue,
+                result => result?,
+            }
+        }
     }
 
 }
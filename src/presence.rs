@@ -0,0 +1,141 @@
+//! Presence of devices
+//!
+//! A [`PresenceTracker`] records when each sender was last heard from, and how many
+//! telegrams it sent. Devices reporting periodically, like battery-powered sensors, are
+//! declared with their reporting interval; [`PresenceTracker::check`] reports them offline
+//! once they missed [`MISSED_REPORTS`] reports, and online again after their next telegram.
+//!
+//! ```
+//! use enocean::presence::{PresenceEvent, PresenceTracker};
+//! use std::time::{Duration, Instant};
+//!
+//! let sensor = "0180998e".parse()?;
+//! let mut presence = PresenceTracker::new();
+//! presence.expect(sensor, Duration::from_secs(15 * 60));
+//!
+//! let start = Instant::now();
+//! presence.record(sensor, start);
+//! assert_eq!(presence.check(start + Duration::from_secs(3600)), vec![PresenceEvent::DeviceOffline(sensor)]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::packet::Address;
+
+/// Reports a device can miss before it is reported offline
+pub const MISSED_REPORTS: u32 = 3;
+
+/// What is known of the presence of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Presence {
+    /// When the last telegram was received, `None` if none was yet
+    pub last_seen: Option<Instant>,
+    /// Telegrams received
+    pub count: u64,
+    /// Expected interval between two reports
+    pub interval: Option<Duration>,
+    pub online: bool,
+}
+
+/// A change in the presence of a device with a reporting interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresenceEvent {
+    /// The device sent a telegram after being offline
+    DeviceOnline(Address),
+    /// The device missed [`MISSED_REPORTS`] reports
+    DeviceOffline(Address),
+}
+
+/// Presence of devices, by address
+#[derive(Debug, Clone, Default)]
+pub struct PresenceTracker {
+    devices: BTreeMap<Address, Presence>,
+    /// Devices back online since the last check
+    back_online: Vec<Address>,
+}
+
+impl PresenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that `address` reports every `interval`. It is reported offline if it is not
+    /// heard from in time, starting from its first telegram.
+    pub fn expect(&mut self, address: Address, interval: Duration) {
+        self.entry(address).interval = Some(interval);
+    }
+
+    /// Stop tracking a device
+    pub fn forget(&mut self, address: &Address) -> Option<Presence> {
+        self.devices.remove(address)
+    }
+
+    /// Record a telegram of `address`, received at `now`
+    pub fn record(&mut self, address: Address, now: Instant) {
+        let presence = self.entry(address);
+        let back_online = !presence.online && presence.last_seen.is_some() && presence.interval.is_some();
+        presence.last_seen = Some(now);
+        presence.count += 1;
+        presence.online = true;
+        if back_online {
+            self.back_online.push(address);
+        }
+    }
+
+    /// The devices heard from again since the last check, and the devices that went silent
+    /// for [`MISSED_REPORTS`] intervals as of `now`. Each device is reported offline once,
+    /// until it is heard from again.
+    pub fn check(&mut self, now: Instant) -> Vec<PresenceEvent> {
+        let mut events: Vec<_> = self.back_online.drain(..).map(PresenceEvent::DeviceOnline).collect();
+        for (address, presence) in &mut self.devices {
+            let Some(deadline) = presence.last_seen.zip(presence.interval)
+                .map(|(last_seen, interval)| last_seen + interval * MISSED_REPORTS) else { continue };
+            if presence.online && now > deadline {
+                presence.online = false;
+                events.push(PresenceEvent::DeviceOffline(*address));
+            }
+        }
+        events
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&Presence> {
+        self.devices.get(address)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &Presence)> {
+        self.devices.iter()
+    }
+
+    fn entry(&mut self, address: Address) -> &mut Presence {
+        self.devices.entry(address)
+            .or_insert(Presence { last_seen: None, count: 0, interval: None, online: false })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_silent_sensor_then_report_it_offline_once() {
+        let (sensor, switch) = ("0180998e".parse().unwrap(), "fefa1234".parse().unwrap());
+        let minute = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut presence = PresenceTracker::new();
+        presence.expect(sensor, 10 * minute);
+
+        presence.record(sensor, start);
+        presence.record(switch, start);
+        assert!(presence.check(start + 30 * minute).is_empty());
+        assert_eq!(presence.check(start + 31 * minute), vec![PresenceEvent::DeviceOffline(sensor)]);
+        assert!(presence.check(start + 40 * minute).is_empty());
+        assert!(!presence.get(&sensor).unwrap().online);
+
+        presence.record(sensor, start + 45 * minute);
+        assert_eq!(presence.check(start + 46 * minute), vec![PresenceEvent::DeviceOnline(sensor)]);
+        assert_eq!(presence.get(&sensor).unwrap().count, 2);
+        assert_eq!(presence.get(&switch).unwrap().last_seen, Some(start));
+    }
+}
@@ -0,0 +1,143 @@
+//! Prometheus metrics (feature `prometheus`)
+//!
+//! An [`Exporter`] counts the telegrams received from each sender, keeps their link quality
+//! and the latest values decoded from them, and renders it all in the Prometheus text
+//! format. The application serves [`Exporter::render`] on its `/metrics` endpoint:
+//!
+//! ```text
+//! # TYPE enocean_telegrams_total counter
+//! enocean_telegrams_total{sender="0180998e"} 42
+//! # TYPE enocean_rssi_dbm gauge
+//! enocean_rssi_dbm{sender="0180998e"} -74
+//! # TYPE enocean_value gauge
+//! enocean_value{sender="0180998e",eep="A5-02-05",field="temperature",unit="°C"} 19.92
+//! ```
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::prometheus::Exporter;
+//! use enocean::registry::{DeviceEntry, DeviceRegistry};
+//!
+//! let mut registry = DeviceRegistry::new();
+//! registry.insert("0180998e".parse()?, DeviceEntry::new("A5-02-05".parse()?));
+//! let mut exporter = Exporter::new();
+//!
+//! let data = [0xa5, 0x00, 0x00, 0x80, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! exporter.record(&erp, registry.decode(&erp).ok().as_ref());
+//! assert!(exporter.render().contains("enocean_telegrams_total{sender=\"0180998e\"} 1\n"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::eep::{DataPoint, Report};
+use crate::packet::{Address, EEPProfileCode, RadioErp1};
+
+/// What is known of a sender
+#[derive(Debug, Clone, Default)]
+struct Sender {
+    telegrams: u64,
+    decoded: u64,
+    rssi: Option<i16>,
+    hops: u8,
+    /// Seconds since the Unix epoch
+    last_seen: f64,
+    /// Profile and values of the last decoded telegram
+    values: Option<(EEPProfileCode, Vec<DataPoint>)>,
+}
+
+/// Metrics of the telegrams received by a gateway
+#[derive(Debug, Clone, Default)]
+pub struct Exporter {
+    senders: BTreeMap<Address, Sender>,
+}
+
+impl Exporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a received telegram, and its values if it was decoded
+    pub fn record(&mut self, erp: &RadioErp1, report: Option<&Report>) {
+        let sender = self.senders.entry(erp.sender_id).or_default();
+        sender.telegrams += 1;
+        sender.rssi = erp.rssi_dbm().or(sender.rssi);
+        sender.hops = erp.status_byte().repeater_count();
+        sender.last_seen = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+        if let Some(report) = report {
+            sender.decoded += 1;
+            sender.values = Some((report.eep, report.data_points()));
+        }
+    }
+
+    /// Forget a sender, e.g. a device removed from the installation
+    pub fn forget(&mut self, address: &Address) {
+        self.senders.remove(address);
+    }
+
+    /// The metrics, in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.family(&mut out, "enocean_telegrams_total", "counter", "Telegrams received", |s| Some(s.telegrams as f64));
+        self.family(&mut out, "enocean_decoded_total", "counter", "Telegrams decoded with the profile of their sender", |s| Some(s.decoded as f64));
+        self.family(&mut out, "enocean_rssi_dbm", "gauge", "Signal strength of the last telegram", |s| s.rssi.map(f64::from));
+        self.family(&mut out, "enocean_repeater_hops", "gauge", "Repeaters the last telegram went through", |s| Some(s.hops as f64));
+        self.family(&mut out, "enocean_last_seen_timestamp_seconds", "gauge", "When the last telegram was received", |s| Some(s.last_seen));
+
+        let _ = writeln!(out, "# HELP enocean_value Last value decoded from the telegrams of a device");
+        let _ = writeln!(out, "# TYPE enocean_value gauge");
+        for (address, sender) in &self.senders {
+            let Some((eep, points)) = &sender.values else { continue };
+            for point in points {
+                let _ = writeln!(out, "enocean_value{{sender=\"{}\",eep=\"{}\",field=\"{}\",unit=\"{}\"}} {}",
+                    address, eep, escape(&point.field), escape(point.unit.as_deref().unwrap_or("")), point.value);
+            }
+        }
+        out
+    }
+
+    /// Util : render a metric family with a sample per sender
+    fn family(&self, out: &mut String, name: &str, kind: &str, help: &str, value: impl Fn(&Sender) -> Option<f64>) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (address, sender) in &self.senders {
+            if let Some(value) = value(sender) {
+                let _ = writeln!(out, "{}{{sender=\"{}\"}} {}", name, address, value);
+            }
+        }
+    }
+}
+
+/// Util : escape a label value
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ESP3Frame;
+
+    #[test]
+    fn given_telegrams_then_count_them_per_sender() {
+        let mut exporter = Exporter::new();
+        for status in [0x30, 0x31] {
+            let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x80, 0x99, 0x8e, status], &[0x01, 0xff, 0xff, 0xff, 0xff, 0x4a, 0x00]);
+            exporter.record(&RadioErp1::decode(frame.as_ref()).unwrap(), None);
+        }
+
+        let metrics = exporter.render();
+        assert!(metrics.contains("# TYPE enocean_telegrams_total counter\nenocean_telegrams_total{sender=\"0180998e\"} 2\n"));
+        assert!(metrics.contains("enocean_decoded_total{sender=\"0180998e\"} 0\n"));
+        assert!(metrics.contains("enocean_rssi_dbm{sender=\"0180998e\"} -74\n"));
+        assert!(metrics.contains("enocean_repeater_hops{sender=\"0180998e\"} 1\n"));
+        assert_eq!(escape("a\"b\\"), "a\\\"b\\\\");
+
+        exporter.forget(&"0180998e".parse().unwrap());
+        assert!(!exporter.render().contains("0180998e"));
+    }
+}
@@ -0,0 +1,209 @@
+//! Python bindings (feature `python`)
+//!
+//! A Python extension module named `enocean`, close to the Python `enocean` package, so
+//! that its scripts can move over with few changes. Build it with
+//! `maturin build --release --features python`.
+//!
+//! ```python
+//! from enocean import Gateway
+//!
+//! gateway = Gateway("/dev/ttyUSB0")
+//! gateway.add_device("0180998e", "A5-02-05")
+//! device = gateway.learn(30)      # {"address": ..., "eep": ..., "manufacturer": ...} or None
+//! while True:
+//!     packet = gateway.receive()
+//!     if packet and packet.parsed:
+//!         print(packet.sender, packet.parsed)
+//! ```
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::eep;
+use crate::enocean::Rorg;
+use crate::learn::LearnManager;
+use crate::packet::{Address, EEPProfileCode, IdBase, Packet as EspPacket, RadioErp1, BROADCAST};
+use crate::port::Port;
+use crate::registry::{DeviceEntry, DeviceRegistry};
+use crate::FrameReadError;
+
+fn io_error(e: impl std::fmt::Display) -> PyErr {
+    PyIOError::new_err(e.to_string())
+}
+
+fn value_error(e: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Util : convert decoded values to Python objects, through their JSON
+fn to_python(py: Python, values: &eep::Decoded) -> PyResult<PyObject> {
+    let json = serde_json::to_string(values).map_err(value_error)?;
+    Ok(py.import_bound("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// A received radio telegram
+#[pyclass]
+#[derive(Clone)]
+pub struct Packet {
+    #[pyo3(get)]
+    rorg: u8,
+    /// Sender address, as `0180998e`
+    #[pyo3(get)]
+    sender: String,
+    #[pyo3(get)]
+    status: u8,
+    /// Signal strength in dBm
+    #[pyo3(get)]
+    dbm: Option<i16>,
+    #[pyo3(get)]
+    data: Vec<u8>,
+    /// Values decoded with the profile of the sender, if known
+    #[pyo3(get)]
+    parsed: Option<PyObject>,
+}
+
+impl Packet {
+    fn erp(&self) -> PyResult<RadioErp1<'_>> {
+        Ok(RadioErp1 {
+            choice: Rorg::from(self.rorg),
+            user_data: Cow::Borrowed(&self.data),
+            sender_id: self.sender.parse().map_err(value_error)?,
+            status: self.status,
+            subtel_num: None,
+            destination: None,
+            rssi: None,
+            security: None,
+        })
+    }
+}
+
+#[pymethods]
+impl Packet {
+    /// Decode the values of the telegram with the given profile, e.g. `"A5-02-05"`
+    fn parse_eep(&self, py: Python, eep: &str) -> PyResult<PyObject> {
+        let eep: EEPProfileCode = eep.parse().map_err(value_error)?;
+        let values = eep::decode(eep, &self.erp()?).map_err(value_error)?;
+        to_python(py, &values)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Packet {:02X} from {} {}>", self.rorg, self.sender, hex::encode(&self.data))
+    }
+}
+
+/// A gateway on a serial port, with its known devices
+#[pyclass(unsendable)]
+pub struct Gateway {
+    port: Port,
+    registry: DeviceRegistry,
+    base: Option<IdBase>,
+}
+
+impl Gateway {
+    fn base(&mut self) -> PyResult<IdBase> {
+        if let Some(base) = self.base {
+            return Ok(base);
+        }
+        let base = self.port.read_id_base().map_err(io_error)?;
+        Ok(*self.base.insert(base))
+    }
+}
+
+#[pymethods]
+impl Gateway {
+    #[new]
+    fn new(port: &str) -> PyResult<Self> {
+        Ok(Self { port: Port::open(port).map_err(io_error)?, registry: DeviceRegistry::new(), base: None })
+    }
+
+    /// Register the profile of a device, e.g. `add_device("0180998e", "A5-02-05")`
+    fn add_device(&mut self, address: &str, eep: &str) -> PyResult<()> {
+        let address: Address = address.parse().map_err(value_error)?;
+        self.registry.insert(address, DeviceEntry::new(eep.parse().map_err(value_error)?));
+        Ok(())
+    }
+
+    /// Wait for the next radio telegram. Returns `None` if none was received within the read
+    /// timeout of the port (100 ms).
+    fn receive(&mut self, py: Python) -> PyResult<Option<Packet>> {
+        loop {
+            let frame = match self.port.read_frame() {
+                Ok(frame) => frame,
+                Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => return Ok(None),
+                Err(e) => return Err(io_error(e)),
+            };
+            let Ok(EspPacket::RadioErp1(erp)) = EspPacket::decode(frame.as_ref()) else { continue };
+            let parsed = match self.registry.decode(&erp) {
+                Ok(report) => Some(to_python(py, &report.values)?),
+                Err(_) => None,
+            };
+            return Ok(Some(Packet {
+                rorg: erp.choice.into(),
+                sender: erp.sender_id.to_string(),
+                status: erp.status,
+                dbm: erp.rssi_dbm(),
+                data: erp.user_data.to_vec(),
+                parsed,
+            }));
+        }
+    }
+
+    /// Send a telegram from the base ID of the gateway, plus `offset`, broadcast unless
+    /// `destination` is given
+    #[pyo3(signature = (rorg, data, destination=None, offset=0))]
+    fn send(&mut self, rorg: u8, data: Vec<u8>, destination: Option<&str>, offset: u8) -> PyResult<()> {
+        let rorg = Rorg::from(rorg);
+        let sender = self.base()?.sender(offset).ok_or_else(|| value_error("offset out of the base ID range"))?;
+        let erp = match destination {
+            Some(destination) => RadioErp1::addressed(rorg, &data, sender, destination.parse().map_err(value_error)?),
+            None => RadioErp1 { choice: rorg, user_data: data.as_slice().into(), sender_id: sender, status: 0, subtel_num: None, destination: None, rssi: None, security: None },
+        };
+        let response = self.port.write_packet(EspPacket::RadioErp1(erp)).map_err(io_error)?;
+        response.code.result().map_err(io_error)
+    }
+
+    /// Accept teach-in telegrams for `timeout` seconds. Returns the first device learned, as
+    /// a dict, after registering it; `None` if no device was learned.
+    fn learn(&mut self, py: Python, timeout: f64) -> PyResult<Option<PyObject>> {
+        let sender = self.base()?.0;
+        let mut manager = LearnManager::new(sender);
+        manager.start(Duration::from_secs_f64(timeout));
+        let Some(learned) = manager.run(&mut self.port).map_err(io_error)? else { return Ok(None) };
+        self.registry.learn(&learned);
+
+        let device = PyDict::new_bound(py);
+        device.set_item("address", learned.address.to_string())?;
+        device.set_item("eep", learned.eep.map(|eep| eep.to_string()))?;
+        device.set_item("manufacturer", learned.manufacturer)?;
+        Ok(Some(device.into_any().unbind()))
+    }
+}
+
+/// Decode user data with the typed decoder of a profile, without a gateway
+#[pyfunction]
+fn decode(py: Python, eep: &str, rorg: u8, data: Vec<u8>) -> PyResult<PyObject> {
+    let erp = RadioErp1 {
+        choice: Rorg::from(rorg),
+        user_data: data.as_slice().into(),
+        sender_id: BROADCAST,
+        status: 0,
+        subtel_num: None,
+        destination: None,
+        rssi: None,
+        security: None,
+    };
+    let values = eep::decode(eep.parse().map_err(value_error)?, &erp).map_err(value_error)?;
+    to_python(py, &values)
+}
+
+#[pymodule]
+fn enocean(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Gateway>()?;
+    m.add_class::<Packet>()?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    Ok(())
+}
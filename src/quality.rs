@@ -0,0 +1,133 @@
+//! Radio link quality of devices
+//!
+//! A [`SignalQuality`] keeps the received signal strength and the repeater hop count of the
+//! last telegrams of each sender, and summarizes them, e.g. to spot the devices at the edge
+//! of the radio range:
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::quality::SignalQuality;
+//!
+//! let data = [0xf6, 0x50, 0x01, 0x80, 0x99, 0x8e, 0x31];
+//! let optional = [0x01, 0xff, 0xff, 0xff, 0xff, 0x4a, 0x00];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &optional })?;
+//!
+//! let mut quality = SignalQuality::new(16);
+//! quality.record(&erp);
+//! let link = quality.get(&"0180998e".parse()?).unwrap();
+//! assert_eq!(link.rssi.unwrap().max, -74);
+//! assert_eq!(link.hops.max, 1);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::packet::{Address, RadioErp1};
+
+/// Telegrams kept per sender by default
+pub const WINDOW: usize = 32;
+
+/// Minimum, average and maximum of a series of samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    pub min: i16,
+    pub avg: f32,
+    pub max: i16,
+    pub samples: usize,
+}
+
+impl Stats {
+    pub(crate) fn of(samples: impl Iterator<Item = i16>) -> Option<Self> {
+        samples.fold(None, |stats: Option<(i16, i32, i16, usize)>, sample| Some(match stats {
+            None => (sample, sample as i32, sample, 1),
+            Some((min, sum, max, n)) => (min.min(sample), sum + sample as i32, max.max(sample), n + 1),
+        }))
+        .map(|(min, sum, max, samples)| Self { min, avg: sum as f32 / samples as f32, max, samples })
+    }
+}
+
+/// Summary of the last telegrams of a sender
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LinkQuality {
+    /// Signal strength in dBm, of the telegrams that reported it
+    pub rssi: Option<Stats>,
+    /// Repeaters the telegrams went through
+    pub hops: Stats,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    rssi: Option<i16>,
+    hops: u8,
+}
+
+/// Signal strength and hop counts of the last telegrams, by sender
+#[derive(Debug, Clone)]
+pub struct SignalQuality {
+    window: usize,
+    senders: BTreeMap<Address, VecDeque<Sample>>,
+}
+
+impl Default for SignalQuality {
+    fn default() -> Self {
+        Self::new(WINDOW)
+    }
+}
+
+impl SignalQuality {
+    /// Keep the last `window` telegrams of each sender
+    pub fn new(window: usize) -> Self {
+        Self { window: window.max(1), senders: BTreeMap::new() }
+    }
+
+    /// Record a received telegram
+    pub fn record(&mut self, erp: &RadioErp1) {
+        let samples = self.senders.entry(erp.sender_id).or_default();
+        if samples.len() == self.window {
+            samples.pop_front();
+        }
+        samples.push_back(Sample { rssi: erp.rssi_dbm(), hops: erp.status_byte().repeater_count() });
+    }
+
+    /// Summary of the last telegrams of `address`, if any was received
+    pub fn get(&self, address: &Address) -> Option<LinkQuality> {
+        let samples = self.senders.get(address)?;
+        Some(LinkQuality {
+            rssi: Stats::of(samples.iter().filter_map(|sample| sample.rssi)),
+            hops: Stats::of(samples.iter().map(|sample| sample.hops as i16))?,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Address, LinkQuality)> + '_ {
+        self.senders.keys().filter_map(|address| Some((*address, self.get(address)?)))
+    }
+
+    /// Forget the telegrams of a sender
+    pub fn clear(&mut self, address: &Address) {
+        self.senders.remove(address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::ESP3Frame;
+
+    #[test]
+    fn given_telegrams_then_summarize_last_window() {
+        let mut quality = SignalQuality::new(3);
+        for (status, rssi) in [(0x30, 0x20), (0x31, 0x50), (0x32, 0x40), (0x30, 0x48)] {
+            let frame = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x80, 0x99, 0x8e, status], &[0x01, 0xff, 0xff, 0xff, 0xff, rssi, 0x00]);
+            quality.record(&RadioErp1::decode(frame.as_ref()).unwrap());
+        }
+
+        let link = quality.get(&"0180998e".parse().unwrap()).unwrap();
+        assert_eq!(link.rssi, Some(Stats { min: -0x50, avg: -0x48 as f32, max: -0x40, samples: 3 }));
+        assert_eq!((link.hops.min, link.hops.max, link.hops.samples), (0, 2, 3));
+        assert_eq!(link.hops.avg, 1.0);
+        assert!(quality.get(&"fefa1234".parse().unwrap()).is_none());
+    }
+}
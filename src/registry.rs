@@ -0,0 +1,276 @@
+//! Registry of known devices
+//!
+//! The [`DeviceRegistry`] remembers the profile of each device, so that incoming telegrams
+//! can be handed to the right decoder. With the `serde` feature it can be saved and loaded
+//! with any serde format; in JSON it looks like:
+//!
+//! ```json
+//! {
+//!   "0180998e": { "eep": "A5-02-05", "name": "Living room" },
+//!   "fefa1234": { "eep": "F6-02-01", "options": { "room": "kitchen" } },
+//!   "05123456": { "eep": "D2-01-12", "channels": { "0": "Ceiling", "1": "Wall lamp" } },
+//!   "ff9b1282": { "eep": "A5-3F-7F", "mapping": { "level": "0:10 0..1000 = 0..100 %" } }
+//! }
+//! ```
+//!
+//! The channels of multi-channel actuators are named in [`DeviceEntry::channels`], each of
+//! them an [`Endpoint`] of its own. Devices with the universal profile A5-3F-7F, or any
+//! other 4BS device without a decoder, are decoded with the fields of
+//! [`DeviceEntry::mapping`], see [`a5_3f`](crate::eep::a5_3f).
+//!
+//! Tables of commissioning tools are imported and exported with [`commissioning`].
+
+pub mod commissioning;
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::eep::a5_3f::{self, FieldMapping, MappedValue, ParseMappingError};
+use crate::eep::profiles::ProfileDecoders;
+use crate::eep::{DecodeError, Decoded, Report};
+use crate::endpoint::Endpoint;
+use crate::learn::DeviceLearned;
+use crate::packet::{Address, DecodeOptions, EEPProfileCode, RadioErp1};
+
+/// What is known about a device
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceEntry {
+    pub eep: EEPProfileCode,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub name: Option<String>,
+    /// Free-form settings, left to the application
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "HashMap::is_empty"))]
+    pub options: HashMap<String, String>,
+    /// Names of the channels of a multi-channel device, by channel number
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub channels: BTreeMap<u8, String>,
+    /// Fields of the 4BS data telegrams, by name, for devices without a decoder. At most
+    /// [`a5_3f::MAX_FIELDS`] fields are decoded.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty", deserialize_with = "deserialize_mapping"))]
+    pub mapping: BTreeMap<String, FieldMapping>,
+}
+
+impl DeviceEntry {
+    pub fn new(eep: EEPProfileCode) -> Self {
+        Self { eep, name: None, options: HashMap::new(), channels: BTreeMap::new(), mapping: BTreeMap::new() }
+    }
+
+    /// Map a field of the data telegrams of the device, refused beyond
+    /// [`a5_3f::MAX_FIELDS`] fields
+    pub fn with_field(mut self, name: impl Into<String>, field: FieldMapping) -> Result<Self, ParseMappingError> {
+        let name = name.into();
+        if self.mapping.len() >= a5_3f::MAX_FIELDS && !self.mapping.contains_key(&name) {
+            return Err(ParseMappingError::TooManyFields);
+        }
+        self.mapping.insert(name, field);
+        Ok(self)
+    }
+
+    /// Decode a telegram of the device, with its field mapping if any, or else with the typed
+    /// decoder of its profile
+    pub fn decode(&self, erp: &RadioErp1, options: DecodeOptions) -> Result<Decoded, DecodeError> {
+        if self.mapping.is_empty() {
+            return crate::eep::decode_with(self.eep, erp, options);
+        }
+        crate::eep::check_options(erp, options)?;
+        Ok(Decoded::Mapped(a5_3f::decode_reading(&self.mapping, erp)?))
+    }
+
+    /// Decode a telegram of the device like [`Self::decode`], with the decoder registered in
+    /// `profiles` for its profile if any. A field mapping takes precedence.
+    pub fn decode_with(&self, profiles: &ProfileDecoders<Decoded>, erp: &RadioErp1, options: DecodeOptions) -> Result<Decoded, DecodeError> {
+        if !self.mapping.is_empty() || !profiles.is_registered(self.eep) {
+            return self.decode(erp, options);
+        }
+        crate::eep::check_options(erp, options)?;
+        profiles.decode(self.eep, erp)
+    }
+
+    /// Name a channel of the device, making it an endpoint
+    pub fn with_channel(mut self, channel: u8, name: impl Into<String>) -> Self {
+        self.channels.insert(channel, name.into());
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_mapping<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, FieldMapping>, D::Error> {
+    use serde::Deserialize;
+    let mapping = BTreeMap::deserialize(deserializer)?;
+    if mapping.len() > a5_3f::MAX_FIELDS {
+        return Err(serde::de::Error::custom(ParseMappingError::TooManyFields));
+    }
+    Ok(mapping)
+}
+
+/// Known devices, by address
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct DeviceRegistry {
+    devices: BTreeMap<Address, DeviceEntry>,
+}
+
+impl DeviceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a device, returning the previous entry
+    pub fn insert(&mut self, address: Address, entry: DeviceEntry) -> Option<DeviceEntry> {
+        self.devices.insert(address, entry)
+    }
+
+    pub fn remove(&mut self, address: &Address) -> Option<DeviceEntry> {
+        self.devices.remove(address)
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&DeviceEntry> {
+        self.devices.get(address)
+    }
+
+    pub fn get_mut(&mut self, address: &Address) -> Option<&mut DeviceEntry> {
+        self.devices.get_mut(address)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &DeviceEntry)> {
+        self.devices.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// The endpoints of the registered devices: one per named channel, or channel 0 for the
+    /// devices without named channels
+    pub fn endpoints(&self) -> impl Iterator<Item = Endpoint> + '_ {
+        self.devices.iter().flat_map(|(address, entry)| {
+            let channels = if entry.channels.is_empty() { vec![0] } else { entry.channels.keys().copied().collect() };
+            channels.into_iter().map(|channel| Endpoint::new(*address, channel))
+        })
+    }
+
+    /// The name of an endpoint: the name of its channel, or of its device
+    pub fn endpoint_name(&self, endpoint: &Endpoint) -> Option<&str> {
+        let entry = self.get(&endpoint.address)?;
+        entry.channels.get(&endpoint.channel).or(entry.name.as_ref()).map(String::as_str)
+    }
+
+    /// Record a device enrolled in learn mode. Devices without a known profile are skipped.
+    /// An already known device keeps its name and options.
+    pub fn learn(&mut self, device: &DeviceLearned) -> bool {
+        let Some(eep) = device.eep else { return false };
+        self.devices.entry(device.address)
+            .and_modify(|entry| entry.eep = eep)
+            .or_insert_with(|| DeviceEntry::new(eep));
+        true
+    }
+
+    /// Decode a telegram with the profile, or the field mapping, registered for its sender
+    pub fn decode(&self, erp: &RadioErp1) -> Result<Report, DecodeError> {
+        let entry = self.get(&erp.sender_id).ok_or(DecodeError::UnknownDevice(erp.sender_id))?;
+        Ok(Report {
+            eep: entry.eep,
+            sender: erp.sender_id,
+            raw: hex::encode(&erp.user_data),
+            values: entry.decode(erp, DecodeOptions::default())?,
+            mapping: entry.mapping.clone(),
+        })
+    }
+
+    /// Decode a telegram with the decoder registered in `decoders` for the profile of its sender
+    pub fn decode_with<T>(&self, decoders: &ProfileDecoders<T>, erp: &RadioErp1) -> Result<T, DecodeError> {
+        let entry = self.get(&erp.sender_id).ok_or(DecodeError::UnknownDevice(erp.sender_id))?;
+        decoders.decode(entry.eep, erp)
+    }
+
+    /// Decode a telegram with the field mapping registered for its sender
+    pub fn decode_mapped(&self, erp: &RadioErp1) -> Result<Vec<MappedValue<'_>>, DecodeError> {
+        let entry = self.get(&erp.sender_id).ok_or(DecodeError::UnknownDevice(erp.sender_id))?;
+        if entry.mapping.is_empty() {
+            return Err(DecodeError::UnsupportedProfile(entry.eep));
+        }
+        a5_3f::decode(&entry.mapping, erp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_registered_device_then_decode_with_its_profile() {
+        let mut registry = DeviceRegistry::new();
+        let sender = "0180998e".parse().unwrap();
+        registry.learn(&DeviceLearned { address: sender, eep: "A5-02-05".parse().ok(), manufacturer: None });
+        registry.get_mut(&sender).unwrap().name = Some("Living room".into());
+        registry.learn(&DeviceLearned { address: sender, eep: "A5-02-05".parse().ok(), manufacturer: None });
+        assert_eq!(registry.get(&sender).unwrap().name.as_deref(), Some("Living room"));
+
+        let report = registry.decode(&erp1(&[0xa5, 0x00, 0x00, 0x80, 0x08, 0x01, 0x80, 0x99, 0x8e, 0x00])).unwrap();
+        assert!(matches!(report.values, Decoded::Temperature(_)));
+        assert!(matches!(
+            registry.decode(&erp1(&[0xa5, 0x00, 0x00, 0x80, 0x08, 1, 2, 3, 4, 0x00])),
+            Err(DecodeError::UnknownDevice(_))
+        ));
+    }
+
+    #[test]
+    fn given_named_channels_then_list_endpoints() {
+        let mut registry = DeviceRegistry::new();
+        let (sensor, relay) = ("0180998e".parse().unwrap(), "05123456".parse().unwrap());
+        registry.insert(sensor, DeviceEntry { name: Some("Living room".into()), ..DeviceEntry::new("A5-02-05".parse().unwrap()) });
+        registry.insert(relay, DeviceEntry::new("D2-01-12".parse().unwrap()).with_channel(0, "Ceiling").with_channel(1, "Wall lamp"));
+        assert_eq!(registry.endpoints().collect::<Vec<_>>(), vec![
+            Endpoint::new(sensor, 0), Endpoint::new(relay, 0), Endpoint::new(relay, 1),
+        ]);
+        assert_eq!(registry.endpoint_name(&Endpoint::new(relay, 1)), Some("Wall lamp"));
+        assert_eq!(registry.endpoint_name(&Endpoint::from(sensor)), Some("Living room"));
+        assert_eq!(registry.endpoint_name(&Endpoint::new(relay, 2)), None);
+    }
+
+    #[test]
+    fn given_field_mapping_then_decode_universal_profile() {
+        let mut registry = DeviceRegistry::new();
+        let sender = "ff9b1282".parse().unwrap();
+        registry.insert(sender, DeviceEntry::new(a5_3f::EEP).with_field("level", "0:8 0..250 = 0..100 %".parse().unwrap()).unwrap());
+        let erp = erp1(&[0xa5, 0x7d, 0x00, 0x00, 0x08, 0xff, 0x9b, 0x12, 0x82, 0x00]);
+        assert_eq!(registry.decode_mapped(&erp).unwrap(), vec![MappedValue { name: "level", value: 50.0, unit: "%" }]);
+        let Decoded::Mapped(reading) = registry.decode(&erp).unwrap().values else { unreachable!() };
+        assert_eq!(reading.values(), &[50.0]);
+    }
+
+    #[test]
+    fn given_too_many_fields_then_refuse_the_mapping() {
+        let entry = (0..a5_3f::MAX_FIELDS).try_fold(DeviceEntry::new(a5_3f::EEP), |entry, bit| {
+            entry.with_field(format!("bit{}", bit), FieldMapping::raw(bit, 1))
+        }).unwrap();
+        let entry = entry.with_field("bit0", FieldMapping::raw(8, 1)).unwrap();
+        assert_eq!(entry.mapping["bit0"], FieldMapping::raw(8, 1));
+        assert_eq!(entry.with_field("bit8", FieldMapping::raw(8, 1)), Err(ParseMappingError::TooManyFields));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_registry_then_round_trip_through_json() {
+        let json = r#"{"0180998e":{"eep":"A5-02-05","name":"Living room"},"fefa1234":{"eep":"F6-02-01"}}"#;
+        let registry: DeviceRegistry = serde_json::from_str(json).unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(&"fefa1234".parse().unwrap()).unwrap().eep, EEPProfileCode::new(0xF6, 0x02, 0x01));
+        assert_eq!(serde_json::to_string(&registry).unwrap(), json);
+
+        let json = r#"{"ff9b1282":{"eep":"A5-3F-7F","mapping":{"level":"0:8 0..250 = 0..100 %"}}}"#;
+        let registry: DeviceRegistry = serde_json::from_str(json).unwrap();
+        assert_eq!(registry.get(&"ff9b1282".parse().unwrap()).unwrap().mapping["level"], FieldMapping::raw(0, 8).scaled((0, 250), (0.0, 100.0), "%"));
+        assert_eq!(serde_json::to_string(&registry).unwrap(), json);
+
+        let fields: Vec<_> = (0..=a5_3f::MAX_FIELDS).map(|bit| format!(r#""bit{}":"{}:1""#, bit, bit)).collect();
+        let json = format!(r#"{{"ff9b1282":{{"eep":"A5-3F-7F","mapping":{{{}}}}}}}"#, fields.join(","));
+        assert!(serde_json::from_str::<DeviceRegistry>(&json).is_err());
+    }
+}
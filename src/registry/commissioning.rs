@@ -0,0 +1,329 @@
+//! Exchange with commissioning tools
+//!
+//! Commercial commissioning software exports the devices of an installation as a table:
+//! one row per device with its address, profile, name and, for secure devices, AES key,
+//! security layer format (SLF) and rolling code (RLC). [`import_csv`] reads such tables, with
+//! `,`, `;` or tab separators and the usual column titles (`ID`, `EnOcean ID`, `Profile`,
+//! `Description`, `AES Key`, `SLF`, `RLC`...); other columns are ignored. Fields may be
+//! quoted, but not span lines. [`export_csv`] writes the `Address,EEP,Name,Key,SLF,RLC`
+//! layout, the SLF and RLC in hexadecimal:
+//!
+//! ```csv
+//! Address,EEP,Name,Key,SLF,RLC
+//! 0180998E,A5-02-05,Living room,,,
+//! FEFA1234,D2-03-00,"Hall, left",3410DE8F1ABA3EFF9F5A117172EACABD,8B,00000102
+//! ```
+//!
+//! With the `serde` feature, a [`CommissionedDevice`] reads and writes the JSON layout, an
+//! array of objects with the same fields.
+//!
+//! Keys are not kept in the [`DeviceRegistry`] but in a
+//! [`KeyStore`](crate::security::keystore::KeyStore) (feature `security`):
+//! [`DeviceRegistry::import_with_keys`] and [`DeviceRegistry::export_with_keys`] move them
+//! between the table and the store along with the devices.
+
+#[cfg(feature = "security")]
+use std::io;
+
+use thiserror::Error;
+
+use super::{DeviceEntry, DeviceRegistry};
+use crate::packet::{Address, EEPProfileCode};
+#[cfg(feature = "security")]
+use crate::security::{keystore::KeyStore, SecureDevice, Slf};
+
+/// A device, as listed by a commissioning tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommissionedDevice {
+    #[cfg_attr(feature = "serde", serde(alias = "id"))]
+    pub address: Address,
+    #[cfg_attr(feature = "serde", serde(default, alias = "profile", skip_serializing_if = "Option::is_none"))]
+    pub eep: Option<EEPProfileCode>,
+    #[cfg_attr(feature = "serde", serde(default, alias = "description", skip_serializing_if = "Option::is_none"))]
+    pub name: Option<String>,
+    /// AES key of a secure device
+    #[cfg_attr(feature = "serde", serde(default, with = "hex_key", skip_serializing_if = "Option::is_none"))]
+    pub key: Option<[u8; 16]>,
+    /// Security layer format byte of a secure device
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub slf: Option<u8>,
+    /// Rolling code of a secure device
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub rlc: Option<u32>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("No address column")]               NoAddressColumn,
+    #[error("Line {0}: unterminated quote")]    Quote(usize),
+    #[error("Line {0}: invalid address")]       Address(usize),
+    #[error("Line {0}: invalid profile code")]  ProfileCode(usize),
+    #[error("Line {0}: invalid key")]           Key(usize),
+    #[error("Line {0}: invalid SLF")]           Slf(usize),
+    #[error("Line {0}: invalid rolling code")]  Rlc(usize),
+}
+
+const ADDRESS_TITLES: &[&str] = &["address", "id", "enocean id", "device id", "chip id"];
+const EEP_TITLES: &[&str] = &["eep", "profile"];
+const NAME_TITLES: &[&str] = &["name", "description", "label"];
+const KEY_TITLES: &[&str] = &["key", "aes key", "security key"];
+const SLF_TITLES: &[&str] = &["slf", "security level format", "security layer format"];
+const RLC_TITLES: &[&str] = &["rlc", "rolling code"];
+
+/// Read the devices of a table exported by a commissioning tool. The first line holds the
+/// column titles; empty fields are left as `None`.
+pub fn import_csv(text: &str) -> Result<Vec<CommissionedDevice>, ImportError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, header)) = lines.next() else { return Ok(vec![]) };
+    let separator = [';', '\t', ','].into_iter().find(|s| header.contains(*s)).unwrap_or(',');
+    let titles = split(header, separator).ok_or(ImportError::Quote(1))?;
+    let column = |names: &[&str]| titles.iter().position(|title| names.contains(&title.to_lowercase().as_str()));
+    let address = column(ADDRESS_TITLES).ok_or(ImportError::NoAddressColumn)?;
+    let (eep, name, key) = (column(EEP_TITLES), column(NAME_TITLES), column(KEY_TITLES));
+    let (slf, rlc) = (column(SLF_TITLES), column(RLC_TITLES));
+
+    let mut devices = vec![];
+    for (number, line) in lines {
+        let number = number + 1;
+        let fields = split(line, separator).ok_or(ImportError::Quote(number))?;
+        let field = |column: Option<usize>| column
+            .and_then(|column| fields.get(column))
+            .map(|field| field.as_str())
+            .filter(|field| !field.is_empty());
+        devices.push(CommissionedDevice {
+            address: parse_address(field(Some(address)).unwrap_or_default()).ok_or(ImportError::Address(number))?,
+            eep: field(eep).map(|eep| eep.parse()).transpose().map_err(|_| ImportError::ProfileCode(number))?,
+            name: field(name).map(str::to_owned),
+            key: field(key).map(|key| parse_key(key).ok_or(ImportError::Key(number))).transpose()?,
+            slf: field(slf).map(|slf| u8::from_str_radix(slf.trim_start_matches("0x"), 16)).transpose().map_err(|_| ImportError::Slf(number))?,
+            rlc: field(rlc).map(|rlc| u32::from_str_radix(rlc.trim_start_matches("0x"), 16)).transpose().map_err(|_| ImportError::Rlc(number))?,
+        });
+    }
+    Ok(devices)
+}
+
+/// Write devices as an `Address,EEP,Name,Key,SLF,RLC` table
+pub fn export_csv(devices: &[CommissionedDevice]) -> String {
+    let mut text = String::from("Address,EEP,Name,Key,SLF,RLC\n");
+    for device in devices {
+        let name = device.name.as_deref().unwrap_or_default();
+        text += &format!(
+            "{},{},{},{},{},{}\n",
+            device.address.to_string().to_uppercase(),
+            device.eep.map(|eep| eep.to_string()).unwrap_or_default(),
+            if name.contains([',', '"']) { format!("\"{}\"", name.replace('"', "\"\"")) } else { name.to_owned() },
+            device.key.map(hex::encode_upper).unwrap_or_default(),
+            device.slf.map(|slf| format!("{slf:02X}")).unwrap_or_default(),
+            device.rlc.map(|rlc| format!("{rlc:08X}")).unwrap_or_default(),
+        );
+    }
+    text
+}
+
+impl DeviceRegistry {
+    /// Register commissioned devices, returning how many were. Devices without a profile are
+    /// skipped; an already known device keeps its options, and its name unless one is given.
+    pub fn import(&mut self, devices: &[CommissionedDevice]) -> usize {
+        let mut count = 0;
+        for device in devices {
+            let Some(eep) = device.eep else { continue };
+            let entry = self.devices.entry(device.address).or_insert_with(|| DeviceEntry::new(eep));
+            entry.eep = eep;
+            if device.name.is_some() {
+                entry.name.clone_from(&device.name);
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// The registered devices, for [`export_csv`]. Keys are left empty, see
+    /// [`Self::export_with_keys`].
+    pub fn export(&self) -> Vec<CommissionedDevice> {
+        self.iter()
+            .map(|(address, entry)| CommissionedDevice {
+                address: *address,
+                eep: Some(entry.eep),
+                name: entry.name.clone(),
+                key: None,
+                slf: None,
+                rlc: None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "security")]
+impl DeviceRegistry {
+    /// Register commissioned devices as [`Self::import`] does, and store the keys of the
+    /// secure ones in `keys`. A key without SLF gets [`DEFAULT_SLF`], without rolling code 0.
+    pub fn import_with_keys<K: KeyStore>(&mut self, devices: &[CommissionedDevice], keys: &mut K) -> io::Result<usize> {
+        for device in devices {
+            let Some(key) = device.key else { continue };
+            let slf = device.slf.unwrap_or(DEFAULT_SLF);
+            let slf = Slf::try_from(slf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            keys.insert(device.address, &SecureDevice { key, slf, rlc: device.rlc.unwrap_or(0) })?;
+        }
+        Ok(self.import(devices))
+    }
+
+    /// The registered devices, with the keys, SLF and rolling codes stored in `keys`
+    pub fn export_with_keys<K: KeyStore>(&self, keys: &mut K) -> io::Result<Vec<CommissionedDevice>> {
+        self.export()
+            .into_iter()
+            .map(|device| match keys.lookup(device.address)? {
+                Some(material) => Ok(CommissionedDevice {
+                    key: Some(material.key),
+                    slf: Some(material.slf.into()),
+                    rlc: Some(material.rlc),
+                    ..device
+                }),
+                None => Ok(device),
+            })
+            .collect()
+    }
+}
+
+/// SLF of imported keys without one: 24-bit rolling code, not transmitted, 3-byte CMAC and
+/// VAES encryption
+#[cfg(feature = "security")]
+pub const DEFAULT_SLF: u8 = 0x8b;
+
+/// Util : split a line into fields, unquoting them. `None` on an unterminated quote.
+fn split(line: &str, separator: char) -> Option<Vec<String>> {
+    let mut fields = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        while chars.next_if(|c| *c == ' ').is_some() {}
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '"' if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    '"' => break,
+                    c => field.push(c),
+                }
+            }
+            while chars.next_if(|c| *c != separator).is_some() {}
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != separator) {
+                field.push(c);
+            }
+        }
+        fields.push(field.trim().to_owned());
+        if chars.next().is_none() {
+            return Some(fields);
+        }
+    }
+}
+
+/// Util : parse an address, also written `01:80:99:8E`, `01-80-99-8E` or `0x0180998E`
+fn parse_address(s: &str) -> Option<Address> {
+    let s = s.trim_start_matches("0x").replace([':', '-', ' '], "");
+    s.parse().ok()
+}
+
+/// Util : parse a key, possibly split in groups by spaces
+fn parse_key(s: &str) -> Option<[u8; 16]> {
+    let mut key = [0; 16];
+    hex::decode_to_slice(s.replace(' ', ""), &mut key).ok()?;
+    Some(key)
+}
+
+#[cfg(feature = "serde")]
+mod hex_key {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &Option<[u8; 16]>, serializer: S) -> Result<S::Ok, S::Error> {
+        match key {
+            Some(key) => serializer.serialize_str(&hex::encode_upper(key)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<[u8; 16]>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .filter(|key| !key.is_empty())
+            .map(|key| super::parse_key(&key).ok_or_else(|| serde::de::Error::custom("invalid key")))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 16] = [0x34, 0x10, 0xde, 0x8f, 0x1a, 0xba, 0x3e, 0xff, 0x9f, 0x5a, 0x11, 0x71, 0x72, 0xea, 0xca, 0xbd];
+
+    #[test]
+    fn given_tool_export_then_import_and_export_again() {
+        let text = "EnOcean ID;Room;Profile;Description;AES Key;SLF;RLC\n\
+                    01:80:99:8E;Living;A5-02-05;Living room;\n\
+                    \n\
+                    FEFA1234;Hall;d20300;\"Hall, \"\"left\"\"\";3410 DE8F 1ABA 3EFF 9F5A 1171 72EA CABD;8b;102\n\
+                    0x05060708;;;;\n";
+        let devices = import_csv(text).unwrap();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].address, "0180998e".parse().unwrap());
+        assert_eq!(devices[0].name.as_deref(), Some("Living room"));
+        assert_eq!(devices[1].eep, Some(EEPProfileCode::new(0xD2, 0x03, 0x00)));
+        assert_eq!(devices[1].name.as_deref(), Some("Hall, \"left\""));
+        assert_eq!(devices[1].key, Some(KEY));
+        assert_eq!((devices[1].slf, devices[1].rlc), (Some(0x8b), Some(0x102)));
+        assert_eq!(devices[2], CommissionedDevice { address: Address::new(0x05060708), eep: None, name: None, key: None, slf: None, rlc: None });
+
+        let text = export_csv(&devices);
+        assert_eq!(text.lines().nth(2), Some("FEFA1234,D2-03-00,\"Hall, \"\"left\"\"\",3410DE8F1ABA3EFF9F5A117172EACABD,8B,00000102"));
+        assert_eq!(import_csv(&text).unwrap(), devices);
+
+        let mut registry = DeviceRegistry::new();
+        assert_eq!(registry.import(&devices), 2);
+        assert_eq!(registry.export(), vec![
+            CommissionedDevice { key: None, ..devices[0].clone() },
+            CommissionedDevice { key: None, slf: None, rlc: None, ..devices[1].clone() },
+        ]);
+
+        assert_eq!(import_csv("Name,EEP\nx,A5-02-05"), Err(ImportError::NoAddressColumn));
+        assert_eq!(import_csv("ID,Key\n0180998e,abcd"), Err(ImportError::Key(2)));
+        assert_eq!(import_csv("ID,Name\n0180998e,\"open"), Err(ImportError::Quote(2)));
+        assert_eq!(import_csv("ID,SLF\n0180998e,8b3"), Err(ImportError::Slf(2)));
+    }
+
+    #[cfg(feature = "security")]
+    #[test]
+    fn given_secure_devices_then_import_and_export_their_keys() {
+        use crate::security::keystore::MemoryKeyStore;
+
+        let devices = import_csv("ID,EEP,Key,SLF,RLC\n\
+                                  0180998e,A5-02-05,,,\n\
+                                  fefa1234,D2-03-00,3410DE8F1ABA3EFF9F5A117172EACABD,f3,102\n\
+                                  05060708,D2-03-00,3410DE8F1ABA3EFF9F5A117172EACABD,,\n").unwrap();
+        let (mut registry, mut keys) = (DeviceRegistry::new(), MemoryKeyStore::new());
+        assert_eq!(registry.import_with_keys(&devices, &mut keys).unwrap(), 3);
+        let material = keys.lookup("fefa1234".parse().unwrap()).unwrap().unwrap();
+        assert_eq!((material.key, u8::from(material.slf), material.rlc), (KEY, 0xf3, 0x102));
+        assert_eq!(u8::from(keys.lookup(Address::new(0x05060708)).unwrap().unwrap().slf), DEFAULT_SLF);
+
+        let exported = registry.export_with_keys(&mut keys).unwrap();
+        assert_eq!(exported[2], CommissionedDevice { name: None, ..devices[1].clone() });
+        assert_eq!(exported[0].key, None);
+        assert_eq!(import_csv(&export_csv(&exported)).unwrap()[2], exported[2]);
+
+        let invalid = import_csv("ID,EEP,Key,SLF\nfefa1234,D2-03-00,3410DE8F1ABA3EFF9F5A117172EACABD,ff\n").unwrap();
+        assert!(registry.import_with_keys(&invalid, &mut keys).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_tool_json_then_read_devices() {
+        let json = r#"[{"id":"0180998E","profile":"A5-02-05","description":"Living room"},{"address":"fefa1234","eep":"D2-03-00","key":"3410DE8F1ABA3EFF9F5A117172EACABD"}]"#;
+        let devices: Vec<CommissionedDevice> = serde_json::from_str(json).unwrap();
+        assert_eq!(devices[0].name.as_deref(), Some("Living room"));
+        assert_eq!(devices[1].key, Some(KEY));
+        assert_eq!(
+            serde_json::to_string(&devices[1]).unwrap(),
+            r#"{"address":"fefa1234","eep":"D2-03-00","key":"3410DE8F1ABA3EFF9F5A117172EACABD"}"#
+        );
+    }
+}
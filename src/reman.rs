@@ -0,0 +1,394 @@
+//! Remote management
+//!
+//! Remote management lets a controller configure devices over the air. The transceiver
+//! wraps the messages in SYS_EX telegrams; the host exchanges [`RemoteManCommand`]s (packet
+//! type 0x07) with it.
+//!
+//! | Bytes  | Data                              | Optional data                         |
+//! |--------|-----------------------------------|---------------------------------------|
+//! | 0..2   | Function number (12 bits)         | Destination ID                        |
+//! | 2..4   | Manufacturer ID (11 bits)         | ..                                    |
+//! | 4..    | Message data (up to 511 bytes)    | Source ID (4), dBm (1), delay flag (1)|
+//!
+//! The standard remote management commands (RMCC) use the manufacturer ID `0x7FF`. They
+//! are described by [`RmccRequest`], and their answers by [`RmccAnswer`]. Most devices
+//! ignore everything but unlock, ping and query ID until they are unlocked with their
+//! security code. Remote commissioning requests are in [`recom`].
+//!
+//! A [`RemoteManager`] (feature `std`) matches the answers with the requests it sent.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use crate::frame::{ESP3Frame, ESP3FrameRef};
+use crate::packet::{Address, EEPProfileCode, ParseError, BROADCAST};
+#[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+use crate::{packet::Packet, port::Port, FrameReadError, PacketError};
+
+pub mod recom;
+
+/// Manufacturer ID of the standard remote management commands
+pub const RMCC_MANUFACTURER: u16 = 0x7ff;
+
+/// A remote management message (packet type 0x07)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RemoteManCommand {
+    pub function: u16,
+    pub manufacturer: u16,
+    pub data: Vec<u8>,
+    pub destination: Address,
+    /// Filled in by the transceiver when sending
+    pub source: Address,
+    /// Negated dBm value, 0xFF when sending
+    pub rssi: u8,
+    /// Randomly delay sending, so that the answers to a broadcast do not collide
+    pub send_with_delay: bool,
+}
+
+impl RemoteManCommand {
+    /// A message to be sent to `destination`
+    pub fn new(function: u16, manufacturer: u16, data: Vec<u8>, destination: Address) -> Self {
+        Self {
+            function, manufacturer, data, destination,
+            source: Address([0; 4]),
+            rssi: 0xff,
+            send_with_delay: false,
+        }
+    }
+
+    pub fn encode(&self) -> ESP3Frame {
+        let mut data = Vec::with_capacity(4 + self.data.len());
+        data.extend_from_slice(&(self.function & 0x0fff).to_be_bytes());
+        data.extend_from_slice(&(self.manufacturer & 0x07ff).to_be_bytes());
+        data.extend_from_slice(&self.data);
+        let mut optional = Vec::with_capacity(10);
+        optional.extend_from_slice(&self.destination.0);
+        optional.extend_from_slice(&self.source.0);
+        optional.extend_from_slice(&[self.rssi, self.send_with_delay as u8]);
+        ESP3Frame::assemble(0x07, &data, &optional)
+    }
+
+    pub fn decode(frame: ESP3FrameRef) -> Result<Self, ParseError> {
+        let d = frame.data;
+        if d.len() < 4 {
+            return Err(ParseError::PacketTooShort { packet_type: frame.packet_type, length: d.len() });
+        }
+        let optional = frame.optional_data;
+        let address = |range: core::ops::Range<usize>| optional.get(range).map(|a| Address(a.try_into().unwrap()));
+        Ok(Self {
+            function: u16::from_be_bytes([d[0], d[1]]) & 0x0fff,
+            manufacturer: u16::from_be_bytes([d[2], d[3]]) & 0x07ff,
+            data: d[4..].to_vec(),
+            destination: address(0..4).unwrap_or(BROADCAST),
+            source: address(4..8).unwrap_or(Address([0; 4])),
+            rssi: optional.get(8).copied().unwrap_or(0xff),
+            send_with_delay: optional.get(9).is_some_and(|delay| *delay != 0),
+        })
+    }
+}
+
+/// A remote management request, and how to decode its answer
+pub trait RemoteRequest {
+    type Answer;
+
+    /// Function number of the answer, for requests that get one
+    fn answer_function(&self) -> Option<u16>;
+
+    fn command(&self, destination: Address) -> RemoteManCommand;
+
+    fn decode_answer(command: &RemoteManCommand) -> Result<Self::Answer, ParseError>;
+}
+
+/// Security code of a device, used to unlock it
+pub type SecurityCode = u32;
+
+/// Standard remote management commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RmccRequest {
+    Unlock { code: SecurityCode },
+    Lock { code: SecurityCode },
+    SetCode { code: SecurityCode },
+    /// Devices answer with their ID, only those with the given profile if one is set
+    QueryId { eep: Option<EEPProfileCode> },
+    Action,
+    Ping,
+    QueryFunction,
+    QueryStatus,
+}
+
+impl RmccRequest {
+    pub fn function(&self) -> u16 {
+        match self {
+            Self::Unlock { .. } => 0x001,
+            Self::Lock { .. } => 0x002,
+            Self::SetCode { .. } => 0x003,
+            Self::QueryId { .. } => 0x004,
+            Self::Action => 0x005,
+            Self::Ping => 0x006,
+            Self::QueryFunction => 0x007,
+            Self::QueryStatus => 0x008,
+        }
+    }
+}
+
+impl RemoteRequest for RmccRequest {
+    type Answer = RmccAnswer;
+
+    fn answer_function(&self) -> Option<u16> {
+        match self {
+            Self::QueryId { .. } | Self::Ping | Self::QueryFunction | Self::QueryStatus => Some(0x600 | self.function()),
+            _ => None,
+        }
+    }
+
+    fn command(&self, destination: Address) -> RemoteManCommand {
+        let data = match *self {
+            Self::Unlock { code } | Self::Lock { code } | Self::SetCode { code } => code.to_be_bytes().to_vec(),
+            Self::QueryId { eep: Some(eep) } => {
+                let mut data = pack_eep(eep);
+                data[2] |= 0x04;
+                data.to_vec()
+            }
+            Self::QueryId { eep: None } => vec![0; 3],
+            _ => vec![],
+        };
+        RemoteManCommand::new(self.function(), RMCC_MANUFACTURER, data, destination)
+    }
+
+    fn decode_answer(command: &RemoteManCommand) -> Result<RmccAnswer, ParseError> {
+        RmccAnswer::decode(command)
+    }
+}
+
+/// Profiles are sent on 21 bits: RORG (8), FUNC (6), TYPE (7)
+fn pack_eep(eep: EEPProfileCode) -> [u8; 3] {
+    [eep.rorg(), eep.func() << 2 | eep.eep_type() >> 5, eep.eep_type() << 3]
+}
+
+fn unpack_eep(data: &[u8]) -> EEPProfileCode {
+    EEPProfileCode::new(data[0], data[1] >> 2, (data[1] & 0x03) << 5 | data[2] >> 3)
+}
+
+/// A remote function supported by a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteFunction {
+    pub function: u16,
+    pub manufacturer: u16,
+}
+
+/// Answers to the standard remote management commands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RmccAnswer {
+    QueryId { eep: EEPProfileCode },
+    /// `rssi` is the negated dBm value the ping was received with
+    Ping { eep: EEPProfileCode, rssi: u8 },
+    QueryFunction(Vec<RemoteFunction>),
+    QueryStatus { code_set: bool, last_function: u16, last_return_code: u8 },
+}
+
+impl RmccAnswer {
+    pub fn decode(command: &RemoteManCommand) -> Result<Self, ParseError> {
+        let d = &command.data[..];
+        let need = |len: usize| if d.len() < len { Err(ParseError::PacketTooShort { packet_type: 0x07, length: d.len() + 4 }) } else { Ok(()) };
+        match command.function {
+            0x604 => {
+                need(3)?;
+                Ok(Self::QueryId { eep: unpack_eep(d) })
+            }
+            0x606 => {
+                need(4)?;
+                Ok(Self::Ping { eep: unpack_eep(d), rssi: d[3] })
+            }
+            0x607 => Ok(Self::QueryFunction(d.chunks_exact(4)
+                .map(|f| RemoteFunction {
+                    function: u16::from_be_bytes([f[0], f[1]]) & 0x0fff,
+                    manufacturer: u16::from_be_bytes([f[2], f[3]]) & 0x07ff,
+                })
+                .collect())),
+            0x608 => {
+                need(4)?;
+                Ok(Self::QueryStatus {
+                    code_set: d[0] & 0x80 != 0,
+                    last_function: u16::from_be_bytes([d[1], d[2]]) & 0x0fff,
+                    last_return_code: d[3],
+                })
+            }
+            function => Err(ParseError::UnknownFunction(function)),
+        }
+    }
+}
+
+/// An answer, with the device it comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply<A> {
+    pub source: Address,
+    pub answer: A,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+struct Pending {
+    destination: Address,
+    answer_function: u16,
+    deadline: Instant,
+}
+
+/// Sends remote management requests, and matches the answers with them
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct RemoteManager {
+    timeout: Duration,
+    pending: Vec<Pending>,
+}
+
+#[cfg(feature = "std")]
+impl Default for RemoteManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl RemoteManager {
+    /// A manager waiting up to 2 seconds for answers
+    pub fn new() -> Self {
+        Self { timeout: Duration::from_secs(2), pending: Vec::new() }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Build the message for `request`, and expect its answer
+    pub fn request<R: RemoteRequest>(&mut self, request: &R, destination: Address) -> RemoteManCommand {
+        let now = Instant::now();
+        self.pending.retain(|p| p.deadline > now);
+        if let Some(answer_function) = request.answer_function() {
+            self.pending.push(Pending { destination, answer_function, deadline: now + self.timeout });
+        }
+        request.command(destination)
+    }
+
+    /// Whether answers are still expected
+    pub fn is_waiting(&self) -> bool {
+        let now = Instant::now();
+        self.pending.iter().any(|p| p.deadline > now)
+    }
+
+    /// Process a received message. Returns whether it answers a request. Answers to a
+    /// broadcast request are accepted from any device until the request times out.
+    pub fn handle(&mut self, command: &RemoteManCommand) -> bool {
+        let now = Instant::now();
+        let Some(index) = self.pending.iter().position(|p| {
+            p.deadline > now && p.answer_function == command.function
+                && (p.destination == command.source || p.destination.is_broadcast())
+        }) else { return false };
+        if !self.pending[index].destination.is_broadcast() {
+            self.pending.remove(index);
+        }
+        true
+    }
+
+    /// Send `request` through `port`, and collect the answers until the request times out,
+    /// or until the answer of the destination device is received. Answers that do not decode
+    /// are skipped.
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    pub fn query<R: RemoteRequest>(&mut self, port: &mut Port, request: &R, destination: Address) -> Result<Vec<Reply<R::Answer>>, PacketError> {
+        let command = self.request(request, destination);
+        port.write_packet(Packet::RemoteManCommand(command))?;
+
+        let mut replies = Vec::new();
+        let deadline = Instant::now() + self.timeout;
+        while request.answer_function().is_some() && Instant::now() < deadline {
+            let frame = match port.read_frame() {
+                Ok(frame) => frame,
+                Err(FrameReadError::IOError(e)) if e.kind() == std::io::ErrorKind::TimedOut => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let Ok(Packet::RemoteManCommand(command)) = Packet::decode(frame.as_ref()) else { continue };
+            if !self.handle(&command) {
+                continue;
+            }
+            let Ok(answer) = R::decode_answer(&command) else { continue };
+            replies.push(Reply { source: command.source, answer });
+            if !destination.is_broadcast() {
+                break;
+            }
+        }
+        Ok(replies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    fn answer(function: u16, data: &[u8], source: Address) -> RemoteManCommand {
+        RemoteManCommand { source, ..RemoteManCommand::new(function, RMCC_MANUFACTURER, data.to_vec(), BROADCAST) }
+    }
+
+    #[test]
+    fn given_rmcc_requests_then_encode_them() {
+        let device: Address = "0180a1b2".parse().unwrap();
+        let frame = RmccRequest::Unlock { code: 0x12345678 }.command(device).encode();
+        assert_eq!(frame.packet_type(), 0x07);
+        assert_eq!(frame.data(), &[0x00, 0x01, 0x07, 0xff, 0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(frame.optional_data()[..4], device.0);
+
+        let query = RmccRequest::QueryId { eep: Some(EEPProfileCode::new(0xa5, 0x38, 0x08)) }.command(BROADCAST);
+        assert_eq!(query.data, [0xa5, 0xe0, 0x44]);
+        assert_eq!(RemoteManCommand::decode(query.encode().as_ref()).unwrap(), query);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn given_answers_then_match_them_with_requests() {
+        let device: Address = "0180a1b2".parse().unwrap();
+        let other: Address = "0180a1b3".parse().unwrap();
+        let mut manager = RemoteManager::new();
+        manager.request(&RmccRequest::Ping, device);
+        assert!(manager.is_waiting());
+
+        let ping = answer(0x606, &[0xa5, 0xe0, 0x40, 0x2d], device);
+        assert!(!manager.handle(&answer(0x606, &[0xa5, 0xe0, 0x40, 0x2d], other)));
+        assert!(manager.handle(&ping));
+        assert_eq!(RmccRequest::decode_answer(&ping).unwrap(), RmccAnswer::Ping { eep: EEPProfileCode::new(0xa5, 0x38, 0x08), rssi: 0x2d });
+        assert!(!manager.is_waiting());
+
+        manager.request(&RmccRequest::QueryFunction, BROADCAST);
+        let functions = answer(0x607, &[0x00, 0x06, 0x07, 0xff, 0x02, 0x20, 0x00, 0x0d], device);
+        assert!(manager.handle(&functions));
+        assert!(manager.handle(&RemoteManCommand { source: other, ..functions.clone() }));
+        assert_eq!(RmccAnswer::decode(&functions).unwrap(), RmccAnswer::QueryFunction(vec![
+            RemoteFunction { function: 0x006, manufacturer: 0x7ff },
+            RemoteFunction { function: 0x220, manufacturer: 0x00d },
+        ]));
+    }
+
+    #[test]
+    #[cfg(all(feature = "sync", not(target_arch = "wasm32")))]
+    fn given_corrupt_answer_then_keep_the_other_replies() {
+        use crate::sim::Transceiver;
+
+        let first: Address = "0180a1b2".parse().unwrap();
+        let corrupt: Address = "0180a1b3".parse().unwrap();
+        let last: Address = "0180a1b4".parse().unwrap();
+        let transceiver = Transceiver::new("ff800000".parse().unwrap());
+        for (data, source) in [(&[0xa5, 0xe0, 0x40, 0x2d][..], first), (&[0xa5][..], corrupt), (&[0xa5, 0xe0, 0x40, 0x40][..], last)] {
+            transceiver.inject(&answer(0x606, data, source).encode());
+        }
+
+        let mut port = Port::from_serial(transceiver);
+        let mut manager = RemoteManager::new().with_timeout(Duration::from_millis(50));
+        let replies = manager.query(&mut port, &RmccRequest::Ping, BROADCAST).unwrap();
+        let eep = EEPProfileCode::new(0xa5, 0x38, 0x08);
+        assert_eq!(replies, [
+            Reply { source: first, answer: RmccAnswer::Ping { eep, rssi: 0x2d } },
+            Reply { source: last, answer: RmccAnswer::Ping { eep, rssi: 0x40 } },
+        ]);
+    }
+}
@@ -0,0 +1,232 @@
+//! Remote commissioning (ReCom)
+//!
+//! ReCom gives access to the link tables of a device over remote management: the inbound
+//! table lists the devices it accepts telegrams from, the outbound table the devices it
+//! reports to. Writing an entry in the inbound table of an actuator pairs it with a sensor,
+//! without pressing its learn button. The device must be unlocked first, see
+//! [`super::RmccRequest::Unlock`], and the changes are only applied once requested. Devices
+//! also describe themselves, with their product ID and the parameters of their configuration.
+//!
+//! A link table entry is sent on 9 bytes:
+//!
+//! | Byte | Field                              |
+//! |------|------------------------------------|
+//! | 0    | Index in the table                 |
+//! | 1..5 | Device ID                          |
+//! | 5..8 | EEP (RORG, FUNC, TYPE)             |
+//! | 8    | Channel                            |
+
+use alloc::vec::Vec;
+
+use super::{RemoteManCommand, RemoteRequest, RMCC_MANUFACTURER};
+use crate::packet::{Address, EEPProfileCode, ParseError};
+
+/// Link table of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    fn flag(self) -> u8 {
+        match self {
+            Self::Inbound => 0x00,
+            Self::Outbound => 0x80,
+        }
+    }
+
+    fn from_flag(byte: u8) -> Self {
+        if byte & 0x80 != 0 { Self::Outbound } else { Self::Inbound }
+    }
+}
+
+/// An entry of a link table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkTableEntry {
+    pub index: u8,
+    pub device: Address,
+    pub eep: EEPProfileCode,
+    pub channel: u8,
+}
+
+impl LinkTableEntry {
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.push(self.index);
+        data.extend_from_slice(&self.device.0);
+        data.extend_from_slice(&[self.eep.rorg(), self.eep.func(), self.eep.eep_type(), self.channel]);
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        Self {
+            index: data[0],
+            device: Address(data[1..5].try_into().unwrap()),
+            eep: EEPProfileCode::new(data[5], data[6], data[7]),
+            channel: data[8],
+        }
+    }
+}
+
+/// A parameter of the device configuration, as described by the device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationParameter {
+    pub index: u16,
+    pub value: Vec<u8>,
+}
+
+/// What to apply of the changes made to a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApplyChanges {
+    pub link_table: bool,
+    pub configuration: bool,
+}
+
+/// Remote commissioning requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecomRequest {
+    /// Link table support and sizes, see [`RecomAnswer::LinkTableMetadata`]
+    GetLinkTableMetadata,
+    /// Entries `start..=end` of a link table
+    GetLinkTable { direction: Direction, start: u8, end: u8 },
+    SetLinkTable { direction: Direction, entries: Vec<LinkTableEntry> },
+    ApplyChanges(ApplyChanges),
+    GetProductId,
+    /// Parameters `start..=end` of the device configuration, see
+    /// [`RecomAnswer::DeviceConfiguration`]
+    GetDeviceConfiguration { start: u16, end: u16 },
+}
+
+impl RecomRequest {
+    pub fn function(&self) -> u16 {
+        match self {
+            Self::GetLinkTableMetadata => 0x210,
+            Self::GetLinkTable { .. } => 0x211,
+            Self::SetLinkTable { .. } => 0x212,
+            Self::ApplyChanges(_) => 0x226,
+            Self::GetProductId => 0x227,
+            Self::GetDeviceConfiguration { .. } => 0x230,
+        }
+    }
+}
+
+impl RemoteRequest for RecomRequest {
+    type Answer = RecomAnswer;
+
+    fn answer_function(&self) -> Option<u16> {
+        match self {
+            Self::GetLinkTableMetadata | Self::GetLinkTable { .. } | Self::GetProductId | Self::GetDeviceConfiguration { .. } => {
+                Some(0x600 + self.function())
+            }
+            _ => None,
+        }
+    }
+
+    fn command(&self, destination: Address) -> RemoteManCommand {
+        let data = match self {
+            Self::GetLinkTableMetadata | Self::GetProductId => vec![],
+            Self::GetLinkTable { direction, start, end } => vec![direction.flag(), *start, *end],
+            Self::SetLinkTable { direction, entries } => {
+                let mut data = Vec::with_capacity(1 + 9 * entries.len());
+                data.push(direction.flag());
+                for entry in entries {
+                    entry.encode(&mut data);
+                }
+                data
+            }
+            Self::ApplyChanges(apply) => vec![(apply.link_table as u8) << 7 | (apply.configuration as u8) << 6],
+            // Values of any length
+            Self::GetDeviceConfiguration { start, end } => [&start.to_be_bytes()[..], &end.to_be_bytes(), &[0]].concat(),
+        };
+        RemoteManCommand::new(self.function(), RMCC_MANUFACTURER, data, destination)
+    }
+
+    fn decode_answer(command: &RemoteManCommand) -> Result<RecomAnswer, ParseError> {
+        RecomAnswer::decode(command)
+    }
+}
+
+/// Answers to the remote commissioning requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecomAnswer {
+    /// Sizes of the link tables, as (used entries, capacity)
+    LinkTableMetadata { remote_teach_in: bool, inbound: (u8, u8), outbound: (u8, u8) },
+    LinkTable { direction: Direction, entries: Vec<LinkTableEntry> },
+    ProductId { manufacturer: u16, product: u32 },
+    DeviceConfiguration(Vec<ConfigurationParameter>),
+}
+
+impl RecomAnswer {
+    pub fn decode(command: &RemoteManCommand) -> Result<Self, ParseError> {
+        let d = &command.data[..];
+        let need = |len: usize| if d.len() < len { Err(ParseError::PacketTooShort { packet_type: 0x07, length: d.len() + 4 }) } else { Ok(()) };
+        match command.function {
+            0x810 => {
+                need(5)?;
+                Ok(Self::LinkTableMetadata { remote_teach_in: d[0] & 0x80 != 0, inbound: (d[1], d[2]), outbound: (d[3], d[4]) })
+            }
+            0x811 => {
+                need(1)?;
+                Ok(Self::LinkTable {
+                    direction: Direction::from_flag(d[0]),
+                    entries: d[1..].chunks_exact(9).map(LinkTableEntry::decode).collect(),
+                })
+            }
+            0x827 => {
+                need(6)?;
+                Ok(Self::ProductId {
+                    manufacturer: u16::from_be_bytes([d[0], d[1]]) & 0x07ff,
+                    product: u32::from_be_bytes(d[2..6].try_into().unwrap()),
+                })
+            }
+            // Parameters as index (2 bytes), length, value
+            0x830 => {
+                let truncated = || ParseError::PacketTooShort { packet_type: 0x07, length: d.len() + 4 };
+                let mut parameters = vec![];
+                let mut rest = d;
+                while !rest.is_empty() {
+                    let [hi, lo, length, tail @ ..] = rest else { return Err(truncated()) };
+                    let value = tail.get(..*length as usize).ok_or_else(truncated)?;
+                    parameters.push(ConfigurationParameter { index: u16::from_be_bytes([*hi, *lo]), value: value.to_vec() });
+                    rest = &tail[value.len()..];
+                }
+                Ok(Self::DeviceConfiguration(parameters))
+            }
+            function => Err(ParseError::UnknownFunction(function)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_link_table_entries_then_write_and_read_them_back() {
+        let actuator: Address = "0180a1b2".parse().unwrap();
+        let entry = LinkTableEntry { index: 0, device: "fef58ff5".parse().unwrap(), eep: EEPProfileCode::new(0xf6, 0x02, 0x01), channel: 0 };
+
+        let set = RecomRequest::SetLinkTable { direction: Direction::Inbound, entries: vec![entry] }.command(actuator);
+        assert_eq!(set.function, 0x212);
+        assert_eq!(set.data, [0x00, 0x00, 0xfe, 0xf5, 0x8f, 0xf5, 0xf6, 0x02, 0x01, 0x00]);
+
+        let answer = RemoteManCommand { function: 0x811, data: set.data.clone(), ..set };
+        assert_eq!(RecomRequest::decode_answer(&answer).unwrap(), RecomAnswer::LinkTable { direction: Direction::Inbound, entries: vec![entry] });
+        assert_eq!(RecomRequest::GetLinkTable { direction: Direction::Outbound, start: 0, end: 3 }.answer_function(), Some(0x811));
+    }
+
+    #[test]
+    fn given_device_configuration_query_then_read_the_parameters_back() {
+        let request = RecomRequest::GetDeviceConfiguration { start: 0x0001, end: 0x0002 };
+        let query = request.command("0180a1b2".parse().unwrap());
+        assert_eq!((query.function, &query.data[..]), (0x230, &[0x00, 0x01, 0x00, 0x02, 0x00][..]));
+        assert_eq!(request.answer_function(), Some(0x830));
+
+        let answer = RemoteManCommand { function: 0x830, data: vec![0x00, 0x01, 0x01, 0x64, 0x00, 0x02, 0x02, 0x12, 0x34], ..query };
+        assert_eq!(RecomRequest::decode_answer(&answer).unwrap(), RecomAnswer::DeviceConfiguration(vec![
+            ConfigurationParameter { index: 1, value: vec![0x64] },
+            ConfigurationParameter { index: 2, value: vec![0x12, 0x34] },
+        ]));
+        let truncated = RemoteManCommand { data: vec![0x00, 0x01, 0x02, 0x64], ..answer };
+        assert!(matches!(RecomRequest::decode_answer(&truncated), Err(ParseError::PacketTooShort { .. })));
+    }
+}
@@ -0,0 +1,528 @@
+//! Secure telegrams
+//!
+//! Secure devices encrypt their data with a 128-bit AES key shared with the receiver during
+//! teach-in, and a rolling code (RLC) incremented for each telegram. The security layer
+//! format (SLF) byte, also exchanged during teach-in, tells how a telegram is built:
+//!
+//! | Bits | Field                                                                    |
+//! |------|--------------------------------------------------------------------------|
+//! | 7..6 | RLC algorithm: none, 16, 24 or 32 bits                                   |
+//! | 5    | RLC transmitted in the telegram                                          |
+//! | 4..3 | MAC algorithm: none, AES-CMAC truncated to 3 or 4 bytes                  |
+//! | 2..0 | Encryption: 0 none, 3 VAES, 4 AES-CBC                                    |
+//!
+//! Secure telegrams (RORG 0x30 and 0x31) carry the encrypted data, followed by the RLC
+//! when it is transmitted, and by the MAC. With RORG 0x31, the decrypted data starts with
+//! the RORG of the original telegram; with RORG 0x30, it is a non-secure telegram (RORG 0x32)
+//! whose profile is known from teach-in.
+//!
+//! The MAC is the AES-CMAC of the secure RORG, the encrypted data and the RLC, truncated to
+//! its first 3 or 4 bytes. Only telegrams with a valid MAC and a rolling code ahead of the
+//! last one received, by at most [`DEFAULT_RLC_WINDOW`], are accepted.
+//!
+//! [`decrypt`] checks and turns a secure telegram back into a plain telegram, which can be
+//! handed to the EEP decoders:
+//!
+//! [`encrypt`] does the opposite, to control secure actuators or emulate secure switches.
+//!
+//! ```no_run
+//! # use enocean::packet::RadioErp1;
+//! # fn receive() -> RadioErp1<'static> { unimplemented!() }
+//! use enocean::eep;
+//! use enocean::security::{self, SecureDevice};
+//!
+//! let device = SecureDevice { key: [0x55; 16], slf: 0x8b.try_into()?, rlc: 0 };
+//! let telegram = security::decrypt(&receive(), &device)?;
+//! let decoded = eep::decode("D2-03-0A".parse()?, &telegram.erp1())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::borrow::Cow;
+
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use aes::Aes128;
+use cmac::{Cmac, Mac};
+use thiserror::Error;
+
+use crate::enocean::Rorg;
+use crate::packet::{Address, RadioErp1, Security, SubtelNum};
+
+pub mod keystore;
+pub mod maintenance;
+pub mod psk;
+pub mod rlc;
+pub mod teach_in;
+
+/// How far ahead of the last received rolling code a telegram may be
+pub const DEFAULT_RLC_WINDOW: u32 = 128;
+
+/// Public key of the VAES algorithm, defined by the EnOcean security specification
+const VAES_PUBLIC_KEY: [u8; 16] = [
+    0x34, 0x10, 0xde, 0x8f, 0x1a, 0xba, 0x3e, 0xff, 0x9f, 0x5a, 0x11, 0x71, 0x72, 0xea, 0xca, 0xbd,
+];
+
+#[derive(Debug, Error)]
+pub enum SecurityError {
+    #[error("Not a secure telegram")]         NotSecure(Rorg),
+    #[error("Bad secure telegram length")]    BadLength(usize),
+    #[error("Invalid security layer format")] InvalidSlf(u8),
+    #[error("Unsupported encryption")]        UnsupportedEncryption,
+    #[error("Bad MAC")]                       BadMac,
+    #[error("Rolling code out of window")]    RlcOutOfWindow(u32),
+    #[error("Unknown secure device {0}")]     UnknownDevice(Address),
+    #[error("Key storage failed")]            Storage(#[from] std::io::Error),
+    #[error("Unexpected teach-in telegram")]  TeachInSequence,
+    #[error("Teach-in key encrypted by PSK")] PskRequired,
+    #[error("Invalid pre-shared key")]        InvalidPsk,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RlcAlgorithm {
+    None,
+    Rlc16,
+    Rlc24,
+    Rlc32,
+}
+
+impl RlcAlgorithm {
+    /// Size of the rolling code, in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Rlc16 => 2,
+            Self::Rlc24 => 3,
+            Self::Rlc32 => 4,
+        }
+    }
+
+    /// Largest rolling code value, after which it wraps to 0
+    pub fn max(&self) -> u32 {
+        match self.size() {
+            4 => u32::MAX,
+            size => (1 << (8 * size)) - 1,
+        }
+    }
+
+    /// Rolling code `steps` after `rlc`
+    pub fn advance(&self, rlc: u32, steps: u32) -> u32 {
+        rlc.wrapping_add(steps) & self.max()
+    }
+
+    /// Number of steps from `last` to `rlc`
+    pub fn distance(&self, last: u32, rlc: u32) -> u32 {
+        rlc.wrapping_sub(last) & self.max()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacAlgorithm {
+    None,
+    Cmac3,
+    Cmac4,
+}
+
+impl MacAlgorithm {
+    /// Size of the MAC, in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Cmac3 => 3,
+            Self::Cmac4 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    None,
+    Vaes,
+    AesCbc,
+}
+
+/// Security layer format of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slf {
+    pub rlc_algorithm: RlcAlgorithm,
+    pub rlc_transmitted: bool,
+    pub mac: MacAlgorithm,
+    pub encryption: Encryption,
+}
+
+impl TryFrom<u8> for Slf {
+    type Error = SecurityError;
+
+    fn try_from(slf: u8) -> Result<Self, Self::Error> {
+        Ok(Self {
+            rlc_algorithm: match slf >> 6 {
+                0 => RlcAlgorithm::None,
+                1 => RlcAlgorithm::Rlc16,
+                2 => RlcAlgorithm::Rlc24,
+                _ => RlcAlgorithm::Rlc32,
+            },
+            rlc_transmitted: slf & 0x20 != 0,
+            mac: match (slf >> 3) & 0x03 {
+                0 => MacAlgorithm::None,
+                1 => MacAlgorithm::Cmac3,
+                2 => MacAlgorithm::Cmac4,
+                _ => return Err(SecurityError::InvalidSlf(slf)),
+            },
+            encryption: match slf & 0x07 {
+                0 => Encryption::None,
+                3 => Encryption::Vaes,
+                4 => Encryption::AesCbc,
+                _ => return Err(SecurityError::InvalidSlf(slf)),
+            },
+        })
+    }
+}
+
+impl From<Slf> for u8 {
+    fn from(slf: Slf) -> u8 {
+        let rlc = match slf.rlc_algorithm {
+            RlcAlgorithm::None => 0,
+            RlcAlgorithm::Rlc16 => 1,
+            RlcAlgorithm::Rlc24 => 2,
+            RlcAlgorithm::Rlc32 => 3,
+        };
+        let mac = match slf.mac {
+            MacAlgorithm::None => 0,
+            MacAlgorithm::Cmac3 => 1,
+            MacAlgorithm::Cmac4 => 2,
+        };
+        let encryption = match slf.encryption {
+            Encryption::None => 0,
+            Encryption::Vaes => 3,
+            Encryption::AesCbc => 4,
+        };
+        rlc << 6 | (slf.rlc_transmitted as u8) << 5 | mac << 3 | encryption
+    }
+}
+
+/// Security material of a device, as learned during secure teach-in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecureDevice {
+    pub key: [u8; 16],
+    pub slf: Slf,
+    /// Rolling code of the last telegram received from the device
+    pub rlc: u32,
+}
+
+/// A decrypted secure telegram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecureTelegram {
+    /// RORG of the original telegram, [`Rorg::NonSec`] for RORG 0x30 telegrams
+    pub rorg: Rorg,
+    pub data: Vec<u8>,
+    pub sender_id: Address,
+    pub status: u8,
+    pub destination: Option<Address>,
+    pub rssi: Option<u8>,
+    /// Rolling code the telegram was sent with
+    pub rlc: u32,
+    pub security: Security,
+}
+
+impl SecureTelegram {
+    /// View the decrypted telegram as a plain telegram
+    pub fn erp1(&self) -> RadioErp1<'_> {
+        RadioErp1 {
+            choice: self.rorg,
+            user_data: Cow::Borrowed(&self.data),
+            sender_id: self.sender_id,
+            status: self.status,
+            subtel_num: None,
+            destination: self.destination,
+            rssi: self.rssi,
+            security: Some(self.security),
+        }
+    }
+}
+
+/// A secure telegram built by [`encrypt`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedTelegram {
+    /// [`Rorg::SecEncaps`], or [`Rorg::Sec`] for non-secure telegrams (RORG 0x32)
+    pub rorg: Rorg,
+    /// Encrypted data, followed by the RLC when transmitted, and by the MAC
+    pub data: Vec<u8>,
+    pub sender_id: Address,
+    pub status: u8,
+    pub destination: Option<Address>,
+    /// Rolling code the telegram was sent with
+    pub rlc: u32,
+}
+
+impl EncryptedTelegram {
+    /// View the secure telegram as a telegram ready to be sent
+    pub fn erp1(&self) -> RadioErp1<'_> {
+        RadioErp1 {
+            choice: self.rorg,
+            user_data: Cow::Borrowed(&self.data),
+            sender_id: self.sender_id,
+            status: self.status,
+            subtel_num: self.destination.map(|_| SubtelNum::Send),
+            destination: self.destination,
+            rssi: None,
+            security: None,
+        }
+    }
+}
+
+/// Util : encrypt a single AES block
+fn aes_encrypt(key: &[u8; 16], block: [u8; 16]) -> [u8; 16] {
+    let mut block = GenericArray::from(block);
+    Aes128::new(GenericArray::from_slice(key)).encrypt_block(&mut block);
+    let mut output = [0; 16];
+    output.copy_from_slice(&block);
+    output
+}
+
+/// Util : MAC of a secure telegram, truncated to `size` bytes
+fn mac(key: &[u8; 16], rorg: Rorg, payload: &[u8], rlc: &[u8], size: usize) -> Vec<u8> {
+    let mut cmac = <Cmac<Aes128> as KeyInit>::new(GenericArray::from_slice(key));
+    cmac.update(&[rorg.into()]);
+    cmac.update(payload);
+    cmac.update(rlc);
+    cmac.finalize().into_bytes()[..size].to_vec()
+}
+
+/// Util : rolling code as transmitted, on `size` bytes
+fn rlc_bytes(rlc: u32, size: usize) -> Vec<u8> {
+    rlc.to_be_bytes()[4 - size..].to_vec()
+}
+
+/// VAES encryption, which is its own inverse: the data (at most one block) is XORed with the
+/// AES encryption of the public key XORed with the RLC
+fn vaes(key: &[u8; 16], rlc: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut input = VAES_PUBLIC_KEY;
+    input.iter_mut().zip(rlc).for_each(|(byte, rlc)| *byte ^= rlc);
+    let stream = aes_encrypt(key, input);
+    data.iter().zip(stream).map(|(byte, key)| byte ^ key).collect()
+}
+
+/// Authenticate and decrypt a secure telegram (RORG 0x30 or 0x31), with the default
+/// rolling code window.
+///
+/// When the device does not transmit its rolling code, the rolling codes of the window are
+/// tried in turn until the MAC matches. Without a MAC, the telegram is assumed to follow the
+/// last one received.
+///
+/// The rolling code of `device` is left untouched, see [`rlc::SecureReceiver`] to track it.
+pub fn decrypt(erp: &RadioErp1, device: &SecureDevice) -> Result<SecureTelegram, SecurityError> {
+    decrypt_in_window(erp, device, DEFAULT_RLC_WINDOW)
+}
+
+/// Same as [`decrypt`], accepting rolling codes up to `window` steps ahead
+pub fn decrypt_in_window(erp: &RadioErp1, device: &SecureDevice, window: u32) -> Result<SecureTelegram, SecurityError> {
+    if !matches!(erp.choice, Rorg::Sec | Rorg::SecEncaps) {
+        return Err(SecurityError::NotSecure(erp.choice));
+    }
+    let slf = device.slf;
+    let rlc_size = slf.rlc_algorithm.size();
+    let trailer = slf.mac.size() + if slf.rlc_transmitted { rlc_size } else { 0 };
+    let d = &erp.user_data[..];
+    if d.len() <= trailer || d.len() - trailer > 16 {
+        return Err(SecurityError::BadLength(d.len()));
+    }
+    let (payload, trailer) = d.split_at(d.len() - trailer);
+    let (transmitted_rlc, received_mac) = trailer.split_at(trailer.len() - slf.mac.size());
+    let valid = |rlc| received_mac == mac(&device.key, erp.choice, payload, &rlc_bytes(rlc, rlc_size), slf.mac.size());
+    let in_window = |rlc| {
+        let distance = slf.rlc_algorithm.distance(device.rlc, rlc);
+        rlc_size == 0 || (1..=window).contains(&distance)
+    };
+
+    let rlc = if slf.rlc_transmitted {
+        let rlc = transmitted_rlc.iter().fold(0, |rlc, byte| rlc << 8 | *byte as u32);
+        if !in_window(rlc) {
+            return Err(SecurityError::RlcOutOfWindow(rlc));
+        }
+        if !valid(rlc) {
+            return Err(SecurityError::BadMac);
+        }
+        rlc
+    } else {
+        (1..=window)
+            .map(|steps| slf.rlc_algorithm.advance(device.rlc, steps))
+            .find(|rlc| valid(*rlc))
+            .ok_or(SecurityError::BadMac)?
+    };
+
+    let plain = match slf.encryption {
+        Encryption::None => payload.to_vec(),
+        Encryption::Vaes => vaes(&device.key, &rlc_bytes(rlc, rlc_size), payload),
+        Encryption::AesCbc => return Err(SecurityError::UnsupportedEncryption),
+    };
+
+    let (rorg, data) = match erp.choice {
+        Rorg::SecEncaps => {
+            let rorg = Rorg::from(plain[0]);
+            (rorg, plain[1..].to_vec())
+        }
+        _ => (Rorg::NonSec, plain),
+    };
+
+    Ok(SecureTelegram {
+        rorg,
+        data,
+        sender_id: erp.sender_id,
+        status: erp.status,
+        destination: erp.destination,
+        rssi: erp.rssi,
+        rlc,
+        security: match (slf.mac, slf.encryption) {
+            (MacAlgorithm::None, _) => Security::Decrypted,
+            (_, Encryption::None) => Security::Authenticated,
+            _ => Security::AuthAndDecrypted,
+        },
+    })
+}
+
+/// Encrypt and authenticate a telegram for `device`, using the rolling code following the
+/// last one sent. The rolling code of `device` is advanced, and should be saved.
+pub fn encrypt(erp: &RadioErp1, device: &mut SecureDevice) -> Result<EncryptedTelegram, SecurityError> {
+    let slf = device.slf;
+    let (rorg, plain) = match erp.choice {
+        Rorg::NonSec => (Rorg::Sec, erp.user_data.to_vec()),
+        rorg => (Rorg::SecEncaps, [&[rorg.into()], &erp.user_data[..]].concat()),
+    };
+    if plain.len() > 16 {
+        return Err(SecurityError::BadLength(plain.len()));
+    }
+
+    let rlc = slf.rlc_algorithm.advance(device.rlc, 1);
+    let rlc_bytes = rlc_bytes(rlc, slf.rlc_algorithm.size());
+    let mut data = match slf.encryption {
+        Encryption::None => plain,
+        Encryption::Vaes => vaes(&device.key, &rlc_bytes, &plain),
+        Encryption::AesCbc => return Err(SecurityError::UnsupportedEncryption),
+    };
+    let mac = mac(&device.key, rorg, &data, &rlc_bytes, slf.mac.size());
+    if slf.rlc_transmitted {
+        data.extend_from_slice(&rlc_bytes);
+    }
+    data.extend(mac);
+    device.rlc = rlc;
+
+    Ok(EncryptedTelegram {
+        rorg,
+        data,
+        sender_id: erp.sender_id,
+        status: erp.status,
+        destination: erp.destination,
+        rlc,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    pub(crate) const KEY: [u8; 16] = [
+        0x86, 0x9f, 0xab, 0x7d, 0x29, 0x6c, 0x9e, 0x48, 0xce, 0xbf, 0xf3, 0x4d, 0xf6, 0x37, 0x35, 0x8a,
+    ];
+
+    #[test]
+    fn given_slf_byte_then_round_trip_it() {
+        let slf = Slf::try_from(0xf3).unwrap();
+        assert_eq!(slf.rlc_algorithm, RlcAlgorithm::Rlc32);
+        assert!(slf.rlc_transmitted);
+        assert_eq!(slf.mac, MacAlgorithm::Cmac4);
+        assert_eq!(slf.encryption, Encryption::Vaes);
+        assert_eq!(u8::from(slf), 0xf3);
+        assert!(matches!(Slf::try_from(0x18), Err(SecurityError::InvalidSlf(0x18))));
+    }
+
+    /// Util : build a secure telegram from its plain data
+    pub(crate) fn secure(rorg: Rorg, device: &SecureDevice, rlc: u32, plain: &[u8]) -> Vec<u8> {
+        let rlc = rlc_bytes(rlc, device.slf.rlc_algorithm.size());
+        let payload = vaes(&device.key, &rlc, plain);
+        let mut telegram = vec![rorg.into()];
+        telegram.extend_from_slice(&payload);
+        if device.slf.rlc_transmitted {
+            telegram.extend_from_slice(&rlc);
+        }
+        telegram.extend(mac(&device.key, rorg, &payload, &rlc, device.slf.mac.size()));
+        telegram.extend_from_slice(&[0x01, 0x02, 0x03, 0x04, 0x00]);
+        telegram
+    }
+
+    #[test]
+    fn given_encapsulated_telegram_then_decrypt_it() {
+        // RLC 0x1234 transmitted, 4-byte MAC
+        let device = SecureDevice { key: KEY, slf: Slf::try_from(0x73).unwrap(), rlc: 0x1200 };
+        let telegram = secure(Rorg::SecEncaps, &device, 0x1234, &[0xd2, 0x64, 0x01]);
+
+        let decrypted = decrypt(&erp1(&telegram), &device).unwrap();
+        assert_eq!(decrypted.rlc, 0x1234);
+        assert_eq!(decrypted.rorg, Rorg::Vld);
+        assert_eq!(decrypted.security, Security::AuthAndDecrypted);
+        assert_eq!(decrypted.erp1().user_data[..], [0x64, 0x01]);
+    }
+
+    #[test]
+    fn given_implicit_rlc_then_find_it_in_window() {
+        let device = SecureDevice { key: KEY, slf: Slf::try_from(0x8b).unwrap(), rlc: 0x0041 };
+        let telegram = secure(Rorg::Sec, &device, 0x45, &[0x05]);
+
+        let decrypted = decrypt(&erp1(&telegram), &device).unwrap();
+        assert_eq!(decrypted.rlc, 0x45);
+        assert_eq!(decrypted.rorg, Rorg::NonSec);
+        assert_eq!(decrypted.data, vec![0x05]);
+        let truncated = [0x30, 0xaa, 0xbb, 0xcc, 0x01, 0x02, 0x03, 0x04, 0x00];
+        assert!(matches!(decrypt(&erp1(&truncated), &device), Err(SecurityError::BadLength(3))));
+    }
+
+    #[test]
+    fn given_spoofed_or_replayed_telegram_then_reject_it() {
+        let device = SecureDevice { key: KEY, slf: Slf::try_from(0x73).unwrap(), rlc: 0x1234 };
+        let replayed = secure(Rorg::SecEncaps, &device, 0x1234, &[0xd2, 0x64, 0x01]);
+        assert!(matches!(decrypt(&erp1(&replayed), &device), Err(SecurityError::RlcOutOfWindow(0x1234))));
+
+        let mut spoofed = secure(Rorg::SecEncaps, &device, 0x1235, &[0xd2, 0x64, 0x01]);
+        spoofed[2] ^= 0x01;
+        assert!(matches!(decrypt(&erp1(&spoofed), &device), Err(SecurityError::BadMac)));
+
+        let device = SecureDevice { rlc: 0xfffe, ..device };
+        let wrapped = secure(Rorg::SecEncaps, &device, 0x0001, &[0xd2, 0x64, 0x01]);
+        assert_eq!(decrypt(&erp1(&wrapped), &device).unwrap().rlc, 1);
+    }
+
+    #[test]
+    fn given_known_answers_then_encrypt_and_authenticate_alike() {
+        // FIPS-197, appendix C.1
+        let key = core::array::from_fn(|i| i as u8);
+        assert_eq!(hex::encode(aes_encrypt(&key, 0x00112233445566778899aabbccddeeff_u128.to_be_bytes())), "69c4e0d86a7b0430d8cdb78070b4c55a");
+        // RFC 4493, example 2, with the first byte of the message as RORG
+        let key = 0x2b7e151628aed2a6abf7158809cf4f3c_u128.to_be_bytes();
+        let message = hex::decode("c1bee22e409f96e93d7e117393172a").unwrap();
+        assert_eq!(hex::encode(mac(&key, Rorg::from(0x6b), &message, &[], 16)), "070a16b46b4d4144f79bdd9dd04a287c");
+
+        // RLC 0x1234 transmitted, VAES, 4-byte MAC; computed apart with another AES-CMAC
+        // implementation
+        let mut device = SecureDevice { key: KEY, slf: Slf::try_from(0x73).unwrap(), rlc: 0x1233 };
+        let encrypted = encrypt(&erp1(&[0xd2, 0x64, 0x01, 0x01, 0x02, 0x03, 0x04, 0x00]), &mut device).unwrap();
+        assert_eq!(encrypted.rorg, Rorg::SecEncaps);
+        assert_eq!(hex::encode(&encrypted.data), "64a9c0123456b5141a");
+        let decrypted = decrypt(&encrypted.erp1(), &SecureDevice { rlc: 0x1233, ..device }).unwrap();
+        assert_eq!((decrypted.rorg, decrypted.data), (Rorg::Vld, vec![0x64, 0x01]));
+    }
+
+    #[test]
+    fn given_plain_telegram_then_encrypt_it_for_the_receiver() {
+        let mut sender = SecureDevice { key: KEY, slf: Slf::try_from(0x8b).unwrap(), rlc: 0x0fff };
+        let receiver = sender.clone();
+        let plain = erp1(&[0xd2, 0x64, 0x01, 0xff, 0x9b, 0x12, 0x80, 0x00]);
+
+        let encrypted = encrypt(&plain, &mut sender).unwrap();
+        assert_eq!(encrypted.rorg, Rorg::SecEncaps);
+        assert_eq!(encrypted.data.len(), 3 + 3);
+        assert_eq!(sender.rlc, 0x1000);
+
+        let decrypted = decrypt(&encrypted.erp1(), &receiver).unwrap();
+        assert_eq!(decrypted.rlc, 0x1000);
+        assert_eq!(decrypted.rorg, Rorg::Vld);
+        assert_eq!(decrypted.data, vec![0x64, 0x01]);
+    }
+}
@@ -0,0 +1,206 @@
+//! Storage of security material
+//!
+//! A [`KeyStore`] keeps the key, security layer format and rolling code of the secure
+//! devices, by address. The gateway stores the material learned at secure teach-in, restores
+//! it on start, and saves each rolling code advance, see
+//! [`SecureReceiver::learn`](super::rlc::SecureReceiver::learn) and
+//! [`SecureReceiver::restore`](super::rlc::SecureReceiver::restore). Every key store is also
+//! a [`RlcStore`].
+//!
+//! [`MemoryKeyStore`] keeps nothing across restarts; [`FileKeyStore`] keeps a text file.
+//! Integrators implement the trait over their own secret store, e.g. an HSM, the OS keyring
+//! or a database.
+//!
+//! ```no_run
+//! use enocean::security::keystore::FileKeyStore;
+//! use enocean::security::rlc::SecureReceiver;
+//!
+//! let mut receiver = SecureReceiver::new(FileKeyStore::open("/var/lib/enocean/keys")?);
+//! receiver.restore()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::rlc::RlcStore;
+use super::{SecureDevice, Slf};
+use crate::packet::Address;
+
+/// Persistent storage for the security material of devices
+pub trait KeyStore {
+    /// The material of a device, if stored
+    fn lookup(&mut self, device: Address) -> io::Result<Option<SecureDevice>>;
+
+    /// Store the material of a device, replacing the previous one
+    fn insert(&mut self, device: Address, material: &SecureDevice) -> io::Result<()>;
+
+    fn remove(&mut self, device: Address) -> io::Result<()>;
+
+    /// The addresses of the stored devices
+    fn devices(&mut self) -> io::Result<Vec<Address>>;
+
+    /// Save the rolling code of a device after it advanced. Unknown devices are ignored.
+    fn update_rlc(&mut self, device: Address, rlc: u32) -> io::Result<()> {
+        match self.lookup(device)? {
+            Some(material) => self.insert(device, &SecureDevice { rlc, ..material }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<K: KeyStore> RlcStore for K {
+    fn load(&mut self, device: Address) -> io::Result<Option<u32>> {
+        Ok(self.lookup(device)?.map(|material| material.rlc))
+    }
+
+    fn save(&mut self, device: Address, rlc: u32) -> io::Result<()> {
+        self.update_rlc(device, rlc)
+    }
+}
+
+/// In-memory storage, for tests or gateways that learn their devices again on start
+#[derive(Debug, Clone, Default)]
+pub struct MemoryKeyStore {
+    devices: BTreeMap<Address, SecureDevice>,
+}
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn lookup(&mut self, device: Address) -> io::Result<Option<SecureDevice>> {
+        Ok(self.devices.get(&device).cloned())
+    }
+
+    fn insert(&mut self, device: Address, material: &SecureDevice) -> io::Result<()> {
+        self.devices.insert(device, material.clone());
+        Ok(())
+    }
+
+    fn remove(&mut self, device: Address) -> io::Result<()> {
+        self.devices.remove(&device);
+        Ok(())
+    }
+
+    fn devices(&mut self) -> io::Result<Vec<Address>> {
+        Ok(self.devices.keys().copied().collect())
+    }
+}
+
+/// Storage in a text file, with a line per device: address, SLF, rolling code and key, in
+/// hexadecimal, e.g. `0180998e 8b 00000102 3410de8f1aba3eff9f5a117172eacabd`.
+///
+/// The file is read once when opened, and written again, through a temporary file, on each
+/// change. On Unix, it is created readable by its owner only.
+#[derive(Debug)]
+pub struct FileKeyStore {
+    path: PathBuf,
+    memory: MemoryKeyStore,
+}
+
+impl FileKeyStore {
+    /// Open the store at `path`, empty if the file does not exist yet
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut memory = MemoryKeyStore::new();
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                for (number, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                    let (address, material) = parse_line(line).ok_or_else(|| io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}:{}: invalid key store line", path.display(), number + 1),
+                    ))?;
+                    memory.devices.insert(address, material);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(Self { path, memory })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let mut text = String::new();
+        for (address, material) in &self.memory.devices {
+            text += &format!("{} {:02x} {:08x} {}\n", address, u8::from(material.slf), material.rlc, hex::encode(material.key));
+        }
+        let temporary = self.path.with_extension("tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&temporary)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+impl KeyStore for FileKeyStore {
+    fn lookup(&mut self, device: Address) -> io::Result<Option<SecureDevice>> {
+        self.memory.lookup(device)
+    }
+
+    fn insert(&mut self, device: Address, material: &SecureDevice) -> io::Result<()> {
+        self.memory.insert(device, material)?;
+        self.write()
+    }
+
+    fn remove(&mut self, device: Address) -> io::Result<()> {
+        self.memory.remove(device)?;
+        self.write()
+    }
+
+    fn devices(&mut self) -> io::Result<Vec<Address>> {
+        self.memory.devices()
+    }
+}
+
+/// Util : parse a line of a key store file
+fn parse_line(line: &str) -> Option<(Address, SecureDevice)> {
+    let mut fields = line.split_whitespace();
+    let address = fields.next()?.parse().ok()?;
+    let slf = Slf::try_from(u8::from_str_radix(fields.next()?, 16).ok()?).ok()?;
+    let rlc = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let mut key = [0; 16];
+    hex::decode_to_slice(fields.next()?, &mut key).ok()?;
+    fields.next().is_none().then_some((address, SecureDevice { key, slf, rlc }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::tests::KEY;
+
+    #[test]
+    fn given_file_store_then_keep_material_across_opens() {
+        let path = std::env::temp_dir().join(format!("enocean-keystore-{}", std::process::id()));
+        let address = "0180998e".parse().unwrap();
+        let device = SecureDevice { key: KEY, slf: Slf::try_from(0x8b).unwrap(), rlc: 0x0102 };
+
+        let mut store = FileKeyStore::open(&path).unwrap();
+        store.insert(address, &device).unwrap();
+        RlcStore::save(&mut store, address, 0x0110).unwrap();
+        RlcStore::save(&mut store, "fefa1234".parse().unwrap(), 0x01).unwrap();
+
+        let mut store = FileKeyStore::open(&path).unwrap();
+        assert_eq!(store.devices().unwrap(), vec![address]);
+        assert_eq!(store.lookup(address).unwrap(), Some(SecureDevice { rlc: 0x0110, ..device }));
+        store.remove(address).unwrap();
+        assert_eq!(FileKeyStore::open(&path).unwrap().devices().unwrap(), vec![]);
+
+        fs::write(&path, "0180998e 8b zz\n").unwrap();
+        assert_eq!(FileKeyStore::open(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
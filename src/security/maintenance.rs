@@ -0,0 +1,116 @@
+//! Maintenance keys
+//!
+//! Besides its operational key, a secure device can accept a maintenance key, known to the
+//! installer. When an installation is re-commissioned, telegrams secured with the
+//! maintenance key let the installer re-key devices without physical access to them.
+//!
+//! The transceiver stores the maintenance keys (`CO_WR_SECUREDEVICE_MAINTENANCEKEY`); a
+//! [`MaintenanceKeyring`] keeps the host-side copy used to secure maintenance telegrams.
+
+use std::collections::HashMap;
+
+use super::{SecureDevice, SecurityError, Slf};
+use crate::packet::{Address, CommonCommand, Response};
+
+/// Maintenance key of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceKey {
+    pub device: Address,
+    pub key: [u8; 16],
+    /// Key slot on the device
+    pub number: u8,
+}
+
+impl MaintenanceKey {
+    /// Command storing the key in the transceiver
+    pub fn write_command(&self) -> CommonCommand<'static> {
+        CommonCommand::WriteSecureDeviceMaintenanceKey { device: self.device, key: self.key, number: self.number }
+    }
+
+    /// Decode the response to [`CommonCommand::ReadSecureDeviceMaintenanceKey`]
+    pub fn decode(response: &Response) -> Result<Self, SecurityError> {
+        let d = &response.data;
+        if d.len() != 21 {
+            return Err(SecurityError::BadLength(d.len()));
+        }
+        Ok(Self {
+            device: Address(d[0..4].try_into().unwrap()),
+            key: d[4..20].try_into().unwrap(),
+            number: d[20],
+        })
+    }
+}
+
+/// Maintenance keys known to the host, by device
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceKeyring {
+    keys: HashMap<Address, MaintenanceKey>,
+    rlcs: HashMap<Address, u32>,
+}
+
+impl MaintenanceKeyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the key of a device, returning the command that stores it in the transceiver
+    pub fn insert(&mut self, key: MaintenanceKey) -> CommonCommand<'static> {
+        self.keys.insert(key.device, key);
+        key.write_command()
+    }
+
+    pub fn get(&self, device: &Address) -> Option<&MaintenanceKey> {
+        self.keys.get(device)
+    }
+
+    pub fn remove(&mut self, device: &Address) -> Option<MaintenanceKey> {
+        self.rlcs.remove(device);
+        self.keys.remove(device)
+    }
+
+    /// Security material to secure maintenance telegrams for a device, with the security
+    /// layer format of its operational key. Rolling codes of maintenance sessions are
+    /// tracked apart from the operational ones; save the device back with
+    /// [`Self::end_session`] once done.
+    pub fn session(&self, device: &Address, slf: Slf) -> Result<SecureDevice, SecurityError> {
+        let key = self.keys.get(device).ok_or(SecurityError::UnknownDevice(*device))?;
+        Ok(SecureDevice { key: key.key, slf, rlc: self.rlcs.get(device).copied().unwrap_or(0) })
+    }
+
+    /// Record the rolling code reached during a maintenance session
+    pub fn end_session(&mut self, device: Address, session: &SecureDevice) {
+        self.rlcs.insert(device, session.rlc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+    use crate::enocean::ReturnCode;
+    use crate::packet::Packet;
+    use crate::security::tests::KEY;
+    use crate::security::{decrypt, encrypt};
+
+    #[test]
+    fn given_maintenance_key_then_secure_maintenance_telegrams() {
+        let device = "01020304".parse().unwrap();
+        let mut keyring = MaintenanceKeyring::new();
+        let command = keyring.insert(MaintenanceKey { device, key: KEY, number: 1 });
+        let frame = Packet::CommonCommand(command).encode();
+        assert_eq!(frame.data()[0], 0x3c);
+        assert_eq!(frame.data()[21], 1);
+
+        let slf = Slf::try_from(0x73).unwrap();
+        let mut session = keyring.session(&device, slf).unwrap();
+        let telegram = encrypt(&erp1(&[0xd2, 0x64, 0x01, 0xff, 0x9b, 0x12, 0x80, 0x00]), &mut session).unwrap();
+        keyring.end_session(device, &session);
+        assert_eq!(keyring.session(&device, slf).unwrap().rlc, 1);
+
+        let receiver = SecureDevice { key: KEY, slf, rlc: 0 };
+        assert_eq!(decrypt(&telegram.erp1(), &receiver).unwrap().data, vec![0x64, 0x01]);
+
+        let response = Response { code: ReturnCode::Ok, data: [&[1, 2, 3, 4][..], &KEY, &[1]].concat().into() };
+        assert_eq!(MaintenanceKey::decode(&response).unwrap(), *keyring.get(&device).unwrap());
+    }
+}
@@ -0,0 +1,86 @@
+//! Pre-shared keys
+//!
+//! Some secure devices never send their key over the air: it is printed on the device,
+//! usually as a QR code, and must be entered on the receiver. The transceiver then uses it
+//! to accept the encrypted teach-in of the device.
+//!
+//! QR codes hold `+`-separated fields, each starting with an identifier. The device ID is
+//! in the `30S` field and the key in the `Z` field:
+//!
+//! ```
+//! use enocean::security::psk::PreSharedKey;
+//!
+//! let psk: PreSharedKey = "30SE21500111A2B3C+Z0123456789ABCDEF0123456789ABCDEF+30PS3221-A215".parse()?;
+//! assert_eq!(psk.device.unwrap().to_string(), "111a2b3c");
+//! # Ok::<(), enocean::security::SecurityError>(())
+//! ```
+
+use std::str::FromStr;
+
+use super::SecurityError;
+use crate::packet::{Address, CommonCommand};
+
+/// A key entered on the receiver, with the device it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreSharedKey {
+    /// Device ID, if known from the QR code
+    pub device: Option<Address>,
+    pub key: [u8; 16],
+}
+
+impl PreSharedKey {
+    /// Command registering the key with the transceiver, for `device` when it is not known from the key entry
+    pub fn add_command(&self, device: Address) -> CommonCommand<'static> {
+        CommonCommand::WriteSecureDeviceAddPsk { device: self.device.unwrap_or(device), psk: self.key }
+    }
+}
+
+impl FromStr for PreSharedKey {
+    type Err = SecurityError;
+
+    /// Parses a QR code, or a bare key of 32 hexadecimal digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_key = |hex_key: &str| -> Result<[u8; 16], SecurityError> {
+            let mut key = [0; 16];
+            hex::decode_to_slice(hex_key, &mut key).map_err(|_| SecurityError::InvalidPsk)?;
+            Ok(key)
+        };
+
+        if !s.contains('+') {
+            return Ok(Self { device: None, key: parse_key(s.trim())? });
+        }
+
+        let mut device = None;
+        let mut key = None;
+        for field in s.trim().split('+') {
+            if let Some(hex_key) = field.strip_prefix('Z') {
+                key = Some(parse_key(hex_key)?);
+            } else if let Some(serial) = field.strip_prefix("30S") {
+                // The serial number ends with the 8 digits of the device ID
+                let id = serial.get(serial.len().saturating_sub(8)..).ok_or(SecurityError::InvalidPsk)?;
+                device = Some(id.parse().map_err(|_| SecurityError::InvalidPsk)?);
+            }
+        }
+        Ok(Self { device, key: key.ok_or(SecurityError::InvalidPsk)? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::Packet;
+
+    #[test]
+    fn given_qr_code_or_bare_key_then_parse_psk() {
+        let psk: PreSharedKey = "30SE2150011A2B3C4+Z869FAB7D296C9E48CEBFF34DF637358A+30PS3221-A215".parse().unwrap();
+        assert_eq!(psk.device, "11a2b3c4".parse().ok());
+        assert_eq!(psk.key[..2], [0x86, 0x9f]);
+        let bare: PreSharedKey = "869FAB7D296C9E48CEBFF34DF637358A".parse().unwrap();
+        assert_eq!(bare.key, psk.key);
+        assert!("30SE2150011A2B3C4+30PS3221-A215".parse::<PreSharedKey>().is_err());
+
+        let frame = Packet::CommonCommand(psk.add_command("ffffffff".parse().unwrap())).encode();
+        assert_eq!(frame.data()[..5], [0x1f, 0x11, 0xa2, 0xb3, 0xc4]);
+        assert_eq!(frame.data().len(), 21);
+    }
+}
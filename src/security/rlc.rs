@@ -0,0 +1,167 @@
+//! Rolling code tracking
+//!
+//! A [`SecureReceiver`] holds the secure devices known to the gateway, and advances the
+//! rolling code of a device each time one of its telegrams is accepted. The security
+//! specification requires rolling codes to survive restarts, otherwise recorded telegrams
+//! could be replayed: each advance is saved to a [`RlcStore`]. With a
+//! [`KeyStore`](super::keystore::KeyStore), the keys are kept as well.
+
+use std::collections::HashMap;
+use std::io;
+
+use super::keystore::KeyStore;
+use super::teach_in::SecureDeviceInfo;
+use super::{decrypt_in_window, SecureDevice, SecureTelegram, SecurityError, DEFAULT_RLC_WINDOW};
+use crate::packet::{Address, RadioErp1};
+
+/// Persistent storage for rolling codes
+pub trait RlcStore {
+    /// Last rolling code saved for a device, if any
+    fn load(&mut self, device: Address) -> io::Result<Option<u32>>;
+    fn save(&mut self, device: Address, rlc: u32) -> io::Result<()>;
+}
+
+/// In-memory storage, for tests or devices that do not need persistence
+impl RlcStore for HashMap<Address, u32> {
+    fn load(&mut self, device: Address) -> io::Result<Option<u32>> {
+        Ok(self.get(&device).copied())
+    }
+
+    fn save(&mut self, device: Address, rlc: u32) -> io::Result<()> {
+        self.insert(device, rlc);
+        Ok(())
+    }
+}
+
+/// Receiving side of the secure devices
+#[derive(Debug)]
+pub struct SecureReceiver<S> {
+    devices: HashMap<Address, SecureDevice>,
+    window: u32,
+    store: S,
+}
+
+impl<S: RlcStore> SecureReceiver<S> {
+    pub fn new(store: S) -> Self {
+        Self { devices: HashMap::new(), window: DEFAULT_RLC_WINDOW, store }
+    }
+
+    /// How far ahead of the last received rolling code a telegram may be
+    pub fn with_window(mut self, window: u32) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Register a device. A rolling code found in the store takes precedence over the one of `device`.
+    pub fn add(&mut self, address: Address, mut device: SecureDevice) -> Result<(), SecurityError> {
+        if let Some(rlc) = self.store.load(address)? {
+            device.rlc = rlc;
+        }
+        self.devices.insert(address, device);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, address: &Address) -> Option<SecureDevice> {
+        self.devices.remove(address)
+    }
+
+    pub fn device(&self, address: &Address) -> Option<&SecureDevice> {
+        self.devices.get(address)
+    }
+
+    /// Authenticate and decrypt a telegram, then advance the rolling code of its sender
+    pub fn receive(&mut self, erp: &RadioErp1) -> Result<SecureTelegram, SecurityError> {
+        let device = self.devices.get_mut(&erp.sender_id).ok_or(SecurityError::UnknownDevice(erp.sender_id))?;
+        let telegram = decrypt_in_window(erp, device, self.window)?;
+        device.rlc = telegram.rlc;
+        self.store.save(erp.sender_id, telegram.rlc)?;
+        Ok(telegram)
+    }
+
+    /// Set the rolling code of a device, after it was reset or went out of the window
+    pub fn resynchronize(&mut self, address: Address, rlc: u32) -> Result<(), SecurityError> {
+        let device = self.devices.get_mut(&address).ok_or(SecurityError::UnknownDevice(address))?;
+        device.rlc = rlc;
+        self.store.save(address, rlc)?;
+        Ok(())
+    }
+}
+
+impl<S: KeyStore> SecureReceiver<S> {
+    /// Register a device learned at secure teach-in, and store its material
+    pub fn learn(&mut self, info: &SecureDeviceInfo) -> Result<(), SecurityError> {
+        let device = info.device();
+        self.store.insert(info.address, &device)?;
+        self.devices.insert(info.address, device);
+        Ok(())
+    }
+
+    /// Register all the devices of the store, returning how many there are
+    pub fn restore(&mut self) -> Result<usize, SecurityError> {
+        let addresses = self.store.devices()?;
+        for address in &addresses {
+            if let Some(device) = self.store.lookup(*address)? {
+                self.devices.insert(*address, device);
+            }
+        }
+        Ok(addresses.len())
+    }
+
+    /// Unregister a device, and erase its material from the store
+    pub fn forget(&mut self, address: &Address) -> Result<Option<SecureDevice>, SecurityError> {
+        self.store.remove(*address)?;
+        Ok(self.devices.remove(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+    use crate::enocean::Rorg;
+    use crate::security::keystore::MemoryKeyStore;
+    use crate::security::tests::{secure, KEY};
+    use crate::security::Slf;
+
+    #[test]
+    fn given_accepted_telegrams_then_advance_and_save_rlc() {
+        let address = "01020304".parse().unwrap();
+        let device = SecureDevice { key: KEY, slf: Slf::try_from(0x73).unwrap(), rlc: 0 };
+        let store = HashMap::from([(address, 0x10)]);
+        let mut receiver = SecureReceiver::new(store).with_window(4);
+        receiver.add(address, device.clone()).unwrap();
+
+        let telegram = secure(Rorg::SecEncaps, &device, 0x12, &[0xd2, 0x64, 0x01]);
+        assert_eq!(receiver.receive(&erp1(&telegram)).unwrap().rlc, 0x12);
+        assert_eq!(receiver.store.get(&address), Some(&0x12));
+        assert!(matches!(receiver.receive(&erp1(&telegram)), Err(SecurityError::RlcOutOfWindow(0x12))));
+
+        let telegram = secure(Rorg::SecEncaps, &device, 0x40, &[0xd2, 0x64, 0x01]);
+        assert!(matches!(receiver.receive(&erp1(&telegram)), Err(SecurityError::RlcOutOfWindow(0x40))));
+        receiver.resynchronize(address, 0x3f).unwrap();
+        assert!(receiver.receive(&erp1(&telegram)).is_ok());
+        assert_eq!(receiver.device(&address).unwrap().rlc, 0x40);
+    }
+
+    #[test]
+    fn given_key_store_then_learn_and_restore_devices() {
+        let address = "01020304".parse().unwrap();
+        let info = SecureDeviceInfo {
+            address,
+            kind: crate::security::teach_in::DeviceKind::Other { bidirectional: false },
+            slf: Slf::try_from(0x73).unwrap(),
+            rlc: 0x10,
+            key: KEY,
+        };
+        let mut receiver = SecureReceiver::new(MemoryKeyStore::new()).with_window(4);
+        receiver.learn(&info).unwrap();
+        let telegram = secure(Rorg::SecEncaps, &info.device(), 0x12, &[0xd2, 0x64, 0x01]);
+        receiver.receive(&erp1(&telegram)).unwrap();
+
+        let mut restored = SecureReceiver::new(receiver.store.clone());
+        assert_eq!(restored.restore().unwrap(), 1);
+        assert_eq!(restored.device(&address).unwrap().rlc, 0x12);
+        assert!(restored.forget(&address).unwrap().is_some());
+        assert_eq!(restored.store.devices().unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Secure teach-in (RORG 0x35)
+//!
+//! A secure device sends its security material in one or more SEC_TI telegrams. The first
+//! one carries the security layer format and the current rolling code, and the key is split
+//! over all of them:
+//!
+//! | Byte   | First telegram (IDX = 0)       | Following telegrams      |
+//! |--------|--------------------------------|--------------------------|
+//! | 0      | TEACH_IN_INFO                  | TEACH_IN_INFO            |
+//! | 1      | SLF                            | Key, continued           |
+//! | 2..    | RLC (2 to 4 bytes, per SLF)    |                          |
+//! | ..     | Key, first part                |                          |
+//!
+//! TEACH_IN_INFO: .7..6 telegram index, .5..4 telegram count, .3 key encrypted with the
+//! pre-shared key, .2 PTM switch, .1..0 rocker (PTM) or bidirectional flag (other devices).
+//!
+//! Teach-ins encrypted with a pre-shared key are left to the transceiver, which must be
+//! given the key beforehand, see [`super::psk`].
+//!
+//! The reassembled [`SecureDeviceInfo`] is kept with
+//! [`SecureReceiver::learn`](super::rlc::SecureReceiver::learn), which stores it in a
+//! [`KeyStore`](super::keystore::KeyStore).
+
+use std::collections::HashMap;
+
+use super::{SecureDevice, SecurityError, Slf};
+use crate::enocean::Rorg;
+use crate::learn::DeviceLearned;
+use crate::packet::{Address, EEPProfileCode, RadioErp1};
+
+/// Kind of secure device, from its teach-in info
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    /// PTM switch module; the rocker that was used for teach-in
+    Ptm { rocker: u8 },
+    Other { bidirectional: bool },
+}
+
+/// Security material announced by a device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecureDeviceInfo {
+    pub address: Address,
+    pub kind: DeviceKind,
+    pub slf: Slf,
+    pub rlc: u32,
+    pub key: [u8; 16],
+}
+
+impl SecureDeviceInfo {
+    /// Security material to register with a [`super::rlc::SecureReceiver`]
+    pub fn device(&self) -> SecureDevice {
+        SecureDevice { key: self.key, slf: self.slf, rlc: self.rlc }
+    }
+
+    /// Profile of the device, when implied by its kind: PTM switches send D2-03-00 telegrams.
+    /// Other devices announce their profile in a teach-in telegram sent securely afterwards.
+    pub fn eep(&self) -> Option<EEPProfileCode> {
+        match self.kind {
+            DeviceKind::Ptm { .. } => Some(EEPProfileCode::new(0xD2, 0x03, 0x00)),
+            DeviceKind::Other { .. } => None,
+        }
+    }
+
+    /// The device as a learned device, for the [`crate::registry::DeviceRegistry`]
+    pub fn learned(&self) -> DeviceLearned {
+        DeviceLearned { address: self.address, eep: self.eep(), manufacturer: None }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Partial {
+    info: u8,
+    slf: Slf,
+    rlc: u32,
+    key: Vec<u8>,
+    next_index: u8,
+}
+
+/// Reassembles the secure teach-in telegrams of each device
+#[derive(Debug, Clone, Default)]
+pub struct TeachInAssembler {
+    pending: HashMap<Address, Partial>,
+}
+
+impl TeachInAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a SEC_TI telegram. Returns the device information once all its telegrams
+    /// have been received.
+    pub fn push(&mut self, erp: &RadioErp1) -> Result<Option<SecureDeviceInfo>, SecurityError> {
+        if erp.choice != Rorg::SecTi {
+            return Err(SecurityError::NotSecure(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        if d.len() < 2 {
+            return Err(SecurityError::BadLength(d.len()));
+        }
+        let info = d[0];
+        let index = info >> 6;
+
+        if index == 0 {
+            let slf = Slf::try_from(d[1])?;
+            let rlc_size = slf.rlc_algorithm.size();
+            if d.len() < 2 + rlc_size {
+                return Err(SecurityError::BadLength(d.len()));
+            }
+            let rlc = d[2..2 + rlc_size].iter().fold(0, |rlc, byte| rlc << 8 | *byte as u32);
+            let key = d[2 + rlc_size..].to_vec();
+            self.pending.insert(erp.sender_id, Partial { info, slf, rlc, key, next_index: 1 });
+        } else {
+            let partial = self.pending.get_mut(&erp.sender_id)
+                .filter(|partial| partial.next_index == index)
+                .ok_or(SecurityError::TeachInSequence)?;
+            partial.key.extend_from_slice(&d[1..]);
+            partial.next_index += 1;
+        }
+
+        let partial = &self.pending[&erp.sender_id];
+        if partial.key.len() < 16 {
+            return Ok(None);
+        }
+        let partial = self.pending.remove(&erp.sender_id).unwrap();
+        if partial.key.len() != 16 {
+            return Err(SecurityError::BadLength(partial.key.len()));
+        }
+        if partial.info & 0x08 != 0 {
+            return Err(SecurityError::PskRequired);
+        }
+
+        Ok(Some(SecureDeviceInfo {
+            address: erp.sender_id,
+            kind: if partial.info & 0x04 != 0 { DeviceKind::Ptm { rocker: partial.info & 0x03 } }
+                  else { DeviceKind::Other { bidirectional: partial.info & 0x01 != 0 } },
+            slf: partial.slf,
+            rlc: partial.rlc,
+            key: partial.key.try_into().unwrap(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+    use crate::security::tests::KEY;
+    use crate::security::RlcAlgorithm;
+
+    #[test]
+    fn given_two_teach_in_telegrams_then_reassemble_key() {
+        // 2 telegrams, PTM rocker B; SLF 0x8b: 24-bit RLC, CMAC 3 bytes, VAES
+        let mut first = vec![0x35, 0x25, 0x8b, 0x00, 0x01, 0x02];
+        first.extend_from_slice(&KEY[..9]);
+        first.extend_from_slice(&[0x01, 0x02, 0x03, 0x04, 0x00]);
+        let mut second = vec![0x35, 0x40];
+        second.extend_from_slice(&KEY[9..]);
+        second.extend_from_slice(&[0x01, 0x02, 0x03, 0x04, 0x00]);
+
+        let mut assembler = TeachInAssembler::new();
+        assert!(matches!(assembler.push(&erp1(&second)), Err(SecurityError::TeachInSequence)));
+        assert_eq!(assembler.push(&erp1(&first)).unwrap(), None);
+        let info = assembler.push(&erp1(&second)).unwrap().unwrap();
+        assert_eq!(info.kind, DeviceKind::Ptm { rocker: 1 });
+        assert_eq!(info.slf.rlc_algorithm, RlcAlgorithm::Rlc24);
+        assert_eq!(info.rlc, 0x0102);
+        assert_eq!(info.key, KEY);
+        assert_eq!(info.learned().eep, Some(EEPProfileCode::new(0xD2, 0x03, 0x00)));
+    }
+}
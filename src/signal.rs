@@ -0,0 +1,94 @@
+//! SIGNAL telegrams (RORG D0)
+//!
+//! Devices send SIGNAL telegrams to report on themselves rather than on what they measure.
+//! The first data byte is the message ID (MID), followed by its parameters:
+//!
+//! | MID  | Message                                  | Parameters               |
+//! |------|------------------------------------------|--------------------------|
+//! | 0x01 | Smart Ack: mailbox empty                 |                          |
+//! | 0x02 | Smart Ack: mailbox does not exist        |                          |
+//! | 0x03 | Smart Ack: reset                         |                          |
+//! | 0x04 | Triggered state of the device            | Trigger type             |
+//! | 0x05 | Last unicast message acknowledged        |                          |
+//! | 0x06 | Energy status                            | 0..100 %                 |
+//! | 0x08 | Heartbeat                                |                          |
+//! | 0x09 | RX window open                           |                          |
+//! | 0x10 | Backup battery status                    | 0..100 %                 |
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::signal::Signal;
+//!
+//! let data = [0xd0, 0x06, 0x4b, 0x01, 0x80, 0x99, 0x8e, 0x00];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//! assert_eq!(Signal::decode(&erp)?, Signal::EnergyStatus(75));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::eep::DecodeError;
+use crate::enocean::Rorg;
+use crate::packet::RadioErp1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Signal {
+    MailboxEmpty,
+    MailboxMissing,
+    SmartAckReset,
+    /// Raw trigger type, see the SIGNAL specification
+    Trigger(u8),
+    Acknowledged,
+    /// Energy available to the device, in %
+    EnergyStatus(u8),
+    Heartbeat,
+    /// The device listens for telegrams for a short time
+    RxWindowOpen,
+    /// Backup battery level, in %
+    BackupBattery(u8),
+    /// Other message IDs
+    Other { mid: u8 },
+}
+
+impl Signal {
+    /// Decode a SIGNAL telegram
+    pub fn decode(erp: &RadioErp1) -> Result<Self, DecodeError> {
+        if erp.choice != Rorg::Signal {
+            return Err(DecodeError::WrongRorg(erp.choice));
+        }
+        let d = &erp.user_data[..];
+        let param = || d.get(1).copied().ok_or(DecodeError::BadLength(d.len()));
+        let percent = || param().and_then(|value| match value {
+            0..=100 => Ok(value),
+            _ => Err(DecodeError::InvalidValue(value)),
+        });
+
+        match *d.first().ok_or(DecodeError::BadLength(0))? {
+            0x01 => Ok(Self::MailboxEmpty),
+            0x02 => Ok(Self::MailboxMissing),
+            0x03 => Ok(Self::SmartAckReset),
+            0x04 => Ok(Self::Trigger(param()?)),
+            0x05 => Ok(Self::Acknowledged),
+            0x06 => Ok(Self::EnergyStatus(percent()?)),
+            0x08 => Ok(Self::Heartbeat),
+            0x09 => Ok(Self::RxWindowOpen),
+            0x10 => Ok(Self::BackupBattery(percent()?)),
+            mid => Ok(Self::Other { mid }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_signal_telegrams_then_decode_them() {
+        assert_eq!(Signal::decode(&erp1(&[0xd0, 0x08, 1, 2, 3, 4, 0])).unwrap(), Signal::Heartbeat);
+        assert_eq!(Signal::decode(&erp1(&[0xd0, 0x10, 20, 1, 2, 3, 4, 0])).unwrap(), Signal::BackupBattery(20));
+        assert_eq!(Signal::decode(&erp1(&[0xd0, 0x0a, 5, 1, 2, 3, 4, 0])).unwrap(), Signal::Other { mid: 0x0a });
+        assert!(matches!(Signal::decode(&erp1(&[0xd0, 0x06, 1, 2, 3, 4, 0])), Err(DecodeError::BadLength(1))));
+        assert!(matches!(Signal::decode(&erp1(&[0xd0, 0x06, 150, 1, 2, 3, 4, 0])), Err(DecodeError::InvalidValue(150))));
+    }
+}
@@ -0,0 +1,602 @@
+//! Simulated transceiver and devices, for end-to-end tests
+//!
+//! A [`Transceiver`] speaks ESP3 over an in-memory byte stream, so that a
+//! [`Port`](crate::port::Port), and everything built on it, runs without hardware. It answers
+//! the version, base ID and frequency commands, and hands the telegrams sent by the host to
+//! virtual devices, whose telegrams it reports as received:
+//!
+//! - [`RockerSwitch`]: F6-02-01 rocker, sending presses and releases on demand
+//! - [`TemperatureSensor`]: A5-02-xx sensor, sending its temperature periodically
+//! - [`Actuator`]: D2-01 switch, answering the commands of the controllers it was taught to
+//!
+//! Time is simulated: each read finding nothing to return counts as a read timeout of the
+//! serial port, [`TICK`], and lets the devices send what is due. Devices are shared with
+//! the transceiver, to act on them and look at their state during the test:
+//!
+//! ```
+//! use enocean::device::Gateway;
+//! use enocean::eep::Decoded;
+//! use enocean::port::Port;
+//! use enocean::registry::DeviceEntry;
+//! use enocean::sim::{TemperatureSensor, Transceiver};
+//! use std::time::Duration;
+//!
+//! let transceiver = Transceiver::new("ff9b1280".parse()?);
+//! let sensor = transceiver.add(TemperatureSensor::new("0180998e".parse()?, 0x05, Duration::from_secs(60)));
+//! sensor.lock().unwrap().set(21.5);
+//!
+//! let mut gateway = Gateway::new(Port::from_serial(transceiver.clone()))?;
+//! gateway.registry_mut().insert("0180998e".parse()?, DeviceEntry::new("A5-02-05".parse()?));
+//! let reading = gateway.device("0180998e".parse()?).unwrap().events().next().unwrap()?;
+//! assert!(matches!(reading, Decoded::Temperature(t) if (t.0 - 21.5).abs() < 0.2));
+//! assert_eq!(transceiver.elapsed(), Duration::from_secs(60));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::eep::a5_02;
+use crate::eep::d2_01::ALL_CHANNELS;
+use crate::eep::f6_02::{Button, RockerEvent};
+use crate::eep::quantity::Temperature;
+use crate::eep::teach_in::{TeachIn4BS, UteRequest, UteRequestType, UteResult};
+use crate::enocean::Rorg;
+use crate::frame::{ESP3Frame, FrameDecoder};
+use crate::packet::{Address, EEPProfileCode, IdBase, RadioErp1, Response, ResponseCode, ResponseData, SubtelNum, BROADCAST};
+
+/// Simulated time passing on each read finding nothing to return, as the read timeout of
+/// [`Port::open`](crate::port::Port::open)
+pub const TICK: Duration = Duration::from_millis(100);
+
+/// Manufacturer ID announced by the simulated devices: multi user manufacturer ID
+const MANUFACTURER: u16 = 0x7ff;
+
+/// A virtual device, on the air with a [`Transceiver`]
+pub trait SimDevice: Send {
+    fn address(&self) -> Address;
+
+    /// A telegram heard over the air, broadcast or addressed to the device. Returns the
+    /// telegrams sent in reply.
+    fn receive(&mut self, _erp: &RadioErp1) -> Vec<RadioErp1<'static>> {
+        vec![]
+    }
+
+    /// The telegrams to send at `now`, the time elapsed since the start of the simulation
+    fn poll(&mut self, _now: Duration) -> Vec<RadioErp1<'static>> {
+        vec![]
+    }
+}
+
+#[derive(Default)]
+struct State {
+    decoder: FrameDecoder,
+    /// Bytes for the host
+    rx: VecDeque<u8>,
+    now: Duration,
+    devices: Vec<Arc<Mutex<dyn SimDevice>>>,
+    sent: Vec<RadioErp1<'static>>,
+    settings: Settings,
+}
+
+/// Settings of the transceiver, as written by common commands
+#[derive(Default)]
+struct Settings {
+    repeater: [u8; 2],
+    /// Type and value of each filter
+    filters: Vec<[u8; 5]>,
+    learn_mode: u8,
+    transparent_mode: u8,
+    /// -dBm
+    noise_threshold: u8,
+}
+
+/// A simulated ESP3 transceiver. Clones share the same radio and devices.
+#[derive(Clone)]
+pub struct Transceiver {
+    state: Arc<Mutex<State>>,
+    base_id: IdBase,
+    /// Signal strength the telegrams of the devices are received with, in -dBm
+    rssi: u8,
+}
+
+impl Transceiver {
+    /// A transceiver with the base ID `base_id`
+    pub fn new(base_id: Address) -> Self {
+        Self { state: Arc::default(), base_id: IdBase(base_id), rssi: 0x40 }
+    }
+
+    /// Signal strength the telegrams of the devices are received with, in dBm
+    pub fn with_rssi(mut self, dbm: i16) -> Self {
+        self.rssi = dbm.unsigned_abs().min(0xfe) as u8;
+        self
+    }
+
+    /// Put a device on the air, returning it shared with the transceiver
+    pub fn add<D: SimDevice + 'static>(&self, device: D) -> Arc<Mutex<D>> {
+        let device = Arc::new(Mutex::new(device));
+        self.lock().devices.push(device.clone());
+        device
+    }
+
+    /// Time elapsed since the start of the simulation
+    pub fn elapsed(&self) -> Duration {
+        self.lock().now
+    }
+
+    /// Let time pass, and report the telegrams the devices send meanwhile
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.lock();
+        let end = state.now + duration;
+        while state.now < end {
+            state.now = (state.now + TICK).min(end);
+            self.poll(&mut state);
+        }
+    }
+
+    /// Report a frame to the host as received, e.g. a malformed one
+    pub fn inject(&self, frame: &ESP3Frame) {
+        self.lock().rx.extend(Borrow::<[u8]>::borrow(frame));
+    }
+
+    /// Restart, e.g. on a watchdog reset: the settings written by the host are lost, and the
+    /// restart is reported with a CO_READY event
+    pub fn reset(&self) {
+        let mut state = self.lock();
+        state.settings = Settings::default();
+        state.rx.extend(Borrow::<[u8]>::borrow(&ESP3Frame::assemble(0x04, &[0x04, 0x02], &[])));
+    }
+
+    /// The telegrams sent by the host so far
+    pub fn sent(&self) -> Vec<RadioErp1<'static>> {
+        self.lock().sent.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Util : report the telegrams due from the devices
+    fn poll(&self, state: &mut State) {
+        let now = state.now;
+        let telegrams: Vec<_> = state.devices.iter().flat_map(|device| device.lock().unwrap().poll(now)).collect();
+        for erp in telegrams {
+            self.report(state, erp);
+        }
+    }
+
+    /// Util : a telegram of a device, received by the transceiver
+    fn report(&self, state: &mut State, mut erp: RadioErp1<'static>) {
+        erp.subtel_num = Some(SubtelNum::Receive);
+        erp.destination.get_or_insert(BROADCAST);
+        erp.rssi = Some(self.rssi);
+        state.rx.extend(Borrow::<[u8]>::borrow(&erp.encode()));
+    }
+
+    /// Util : answer a frame from the host
+    fn handle(&self, state: &mut State, frame: ESP3Frame) {
+        let respond = |state: &mut State, code, data: &[u8]| {
+            let response = Response { code, data: ResponseData::from_slice(data) };
+            state.rx.extend(Borrow::<[u8]>::borrow(&response.encode()));
+        };
+        match (frame.packet_type(), frame.data().first()) {
+            (0x01, _) => {
+                let Ok(erp) = RadioErp1::decode(frame.as_ref()).map(RadioErp1::into_owned) else {
+                    return respond(state, ResponseCode::WrongParam, &[]);
+                };
+                respond(state, ResponseCode::Ok, &[]);
+                let destination = erp.destination.unwrap_or(BROADCAST);
+                let replies: Vec<_> = state.devices.iter()
+                    .filter_map(|device| {
+                        let mut device = device.lock().unwrap();
+                        (destination == BROADCAST || destination == device.address()).then(|| device.receive(&erp))
+                    })
+                    .flatten()
+                    .collect();
+                state.sent.push(erp);
+                for reply in replies {
+                    self.report(state, reply);
+                }
+            }
+            // CO_RD_VERSION
+            (0x05, Some(0x03)) => {
+                let mut data = vec![1, 0, 0, 0, 2, 11, 0, 0];
+                data.extend_from_slice(&self.base_id.0 .0);
+                data.extend_from_slice(&[0x45, 0x4f, 0x03, 0x00]);
+                data.extend_from_slice(b"SIMULATOR\0\0\0\0\0\0\0");
+                respond(state, ResponseCode::Ok, &data);
+            }
+            // CO_RD_IDBASE, with the remaining write cycles
+            (0x05, Some(0x08)) => respond(state, ResponseCode::Ok, &[&self.base_id.0 .0[..], &[0x0a]].concat()),
+            // CO_GET_FREQUENCY_INFO: 868.3 MHz, ERP1
+            (0x05, Some(0x25)) => respond(state, ResponseCode::Ok, &[0x01, 0x00]),
+            // CO_WR_IDBASE: the base ID of the simulator is fixed
+            (0x05, Some(0x07)) => respond(state, ResponseCode::BaseIdMaxReached, &[]),
+            // CO_WR_REPEATER, CO_RD_REPEATER
+            (0x05, Some(0x09)) if frame.data().len() == 3 => {
+                state.settings.repeater = frame.data()[1..].try_into().unwrap();
+                respond(state, ResponseCode::Ok, &[]);
+            }
+            (0x05, Some(0x0a)) => {
+                let repeater = state.settings.repeater;
+                respond(state, ResponseCode::Ok, &repeater);
+            }
+            // CO_WR_FILTER_ADD, CO_WR_FILTER_DEL_ALL, CO_WR_FILTER_ENABLE, CO_RD_FILTER
+            (0x05, Some(0x0b)) if frame.data().len() == 7 => {
+                state.settings.filters.push(frame.data()[1..6].try_into().unwrap());
+                respond(state, ResponseCode::Ok, &[]);
+            }
+            (0x05, Some(0x0d)) => {
+                state.settings.filters.clear();
+                respond(state, ResponseCode::Ok, &[]);
+            }
+            (0x05, Some(0x0e)) => respond(state, ResponseCode::Ok, &[]),
+            (0x05, Some(0x0f)) => {
+                let filters = state.settings.filters.concat();
+                respond(state, ResponseCode::Ok, &filters);
+            }
+            // CO_WR_LEARNMODE, CO_RD_LEARNMODE
+            (0x05, Some(0x17)) if frame.data().len() >= 2 => {
+                state.settings.learn_mode = frame.data()[1];
+                respond(state, ResponseCode::Ok, &[]);
+            }
+            (0x05, Some(0x18)) => {
+                let learn_mode = state.settings.learn_mode;
+                respond(state, ResponseCode::Ok, &[learn_mode]);
+            }
+            // CO_SET_NOISETHRESHOLD, CO_GET_NOISETHRESHOLD
+            (0x05, Some(0x32)) if frame.data().len() == 2 => {
+                state.settings.noise_threshold = frame.data()[1];
+                respond(state, ResponseCode::Ok, &[]);
+            }
+            (0x05, Some(0x33)) => {
+                let noise_threshold = state.settings.noise_threshold;
+                respond(state, ResponseCode::Ok, &[noise_threshold]);
+            }
+            // CO_WR_TRANSPARENT_MODE, CO_RD_TRANSPARENT_MODE
+            (0x05, Some(0x3e)) if frame.data().len() == 2 => {
+                state.settings.transparent_mode = frame.data()[1];
+                respond(state, ResponseCode::Ok, &[]);
+            }
+            (0x05, Some(0x3f)) => {
+                let transparent_mode = state.settings.transparent_mode;
+                respond(state, ResponseCode::Ok, &[transparent_mode]);
+            }
+            _ => respond(state, ResponseCode::NotSupported, &[]),
+        }
+    }
+}
+
+impl io::Read for Transceiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.lock();
+        if state.rx.is_empty() {
+            state.now += TICK;
+            self.poll(&mut state);
+        }
+        if state.rx.is_empty() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let n = buf.len().min(state.rx.len());
+        for (b, r) in buf.iter_mut().zip(state.rx.drain(..n)) {
+            *b = r;
+        }
+        Ok(n)
+    }
+}
+
+impl io::Write for Transceiver {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.lock();
+        for &byte in buf {
+            if let Some(Ok(frame)) = state.decoder.push(byte) {
+                self.handle(&mut state, frame);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An F6-02-01 rocker switch. Its telegrams are sent on the next read.
+#[derive(Debug, Clone)]
+pub struct RockerSwitch {
+    address: Address,
+    pending: Vec<RockerEvent>,
+}
+
+impl RockerSwitch {
+    pub fn new(address: Address) -> Self {
+        Self { address, pending: vec![] }
+    }
+
+    /// Press one or two buttons
+    pub fn press(&mut self, first: Button, second: Option<Button>) {
+        self.pending.push(RockerEvent::Pressed { first, second });
+    }
+
+    pub fn release(&mut self) {
+        self.pending.push(RockerEvent::Released);
+    }
+
+    /// Press and release a button, e.g. to teach the switch in
+    pub fn click(&mut self, button: Button) {
+        self.press(button, None);
+        self.release();
+    }
+}
+
+impl SimDevice for RockerSwitch {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn poll(&mut self, _now: Duration) -> Vec<RadioErp1<'static>> {
+        self.pending.drain(..)
+            .map(|event| {
+                let (data, status) = event.encode();
+                telegram(Rorg::Rps, vec![data], self.address, status)
+            })
+            .collect()
+    }
+}
+
+/// An A5-02-xx temperature sensor, sending its temperature every `period`
+#[derive(Debug, Clone)]
+pub struct TemperatureSensor {
+    address: Address,
+    eep_type: u8,
+    period: Duration,
+    next: Duration,
+    temperature: f32,
+    teach_in: bool,
+}
+
+impl TemperatureSensor {
+    /// A sensor of profile A5-02-`eep_type`, sending its first reading after `period`
+    pub fn new(address: Address, eep_type: u8, period: Duration) -> Self {
+        Self { address, eep_type, period, next: period, temperature: 20.0, teach_in: false }
+    }
+
+    /// Set the temperature sent by the next readings, in °C
+    pub fn set(&mut self, temperature: f32) {
+        self.temperature = temperature;
+    }
+
+    /// Send a teach-in telegram with the profile (4BS variation 2) on the next read
+    pub fn teach_in(&mut self) {
+        self.teach_in = true;
+    }
+
+    pub fn eep(&self) -> EEPProfileCode {
+        EEPProfileCode::new(0xA5, 0x02, self.eep_type)
+    }
+}
+
+impl SimDevice for TemperatureSensor {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn poll(&mut self, now: Duration) -> Vec<RadioErp1<'static>> {
+        let mut telegrams = vec![];
+        if std::mem::take(&mut self.teach_in) {
+            let query = TeachIn4BS { eep: Some(self.eep()), manufacturer: Some(MANUFACTURER), response: None };
+            telegrams.push(telegram(Rorg::Bs4, query.encode().to_vec(), self.address, 0));
+        }
+        if now >= self.next {
+            // Readings missed while the time jumped are not sent
+            while self.next <= now {
+                self.next += self.period;
+            }
+            if let Ok(data) = a5_02::encode(Temperature(self.temperature), self.eep_type) {
+                telegrams.push(telegram(Rorg::Bs4, data.to_vec(), self.address, 0));
+            }
+        }
+        telegrams
+    }
+}
+
+/// A D2-01 actuator. It obeys the controllers it was taught to, and answers each command
+/// with a status response for the channel.
+#[derive(Debug, Clone)]
+pub struct Actuator {
+    address: Address,
+    eep_type: u8,
+    outputs: Vec<u8>,
+    controllers: Vec<Address>,
+    teach_in: bool,
+}
+
+impl Actuator {
+    /// An actuator of profile D2-01-`eep_type`, with `channels` outputs, all off
+    pub fn new(address: Address, eep_type: u8, channels: u8) -> Self {
+        Self { address, eep_type, outputs: vec![0; channels as usize], controllers: vec![], teach_in: false }
+    }
+
+    /// An actuator already taught to `controller`
+    pub fn with_controller(mut self, controller: Address) -> Self {
+        self.controllers.push(controller);
+        self
+    }
+
+    /// Send a UTE teach-in request on the next read. The controller is learned from the
+    /// response accepting it.
+    pub fn teach_in(&mut self) {
+        self.teach_in = true;
+    }
+
+    /// Output value of a channel, 0 = off, 1..100 %
+    pub fn output(&self, channel: u8) -> Option<u8> {
+        self.outputs.get(channel as usize).copied()
+    }
+
+    pub fn controllers(&self) -> &[Address] {
+        &self.controllers
+    }
+
+    pub fn eep(&self) -> EEPProfileCode {
+        EEPProfileCode::new(0xD2, 0x01, self.eep_type)
+    }
+
+    /// Util : status response for a channel
+    fn status(&self, channel: u8, controller: Address) -> RadioErp1<'static> {
+        let value = self.output(channel).unwrap_or(0x7f);
+        let mut erp = telegram(Rorg::Vld, vec![0x04, channel & 0x1f, value], self.address, 0);
+        erp.destination = Some(controller);
+        erp
+    }
+
+    /// Util : measurement response for a channel, of power in W or energy in Wh
+    fn measurement(&self, channel: u8, power: bool, controller: Address) -> RadioErp1<'static> {
+        let unit = if power { 0x60 } else { 0x20 };
+        let mut erp = telegram(Rorg::Vld, vec![0x07, unit | channel, 0, 0, 0, 0], self.address, 0);
+        erp.destination = Some(controller);
+        erp
+    }
+}
+
+impl SimDevice for Actuator {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn receive(&mut self, erp: &RadioErp1) -> Vec<RadioErp1<'static>> {
+        let d = &erp.user_data[..];
+        match erp.choice {
+            // Teach-in response accepting the controller
+            Rorg::Ute if d.len() == 7 && d[0] & 0x0f == 0x01 => {
+                if (d[0] >> 4) & 0x03 == UteResult::Accepted as u8 && !self.controllers.contains(&erp.sender_id) {
+                    self.controllers.push(erp.sender_id);
+                }
+                vec![]
+            }
+            Rorg::Vld if !d.is_empty() && self.controllers.contains(&erp.sender_id) => match (d[0] & 0x0f, d.get(1), d.get(2)) {
+                // CMD 0x1 : set output
+                (0x01, Some(channel), Some(value)) => {
+                    let channel = channel & 0x1f;
+                    for (index, output) in self.outputs.iter_mut().enumerate() {
+                        if channel == ALL_CHANNELS || channel as usize == index {
+                            *output = (*value).min(100);
+                        }
+                    }
+                    let replied = if channel == ALL_CHANNELS { 0 } else { channel };
+                    vec![self.status(replied, erp.sender_id)]
+                }
+                // CMD 0x3 : status query
+                (0x03, Some(channel), _) => vec![self.status(channel & 0x1f, erp.sender_id)],
+                // CMD 0x6 : measurement query, answered with no load
+                (0x06, Some(query), _) => vec![self.measurement(query & 0x1f, query & 0x20 != 0, erp.sender_id)],
+                _ => vec![],
+            },
+            _ => vec![],
+        }
+    }
+
+    fn poll(&mut self, _now: Duration) -> Vec<RadioErp1<'static>> {
+        if !std::mem::take(&mut self.teach_in) {
+            return vec![];
+        }
+        let request = UteRequest {
+            eep: self.eep(),
+            manufacturer: MANUFACTURER,
+            bidirectional: true,
+            response_expected: true,
+            request_type: UteRequestType::TeachIn,
+            channels: 0xff,
+        };
+        vec![telegram(Rorg::Ute, request.encode().to_vec(), self.address, 0)]
+    }
+}
+
+/// Util : a telegram sent by a device
+fn telegram(rorg: Rorg, user_data: Vec<u8>, sender: Address, status: u8) -> RadioErp1<'static> {
+    RadioErp1::builder()
+        .choice(rorg)
+        .user_data(user_data)
+        .sender(sender)
+        .status(status)
+        .build()
+        .expect("valid telegram")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actuator::CommandOutcome;
+    use crate::device::Gateway;
+    use crate::eep::Decoded;
+    use crate::learn::LearnManager;
+    use crate::port::Port;
+
+    #[test]
+    fn given_simulated_devices_then_teach_in_decode_and_command() {
+        let base: Address = "ff9b1280".parse().unwrap();
+        let transceiver = Transceiver::new(base);
+        let switch = transceiver.add(RockerSwitch::new("fef58ff5".parse().unwrap()));
+        let actuator = transceiver.add(Actuator::new("05123456".parse().unwrap(), 0x12, 2));
+
+        let mut port = Port::from_serial(transceiver.clone());
+        let mut learn = LearnManager::new(base);
+        learn.start(Duration::from_secs(5));
+        switch.lock().unwrap().click(Button::A0);
+        actuator.lock().unwrap().teach_in();
+        let mut learned = vec![];
+        while let Some(device) = learn.run(&mut port).unwrap() {
+            learned.push((device.address, device.eep));
+            if learned.len() == 2 {
+                break;
+            }
+        }
+        assert_eq!(learned, vec![
+            ("fef58ff5".parse().unwrap(), Some(EEPProfileCode::new(0xF6, 0x02, 0x01))),
+            ("05123456".parse().unwrap(), Some(EEPProfileCode::new(0xD2, 0x01, 0x12))),
+        ]);
+        // The UTE response reached the actuator
+        assert_eq!(actuator.lock().unwrap().controllers(), &[base]);
+
+        // The learn manager does not wait for the responses to its teach-in responses
+        while port.read_frame().is_ok() {}
+        let mut gateway = Gateway::new(port).unwrap();
+        assert_eq!(gateway.info().unwrap().version.description, "SIMULATOR");
+        for (address, eep) in learned {
+            gateway.registry_mut().learn(&crate::learn::DeviceLearned { address, eep, manufacturer: None });
+        }
+        switch.lock().unwrap().press(Button::B1, None);
+        let event = gateway.device("fef58ff5".parse().unwrap()).unwrap().events().next().unwrap().unwrap();
+        assert_eq!(event, Decoded::Rocker(RockerEvent::Pressed { first: Button::B1, second: None }));
+
+        let mut device = gateway.device("05123456".parse().unwrap()).unwrap();
+        assert!(matches!(device.set_output(1, 60).unwrap(), CommandOutcome::Applied(status) if status.channel == 1));
+        assert_eq!(actuator.lock().unwrap().output(1), Some(60));
+        assert_eq!(actuator.lock().unwrap().output(0), Some(0));
+        assert_eq!(transceiver.sent().last().unwrap().destination, Some("05123456".parse().unwrap()));
+    }
+
+    #[test]
+    fn given_periodic_sensor_then_send_once_per_period() {
+        let transceiver = Transceiver::new("ff9b1280".parse().unwrap()).with_rssi(-72);
+        let sensor = transceiver.add(TemperatureSensor::new("0180998e".parse().unwrap(), 0x05, Duration::from_secs(10)));
+        sensor.lock().unwrap().teach_in();
+        let mut port = Port::from_serial(transceiver.clone());
+        let mut read = || match port.read_frame() {
+            Ok(frame) => Some(RadioErp1::decode(frame.as_ref()).unwrap().into_owned()),
+            Err(crate::FrameReadError::IOError(e)) if e.kind() == io::ErrorKind::TimedOut => None,
+            Err(e) => panic!("{:?}", e),
+        };
+
+        let teach_in = read().unwrap();
+        assert_eq!(TeachIn4BS::decode(&teach_in).unwrap().eep, Some(EEPProfileCode::new(0xA5, 0x02, 0x05)));
+        assert_eq!(teach_in.rssi_dbm(), Some(-72));
+        transceiver.advance(Duration::from_secs(35));
+        let readings: Vec<_> = std::iter::from_fn(&mut read).collect();
+        assert_eq!(readings.len(), 3);
+        assert!(matches!(a5_02::decode(&readings[0], 0x05), Ok(t) if (t.0 - 20.0).abs() < 0.2));
+    }
+}
@@ -0,0 +1,385 @@
+//! Smart Ack
+//!
+//! Smart Ack lets energy harvesting sensors receive data: a postmaster keeps a mailbox for
+//! each of its clients, and the clients collect their mail by sending reclaim telegrams.
+//! The transceiver handles the radio side; the host configures it with [`SmartAckCommand`]s
+//! (packet type 0x06) and decides on learn requests reported through [`Event`]s.
+//!
+//! A [`Postmaster`] makes these decisions: on `SA_CONFIRM_LEARN`, it allocates a mailbox
+//! for a new client (or removes the mailbox of a known one), and builds the response the
+//! transceiver expects.
+//!
+//! `SA_CONFIRM_LEARN` payload:
+//!
+//! | Byte   | Field                                                               |
+//! |--------|---------------------------------------------------------------------|
+//! | 0      | Priority of the postmaster candidate                                |
+//! | 1..3   | Manufacturer ID (11 bits)                                           |
+//! | 3..6   | EEP (RORG, FUNC, TYPE)                                              |
+//! | 6      | RSSI, in -dBm                                                       |
+//! | 7..11  | Postmaster candidate ID                                             |
+//! | 11..15 | Smart Ack client ID                                                 |
+//! | 15     | Hop count                                                           |
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::enocean::ReturnCode;
+use crate::frame::ESP3Frame;
+use crate::packet::{Address, EEPProfileCode, Event, ParseError, RadioErp1, Response, ResponseData};
+
+/// Outcome of a learn request, as sent back to the transceiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfirmCode {
+    LearnIn = 0x00,
+    EepNotAccepted = 0x11,
+    PostmasterFull = 0x12,
+    ControllerFull = 0x13,
+    RssiTooLow = 0x14,
+    LearnOut = 0x20,
+}
+
+/// Learn mode variant of the transceiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LearnModeExtent {
+    Simple = 0,
+    Advanced = 1,
+    AdvancedSelectRepeater = 2,
+}
+
+/// Smart Ack commands (packet type 0x06)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SmartAckCommand {
+    /// SA_WR_LEARNMODE
+    WriteLearnMode { enable: bool, extent: LearnModeExtent, timeout: Duration },
+    /// SA_RD_LEARNMODE
+    ReadLearnMode,
+    /// SA_WR_LEARNCONFIRM
+    WriteLearnConfirm { response_time: u16, confirm: ConfirmCode, postmaster: Address, client: Address },
+    /// SA_WR_CLIENTLEARNRQ
+    WriteClientLearnRequest { manufacturer: u16, eep: EEPProfileCode },
+    /// SA_WR_RESET
+    WriteReset { client: Address },
+    /// SA_RD_LEARNEDCLIENTS, see [`LearnedClient::decode_list`] for the response
+    ReadLearnedClients,
+    /// SA_WR_RECLAIMS
+    WriteReclaims { count: u8 },
+    /// SA_WR_POSTMASTER
+    WritePostmaster { mailboxes: u8 },
+}
+
+impl SmartAckCommand {
+    pub fn encode(&self) -> ESP3Frame {
+        let mut data = Vec::with_capacity(12);
+        match *self {
+            Self::WriteLearnMode { enable, extent, timeout } => {
+                data.extend_from_slice(&[0x01, enable as u8, extent.into()]);
+                data.extend_from_slice(&(timeout.as_millis().min(u32::MAX as u128) as u32).to_be_bytes());
+            }
+            Self::ReadLearnMode => data.push(0x02),
+            Self::WriteLearnConfirm { response_time, confirm, postmaster, client } => {
+                data.push(0x03);
+                data.extend_from_slice(&response_time.to_be_bytes());
+                data.push(confirm.into());
+                data.extend_from_slice(&postmaster.0);
+                data.extend_from_slice(&client.0);
+            }
+            Self::WriteClientLearnRequest { manufacturer, eep } => {
+                // The 5 upper bits of the manufacturer field are set
+                data.push(0x04);
+                data.extend_from_slice(&(0xf800 | manufacturer & 0x07ff).to_be_bytes());
+                data.extend_from_slice(&[eep.rorg(), eep.func(), eep.eep_type()]);
+            }
+            Self::WriteReset { client } => {
+                data.push(0x05);
+                data.extend_from_slice(&client.0);
+            }
+            Self::ReadLearnedClients => data.push(0x06),
+            Self::WriteReclaims { count } => data.extend_from_slice(&[0x07, count]),
+            Self::WritePostmaster { mailboxes } => data.extend_from_slice(&[0x08, mailboxes]),
+        }
+        ESP3Frame::assemble(0x06, &data, &[])
+    }
+}
+
+/// A learn request forwarded by the transceiver (`SA_CONFIRM_LEARN`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LearnRequest {
+    /// .3 already postmaster, .2 place for mailbox, .1 good RSSI, .0 local
+    pub priority: u8,
+    pub manufacturer: u16,
+    pub eep: EEPProfileCode,
+    /// Signal strength of the request, in -dBm
+    pub rssi: u8,
+    pub postmaster_candidate: Address,
+    pub client: Address,
+    pub hop_count: u8,
+}
+
+impl LearnRequest {
+    pub fn decode(data: &[u8; 16]) -> Self {
+        Self {
+            priority: data[0],
+            manufacturer: u16::from_be_bytes([data[1], data[2]]) & 0x07ff,
+            eep: EEPProfileCode::new(data[3], data[4], data[5]),
+            rssi: data[6],
+            postmaster_candidate: Address(data[7..11].try_into().unwrap()),
+            client: Address(data[11..15].try_into().unwrap()),
+            hop_count: data[15],
+        }
+    }
+}
+
+/// Answer of a postmaster to a client learn request (`SA_LEARN_ACK`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LearnAck {
+    /// Time the client should wait before its first reclaim, in ms
+    pub response_time: u16,
+    pub confirm: ConfirmCode,
+}
+
+impl LearnAck {
+    pub fn decode(data: &[u8; 3]) -> Result<Self, ParseError> {
+        Ok(Self {
+            response_time: u16::from_be_bytes([data[0], data[1]]),
+            confirm: ConfirmCode::try_from_primitive(data[2])
+                .map_err(|_| ParseError::InvalidPrimitive { packet_type: 0x04, offset: 3, value: data[2] })?,
+        })
+    }
+}
+
+/// A client known to the transceiver, as listed by `SA_RD_LEARNEDCLIENTS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LearnedClient {
+    pub client: Address,
+    pub controller: Address,
+    pub mailbox_index: u8,
+}
+
+impl LearnedClient {
+    /// Decode the response to [`SmartAckCommand::ReadLearnedClients`]
+    pub fn decode_list(response: &Response) -> Result<Vec<Self>, ParseError> {
+        if !response.data.len().is_multiple_of(9) {
+            return Err(ParseError::PacketTooShort { packet_type: 0x02, length: response.data.len() + 1 });
+        }
+        Ok(response.data.chunks_exact(9).map(|entry| Self {
+            client: Address(entry[0..4].try_into().unwrap()),
+            controller: Address(entry[4..8].try_into().unwrap()),
+            mailbox_index: entry[8],
+        }).collect())
+    }
+}
+
+/// A mailbox held for a Smart Ack client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mailbox {
+    pub client: Address,
+    pub postmaster: Address,
+    pub eep: EEPProfileCode,
+    pub manufacturer: u16,
+    /// Telegrams received from the client since it was learned
+    pub telegrams: u32,
+}
+
+/// Counters of the postmaster activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Statistics {
+    pub learned_in: u32,
+    pub learned_out: u32,
+    pub rejected: u32,
+    /// Reclaims the transceiver could not serve (`SA_RECLAIM_NOT_SUCCESSFUL`)
+    pub reclaims_unsuccessful: u32,
+}
+
+/// What the postmaster decided about a learn request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LearnDecision {
+    LearnedIn(Mailbox),
+    LearnedOut(Mailbox),
+    Rejected(LearnRequest, ConfirmCode),
+}
+
+/// Result of handling an event
+#[derive(Debug, Clone, Default)]
+pub struct PostmasterOutcome {
+    /// Response to send back to the transceiver
+    pub response: Option<Response>,
+    pub decision: Option<LearnDecision>,
+}
+
+/// Smart Ack postmaster state machine
+#[derive(Debug, Clone)]
+pub struct Postmaster {
+    capacity: u8,
+    response_time: u16,
+    accepted: Option<BTreeSet<EEPProfileCode>>,
+    max_rssi: Option<u8>,
+    mailboxes: BTreeMap<Address, Mailbox>,
+    statistics: Statistics,
+}
+
+impl Postmaster {
+    /// A postmaster with room for `capacity` mailboxes, accepting all profiles
+    pub fn new(capacity: u8) -> Self {
+        Self {
+            capacity,
+            response_time: 150,
+            accepted: None,
+            max_rssi: None,
+            mailboxes: BTreeMap::new(),
+            statistics: Statistics::default(),
+        }
+    }
+
+    /// Only accept clients with one of these profiles
+    pub fn accept_only(mut self, eeps: impl IntoIterator<Item = EEPProfileCode>) -> Self {
+        self.accepted = Some(eeps.into_iter().collect());
+        self
+    }
+
+    /// Reject clients whose request was received weaker than `rssi` (in -dBm)
+    pub fn max_rssi(mut self, rssi: u8) -> Self {
+        self.max_rssi = Some(rssi);
+        self
+    }
+
+    /// Time given to clients before their first reclaim, in ms
+    pub fn response_time(mut self, response_time: u16) -> Self {
+        self.response_time = response_time;
+        self
+    }
+
+    /// Command that enables the postmaster function of the transceiver
+    pub fn configure(&self) -> SmartAckCommand {
+        SmartAckCommand::WritePostmaster { mailboxes: self.capacity }
+    }
+
+    pub fn mailboxes(&self) -> impl Iterator<Item = &Mailbox> {
+        self.mailboxes.values()
+    }
+
+    pub fn mailbox(&self, client: &Address) -> Option<&Mailbox> {
+        self.mailboxes.get(client)
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        self.statistics
+    }
+
+    /// Drop the mailbox of a client, returning the command that removes it from the transceiver
+    pub fn forget(&mut self, client: &Address) -> Option<SmartAckCommand> {
+        self.mailboxes.remove(client).map(|mailbox| SmartAckCommand::WriteReset { client: mailbox.client })
+    }
+
+    /// Count a telegram received from a client. Returns false if the sender has no mailbox.
+    pub fn record_telegram(&mut self, erp: &RadioErp1) -> bool {
+        match self.mailboxes.get_mut(&erp.sender_id) {
+            Some(mailbox) => { mailbox.telegrams += 1; true }
+            None => false,
+        }
+    }
+
+    /// Decide on a learn request. A request from a known client removes its mailbox.
+    pub fn decide(&mut self, request: &LearnRequest) -> LearnDecision {
+        if let Some(mailbox) = self.mailboxes.remove(&request.client) {
+            self.statistics.learned_out += 1;
+            return LearnDecision::LearnedOut(mailbox);
+        }
+
+        let rejection = if self.accepted.as_ref().is_some_and(|accepted| !accepted.contains(&request.eep)) {
+            Some(ConfirmCode::EepNotAccepted)
+        } else if self.max_rssi.is_some_and(|max| request.rssi > max) {
+            Some(ConfirmCode::RssiTooLow)
+        } else if self.mailboxes.len() >= self.capacity as usize {
+            Some(ConfirmCode::PostmasterFull)
+        } else {
+            None
+        };
+        if let Some(code) = rejection {
+            self.statistics.rejected += 1;
+            return LearnDecision::Rejected(*request, code);
+        }
+
+        let mailbox = Mailbox {
+            client: request.client,
+            postmaster: request.postmaster_candidate,
+            eep: request.eep,
+            manufacturer: request.manufacturer,
+            telegrams: 0,
+        };
+        self.mailboxes.insert(mailbox.client, mailbox);
+        self.statistics.learned_in += 1;
+        LearnDecision::LearnedIn(mailbox)
+    }
+
+    /// Process an event from the transceiver
+    pub fn handle_event(&mut self, event: &Event) -> PostmasterOutcome {
+        let mut outcome = PostmasterOutcome::default();
+        match event {
+            Event::SAReclaimUnsuccessful => self.statistics.reclaims_unsuccessful += 1,
+            Event::SAConfirmLearn { data } => {
+                let decision = self.decide(&LearnRequest::decode(data));
+                let confirm = match decision {
+                    LearnDecision::LearnedIn(_) => ConfirmCode::LearnIn,
+                    LearnDecision::LearnedOut(_) => ConfirmCode::LearnOut,
+                    LearnDecision::Rejected(_, code) => code,
+                };
+                let mut data = ResponseData::from_slice(&self.response_time.to_be_bytes());
+                data.push(confirm.into());
+                outcome.response = Some(Response { code: ReturnCode::Ok, data });
+                outcome.decision = Some(decision);
+            }
+            _ => (),
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::borrow::Cow;
+
+    const REQUEST: [u8; 16] = [0x0f, 0xf8, 0x0d, 0xa5, 0x02, 0x05, 60, 0xff, 0x9b, 0x12, 0x80, 0x01, 0x80, 0x99, 0x8e, 0];
+
+    #[test]
+    fn given_learn_requests_then_allocate_and_release_mailboxes() {
+        let mut postmaster = Postmaster::new(1);
+        let outcome = postmaster.handle_event(&Event::SAConfirmLearn { data: Cow::Borrowed(&REQUEST) });
+        assert_eq!(outcome.response.unwrap().data[..], [0, 150, 0x00]);
+        let Some(LearnDecision::LearnedIn(mailbox)) = outcome.decision else { panic!() };
+        assert_eq!(mailbox.manufacturer, 0x00d);
+        assert_eq!(mailbox.eep, EEPProfileCode::new(0xa5, 0x02, 0x05));
+
+        let mut other = REQUEST;
+        other[14] = 0x8f;
+        let outcome = postmaster.handle_event(&Event::SAConfirmLearn { data: Cow::Borrowed(&other) });
+        assert!(matches!(outcome.decision, Some(LearnDecision::Rejected(_, ConfirmCode::PostmasterFull))));
+
+        let outcome = postmaster.handle_event(&Event::SAConfirmLearn { data: Cow::Borrowed(&REQUEST) });
+        assert_eq!(outcome.response.unwrap().data[2], 0x20);
+        postmaster.handle_event(&Event::SAReclaimUnsuccessful);
+        assert_eq!(postmaster.statistics(), Statistics { learned_in: 1, learned_out: 1, rejected: 1, reclaims_unsuccessful: 1 });
+    }
+
+    #[test]
+    fn given_learn_mode_command_then_encode_it() {
+        let command = SmartAckCommand::WriteLearnMode { enable: true, extent: LearnModeExtent::Simple, timeout: Duration::from_secs(60) };
+        assert_eq!(command.encode().data(), &[0x01, 0x01, 0x00, 0x00, 0x00, 0xea, 0x60]);
+        assert_eq!(command.encode().packet_type(), 0x06);
+    }
+}
@@ -0,0 +1,183 @@
+//! Async link to an ESP3 device, over tokio (feature `tokio`)
+//!
+//! The async counterpart of [`crate::port::Port`], with the same methods and errors. It
+//! runs over any `AsyncRead + AsyncWrite` stream, e.g. a `tokio_serial::SerialStream`, or a
+//! TCP connection to a serial server.
+//!
+//! ```ignore
+//! let serial = tokio_serial::new("/dev/ttyUSB0", 57600).open_native_async()?;
+//! let mut port = TokioPort::new(serial);
+//! let base = port.read_id_base().await?;
+//! loop {
+//!     let frame = port.read_frame().await?;
+//!     // ...
+//! }
+//! ```
+
+use core::borrow::Borrow;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::frame::ESP3Frame;
+use crate::link::{Link, RawFrame, TxStatus, READ_BUFFER};
+use crate::packet::{CommonCommand, FrequencyInfo, IdBase, Packet, RadioErp1, Response, ResponseCode, VersionResponse};
+use crate::{FrameReadError, PacketError};
+
+/// An ESP3 device on an async stream
+pub struct TokioPort<T> {
+    stream: T,
+    /// Frames received and not yet returned, e.g. while waiting for a response
+    link: Link,
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> TokioPort<T> {
+    pub fn new(stream: T) -> Self {
+        Self { stream, link: Link::new() }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.stream
+    }
+
+    /// Hand every frame read or written to `tap` as raw bytes, including frames failing
+    /// their CRC. See [`crate::link`].
+    pub fn set_tap(&mut self, tap: impl FnMut(&RawFrame) + Send + 'static) {
+        self.link.set_tap(tap);
+    }
+
+    pub fn clear_tap(&mut self) {
+        self.link.clear_tap();
+    }
+
+    pub async fn read_version_information(&mut self) -> Result<VersionResponse, PacketError> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadVersion)).await?;
+        Ok(VersionResponse::decode(&response)?)
+    }
+
+    pub async fn read_id_base(&mut self) -> Result<IdBase, PacketError> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::ReadIdBase)).await?;
+        Ok(IdBase::decode(&response)?)
+    }
+
+    /// The radio frequency and protocol, `None` if the firmware does not support the command
+    pub async fn read_frequency_info(&mut self) -> Result<Option<FrequencyInfo>, PacketError> {
+        let response = self.write_packet(Packet::CommonCommand(CommonCommand::GetFrequencyInfo)).await?;
+        if response.code != ResponseCode::Ok {
+            return Ok(None);
+        }
+        Ok(Some(FrequencyInfo::decode(&response)?))
+    }
+
+    /// Read the bytes available from the stream
+    async fn receive(&mut self) -> Result<(), FrameReadError> {
+        let mut buffer = [0; READ_BUFFER];
+        let n = self.stream.read(&mut buffer).await?;
+        if n == 0 {
+            return Err(FrameReadError::EOF);
+        }
+        self.link.feed(&buffer[..n]);
+        Ok(())
+    }
+
+    /// Read the next frame, starting with the frames set aside while waiting for a response.
+    pub async fn read_frame(&mut self) -> Result<ESP3Frame, FrameReadError> {
+        loop {
+            if let Some(frame) = self.link.next_frame() {
+                return frame;
+            }
+            self.receive().await?;
+        }
+    }
+
+    pub async fn write_frame(&mut self, frame: &ESP3Frame) -> Result<(), std::io::Error> {
+        self.stream.write_all(frame.borrow()).await?;
+        self.stream.flush().await?;
+        self.link.sent(frame);
+        Ok(())
+    }
+
+    /// Send a packet and wait for the response of the device
+    pub async fn write_packet(&mut self, packet: Packet<'_>) -> Result<Response, PacketError> {
+        self.write_frame(&packet.encode()).await?;
+
+        loop {
+            if let Some(reply) = self.link.next_response() {
+                return Ok(Response::decode(reply?.as_ref())?);
+            }
+            self.receive().await?;
+        }
+    }
+
+    /// Send a radio telegram, and wait for the transceiver to report whether it left the air
+    /// interface. Frames received meanwhile are returned by the next reads. Wrap it in
+    /// `tokio::time::timeout`, as transceivers may not report.
+    pub async fn send_confirmed(&mut self, erp: RadioErp1<'_>) -> Result<TxStatus, PacketError> {
+        if let Err(e) = self.write_packet(Packet::RadioErp1(erp)).await?.code.result() {
+            return Ok(TxStatus::Refused(e));
+        }
+
+        loop {
+            if let Some(status) = self.link.next_tx_status() {
+                return Ok(status?);
+            }
+            self.receive().await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::{pin, Pin};
+    use core::task::{Context, Poll, Waker};
+    use std::io;
+    use tokio::io::ReadBuf;
+
+    /// A stream replaying received bytes, and recording the sent ones. Always ready.
+    struct Stream {
+        rx: &'static [u8],
+        tx: Vec<u8>,
+    }
+
+    impl AsyncRead for Stream {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.rx).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Stream {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.tx).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.tx).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.tx).poll_shutdown(cx)
+        }
+    }
+
+    /// Util : run a future that never waits
+    fn ready<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("future is pending"),
+        }
+    }
+
+    #[test]
+    fn given_telegram_before_response_then_queue_it() {
+        let telegram = ESP3Frame::assemble(0x01, &[0xf6, 0x50, 0x01, 0x02, 0x03, 0x04, 0x30], &[]);
+        let response = ESP3Frame::assemble(0x02, &[0x00, 0xff, 0x80, 0x00, 0x00], &[]);
+        let rx = [Borrow::<[u8]>::borrow(&telegram), Borrow::<[u8]>::borrow(&response)].concat();
+        let mut port = TokioPort::new(Stream { rx: rx.leak(), tx: Vec::new() });
+
+        assert_eq!(ready(port.read_id_base()).unwrap(), IdBase("ff800000".parse().unwrap()));
+        assert_eq!(ready(port.read_frame()).unwrap().data(), telegram.data());
+        assert!(matches!(ready(port.read_frame()), Err(FrameReadError::EOF)));
+        assert_eq!(port.into_inner().tx, Borrow::<[u8]>::borrow(&Packet::CommonCommand(CommonCommand::ReadIdBase).encode()));
+    }
+}
@@ -0,0 +1,200 @@
+//! Radio traffic statistics
+//!
+//! A [`TrafficStats`] keeps the telegrams received over a sliding window, and summarizes
+//! them: telegrams per second, overall and by sender, their distribution by RORG, and the
+//! airtime they took. Chatty devices saturating the band stand out at the top of
+//! [`TrafficSummary::senders`]:
+//!
+//! ```
+//! # use enocean::frame::ESP3FrameRef;
+//! # use enocean::packet::RadioErp1;
+//! use enocean::traffic::TrafficStats;
+//! use std::time::{Duration, Instant};
+//!
+//! let data = [0xf6, 0x50, 0x01, 0x80, 0x99, 0x8e, 0x30];
+//! let erp = RadioErp1::decode(ESP3FrameRef { packet_type: 1, data: &data, optional_data: &[] })?;
+//!
+//! let mut traffic = TrafficStats::new(Duration::from_secs(10));
+//! let now = Instant::now();
+//! for _ in 0..20 {
+//!     traffic.record(&erp, now);
+//! }
+//! let summary = traffic.summary(now);
+//! assert_eq!(summary.rate, 2.0);
+//! assert_eq!(summary.senders[0].sender, "0180998e".parse()?);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Airtime is estimated from the length of the telegrams, see [`airtime`].
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::packet::{Address, RadioErp1};
+
+/// Default window of the statistics
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Subtelegrams sent per telegram, as ERP1 transmitters do
+pub const SUBTELEGRAMS: u32 = 3;
+
+/// Bit rate of ERP1 at 868 MHz
+const BIT_RATE: u64 = 125_000;
+
+/// Estimated time on air of a telegram, for its [`SUBTELEGRAMS`] subtelegrams.
+///
+/// A subtelegram holds the RORG, user data, sender, status and CRC bytes (and the
+/// destination of addressed telegrams), each sent as 12 bits with the ERP1 synchronization
+/// bits, between a preamble, a start and an end of frame of 20 bits in all. Pauses between
+/// subtelegrams are not counted.
+pub fn airtime(erp: &RadioErp1) -> Duration {
+    let mut bytes = 7 + erp.user_data.len() as u64;
+    if erp.destination.is_some() {
+        bytes += 5;
+    }
+    let bits = 20 + 12 * bytes;
+    Duration::from_micros(bits * 1_000_000 / BIT_RATE) * SUBTELEGRAMS
+}
+
+/// Traffic of a sender over the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SenderTraffic {
+    pub sender: Address,
+    pub telegrams: usize,
+    /// Telegrams per second
+    pub rate: f32,
+    pub airtime: Duration,
+}
+
+/// Traffic over the window
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TrafficSummary {
+    pub window: Duration,
+    pub telegrams: usize,
+    /// Telegrams per second
+    pub rate: f32,
+    pub airtime: Duration,
+    /// Share of the window the band was busy, 0..1
+    pub utilization: f32,
+    /// Telegrams by RORG
+    pub rorgs: BTreeMap<u8, usize>,
+    /// By decreasing number of telegrams
+    pub senders: Vec<SenderTraffic>,
+}
+
+impl TrafficSummary {
+    /// The senders above `rate` telegrams per second
+    pub fn above(&self, rate: f32) -> impl Iterator<Item = &SenderTraffic> + '_ {
+        self.senders.iter().filter(move |sender| sender.rate > rate)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    sender: Address,
+    rorg: u8,
+    airtime: Duration,
+}
+
+/// Telegrams received over a sliding window
+#[derive(Debug, Clone)]
+pub struct TrafficStats {
+    window: Duration,
+    samples: VecDeque<Sample>,
+}
+
+impl Default for TrafficStats {
+    fn default() -> Self {
+        Self::new(WINDOW)
+    }
+}
+
+impl TrafficStats {
+    /// Keep the telegrams of the last `window`
+    pub fn new(window: Duration) -> Self {
+        Self { window: window.max(Duration::from_secs(1)), samples: VecDeque::new() }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Record a telegram received at `now`, dropping those out of the window
+    pub fn record(&mut self, erp: &RadioErp1, now: Instant) {
+        while self.samples.front().is_some_and(|sample| !self.in_window(sample, now)) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample { at: now, sender: erp.sender_id, rorg: erp.choice.into(), airtime: airtime(erp) });
+    }
+
+    /// Summary of the telegrams of the window ending at `now`
+    pub fn summary(&self, now: Instant) -> TrafficSummary {
+        let seconds = self.window.as_secs_f32();
+        let mut summary = TrafficSummary {
+            window: self.window,
+            telegrams: 0,
+            rate: 0.0,
+            airtime: Duration::ZERO,
+            utilization: 0.0,
+            rorgs: BTreeMap::new(),
+            senders: Vec::new(),
+        };
+        let mut senders: BTreeMap<Address, SenderTraffic> = BTreeMap::new();
+        for sample in self.samples.iter().filter(|sample| self.in_window(sample, now)) {
+            summary.telegrams += 1;
+            summary.airtime += sample.airtime;
+            *summary.rorgs.entry(sample.rorg).or_default() += 1;
+            let sender = senders.entry(sample.sender).or_insert(SenderTraffic {
+                sender: sample.sender, telegrams: 0, rate: 0.0, airtime: Duration::ZERO,
+            });
+            sender.telegrams += 1;
+            sender.airtime += sample.airtime;
+        }
+        summary.rate = summary.telegrams as f32 / seconds;
+        summary.utilization = summary.airtime.as_secs_f32() / seconds;
+        summary.senders = senders.into_values()
+            .map(|sender| SenderTraffic { rate: sender.telegrams as f32 / seconds, ..sender })
+            .collect();
+        summary.senders.sort_by_key(|sender| Reverse(sender.telegrams));
+        summary
+    }
+
+    fn in_window(&self, sample: &Sample, now: Instant) -> bool {
+        now.saturating_duration_since(sample.at) < self.window
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eep::tests::erp1;
+
+    #[test]
+    fn given_telegrams_then_summarize_window() {
+        let start = Instant::now();
+        let rps = erp1(&[0xf6, 0x50, 0x01, 0x80, 0x99, 0x8e, 0x30]);
+        let bs4 = erp1(&[0xa5, 0x00, 0x00, 0x64, 0x08, 0x05, 0x12, 0x34, 0x56, 0x00]);
+        assert_eq!(airtime(&rps), Duration::from_micros(3 * 928));
+
+        let mut traffic = TrafficStats::new(Duration::from_secs(10));
+        traffic.record(&bs4, start);
+        for second in 5..15 {
+            traffic.record(&rps, start + Duration::from_secs(second));
+        }
+
+        let summary = traffic.summary(start + Duration::from_secs(14));
+        assert_eq!((summary.telegrams, summary.rate), (10, 1.0));
+        assert_eq!(summary.rorgs, BTreeMap::from([(0xf6, 10)]));
+        assert_eq!(summary.airtime, airtime(&rps) * 10);
+        assert_eq!(summary.senders.len(), 1);
+        assert_eq!(summary.above(0.5).count(), 1);
+
+        let summary = traffic.summary(start + Duration::from_secs(30));
+        assert_eq!(summary.telegrams, 0);
+        assert!(summary.senders.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! WebAssembly bindings (feature `wasm`)
+//!
+//! The parsing core builds for `wasm32-unknown-unknown`, without the serial transports
+//! (`port`, `communicator`). This module exposes it to JavaScript with wasm-bindgen, for
+//! browser tools decoding pasted hex dumps. Build it with
+//! `cargo rustc --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib`,
+//! then generate the JavaScript glue with `wasm-bindgen --target web`.
+//!
+//! ```js
+//! import init, { decode_frame, decode_telegram } from "./pkg/enocean.js";
+//!
+//! await init();
+//! const frame = JSON.parse(decode_frame("55 00 0a 07 01 eb a5 00 00 80 08 01 80 99 8e 00 ..."));
+//! const report = JSON.parse(decode_telegram(hex, "A5-02-05"));
+//! ```
+//!
+//! Hex dumps may contain whitespace, `:` or `-` between bytes. Results are JSON strings.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::eep::Report;
+use crate::frame::ESP3Frame;
+use crate::packet::{Address, EEPProfileCode, Packet, RadioErp1};
+
+/// A radio telegram, as in [`FrameJson`]
+#[derive(Serialize)]
+struct TelegramJson {
+    rorg: u8,
+    sender: Address,
+    destination: Option<Address>,
+    status: u8,
+    dbm: Option<i16>,
+    /// User data, as hex
+    user_data: String,
+}
+
+/// A frame, with its telegram if it is an ERP1 packet
+#[derive(Serialize)]
+struct FrameJson {
+    packet_type: u8,
+    data: String,
+    optional_data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    telegram: Option<TelegramJson>,
+}
+
+/// Util : parse a hex dump
+fn parse_hex(dump: &str) -> Result<Vec<u8>, JsError> {
+    let digits: String = dump.chars().filter(|c| !c.is_whitespace() && *c != ':' && *c != '-').collect();
+    Ok(hex::decode(digits)?)
+}
+
+/// Util : parse a hex dump of a complete ESP3 frame
+fn parse_frame(dump: &str) -> Result<ESP3Frame, JsError> {
+    let bytes = parse_hex(dump)?;
+    Ok(ESP3Frame::read_from(&mut &bytes[..])?)
+}
+
+/// Util : the ERP1 telegram of a frame
+fn radio_erp1(frame: &ESP3Frame) -> Result<RadioErp1<'_>, JsError> {
+    match Packet::decode(frame.as_ref())? {
+        Packet::RadioErp1(erp) => Ok(erp),
+        _ => Err(JsError::new("not a radio telegram")),
+    }
+}
+
+/// Decode an ESP3 frame, returning its JSON
+#[wasm_bindgen]
+pub fn decode_frame(dump: &str) -> Result<String, JsError> {
+    let frame = parse_frame(dump)?;
+    let telegram = radio_erp1(&frame).ok().map(|erp| TelegramJson {
+        rorg: erp.choice.into(),
+        sender: erp.sender_id,
+        destination: erp.destination,
+        status: erp.status,
+        dbm: erp.rssi_dbm(),
+        user_data: hex::encode(&erp.user_data),
+    });
+    Ok(serde_json::to_string(&FrameJson {
+        packet_type: frame.packet_type(),
+        data: hex::encode(frame.data()),
+        optional_data: hex::encode(frame.optional_data()),
+        telegram,
+    })?)
+}
+
+/// Decode the telegram of an ESP3 frame with the typed decoder of a profile, e.g. `"A5-02-05"`,
+/// returning the JSON of its [`Report`]
+#[wasm_bindgen]
+pub fn decode_telegram(dump: &str, eep: &str) -> Result<String, JsError> {
+    let eep: EEPProfileCode = eep.parse()?;
+    let frame = parse_frame(dump)?;
+    Ok(serde_json::to_string(&Report::decode(eep, &radio_erp1(&frame)?)?)?)
+}
+
+/// Assemble an ESP3 frame from its packet type and hex data, returning it as hex
+#[wasm_bindgen]
+pub fn encode_frame(packet_type: u8, data: &str, optional_data: &str) -> Result<String, JsError> {
+    let frame = ESP3Frame::assemble(packet_type, &parse_hex(data)?, &parse_hex(optional_data)?);
+    let mut bytes = Vec::new();
+    frame.write_to(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}